@@ -0,0 +1,74 @@
+use std::borrow::Borrow;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{BitArray, config, Empty, intersection, SparseArray, SparseHierarchy, union};
+use hi_sparse_array::level::{IntrusiveListLevel, SingleBlockLevel};
+use hi_sparse_array::level_block::Block;
+
+#[derive(Clone)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+fn sum_get(h: &impl SparseHierarchy<DataType = Data>, indices: &[usize]) -> usize {
+    indices.iter().map(|&i| h.get(i).borrow().0).sum()
+}
+
+fn sum_iter(h: &impl SparseHierarchy<DataType = Data>) -> usize {
+    h.iter().map(|(_, d)| d.borrow().0).sum()
+}
+
+fn bench<Levels>(c: &mut Criterion, name: &str, range: usize, count: usize)
+where
+    Levels: hi_sparse_array::SparseArrayLevels
+{
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xb17_a77a7);
+    let mut a1 = SparseArray::<Levels, Data>::default();
+    let mut a2 = SparseArray::<Levels, Data>::default();
+    let mut indices = Vec::new();
+    for _ in 0..count {
+        let v = rng.gen_range(0..range);
+        a1.insert(v, Data(v+1));
+        indices.push(v);
+        let v = rng.gen_range(0..range);
+        a2.insert(v, Data(v+1));
+    }
+    let and = intersection(&a1, &a2, |l, r| Data(l.0 + r.0));
+    let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+
+    c.bench_function(&format!("{name} get"), |b| b.iter(|| sum_get(black_box(&a1), &indices)));
+    c.bench_function(&format!("{name} iter"), |b| b.iter(|| sum_iter(black_box(&a1))));
+    c.bench_function(&format!("{name} intersection iter"), |b| b.iter(|| sum_iter(black_box(&and))));
+    c.bench_function(&format!("{name} union iter"), |b| b.iter(|| sum_iter(black_box(&or))));
+}
+
+type ArrayWidth256 = (
+    SingleBlockLevel<Block<BitArray<4>, [u8; 256]>>,
+    IntrusiveListLevel<Block<BitArray<4>, [u16; 256]>>,
+    IntrusiveListLevel<Block<BitArray<4>, [u32; 256]>>,
+);
+
+/// Scalar vs SIMD masks of the same width.
+pub fn bench_bit_array(c: &mut Criterion) {
+    bench::<config::width_256::depth_3>(c, "u64x4", 1_000_000, 100_000);
+    bench::<ArrayWidth256>(c, "BitArray<4>", 1_000_000, 100_000);
+    bench::<config::width_512_u64x8::depth_3>(c, "U64x8", 1_000_000, 100_000);
+    bench::<config::width_512::depth_3>(c, "BitArray<8>", 1_000_000, 100_000);
+}
+
+/// Same range (2^18) - more depth with u64 vs wider SIMD levels.
+pub fn bench_width_vs_depth(c: &mut Criterion) {
+    bench::<config::width_64::depth_3>(c, "width_64 depth_3", 1 << 18, 30_000);
+    bench::<config::width_512_u64x8::depth_2>(c, "width_512 U64x8 depth_2", 1 << 18, 30_000);
+    bench::<config::width_512::depth_2>(c, "width_512 BitArray<8> depth_2", 1 << 18, 30_000);
+}
+
+criterion_group!(benches_bit_array, bench_bit_array, bench_width_vs_depth);
+criterion_main!(benches_bit_array);
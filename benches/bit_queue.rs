@@ -0,0 +1,42 @@
+use std::ops::ControlFlow;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use hi_sparse_array::{BitArray, BitBlock};
+use hi_sparse_array::bit_queue::BitQueue;
+
+fn pop_next<B: BitBlock>(block: B) -> usize {
+    let mut s = 0;
+    // `for` goes through next()
+    for i in block.into_bits_iter() {
+        s += i;
+    }
+    s
+}
+
+fn pop_traverse<B: BitBlock>(block: B) -> usize {
+    let mut s = 0;
+    let _ = block.into_bits_iter().traverse(|i| {
+        s += i;
+        ControlFlow::Continue(())
+    });
+    s
+}
+
+fn bench<B: BitBlock>(c: &mut Criterion, name: &str){
+    // 64 set bits, spread over the whole block.
+    let mut block = B::zero();
+    for i in 0..64 {
+        block.set_bit::<true>(i * (B::SIZE / 64));
+    }
+    c.bench_function(&format!("{name} next"), |b| b.iter(|| pop_next(black_box(block.clone()))));
+    c.bench_function(&format!("{name} traverse"), |b| b.iter(|| pop_traverse(black_box(block.clone()))));
+}
+
+pub fn bench_bit_queue(c: &mut Criterion) {
+    bench::<u64>(c, "u64");
+    bench::<BitArray<4>>(c, "BitArray<4>");
+    #[cfg(feature = "simd")]
+    bench::<wide::u64x4>(c, "u64x4");
+}
+
+criterion_group!(benches_bit_queue, bench_bit_queue);
+criterion_main!(benches_bit_queue);
@@ -0,0 +1,47 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection_fold, SparseArray, SparseHierarchy, union};
+
+#[derive(Clone)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_3, Data>;
+
+fn iter_count(h: &impl SparseHierarchy) -> usize {
+    use std::borrow::Borrow;
+    h.iter().filter(|(_, d)| !d.borrow().is_empty()).count()
+}
+
+pub fn bench_count_keys(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let arrays: Vec<_> = (0..3).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..100_000 {
+            let v = rng.gen_range(0..Array::max_range());
+            a.insert(v, Data(v+1));
+        }
+        a
+    }).collect();
+
+    // Non-exact.
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+    c.bench_function("intersection count_keys", |b| b.iter(|| black_box(&and).count_keys()));
+    c.bench_function("intersection iter count", |b| b.iter(|| iter_count(black_box(&and))));
+
+    // Exact.
+    let or = union(&arrays[0], &arrays[1], |l, r| Data(l.0 + r.0));
+    c.bench_function("union count_keys", |b| b.iter(|| black_box(&or).count_keys()));
+    c.bench_function("union iter count", |b| b.iter(|| iter_count(black_box(&or))));
+}
+
+criterion_group!(benches_count_keys, bench_count_keys);
+criterion_main!(benches_count_keys);
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use nohash_hasher::BuildNoHashHasher;
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, dot, SparseArray};
+
+type Vector = SparseArray<config::width_64::depth_4, Option<f64>>;
+type Map = HashMap<usize, f64, BuildNoHashHasher<usize>>;
+
+fn hashmap_dot(a: &Map, b: &Map) -> f64 {
+    let (a, b) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    a.iter()
+        .filter_map(|(k, va)| b.get(k).map(|vb| va * vb))
+        .sum()
+}
+
+pub fn bench_dot(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xd07_d07);
+    let mut vectors = Vec::new();
+    let mut maps = Vec::new();
+    for _ in 0..2 {
+        let mut v = Vector::default();
+        let mut m = Map::default();
+        for _ in 0..100_000 {
+            let k = rng.gen_range(0..1_000_000);
+            let value = rng.gen_range(-1.0..1.0);
+            v.insert(k, Some(value));
+            m.insert(k, value);
+        }
+        vectors.push(v);
+        maps.push(m);
+    }
+
+    c.bench_function("dot", |b| b.iter(||
+        dot(black_box(&vectors[0]), black_box(&vectors[1]))
+    ));
+    c.bench_function("hashmap join dot", |b| b.iter(||
+        hashmap_dot(black_box(&maps[0]), black_box(&maps[1]))
+    ));
+}
+
+criterion_group!(benches_dot, bench_dot);
+criterion_main!(benches_dot);
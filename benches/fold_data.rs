@@ -0,0 +1,53 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection, SparseArray, SparseHierarchy};
+
+#[derive(Clone, Default)]
+struct Data(f32);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0.0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0.0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_4, Data>;
+
+fn iter_sum(h: &impl SparseHierarchy<DataType = Data>) -> f32 {
+    use std::borrow::Borrow;
+    h.iter().fold(0.0, |acc, (_, d)| acc + d.borrow().0)
+}
+
+fn fold_sum(h: &impl SparseHierarchy<DataType = Data>) -> f32 {
+    use std::borrow::Borrow;
+    h.fold_data(0.0, |acc, _, d| acc + d.borrow().0)
+}
+
+pub fn bench_fold_data(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xf01d_da7a);
+    let arrays: Vec<_> = (0..2).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..500_000 {
+            let v = rng.gen_range(0..1_000_000);
+            a.insert(v, Data(1.0));
+        }
+        a
+    }).collect();
+
+    let a = &arrays[0];
+    c.bench_function("array iter fold", |b| b.iter(|| iter_sum(black_box(a))));
+    c.bench_function("array fold_data", |b| b.iter(|| fold_sum(black_box(a))));
+    c.bench_function("array fold_data_unordered", |b| b.iter(||
+        black_box(a).fold_data_unordered(0.0, |acc, _, d| acc + d.0)
+    ));
+
+    let and = intersection(&arrays[0], &arrays[1], |l, r| Data(l.0 * r.0));
+    c.bench_function("intersection iter fold", |b| b.iter(|| iter_sum(black_box(&and))));
+    c.bench_function("intersection fold_data", |b| b.iter(|| fold_sum(black_box(&and))));
+}
+
+criterion_group!(benches_fold_data, bench_fold_data);
+criterion_main!(benches_fold_data);
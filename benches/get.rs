@@ -4,7 +4,7 @@ use rand::seq::SliceRandom;
 use hi_sparse_array::{config, Empty, SparseArray};
 use hi_sparse_array::level_block::{Block, ClusterBlock, SmallBlock};
 use hi_sparse_array::Iter;
-use hi_sparse_array::level::{IntrusiveListLevel, SingleBlockLevel};
+use hi_sparse_array::level::{ChunkedStorage, IntrusiveListLevel, SingleBlockLevel};
 use hi_sparse_array::SparseHierarchy;
 
 const RANGE: usize = 260_000;
@@ -36,26 +36,47 @@ type Map = nohash_hasher::IntMap<u64, DataBlock>;
 //type BlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<Lvl1Block>, IntrusiveListLevel<Lvl2Block>), DataBlock>;
 type BlockArray = SparseArray<config::width_64::depth_3, DataBlock>;
 
+type ChunkedBlockArray = SparseArray<(
+    SingleBlockLevel<Lvl0Block>,
+    IntrusiveListLevel<Lvl1Block, ChunkedStorage<Lvl1Block>>,
+    IntrusiveListLevel<Lvl2Block, ChunkedStorage<Lvl2Block>>
+), DataBlock>;
+
 //type SmallBlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<CompactLvl1Block>, IntrusiveListLevel<CompactLvl2Block>), DataBlock>;
 type SmallBlockArray = SparseArray<config::sbo::width_64::depth_3, DataBlock>;
+type SimdBlockArray = SparseArray<config::width_128::depth_3, DataBlock>;
+type U128BlockArray = SparseArray<config::width_128_u128::depth_3, DataBlock>;
 /*type ClusterBlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<ClusterLvl1Block>), IntrusiveListLevel<DataBlock>>;*/
 
-/*fn cluster_array_get(array: &ClusterBlockArray) -> u64 {
+fn small_array_get(array: &SmallBlockArray, indices: &[usize]) -> u64 {
     let mut s = 0;
-    for (_, i) in CachingBlockIter::new(array){
-        s += i.0;
+    for &i in indices{
+        s += array.get(i).0;
     }
     s
-}*/
-
-fn small_array_get(array: &SmallBlockArray, indices: &[usize]) -> u64 {
+}
+fn array_get(array: &BlockArray, indices: &[usize]) -> u64 {
     let mut s = 0;
     for &i in indices{
         s += array.get(i).0;
     }
     s
 }
-fn array_get(array: &BlockArray, indices: &[usize]) -> u64 {
+fn chunked_array_get(array: &ChunkedBlockArray, indices: &[usize]) -> u64 {
+    let mut s = 0;
+    for &i in indices{
+        s += array.get(i).0;
+    }
+    s
+}
+fn simd_array_get(array: &SimdBlockArray, indices: &[usize]) -> u64 {
+    let mut s = 0;
+    for &i in indices{
+        s += array.get(i).0;
+    }
+    s
+}
+fn u128_array_get(array: &U128BlockArray, indices: &[usize]) -> u64 {
     let mut s = 0;
     for &i in indices{
         s += array.get(i).0;
@@ -73,6 +94,9 @@ fn hashmap_get(array: &Map, indices: &[usize]) -> u64 {
 pub fn bench_iter(c: &mut Criterion) {
     let mut block_array = BlockArray::default();
     let mut small_block_array = SmallBlockArray::default();
+    let mut chunked_block_array = ChunkedBlockArray::default();
+    let mut simd_block_array = SimdBlockArray::default();
+    let mut u128_block_array = U128BlockArray::default();
     /*let mut cluster_block_array = ClusterBlockArray::default();*/
     let mut hashmap = Map::default();
     
@@ -85,14 +109,24 @@ pub fn bench_iter(c: &mut Criterion) {
         
         *block_array.get_mut(v) = DataBlock(v as u64);
         *small_block_array.get_mut(v) = DataBlock(v as u64);
+        *chunked_block_array.get_mut(v) = DataBlock(v as u64);
+        *simd_block_array.get_mut(v) = DataBlock(v as u64);
+        *u128_block_array.get_mut(v) = DataBlock(v as u64);
         /* *cluster_block_array.get_or_insert(v) = DataBlock(v as u64);*/
         hashmap.insert(v as u64, DataBlock(v as u64));
     }
     random_indices.shuffle(&mut rng);
+    
+    // Memory footprint, to go along with timings.
+    eprintln!("level_block array:\n{}", block_array.stats());
+    eprintln!("small level_block array:\n{}", small_block_array.stats());
+    eprintln!("u64x2 level_block array:\n{}", simd_block_array.stats());
 
     c.bench_function("level_block array", |b| b.iter(|| array_get(black_box(&block_array), black_box(&random_indices))));
+    c.bench_function("chunked level_block array", |b| b.iter(|| chunked_array_get(black_box(&chunked_block_array), black_box(&random_indices))));
     c.bench_function("small level_block array", |b| b.iter(|| small_array_get(black_box(&small_block_array), black_box(&random_indices))));
-    /*c.bench_function("cluster level_block array", |b| b.iter(|| cluster_array_get(black_box(&cluster_block_array))));*/
+    c.bench_function("u64x2 level_block array", |b| b.iter(|| simd_array_get(black_box(&simd_block_array), black_box(&random_indices))));
+    c.bench_function("u128 level_block array", |b| b.iter(|| u128_array_get(black_box(&u128_block_array), black_box(&random_indices))));
     c.bench_function("hashmap", |b| b.iter(|| hashmap_get(black_box(&hashmap), black_box(&random_indices))));
 }
 
@@ -0,0 +1,67 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection_fold, intersection_fold_streaming, SparseArray, SparseHierarchy};
+
+#[derive(Clone)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_3, Data>;
+const RANGE: usize = 100_000;
+
+fn sum_get(h: &impl SparseHierarchy<DataType = Data>, indices: &[usize]) -> usize {
+    use std::borrow::Borrow;
+    indices.iter().map(|&i| h.get(i).borrow().0).sum()
+}
+
+fn bench(c: &mut Criterion, name: &str, arrays: &[Array], indices: &[usize]) {
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d|
+        if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+    );
+    let streaming = intersection_fold_streaming(&arrays[0], arrays[1..].iter(), |acc, d|
+        Data(acc.0 + d.0)
+    );
+    c.bench_function(&format!("{name} intersection_fold get"), |b| b.iter(||
+        sum_get(black_box(&and), indices)
+    ));
+    c.bench_function(&format!("{name} intersection_fold_streaming get"), |b| b.iter(||
+        sum_get(black_box(&streaming), indices)
+    ));
+}
+
+pub fn bench_intersection_fold_get(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let indices: Vec<_> = (0..10_000).map(|_| rng.gen_range(0..RANGE)).collect();
+
+    // Almost every query hits.
+    let high: Vec<_> = (0..8).map(|_| {
+        let mut a = Array::default();
+        for i in 0..RANGE {
+            a.insert(i, Data(i+1));
+        }
+        a
+    }).collect();
+    bench(c, "high hit rate", &high, &indices);
+
+    // Almost every query misses at the first sources.
+    let low: Vec<_> = (0..8).map(|_| {
+        let mut a = Array::default();
+        for _ in 0..RANGE/10 {
+            let v = rng.gen_range(0..RANGE);
+            a.insert(v, Data(v+1));
+        }
+        a
+    }).collect();
+    bench(c, "low hit rate", &low, &indices);
+}
+
+criterion_group!(benches_intersection_fold_get, bench_intersection_fold_get);
+criterion_main!(benches_intersection_fold_get);
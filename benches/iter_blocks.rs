@@ -0,0 +1,79 @@
+use std::borrow::Borrow;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection, SparseArray, SparseHierarchy};
+
+#[derive(Clone, Default)]
+struct Data{
+    value: u64,
+    _payload: [u64; 3],
+}
+impl Empty for Data{
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.value == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_3, Data>;
+
+fn iter_sum(h: &impl SparseHierarchy<DataType = Data>) -> u64 {
+    let mut sum = 0;
+    for (_, d) in h.iter() {
+        sum += d.borrow().value;
+    }
+    sum
+}
+
+fn data_blocks_sum(a: &Array) -> u64 {
+    let mut sum = 0;
+    for (_, mask, block) in a.data_blocks() {
+        let mut bits = *mask;
+        while bits != 0 {
+            let lane = bits.trailing_zeros() as usize;
+            sum += block.get(lane).value;
+            bits &= bits - 1;
+        }
+    }
+    sum
+}
+
+fn for_each_block_sum(h: &impl SparseHierarchy<DataType = Data, LevelMaskType = u64>) -> u64 {
+    let mut sum = 0;
+    h.for_each_block(|_, mask, block| {
+        let mut bits = *mask;
+        while bits != 0 {
+            let lane = bits.trailing_zeros() as usize;
+            sum += block.get(lane).borrow().value;
+            bits &= bits - 1;
+        }
+    });
+    sum
+}
+
+pub fn bench_iter_blocks(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x0b10_c4e5);
+    let arrays: Vec<_> = (0..2).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..200_000 {
+            let v = rng.gen_range(0..250_000);
+            a.insert(v, Data{ value: 1, _payload: [0; 3] });
+        }
+        a
+    }).collect();
+
+    let a = &arrays[0];
+    c.bench_function("array iter", |b| b.iter(|| iter_sum(black_box(a))));
+    c.bench_function("array data_blocks", |b| b.iter(|| data_blocks_sum(black_box(a))));
+    c.bench_function("array for_each_block", |b| b.iter(|| for_each_block_sum(black_box(a))));
+
+    let and = intersection(&arrays[0], &arrays[1], |l, r| Data{ value: l.value * r.value, _payload: [0; 3] });
+    c.bench_function("intersection iter", |b| b.iter(|| iter_sum(black_box(&and))));
+    c.bench_function("intersection for_each_block", |b| b.iter(|| for_each_block_sum(black_box(&and))));
+}
+
+criterion_group!(benches_iter_blocks, bench_iter_blocks);
+criterion_main!(benches_iter_blocks);
@@ -0,0 +1,63 @@
+use std::borrow::Borrow;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection_fold, SparseArray, SparseHierarchy};
+
+#[derive(Clone, Default)]
+struct Data(u64);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_4, Data>;
+
+fn next_sum(h: &impl SparseHierarchy<DataType = Data>) -> u64 {
+    let mut sum = 0;
+    // for loop calls next().
+    for (_, d) in h.iter() {
+        sum += d.borrow().0;
+    }
+    sum
+}
+
+fn fold_sum(h: &impl SparseHierarchy<DataType = Data>) -> u64 {
+    h.iter().fold(0, |acc, (_, d)| acc + d.borrow().0)
+}
+
+fn for_each_sum(h: &impl SparseHierarchy<DataType = Data>) -> u64 {
+    let mut sum = 0;
+    h.iter().for_each(|(_, d)| sum += d.borrow().0);
+    sum
+}
+
+pub fn bench_iter_fold(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x17e4_f01d);
+    let mut a = Array::default();
+    for i in 0..1_000_000 {
+        a.insert(i, Data(1));
+    }
+    c.bench_function("array 1M next", |b| b.iter(|| next_sum(black_box(&a))));
+    c.bench_function("array 1M fold", |b| b.iter(|| fold_sum(black_box(&a))));
+    c.bench_function("array 1M for_each", |b| b.iter(|| for_each_sum(black_box(&a))));
+
+    let arrays: Vec<_> = (0..3).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..500_000 {
+            let v = rng.gen_range(0..1_000_000);
+            a.insert(v, Data(1));
+        }
+        a
+    }).collect();
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+    c.bench_function("intersection_fold next", |b| b.iter(|| next_sum(black_box(&and))));
+    c.bench_function("intersection_fold fold", |b| b.iter(|| fold_sum(black_box(&and))));
+}
+
+criterion_group!(benches_iter_fold, bench_iter_fold);
+criterion_main!(benches_iter_fold);
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use criterion::{black_box, Criterion, criterion_group, criterion_main};
-use hi_sparse_array::{Empty, SparseArray};
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use hi_sparse_array::{config, Empty, SparseArray};
 use hi_sparse_array::level_block::{Block, ClusterBlock, SmallBlock};
 use hi_sparse_array::Iter;
 use hi_sparse_array::level::{IntrusiveListLevel, SingleBlockLevel};
@@ -19,13 +21,28 @@ impl Empty for DataBlock{
     }
 
     fn is_empty(&self) -> bool {
-        todo!()
+        self.0 == 0
+    }
+}
+
+/// 64-byte payload.
+#[derive(Clone)]
+struct FatDataBlock([u64; 8]);
+impl Empty for FatDataBlock{
+    fn empty() -> Self {
+        Self([0; 8])
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == [0; 8]
     }
 }
 
 type BlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<Lvl1Block>), DataBlock>;
 type SmallBlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<CompactLvl1Block>), DataBlock>;
 type ClusterBlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<ClusterLvl1Block>), DataBlock>;
+type FatBlockArray = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<Lvl1Block>), FatDataBlock>;
+type BigFatBlockArray = SparseArray<config::width_64::depth_3, FatDataBlock>;
 
 fn cluster_array_iter(array: &ClusterBlockArray) -> u64 {
     let mut s = 0;
@@ -50,6 +67,30 @@ fn array_iter(array: &BlockArray) -> u64 {
     s
 }
 
+fn fat_array_iter(array: &FatBlockArray) -> usize {
+    let mut s = 0;
+    for (k, i) in Iter::new(array){
+        s += k + i.0[0] as usize;
+    }
+    s
+}
+
+fn big_fat_array_iter(array: &BigFatBlockArray) -> usize {
+    let mut s = 0;
+    for (k, i) in Iter::new(array){
+        s += k + i.0[0] as usize;
+    }
+    s
+}
+
+fn fat_array_keys(array: &FatBlockArray) -> usize {
+    let mut s = 0;
+    for k in array.keys(){
+        s += k;
+    }
+    s
+}
+
 fn vec_iter(array: &Vec<DataBlock>) -> u64 {
     let mut s = 0;
     for i in array{
@@ -70,12 +111,14 @@ pub fn bench_iter(c: &mut Criterion) {
     let mut block_array = BlockArray::default();
     let mut small_block_array = SmallBlockArray::default();
     let mut cluster_block_array = ClusterBlockArray::default();
+    let mut fat_block_array = FatBlockArray::default();
     let mut vec = Vec::default();
     let mut hashmap = HashMap::default();
     for i in 0..3000{
         *block_array.get_mut(i) = DataBlock(i as u64);
         *small_block_array.get_mut(i) = DataBlock(i as u64);
         *cluster_block_array.get_mut(i) = DataBlock(i as u64);
+        *fat_block_array.get_mut(i) = FatDataBlock([i as u64; 8]);
         vec.push(DataBlock(i as u64));
         hashmap.insert(i as u64, DataBlock(i as u64));
     }
@@ -83,6 +126,19 @@ pub fn bench_iter(c: &mut Criterion) {
     c.bench_function("level_block array", |b| b.iter(|| array_iter(black_box(&block_array))));
     c.bench_function("small level_block array", |b| b.iter(|| small_array_iter(black_box(&small_block_array))));
     c.bench_function("cluster level_block array", |b| b.iter(|| cluster_array_iter(black_box(&cluster_block_array))));
+    c.bench_function("fat level_block array iter", |b| b.iter(|| fat_array_iter(black_box(&fat_block_array))));
+    c.bench_function("fat level_block array keys", |b| b.iter(|| fat_array_keys(black_box(&fat_block_array))));
+
+    // Items inserted in random order - storage order differs from iteration
+    // order. Compare with and without `prefetch` feature.
+    let mut big_fat_block_array = BigFatBlockArray::default();
+    let mut keys: Vec<usize> = (0..250_000).collect();
+    keys.shuffle(&mut rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f));
+    for i in keys {
+        big_fat_block_array.insert(i, FatDataBlock([i as u64; 8]));
+    }
+    c.bench_function("big shuffled fat level_block array iter", |b| b.iter(|| big_fat_array_iter(black_box(&big_fat_block_array))));
+
     c.bench_function("vec", |b| b.iter(|| vec_iter(black_box(&vec))));
     c.bench_function("hashmap", |b| b.iter(|| hashmap_iter(black_box(&hashmap))));
 }
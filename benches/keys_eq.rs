@@ -0,0 +1,43 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, ExactHierarchy, keys_eq, SparseArray, SparseHierarchy};
+
+#[derive(Clone, PartialEq)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_4, Data>;
+
+fn iter_keys_eq(a1: &Array, a2: &Array) -> bool {
+    a1.iter().map(|(i, _)| i).eq(a2.iter().map(|(i, _)| i))
+}
+
+pub fn bench_keys_eq(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut a1 = Array::default();
+    for _ in 0..500_000 {
+        let v = rng.gen_range(0..Array::max_range() - 1);
+        a1.insert(v, Data(v+1));
+    }
+    // Differs only in the last key.
+    let mut a2 = a1.iter().fold(Array::default(), |mut a, (i, d)| { a.insert(i, d.clone()); a });
+    a2.insert(Array::max_range(), Data(1));
+
+    let e1 = unsafe{ ExactHierarchy::new_unchecked(&a1) };
+    let e2 = unsafe{ ExactHierarchy::new_unchecked(&a2) };
+    c.bench_function("keys_eq exact", |b| b.iter(|| keys_eq(black_box(&e1), black_box(&e2))));
+    c.bench_function("keys_eq", |b| b.iter(|| keys_eq(black_box(&a1), black_box(&a2))));
+    c.bench_function("iter keys eq", |b| b.iter(|| iter_keys_eq(black_box(&a1), black_box(&a2))));
+    c.bench_function("eq", |b| b.iter(|| black_box(&a1) == black_box(&a2)));
+}
+
+criterion_group!(benches_keys_eq, bench_keys_eq);
+criterion_main!(benches_keys_eq);
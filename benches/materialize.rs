@@ -0,0 +1,72 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection_fold, materialize, SparseArray, SparseHierarchy};
+use hi_sparse_array::utils::Take;
+
+#[derive(Clone)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_3, Data>;
+
+fn naive_insert(src: &impl SparseHierarchy<
+    LevelCount = <Array as SparseHierarchy>::LevelCount,
+    LevelMaskType = <Array as SparseHierarchy>::LevelMaskType,
+    DataType = Data
+>) -> Array {
+    let mut dst = Array::default();
+    for (i, d) in src.iter() {
+        dst.insert(i, d.take_or_clone());
+    }
+    dst
+}
+
+pub fn bench_materialize(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let arrays: Vec<_> = (0..3).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..100_000 {
+            let v = rng.gen_range(0..Array::max_range());
+            a.insert(v, Data(v+1));
+        }
+        a
+    }).collect();
+
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+
+    c.bench_function("materialize", |b| b.iter(|| {
+        let m: Array = materialize(black_box(&and));
+        m
+    }));
+    c.bench_function("naive insert", |b| b.iter(|| naive_insert(black_box(&and))));
+
+    c.bench_function("array materialize", |b| b.iter(|| {
+        let m: Array = materialize(black_box(&arrays[0]));
+        m
+    }));
+    c.bench_function("array naive insert", |b| b.iter(|| naive_insert(black_box(&arrays[0]))));
+
+    let mut keys: Vec<usize> = (0..200_000).map(|_| rng.gen_range(0..Array::max_range())).collect();
+    keys.sort();
+    c.bench_function("from_keys", |b| b.iter(||
+        Array::from_keys(black_box(&keys).iter().copied(), |i| Data(i+1))
+    ));
+    c.bench_function("keys naive insert", |b| b.iter(|| {
+        let mut a = Array::default();
+        for &i in black_box(&keys) {
+            a.insert(i, Data(i+1));
+        }
+        a
+    }));
+}
+
+criterion_group!(benches_materialize, bench_materialize);
+criterion_main!(benches_materialize);
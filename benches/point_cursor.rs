@@ -0,0 +1,55 @@
+use std::borrow::Borrow;
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{config, Empty, intersection_fold, SparseArray, SparseHierarchy};
+
+#[derive(Clone)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = SparseArray<config::width_64::depth_3, Data>;
+const RANGE: usize = 200_000;
+
+fn sum_get(h: &impl SparseHierarchy<DataType = Data>, indices: &[usize]) -> usize {
+    indices.iter().map(|&i| h.get(i).borrow().0).sum()
+}
+
+fn sum_point_cursor(h: &impl SparseHierarchy<DataType = Data>, indices: &[usize]) -> usize {
+    let mut cursor = h.point_cursor();
+    indices.iter().map(|&i| cursor.get(i).map_or(0, |d| d.borrow().0)).sum()
+}
+
+pub fn bench_point_cursor(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x9017_c0a5);
+    let arrays: Vec<_> = (0..4).map(|_| {
+        let mut a = Array::default();
+        for _ in 0..RANGE/2 {
+            let v = rng.gen_range(0..RANGE);
+            a.insert(v, Data(v+1));
+        }
+        a
+    }).collect();
+    let mut indices: Vec<_> = (0..100_000).map(|_| rng.gen_range(0..RANGE)).collect();
+    indices.sort_unstable();
+
+    let a = &arrays[0];
+    c.bench_function("array get", |b| b.iter(|| sum_get(black_box(a), &indices)));
+    c.bench_function("array point_cursor", |b| b.iter(|| sum_point_cursor(black_box(a), &indices)));
+
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d|
+        if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+    );
+    c.bench_function("intersection_fold get", |b| b.iter(|| sum_get(black_box(&and), &indices)));
+    c.bench_function("intersection_fold point_cursor", |b| b.iter(|| sum_point_cursor(black_box(&and), &indices)));
+}
+
+criterion_group!(benches_point_cursor, bench_point_cursor);
+criterion_main!(benches_point_cursor);
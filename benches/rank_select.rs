@@ -0,0 +1,36 @@
+use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{BitArray, BitBlock};
+
+fn sum_rank<B: BitBlock>(blocks: &[B], positions: &[usize]) -> usize {
+    blocks.iter().zip(positions).map(|(b, &i)| b.rank(i)).sum()
+}
+
+fn sum_select<B: BitBlock>(blocks: &[B], positions: &[usize]) -> usize {
+    blocks.iter().zip(positions)
+        .map(|(b, &n)| b.select(n % (b.count_ones() + 1)).unwrap_or(0))
+        .sum()
+}
+
+fn bench<B: BitBlock>(c: &mut Criterion, name: &str){
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x4a4b_5e1e);
+    let blocks: Vec<B> = (0..1000).map(|_| {
+        let mut block = B::zero();
+        for _ in 0..B::SIZE/2 {
+            block.set_bit::<true>(rng.gen_range(0..B::SIZE));
+        }
+        block
+    }).collect();
+    let positions: Vec<usize> = (0..1000).map(|_| rng.gen_range(0..B::SIZE)).collect();
+
+    c.bench_function(&format!("{name} rank"), |b| b.iter(|| sum_rank(black_box(&blocks), &positions)));
+    c.bench_function(&format!("{name} select"), |b| b.iter(|| sum_select(black_box(&blocks), &positions)));
+}
+
+pub fn bench_rank_select(c: &mut Criterion) {
+    bench::<u64>(c, "u64");
+    bench::<BitArray<4>>(c, "BitArray<4>");
+}
+
+criterion_group!(benches_rank_select, bench_rank_select);
+criterion_main!(benches_rank_select);
@@ -50,8 +50,9 @@ impl Empty for DataBlock{
         Self(0)
     }
 
+    // AndOp is not exact - iteration checks fold results for emptiness.
     fn is_empty(&self) -> bool {
-        todo!()
+        self.0 == 0
     }
 }
 
@@ -148,19 +149,6 @@ fn fold_iter(list: &[BlockArray]) -> impl Any {
     s
 }
 
-/*fn fold_w_empty_iter(list: &[BlockArray]) -> u64 {
-    let and_op: OrOp<u64, u64, EmptyBitBlock, DataBlock> = OrOp(PhantomData);
-    let empty = Empty::<u64, u64, EmptyBitBlock, DataBlock>::default();
-    
-    let fold = fold(and_op, &empty, list.iter());
-    
-    let mut s = 0;
-    for (_, i) in CachingBlockIter::new(&fold){
-        s += i.0;
-    }
-    s
-}*/
-
 
 fn apply_iter(array1: &BlockArray, array2: &BlockArray) -> u64 {
     let and_op = AndOp(PhantomData);
@@ -190,7 +178,6 @@ pub fn bench_iter(c: &mut Criterion) {
 
     c.bench_function("fold", |b| b.iter(|| fold_iter(black_box(&arrays))));
     c.bench_function("apply", |b| b.iter(|| apply_iter(black_box(&arrays[0]), black_box(&arrays[1]))));
-    //c.bench_function("fold_w_empty", |b| b.iter(|| fold_w_empty_iter(black_box(&arrays))));
 }
 
 criterion_group!(benches_iter, bench_iter);
@@ -0,0 +1,31 @@
+//! Evaluate lazy intersection in parallel - one root block child span per task.
+
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use hi_sparse_array::{config, intersection_fold, split_at_root, SparseArray, SparseHierarchy};
+
+type Array = SparseArray<config::width_64::depth_3, Option<u64>>;
+
+fn main(){
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x9a4a_11e1);
+    let arrays: Vec<_> = (0..4).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..100_000 {
+            let v = rng.gen_range(0..Array::max_range());
+            a.insert(v, Some(v as u64));
+        }
+        a
+    }).collect();
+
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| acc.and(*d));
+    let serial: u64 = and.iter().filter_map(|(_, d)| d).sum();
+
+    // Each view is iterated with its own traverse state.
+    let views: Vec<_> = split_at_root(&and).collect();
+    let parallel: u64 = views.par_iter()
+        .map(|view| view.iter().filter_map(|(_, d)| d).sum::<u64>())
+        .sum();
+
+    assert_eq!(serial, parallel);
+    println!("{} views, sum {}", views.len(), parallel);
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use std::collections::BTreeMap;
+use hi_sparse_array::{config, ArrayOp, SparseArray, SparseHierarchy};
+use libfuzzer_sys::fuzz_target;
+
+type Array = SparseArray<config::width_64::depth_3, Option<u8>>;
+
+fuzz_target!(|input: (Array, Vec<ArrayOp<Option<u8>>>)| {
+    let (mut array, ops) = input;
+    let mut model: BTreeMap<_, _> = array.iter().map(|(i, v)| (i, *v)).collect();
+    for op in &ops {
+        op.apply(&mut array, &mut model);
+    }
+    assert!(array.iter().map(|(i, v)| (i, *v)).eq(model.into_iter()));
+});
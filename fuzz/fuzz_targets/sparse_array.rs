@@ -0,0 +1,10 @@
+#![no_main]
+
+use hi_sparse_array::{config, SparseArray};
+use libfuzzer_sys::fuzz_target;
+
+type Array = SparseArray<config::width_64::depth_3, Option<u8>>;
+
+fuzz_target!(|array: Array| {
+    array.validate().unwrap();
+});
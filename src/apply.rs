@@ -7,6 +7,17 @@ use crate::op::BinaryOp;
 use crate::sparse_hierarchy::SparseHierarchyState;
 use crate::utils::Borrowable;
 
+/// Lazy [SparseHierarchy], that applies [BinaryOp] between two [SparseHierarchy]ies.
+///
+/// Binary ops like [intersection] and [union] are `Apply`s, so it composes with
+/// every other [SparseHierarchy]. Data is passed to [BinaryOp::data_op]
+/// as-is (by value or by reference), so folding chains can avoid cloning.
+///
+/// Constructed by [apply()].
+///
+/// [intersection]: crate::intersection
+/// [union]: crate::union
+/// [apply()]: crate::apply
 pub struct Apply<Op, B1, B2>{
     pub(crate) op: Op,
     pub(crate) s1: B1,
@@ -42,10 +53,13 @@ where
     {
         let s1 = self.s1.borrow(); 
         let s2 = self.s2.borrow();
-        self.op.lvl_op(
-            s1.level_mask(level_indices),
-            s2.level_mask(level_indices)
-        )
+        let mask1 = s1.level_mask(level_indices);
+        let mask2 = s2.level_mask(level_indices);
+        if I::Cap::VALUE == Self::LevelCount::VALUE - 1 {
+            self.op.terminal_lvl_op(mask1, mask2)
+        } else {
+            self.op.lvl_op(mask1, mask2)
+        }
     }
 
     type DataType = Op::Out;
@@ -76,6 +90,21 @@ where
     phantom_data: PhantomData<Apply<Op, B1, B2>>
 }
 
+impl<Op, B1, B2> Clone for ApplyState<Op, B1, B2>
+where
+    B1: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+    B2: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            s1: self.s1.clone(),
+            s2: self.s2.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
 impl<Op, B1, B2> SparseHierarchyState for ApplyState<Op, B1, B2>
 where
     B1: Borrowable<Borrowed: SparseHierarchy>,
@@ -114,8 +143,11 @@ where
             this.s2.borrow(), level_n, level_index
         );
         
-        let mask = this.op.lvl_op(mask1, mask2);
-        mask
+        if N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1 {
+            this.op.terminal_lvl_op(mask1, mask2)
+        } else {
+            this.op.lvl_op(mask1, mask2)
+        }
     }
 
     #[inline]
@@ -134,4 +166,129 @@ where
 
 impl<Op, B1, B2> Borrowable for Apply<Op, B1, B2>{ 
     type Borrowed = Apply<Op, B1, B2>; 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use crate::{apply, config, BitBlock, Empty, SparseArray};
+    use crate::const_utils::ConstFalse;
+    use crate::utils::Take;
+    use super::*;
+
+    thread_local! {
+        static CLONES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[derive(Debug, Default, Eq, PartialEq)]
+    struct Data(Vec<usize>);
+    impl Clone for Data{
+        fn clone(&self) -> Self {
+            CLONES.set(CLONES.get() + 1);
+            Self(self.0.clone())
+        }
+    }
+    impl Empty for Data{
+        fn empty() -> Self {
+            Self(Vec::new())
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    /// Intersection, that appends right items to the left ones.
+    struct AppendOp<Mask>(PhantomData<Mask>);
+    impl<Mask: BitBlock> BinaryOp for AppendOp<Mask>{
+        const EXACT_HIERARCHY: bool = false;
+        type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+        type LevelMask = Mask;
+
+        fn lvl_op(
+            &self,
+            left : impl Take<Self::LevelMask>,
+            right: impl Borrow<Self::LevelMask>
+        ) -> Self::LevelMask {
+            let mut acc = left.take_or_clone();
+            acc &= right.borrow();
+            acc
+        }
+
+        type Left  = Data;
+        type Right = Data;
+        type Out   = Data;
+
+        fn data_op(
+            &self,
+            left : impl Take<Self::Left>,
+            right: impl Borrow<Self::Right>
+        ) -> Self::Out {
+            let mut acc = left.take_or_clone();
+            acc.0.extend_from_slice(&right.borrow().0);
+            acc
+        }
+    }
+
+    #[test]
+    fn by_value_chain_test(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let arrays: Vec<_> = (0..4).map(|n| {
+            let mut a = Array::default();
+            for i in (0..3000).step_by(n + 1) {
+                a.insert(i, Data(vec![n]));
+            }
+            a
+        }).collect();
+        let op = || AppendOp(PhantomData);
+        let chain = apply(op(), apply(op(), apply(op(), &arrays[0], &arrays[1]), &arrays[2]), &arrays[3]);
+
+        let expected: Vec<_> = (0..3000).step_by(12).map(|i| (i, Data(vec![0, 1, 2, 3]))).collect();
+        // Only the first source item is cloned - accumulator is moved
+        // through the rest of the chain.
+        CLONES.set(0);
+        let items: Vec<_> = chain.iter().collect();
+        assert_eq!(CLONES.get(), items.len());
+        assert_eq!(items, expected);
+
+        CLONES.set(0);
+        assert_eq!(chain.get(36), Data(vec![0, 1, 2, 3]));
+        assert_eq!(CLONES.get(), 1);
+    }
+
+    #[test]
+    fn operators_chain_test(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let arrays: Vec<_> = (0..4).map(|n| {
+            let mut a = Array::default();
+            for i in (0..3000).step_by(n + 1) {
+                a.insert(i, Data(vec![n]));
+            }
+            a
+        }).collect();
+
+        // Operators move items through the chain - each resulting
+        // item is cloned once, from the source array.
+        let or = &arrays[3] | &arrays[2] | &arrays[1] | &arrays[0];
+        CLONES.set(0);
+        let items: Vec<_> = or.iter().collect();
+        assert_eq!(CLONES.get(), items.len());
+        assert_eq!(items.len(), 3000);
+        assert_eq!(items[12], (12, Data(vec![3])));
+
+        let and = &arrays[3] & &arrays[2] & &arrays[1] & &arrays[0];
+        CLONES.set(0);
+        let items: Vec<_> = and.iter().collect();
+        assert_eq!(CLONES.get(), items.len());
+        let expected: Vec<_> = (0..3000).step_by(12).map(|i| (i, Data(vec![3]))).collect();
+        assert_eq!(items, expected);
+
+        let xor = &arrays[1] ^ &arrays[2] ^ &arrays[3];
+        let expected: Vec<_> = (0..3000)
+            .filter_map(|i| {
+                let present: Vec<_> = (1..4).filter(|n| i % (n + 1) == 0).collect();
+                (present.len() % 2 == 1).then(|| (i, Data(vec![present[present.len() - 1]])))
+            })
+            .collect();
+        assert_eq!(xor.iter().collect::<Vec<_>>(), expected);
+    }
+}
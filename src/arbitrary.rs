@@ -0,0 +1,152 @@
+//! [arbitrary] support, for fuzzing.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use crate::{Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
+
+/// Max number of inserts of arbitrary [SparseArray].
+const MAX_LEN: usize = 1024;
+
+/// Arbitrary `index` wrapped into `0..=max_index` range, as `K`.
+///
+/// `None` if it does not fit `K`.
+#[inline]
+fn wrap_key<K: Key>(index: usize, max_index: usize) -> Option<K> {
+    let index = match max_index.checked_add(1) {
+        Some(range) => index % range,
+        None => index
+    };
+    K::try_from(index).ok()
+}
+
+/// Up to 1024 in-range items are inserted, then some of them
+/// may be removed.
+impl<'a, Levels, Data, K> Arbitrary<'a> for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Arbitrary<'a>,
+    K: Key,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut array = Self::default();
+        let len = u.arbitrary_len::<(usize, Data)>()?.min(MAX_LEN);
+        let mut keys = Vec::with_capacity(len);
+        for _ in 0..len {
+            let index = u.int_in_range(0..=Self::max_range())?;
+            let value = Data::arbitrary(u)?;
+            if let Ok(key) = K::try_from(index) {
+                array.insert(key, value);
+                keys.push(key);
+            }
+        }
+
+        let removals = u.arbitrary_len::<u32>()?.min(keys.len());
+        for _ in 0..removals {
+            let key = keys[u.choose_index(keys.len())?];
+            array.remove(key);
+        }
+        Ok(array)
+    }
+}
+
+/// [SparseArray] operation, for stateful fuzzing against [BTreeMap] model.
+///
+/// # Example
+///
+/// cargo-fuzz target:
+///
+/// ```no_run
+/// # use std::collections::BTreeMap;
+/// # use hi_sparse_array::{config, ArrayOp, SparseArray, SparseHierarchy};
+/// # macro_rules! fuzz_target { (|$input:ident: $t:ty| $body:block) => { fn fuzz($input: $t) $body }; }
+/// type Array = SparseArray<config::width_64::depth_3, Option<u8>>;
+/// fuzz_target!(|input: (Array, Vec<ArrayOp<Option<u8>>>)| {
+///     let (mut array, ops) = input;
+///     let mut model = array.iter().map(|(i, v)| (i, *v)).collect();
+///     for op in &ops {
+///         op.apply(&mut array, &mut model);
+///     }
+/// });
+/// ```
+#[derive(Arbitrary, Clone, Debug, Eq, PartialEq)]
+pub enum ArrayOp<Data> {
+    Insert(usize, Data),
+    Remove(usize),
+    Get(usize),
+    Clear,
+}
+
+impl<Data> ArrayOp<Data>
+where
+    Data: Empty + Clone + Debug + PartialEq
+{
+    /// Applies op to both `array` and its `model`, and checks that results
+    /// match, and `array` passes [validate()].
+    ///
+    /// Indices are wrapped into `array`'s [max_range()]. Ops with indices
+    /// that does not fit `K` are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics on mismatch.
+    ///
+    /// [validate()]: SparseArray::validate
+    /// [max_range()]: SparseHierarchy::max_range
+    pub fn apply<Levels, K>(&self, array: &mut SparseArray<Levels, Data, K>, model: &mut BTreeMap<usize, Data>)
+    where
+        Levels: SparseArrayLevels,
+        K: Key,
+    {
+        let max_index = SparseArray::<Levels, Data, K>::max_range();
+        let key = |index: usize| wrap_key::<K>(index, max_index).map(|key|
+            (key, wrap_key::<usize>(index, max_index).unwrap())
+        );
+        match self {
+            Self::Insert(index, value) => if let Some((key, index)) = key(*index) {
+                array.insert(key, value.clone());
+                model.insert(index, value.clone());
+            },
+            Self::Remove(index) => if let Some((key, index)) = key(*index) {
+                assert_eq!(array.remove(key), model.remove(&index), "{self:?}");
+            },
+            Self::Get(index) => if let Some((key, index)) = key(*index) {
+                assert_eq!(array.try_get(key), model.get(&index), "{self:?}");
+            },
+            Self::Clear => {
+                array.clear();
+                model.clear();
+            }
+        }
+        if let Err(e) = array.validate() {
+            panic!("{e} after {self:?}");
+        }
+        assert_eq!(array.len(), model.len(), "{self:?}");
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::config;
+    use super::*;
+
+    #[test]
+    fn op_script_test(){
+        type Array = SparseArray<config::width_64::depth_3, Option<u8>>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xa4b_17a4);
+        for _ in 0..100 {
+            let bytes: Vec<u8> = (0..rng.gen_range(0..20000)).map(|_| rng.gen()).collect();
+            let mut u = Unstructured::new(&bytes);
+            let Ok((mut array, ops)) = <(Array, Vec<ArrayOp<Option<u8>>>)>::arbitrary(&mut u) else {
+                continue;
+            };
+            array.validate().unwrap();
+            let mut model: BTreeMap<_, _> = array.iter().map(|(i, v)| (i, *v)).collect();
+            for op in &ops {
+                op.apply(&mut array, &mut model);
+            }
+            assert!(array.iter().map(|(i, v)| (i, *v)).eq(model.into_iter()));
+        }
+    }
+}
@@ -0,0 +1,582 @@
+//! [rkyv] zero-copy serialization of [SparseArray].
+//!
+//! Archived container is [ArchivedSparseArray] - read-only [SparseHierarchy],
+//! that works directly over archive bytes. Block pointers are not archived -
+//! levels are stored breadth-first, and children are addressed by mask bit rank:
+//! `n`th raised bit of a level points to block `n + 1` of the next level
+//! (data item `n + 1` for the last level). Block/item 0 is the empty one.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::marker::PhantomData;
+use rkyv::{Archive, Archived, Deserialize, Place, Portable, Serialize};
+use rkyv::bytecheck::{CheckBytes, Verify};
+use rkyv::munge::munge;
+use rkyv::option::ArchivedOption;
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use crate::{bit_utils, BitBlock, CapacityExceeded, Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstArrayType};
+use crate::sparse_array::InsertBranch;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::Borrowable;
+
+/// u64 words per mask of `M`.
+#[inline]
+const fn mask_words<M: BitBlock>() -> usize {
+    M::SIZE.div_ceil(64)
+}
+
+/// Archived level.
+#[derive(Archive, Serialize)]
+struct Level {
+    /// Mask words of each block, least significant first.
+    masks: Vec<u64>,
+    /// Number of raised bits in blocks before each block. `blocks + 1` long.
+    ranks: Vec<u32>,
+}
+
+/// Inconsistent [ArchivedSparseArray], found by [validate()].
+///
+/// `level` is hierarchy level number, `block_index` - block index within that level.
+///
+/// [validate()]: ArchivedSparseArray::validate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ArchiveViolation {
+    /// Number of levels does not match configuration.
+    LevelCount { levels: usize },
+    /// Level masks/ranks lengths do not match its block count.
+    LevelLength { level: usize },
+    /// Block 0 of non-root level is not empty.
+    ZeroBlockNotEmpty { level: usize },
+    /// Mask bits above configuration's block size are raised.
+    MaskOutOfRange { level: usize, block_index: usize },
+    /// Block rank does not match mask population of the blocks before it.
+    RankMismatch { level: usize, block_index: usize },
+    /// Data storage length does not match last level population.
+    DataLength { values: usize, expected: usize },
+    /// Data item 0 is not empty.
+    ZeroDataNotEmpty,
+}
+
+impl fmt::Display for ArchiveViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ArchiveViolation::*;
+        match *self {
+            LevelCount { levels } => write!(f,
+                "archive has {levels} levels, configuration differs"),
+            LevelLength { level } => write!(f,
+                "level {level}: masks and ranks lengths do not match block count"),
+            ZeroBlockNotEmpty { level } => write!(f,
+                "level {level} block 0: not empty"),
+            MaskOutOfRange { level, block_index } => write!(f,
+                "level {level} block {block_index}: mask bits out of block range"),
+            RankMismatch { level, block_index } => write!(f,
+                "level {level} block {block_index}: rank does not match mask population"),
+            DataLength { values, expected } => write!(f,
+                "archive has {values} data items, last level addresses {expected}"),
+            ZeroDataNotEmpty => write!(f,
+                "data 0: not empty"),
+        }
+    }
+}
+
+impl StdError for ArchiveViolation {}
+
+/// Archived [SparseArray].
+///
+/// Read-only [SparseHierarchy], with the same configuration as the source
+/// [SparseArray]. Get it with validating [rkyv::access] - archive is checked
+/// with [validate()], so out-of-bounds reads are not possible even
+/// with untrusted bytes.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, ArchivedSparseArray, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_3, Option<u32>>;
+/// let mut a = Array::default();
+/// a.insert(10, Some(1));
+/// a.insert(5000, Some(2));
+///
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&a).unwrap();
+/// let archived = rkyv::access::<
+///     ArchivedSparseArray<config::width_64::depth_3, Option<u32>>,
+///     rkyv::rancor::Error
+/// >(&bytes).unwrap();
+/// assert_eq!(archived.get(5000).as_ref().map(|v| v.to_native()), Some(2));
+/// assert!(archived.get(11).is_none());
+///
+/// let b: Array = rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+/// assert!(a == b);
+/// ```
+///
+/// [validate()]: Self::validate
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck, verify)]
+#[repr(C)]
+pub struct ArchivedSparseArray<Levels, Data: Archive, K = usize>{
+    levels: ArchivedVec<ArchivedLevel>,
+    values: ArchivedVec<Data::Archived>,
+    phantom_data: PhantomData<fn() -> (Levels, K)>
+}
+
+/// [SparseArray] [Archive::Resolver].
+pub struct SparseArrayResolver{
+    levels: VecResolver,
+    values: VecResolver,
+    values_len: usize,
+}
+
+impl<Levels, Data, K> Archive for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Archive,
+    K: Key,
+{
+    type Archived = ArchivedSparseArray<Levels, Data, K>;
+    type Resolver = SparseArrayResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedSparseArray{ levels, values, .. } = out);
+        ArchivedVec::resolve_from_len(Levels::LevelCount::VALUE, resolver.levels, levels);
+        ArchivedVec::resolve_from_len(resolver.values_len, resolver.values, values);
+    }
+}
+
+impl<Levels, Data, K, S> Serialize<S> for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Serialize<S>,
+    K: Key,
+    S: Fallible<Error: Source> + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let words = mask_words::<Levels::Mask>();
+        let last_level = Levels::LevelCount::VALUE - 1;
+
+        // Data items are stored in key order.
+        let mut data_order = vec![0];
+        let mut levels = Vec::with_capacity(Levels::LevelCount::VALUE);
+        for (level_n, level) in self.walk_levels().into_iter().enumerate() {
+            let (mut masks, mut ranks) = if level_n == 0 {
+                (Vec::new(), vec![0])
+            } else {
+                (vec![0; words], vec![0, 0])
+            };
+            let mut rank = 0;
+            for block in level.blocks {
+                let block_masks = masks.len();
+                masks.resize(block_masks + words, 0);
+                for &(index, child) in &block.children {
+                    masks[block_masks + index / 64] |= 1 << (index % 64);
+                    if level_n == last_level {
+                        data_order.push(child);
+                    }
+                }
+                rank += block.children.len();
+                let rank = u32::try_from(rank).map_err(|_|
+                    S::Error::new(CapacityExceeded{ max_block_index: u32::MAX as usize })
+                )?;
+                ranks.push(rank);
+            }
+            levels.push(Level{ masks, ranks });
+        }
+
+        Ok(SparseArrayResolver{
+            levels: ArchivedVec::serialize_from_slice(&levels, serializer)?,
+            values: ArchivedVec::serialize_from_iter::<Data, _, _>(
+                data_order.iter().map(|&i| &self.values[i]), serializer
+            )?,
+            values_len: data_order.len(),
+        })
+    }
+}
+
+impl<Levels, Data, K, D> Deserialize<SparseArray<Levels, Data, K>, D> for ArchivedSparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Archive<Archived: Empty + Deserialize<Data, D>>,
+    K: Key,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<SparseArray<Levels, Data, K>, D::Error> {
+        let mut array = SparseArray::default();
+        array.values.reserve(self.values.len());
+        let mut branch = InsertBranch::default();
+        for (index, item) in self.iter() {
+            let value = item.deserialize(deserializer)?;
+            // Keys are ascending.
+            unsafe{ array.insert_with_branch(index, value, &mut branch); }
+        }
+        Ok(array)
+    }
+}
+
+unsafe impl<Levels, Data, K, C> Verify<C> for ArchivedSparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Archive<Archived: Empty>,
+    C: Fallible<Error: Source> + ?Sized,
+{
+    fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+        self.validate().map_err(C::Error::new)
+    }
+}
+
+impl<Levels, Data, K> ArchivedSparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Archive<Archived: Empty>,
+{
+    /// Checks that archive is consistent with configuration, and every
+    /// mask bit addresses existing block or data item.
+    ///
+    /// Called by validating [rkyv::access].
+    pub fn validate(&self) -> Result<(), ArchiveViolation> {
+        use ArchiveViolation::*;
+
+        let levels = self.levels.as_slice();
+        if levels.len() != Levels::LevelCount::VALUE {
+            return Err(LevelCount{ levels: levels.len() });
+        }
+
+        let words = mask_words::<Levels::Mask>();
+        let tail_bits = Levels::Mask::SIZE % 64;
+        let mut blocks_len: usize = 1;
+        for (level_n, level) in levels.iter().enumerate() {
+            let masks = level.masks.as_slice();
+            let ranks = level.ranks.as_slice();
+            if Some(masks.len()) != blocks_len.checked_mul(words) || ranks.len() != blocks_len + 1 {
+                return Err(LevelLength{ level: level_n });
+            }
+            if level_n != 0 && masks[..words].iter().any(|w| w.to_native() != 0) {
+                return Err(ZeroBlockNotEmpty{ level: level_n });
+            }
+            if ranks[0].to_native() != 0 {
+                return Err(RankMismatch{ level: level_n, block_index: 0 });
+            }
+            for (block_index, mask) in masks.chunks_exact(words).enumerate() {
+                let last = mask[words - 1].to_native();
+                if tail_bits != 0 && last >> tail_bits != 0 {
+                    return Err(MaskOutOfRange{ level: level_n, block_index });
+                }
+                let population: u64 = mask.iter().map(|w| w.to_native().count_ones() as u64).sum();
+                let rank = ranks[block_index].to_native() as u64 + population;
+                if ranks[block_index + 1].to_native() as u64 != rank {
+                    return Err(RankMismatch{ level: level_n, block_index: block_index + 1 });
+                }
+            }
+            // Children + empty block/item.
+            blocks_len = ranks[blocks_len].to_native() as usize + 1;
+        }
+
+        let values = self.values.as_slice();
+        if values.len() != blocks_len {
+            return Err(DataLength{ values: values.len(), expected: blocks_len });
+        }
+        if !values[0].is_empty() {
+            return Err(ZeroDataNotEmpty);
+        }
+        Ok(())
+    }
+
+    /// Child block index / data index of `block_index` block's `index` bit.
+    /// 0 if bit is not raised.
+    #[inline]
+    unsafe fn child(&self, level_n: usize, block_index: usize, index: usize) -> usize {
+        let words = mask_words::<Levels::Mask>();
+        let level = self.levels.as_slice().get_unchecked(level_n);
+        let masks = level.masks.as_slice().get_unchecked(block_index * words..);
+        let word = masks.get_unchecked(index / 64).to_native();
+        let bit = index % 64;
+        if word & (1 << bit) == 0 {
+            return 0;
+        }
+        let mut rank = level.ranks.as_slice().get_unchecked(block_index).to_native() as usize;
+        for w in masks.get_unchecked(..index / 64) {
+            rank += w.to_native().count_ones() as usize;
+        }
+        rank + bit_utils::rank(word, bit) + 1
+    }
+
+    #[inline]
+    unsafe fn mask(&self, level_n: usize, block_index: usize) -> Levels::Mask {
+        let words = mask_words::<Levels::Mask>();
+        let level = self.levels.as_slice().get_unchecked(level_n);
+        let masks = level.masks.as_slice().get_unchecked(block_index * words..);
+        let mut mask = Levels::Mask::zero();
+        for (i, w) in masks.get_unchecked(..words).iter().enumerate() {
+            let _ = bit_utils::traverse_one_bits(w.to_native(), |bit| {
+                mask.set_bit::<true>(i * 64 + bit);
+                std::ops::ControlFlow::<()>::Continue(())
+            });
+        }
+        mask
+    }
+
+    /// Block index of `level_indices` path's last level.
+    #[inline]
+    unsafe fn block_index<I: ConstArray<Item=usize>>(&self, level_indices: I) -> usize {
+        let mut block_index = 0;
+        for (level_n, &index) in level_indices.as_ref().iter().enumerate() {
+            block_index = self.child(level_n, block_index, index);
+        }
+        block_index
+    }
+}
+
+impl<Levels, Data, K> SparseHierarchy for ArchivedSparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Archive<Archived: Empty>,
+{
+    const EXACT_HIERARCHY: bool = false;
+
+    type LevelCount = Levels::LevelCount;
+    type LevelMaskType = Levels::Mask;
+    type LevelMask<'a> = Levels::Mask where Self: 'a;
+
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.mask(I::Cap::VALUE, self.block_index(level_indices))
+    }
+
+    type DataType = Archived<Data>;
+    type Data<'a> = &'a Archived<Data> where Self: 'a;
+
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let data_index = self.block_index(level_indices);
+        self.values.as_slice().get_unchecked(data_index)
+    }
+
+    /// Number of archived items - O(1). All archived items are counted,
+    /// even empty ones.
+    #[inline]
+    fn count_keys(&self) -> usize {
+        self.values.len() - 1
+    }
+
+    #[inline]
+    fn iter_len(&self) -> Option<usize> {
+        Some(self.values.len() - 1)
+    }
+
+    type State = ArchivedSparseArrayState<Levels, Data, K>;
+}
+
+pub struct ArchivedSparseArrayState<Levels, Data, K = usize>
+where
+    Levels: SparseArrayLevels
+{
+    /// [usize; Levels::LevelCount]
+    ///
+    /// Selected block index of each level.
+    block_indices: ConstArrayType<usize, Levels::LevelCount>,
+    phantom_data: PhantomData<fn() -> (Data, K)>
+}
+
+impl<Levels, Data, K> Clone for ArchivedSparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            block_indices: Array::from_fn(|i| self.block_indices.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<Levels, Data, K> SparseHierarchyState for ArchivedSparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Archive<Archived: Empty>,
+{
+    type This = ArchivedSparseArray<Levels, Data, K>;
+
+    #[inline]
+    fn new(_: &Self::This) -> Self {
+        Self{
+            block_indices: Array::from_fn(|_| 0),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        let block_indices = self.block_indices.as_mut();
+        let block_index = if N::VALUE == 0 {
+            0
+        } else {
+            let parent = *block_indices.get_unchecked(N::VALUE - 1);
+            this.child(N::VALUE - 1, parent, level_index)
+        };
+        *block_indices.get_unchecked_mut(level_n.value()) = block_index;
+        this.mask(N::VALUE, block_index)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let last_level = Levels::LevelCount::VALUE - 1;
+        let block_index = *self.block_indices.as_ref().get_unchecked(last_level);
+        let data_index = this.child(last_level, block_index, level_index);
+        this.values.as_slice().get_unchecked(data_index)
+    }
+}
+
+impl<Levels, Data: Archive, K> Borrowable for ArchivedSparseArray<Levels, Data, K>{
+    type Borrowed = ArchivedSparseArray<Levels, Data, K>;
+}
+
+impl<T> Empty for ArchivedOption<T>{
+    #[inline]
+    fn empty() -> Self {
+        ArchivedOption::None
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.is_none()
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use rkyv::rancor;
+    use crate::config;
+    use super::*;
+
+    type Levels = config::width_64::depth_3;
+    type Archived = ArchivedSparseArray<Levels, Option<u32>>;
+
+    #[test]
+    fn round_trip_test(){
+        fn do_test<Levels: SparseArrayLevels>(){
+            type Array<Levels> = SparseArray<Levels, Option<u32>>;
+            let range = Array::<Levels>::max_range();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0xa4c_1e7e);
+            let mut a = Array::<Levels>::default();
+            for _ in 0..3000 {
+                let i = rng.gen_range(0..=range);
+                a.insert(i, Some(i as u32));
+            }
+            for _ in 0..1000 {
+                a.remove(rng.gen_range(0..=range));
+            }
+            // Stored empty item.
+            a.insert(7, None);
+            
+            let bytes = rkyv::to_bytes::<rancor::Error>(&a).unwrap();
+            let archived = rkyv::access::<ArchivedSparseArray<Levels, Option<u32>>, rancor::Error>(&bytes).unwrap();
+
+            let native = |v: &ArchivedOption<rkyv::rend::u32_le>| v.as_ref().map(|v| v.to_native());
+            assert_eq!(archived.count_keys(), a.count_keys());
+            assert!(archived.iter().map(|(i, v)| (i, native(v))).eq(a.iter().map(|(i, v)| (i, *v))));
+
+            for _ in 0..10000 {
+                let i = rng.gen_range(0..=range);
+                assert_eq!(native(archived.get(i)), *a.get(i));
+                assert_eq!(archived.may_contain(i), a.may_contain(i));
+            }
+            let mut cursor = archived.point_cursor();
+            for (i, v) in a.iter() {
+                assert_eq!(cursor.get(i).map(native), Some(*v));
+            }
+
+            let b: Array<Levels> = rkyv::deserialize::<_, rancor::Error>(archived).unwrap();
+            assert!(b == a);
+            assert!(b.validate().is_ok());
+
+            let empty = rkyv::to_bytes::<rancor::Error>(&Array::<Levels>::default()).unwrap();
+            let archived = rkyv::access::<ArchivedSparseArray<Levels, Option<u32>>, rancor::Error>(&empty).unwrap();
+            assert_eq!(archived.iter().count(), 0);
+            assert!(archived.get(10).is_none());
+        }
+        do_test::<config::width_64::depth_3>();
+        do_test::<config::width_32::depth_3>();
+        do_test::<config::width_512::depth_2>();
+        do_test::<config::sbo::width_64::depth_4>();
+    }
+
+    #[test]
+    fn validation_test(){
+        /// Same layout as [ArchivedSparseArray].
+        #[derive(Archive, Serialize)]
+        struct Raw {
+            levels: Vec<Level>,
+            values: Vec<Option<u32>>,
+        }
+        let raw = |levels: Vec<(Vec<u64>, Vec<u32>)>, values: Vec<Option<u32>>| {
+            let levels = levels.into_iter().map(|(masks, ranks)| Level{ masks, ranks }).collect();
+            let bytes = rkyv::to_bytes::<rancor::Error>(&Raw{ levels, values }).unwrap();
+            let archived = unsafe{ rkyv::access_unchecked::<Archived>(&bytes) };
+            (archived.validate(), rkyv::access::<Archived, rancor::Error>(&bytes).is_ok())
+        };
+
+        // 1 item at index 64*64 + 64 + 1.
+        let valid = || vec![
+            (vec![0b10], vec![0, 1]),
+            (vec![0, 0b10], vec![0, 0, 1]),
+            (vec![0, 0b10], vec![0, 0, 1]),
+        ];
+        let values = || vec![None, Some(1)];
+        assert_eq!(raw(valid(), values()), (Ok(()), true));
+
+        let mut levels = valid();
+        levels.pop();
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::LevelCount{ levels: 2 }));
+
+        let mut levels = valid();
+        levels[1].0.push(0);
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::LevelLength{ level: 1 }));
+
+        let mut levels = valid();
+        levels[2].0[0] = 1;
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::ZeroBlockNotEmpty{ level: 2 }));
+
+        // Mask points to more children, than next level has.
+        let mut levels = valid();
+        levels[1].0[1] = 0b110;
+        assert_eq!(raw(levels, values()), (Err(ArchiveViolation::RankMismatch{ level: 1, block_index: 2 }), false));
+        let mut levels = valid();
+        levels[1].0[1] = 0b110;
+        levels[1].1[2] = 2;
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::LevelLength{ level: 2 }));
+        let mut levels = valid();
+        levels[2].0[1] = 0b110;
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::RankMismatch{ level: 2, block_index: 2 }));
+        let mut levels = valid();
+        levels[2].0[1] = 0b110;
+        levels[2].1[2] = 2;
+        assert_eq!(raw(levels, values()).0, Err(ArchiveViolation::DataLength{ values: 2, expected: 3 }));
+
+        assert_eq!(raw(valid(), vec![Some(0), Some(1)]).0, Err(ArchiveViolation::ZeroDataNotEmpty));
+
+        // Mask bits above block size.
+        type NarrowArchived = ArchivedSparseArray<config::width_32::depth_1, Option<u32>>;
+        let bytes = rkyv::to_bytes::<rancor::Error>(&Raw{
+            levels: vec![Level{ masks: vec![1 << 40], ranks: vec![0, 1] }],
+            values: values()
+        }).unwrap();
+        let archived = unsafe{ rkyv::access_unchecked::<NarrowArchived>(&bytes) };
+        assert_eq!(archived.validate(), Err(ArchiveViolation::MaskOutOfRange{ level: 0, block_index: 0 }));
+    }
+}
@@ -1,8 +1,11 @@
+use std::iter::Rev;
 use std::mem;
+use std::mem::size_of;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, ControlFlow};
 use crate::bit_queue::{ArrayBitQueue, BitQueue, EmptyBitQueue, PrimitiveBitQueue};
 use crate::bit_utils;
 use crate::utils::array::Array;
+use crate::utils::primitive::Primitive;
 
 pub trait BitBlock
     : Eq
@@ -54,13 +57,80 @@ pub trait BitBlock
         }
     }
     
-    /// Returns [Break] if traverse was interrupted (`f` returns [Break]).
+    /// Number of raised bits.
+    #[inline]
+    fn count_ones(&self) -> usize {
+        self.as_array().as_ref().iter()
+            .map(|&primitive| primitive.count_ones() as usize)
+            .sum()
+    }
+    
+    /// Index of the lowest raised bit. `None` if block is zero.
+    #[inline]
+    fn first_set_bit(&self) -> Option<usize> {
+        let word_bits = word_bits::<Self>();
+        self.as_array().as_ref().iter().enumerate()
+            .find(|(_, &primitive)| !primitive.is_zero())
+            .map(|(i, &primitive)| i*word_bits + primitive.trailing_zeros() as usize)
+    }
+
+    /// Index of the highest raised bit. `None` if block is zero.
+    #[inline]
+    fn last_set_bit(&self) -> Option<usize> {
+        let word_bits = word_bits::<Self>();
+        self.as_array().as_ref().iter().enumerate().rev()
+            .find(|(_, &primitive)| !primitive.is_zero())
+            .map(|(i, &primitive)| i*word_bits + word_bits - 1 - primitive.leading_zeros() as usize)
+    }
+
+    /// Number of raised bits before `bit_index`.
+    /// 
+    /// `bit_index` must be <= [SIZE].
+    /// 
+    /// [SIZE]: Self::SIZE
+    #[inline]
+    fn rank(&self, bit_index: usize) -> usize {
+        debug_assert!(bit_index <= Self::SIZE);
+        let word_bits = word_bits::<Self>();
+        let array = self.as_array().as_ref();
+        let word_index = bit_index / word_bits;
+        let mut rank = array[..word_index].iter()
+            .map(|&primitive| primitive.count_ones() as usize)
+            .sum();
+        if let Some(&primitive) = array.get(word_index) {
+            rank += bit_utils::rank(primitive, bit_index % word_bits);
+        }
+        rank
+    }
+
+    /// Position of `n`th (from 0) raised bit.
+    /// 
+    /// Returns `None` if there is less than `n+1` raised bits.
+    #[inline]
+    fn select(&self, mut n: usize) -> Option<usize> {
+        let word_bits = word_bits::<Self>();
+        for (i, &primitive) in self.as_array().as_ref().iter().enumerate() {
+            let population = primitive.count_ones() as usize;
+            if n < population {
+                return Some(i*word_bits + bit_utils::select(primitive, n));
+            }
+            n -= population;
+        }
+        None
+    }
+
+    /// Calls `f` for each set bit, in ascending order.
+    /// 
+    /// Stops at the first [Break] returned by `f` and passes it through.
+    /// Unlike [into_bits_iter], no iterator state is materialized -
+    /// words are scanned in registers, one at a time.
     /// 
     /// [Break]: ControlFlow::Break
+    /// [into_bits_iter]: Self::into_bits_iter
     #[inline]
-    fn traverse_bits<F>(&self, f: F) -> ControlFlow<()>
+    fn traverse_bits<B, F>(&self, f: F) -> ControlFlow<B>
     where
-        F: FnMut(usize) -> ControlFlow<()>
+        F: FnMut(usize) -> ControlFlow<B>
     {
         let array = self.as_array().as_ref();
         if Self::Array::CAP == 1 {
@@ -74,11 +144,24 @@ pub trait BitBlock
     type BitsIter: BitQueue;
     fn into_bits_iter(self) -> Self::BitsIter;
 
-    type Array: Array<Item = u64>;
+    /// Bits in descending order.
+    #[inline]
+    fn into_bits_iter_rev(self) -> Rev<Self::BitsIter> {
+        self.into_bits_iter().rev()
+    }
+
+    /// Mask as array of words, in bit order.
+    type Array: Array<Item: Primitive>;
     fn as_array(&self) -> &Self::Array;
     fn as_array_mut(&mut self) -> &mut Self::Array;
 }
 
+/// Size of [BitBlock::Array] word in bits.
+#[inline]
+pub(crate) const fn word_bits<B: BitBlock>() -> usize {
+    size_of::<<B::Array as Array>::Item>() * 8
+}
+
 impl BitBlock for u64{
     const SIZE: usize = 64;
 
@@ -106,6 +189,206 @@ impl BitBlock for u64{
     }
 }
 
+/// 32-bit mask, for tiny dense keyspaces.
+impl BitBlock for u32{
+    const SIZE: usize = 32;
+
+    fn zero() -> Self { 0 }
+
+    type BitsIter = PrimitiveBitQueue<u32>;
+    #[inline]
+    fn into_bits_iter(self) -> Self::BitsIter {
+        PrimitiveBitQueue::new(self)
+    }
+
+    type Array = [u32; 1];
+    #[inline]
+    fn as_array(&self) -> &Self::Array {
+        unsafe {
+            mem::transmute::<&u32, &[u32; 1]>(self)
+        }
+    }
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut Self::Array {
+        unsafe {
+            mem::transmute::<&mut u32, &mut [u32; 1]>(self)
+        }
+    }
+}
+
+/// 16-bit mask, for tiny dense keyspaces.
+impl BitBlock for u16{
+    const SIZE: usize = 16;
+
+    fn zero() -> Self { 0 }
+
+    type BitsIter = PrimitiveBitQueue<u16>;
+    #[inline]
+    fn into_bits_iter(self) -> Self::BitsIter {
+        PrimitiveBitQueue::new(self)
+    }
+
+    type Array = [u16; 1];
+    #[inline]
+    fn as_array(&self) -> &Self::Array {
+        unsafe {
+            mem::transmute::<&u16, &[u16; 1]>(self)
+        }
+    }
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut Self::Array {
+        unsafe {
+            mem::transmute::<&mut u16, &mut [u16; 1]>(self)
+        }
+    }
+}
+
+/// Native 128-bit mask - two u64 operations on 64-bit targets.
+///
+/// Little-endian only - [as_array()] words must follow bit order.
+///
+/// [as_array()]: BitBlock::as_array
+#[cfg(target_endian = "little")]
+impl BitBlock for u128{
+    const SIZE: usize = 128;
+
+    fn zero() -> Self { 0 }
+
+    #[inline]
+    fn set_bit<const BIT: bool>(&mut self, bit_index: usize) -> bool {
+        unsafe{ bit_utils::set_bit_unchecked::<BIT, _>(self, bit_index) }
+    }
+
+    #[inline]
+    fn get_bit(&self, bit_index: usize) -> bool {
+        unsafe{ bit_utils::get_bit_unchecked(*self, bit_index) }
+    }
+
+    #[inline]
+    fn count_ones(&self) -> usize {
+        u128::count_ones(*self) as usize
+    }
+
+    #[inline]
+    fn first_set_bit(&self) -> Option<usize> {
+        (*self != 0).then(|| self.trailing_zeros() as usize)
+    }
+
+    #[inline]
+    fn last_set_bit(&self) -> Option<usize> {
+        (*self != 0).then(|| 127 - self.leading_zeros() as usize)
+    }
+
+    #[inline]
+    fn traverse_bits<B, F>(&self, f: F) -> ControlFlow<B>
+    where
+        F: FnMut(usize) -> ControlFlow<B>
+    {
+        bit_utils::traverse_one_bits(*self, f)
+    }
+
+    type BitsIter = PrimitiveBitQueue<u128>;
+    #[inline]
+    fn into_bits_iter(self) -> Self::BitsIter {
+        PrimitiveBitQueue::new(self)
+    }
+
+    type Array = [u64; 2];
+    #[inline]
+    fn as_array(&self) -> &Self::Array {
+        unsafe {
+            mem::transmute::<&u128, &[u64; 2]>(self)
+        }
+    }
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut Self::Array {
+        unsafe {
+            mem::transmute::<&mut u128, &mut [u64; 2]>(self)
+        }
+    }
+}
+
+/// `[u64; N]` mask, without SIMD.
+///
+/// Bitwise operations are element-wise. Works for any `N`, so allows
+/// level widths that have no SIMD type, like [width_512].
+///
+/// [width_512]: crate::config::width_512
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct BitArray<const N: usize>(pub [u64; N]);
+
+impl<const N: usize> BitAnd for BitArray<N>{
+    type Output = Self;
+
+    #[inline]
+    fn bitand(mut self, rhs: Self) -> Self::Output {
+        self &= &rhs;
+        self
+    }
+}
+
+impl<const N: usize> BitAndAssign<&Self> for BitArray<N>{
+    #[inline]
+    fn bitand_assign(&mut self, rhs: &Self) {
+        for (l, r) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *l &= *r;
+        }
+    }
+}
+
+impl<const N: usize> BitOr for BitArray<N>{
+    type Output = Self;
+
+    #[inline]
+    fn bitor(mut self, rhs: Self) -> Self::Output {
+        self |= &rhs;
+        self
+    }
+}
+
+impl<const N: usize> BitOrAssign<&Self> for BitArray<N>{
+    #[inline]
+    fn bitor_assign(&mut self, rhs: &Self) {
+        for (l, r) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *l |= *r;
+        }
+    }
+}
+
+impl<const N: usize> BitBlock for BitArray<N>{
+    const SIZE: usize = N * 64;
+
+    #[inline]
+    fn zero() -> Self {
+        Self([0; N])
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    type BitsIter = ArrayBitQueue<u64, N>;
+
+    #[inline]
+    fn into_bits_iter(self) -> Self::BitsIter {
+        ArrayBitQueue::new(self.0)
+    }
+
+    type Array = [u64; N];
+
+    #[inline]
+    fn as_array(&self) -> &Self::Array {
+        &self.0
+    }
+
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut Self::Array {
+        &mut self.0
+    }
+}
+
 #[cfg(feature = "simd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
 impl BitBlock for wide::u64x2{
@@ -164,4 +447,244 @@ impl BitBlock for wide::u64x4{
     fn as_array_mut(&mut self) -> &mut Self::Array {
         self.as_array_mut()
     }
-}
\ No newline at end of file
+}
+
+/// 512-bit SIMD mask - pair of [wide::u64x4].
+///
+/// `wide` has no `u64x8`. AND/OR go through [wide::u64x4] lanes,
+/// bit access and bit iteration are per-u64 scalar, as for other SIMD masks.
+#[cfg(feature = "simd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+#[repr(C, align(64))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct U64x8([wide::u64x4; 2]);
+
+#[cfg(feature = "simd")]
+impl BitAnd for U64x8{
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self([self.0[0] & rhs.0[0], self.0[1] & rhs.0[1]])
+    }
+}
+
+#[cfg(feature = "simd")]
+impl BitAndAssign<&Self> for U64x8{
+    #[inline]
+    fn bitand_assign(&mut self, rhs: &Self) {
+        self.0[0] &= &rhs.0[0];
+        self.0[1] &= &rhs.0[1];
+    }
+}
+
+#[cfg(feature = "simd")]
+impl BitOr for U64x8{
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self([self.0[0] | rhs.0[0], self.0[1] | rhs.0[1]])
+    }
+}
+
+#[cfg(feature = "simd")]
+impl BitOrAssign<&Self> for U64x8{
+    #[inline]
+    fn bitor_assign(&mut self, rhs: &Self) {
+        self.0[0] |= &rhs.0[0];
+        self.0[1] |= &rhs.0[1];
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+impl BitBlock for U64x8{
+    const SIZE: usize = 512;
+
+    #[inline]
+    fn zero() -> Self {
+        Self([wide::u64x4::ZERO; 2])
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        (self.0[0] | self.0[1]) == wide::u64x4::ZERO
+    }
+
+    type BitsIter = ArrayBitQueue<u64, 8>;
+
+    #[inline]
+    fn into_bits_iter(self) -> Self::BitsIter {
+        ArrayBitQueue::new(*self.as_array())
+    }
+
+    type Array = [u64; 8];
+
+    #[inline]
+    fn as_array(&self) -> &Self::Array {
+        // repr(C) pair of [u64; 4]
+        unsafe {
+            mem::transmute::<&U64x8, &[u64; 8]>(self)
+        }
+    }
+
+    #[inline]
+    fn as_array_mut(&mut self) -> &mut Self::Array {
+        unsafe {
+            mem::transmute::<&mut U64x8, &mut [u64; 8]>(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use super::*;
+
+    fn do_test<B: BitBlock>(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5e1ec7);
+        for density in [0, 1, 8, 32, 64, 100] {
+            let mut block = B::zero();
+            for i in 0..B::SIZE {
+                if rng.gen_range(0..100) < density {
+                    block.set_bit::<true>(i);
+                }
+            }
+            let bits: Vec<usize> = (0..B::SIZE).filter(|&i| block.get_bit(i)).collect();
+
+            for i in 0..=B::SIZE {
+                let expected = bits.iter().filter(|&&b| b < i).count();
+                assert_eq!(block.rank(i), expected);
+            }
+            for n in 0..=B::SIZE {
+                assert_eq!(block.select(n), bits.get(n).copied());
+            }
+        }
+    }
+
+    fn do_test_first_last<B: BitBlock>(){
+        let mut block = B::zero();
+        assert_eq!(block.first_set_bit(), None);
+        assert_eq!(block.last_set_bit(), None);
+
+        // Single bit in each word, at word edges.
+        let word_bits = word_bits::<B>();
+        for word in 0..B::SIZE/word_bits {
+            for bit in [0, 1, word_bits - 2, word_bits - 1] {
+                let i = word*word_bits + bit;
+                let mut block = B::zero();
+                block.set_bit::<true>(i);
+                assert_eq!(block.first_set_bit(), Some(i));
+                assert_eq!(block.last_set_bit(), Some(i));
+            }
+        }
+
+        block.set_bit::<true>(0);
+        block.set_bit::<true>(B::SIZE - 1);
+        assert_eq!(block.first_set_bit(), Some(0));
+        assert_eq!(block.last_set_bit(), Some(B::SIZE - 1));
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xf125_7000);
+        for _ in 0..100 {
+            let mut block = B::zero();
+            for _ in 0..rng.gen_range(0..8) {
+                block.set_bit::<true>(rng.gen_range(0..B::SIZE));
+            }
+            let bits: Vec<usize> = block.clone().into_bits_iter().collect();
+            assert_eq!(block.first_set_bit(), bits.first().copied());
+            assert_eq!(block.last_set_bit(), bits.last().copied());
+        }
+    }
+
+    fn do_test_traverse<B: BitBlock>(){
+        // Empty block never calls `f`.
+        let ctrl = B::zero().traverse_bits(|_| ControlFlow::Break(()));
+        assert!(ctrl.is_continue());
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x74a5e);
+        for _ in 0..100 {
+            let mut block = B::zero();
+            block.set_bit::<true>(0);
+            block.set_bit::<true>(B::SIZE - 1);
+            for _ in 0..rng.gen_range(0..16) {
+                block.set_bit::<true>(rng.gen_range(0..B::SIZE));
+            }
+            let bits: Vec<usize> = block.clone().into_bits_iter().collect();
+
+            let mut all = Vec::new();
+            let ctrl = block.traverse_bits(|i| {
+                all.push(i);
+                ControlFlow::<usize>::Continue(())
+            });
+            assert!(ctrl.is_continue());
+            assert_eq!(all, bits);
+
+            // Break at first, middle and last set bit.
+            for stop in [0, bits.len()/2, bits.len() - 1] {
+                let mut visited = Vec::new();
+                let ctrl = block.traverse_bits(|i| {
+                    visited.push(i);
+                    if i == bits[stop] {
+                        ControlFlow::Break(i * 2)
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                });
+                assert_eq!(ctrl, ControlFlow::Break(bits[stop] * 2));
+                assert_eq!(visited, bits[..=stop]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_traverse_bits(){
+        do_test_traverse::<u16>();
+        do_test_traverse::<u32>();
+        do_test_traverse::<u64>();
+        do_test_traverse::<u128>();
+        do_test_traverse::<BitArray<1>>();
+        do_test_traverse::<BitArray<3>>();
+        do_test_traverse::<BitArray<8>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test_traverse::<wide::u64x2>();
+            do_test_traverse::<wide::u64x4>();
+            do_test_traverse::<U64x8>();
+        }
+    }
+
+    #[test]
+    fn test_first_last_set_bit(){
+        do_test_first_last::<u16>();
+        do_test_first_last::<u32>();
+        do_test_first_last::<u64>();
+        do_test_first_last::<u128>();
+        do_test_first_last::<BitArray<1>>();
+        do_test_first_last::<BitArray<3>>();
+        do_test_first_last::<BitArray<8>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test_first_last::<wide::u64x2>();
+            do_test_first_last::<wide::u64x4>();
+            do_test_first_last::<U64x8>();
+        }
+    }
+
+    #[test]
+    fn test_rank_select(){
+        do_test::<u16>();
+        do_test::<u32>();
+        do_test::<u64>();
+        do_test::<u128>();
+        do_test::<BitArray<1>>();
+        do_test::<BitArray<3>>();
+        do_test::<BitArray<8>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test::<wide::u64x2>();
+            do_test::<wide::u64x4>();
+            do_test::<U64x8>();
+        }
+    }
+}
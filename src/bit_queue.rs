@@ -35,26 +35,42 @@ fn is_empty<P: Primitive>(bit_block_iter: &OneBitsIter<P>) -> bool{
 /// Queue of 1 bits.
 /// 
 /// Pop first set bit on iteration. "Consumed" bit replaced with zero.
+/// [next_back()] pops last set bit instead.
 /// 
 /// Think of it as an iterator that owns data.
-pub trait BitQueue: Iterator<Item = usize> + Clone{
+///
+/// [next_back()]: DoubleEndedIterator::next_back
+pub trait BitQueue: DoubleEndedIterator<Item = usize> + Clone{
     /// All bits 0. Iterator returns None.
     fn empty() -> Self;
 
     /// All bits 1.
     fn filled() -> Self;
 
-    /// Remove first n bits. (Set 0)
+    /// Discard all bits below `n`. (Set 0)
     /// 
+    /// O(1) - masks out whole words, without popping bits one by one.
+    /// 
+    /// Skipping backwards (`n` <= [current()]) is a no-op - bits below
+    /// current position are already consumed.
     /// If n >= BitQueue capacity - make it empty.
-    fn trim_to(&mut self, n: usize);
+    /// 
+    /// [current()]: Self::current
+    fn skip_to(&mut self, n: usize);
 
     /// Current index. Equals capacity - if iteration finished.
     fn current(&self) -> usize;
 
-    fn traverse<F>(self, f: F) -> ControlFlow<()>
+    /// Pop bits in loop, without per-bit [next()] overhead.
+    /// 
+    /// Traversed bits are consumed. On [Break] - the rest of the bits stay
+    /// in queue, and iteration/traverse can be continued.
+    /// 
+    /// [next()]: Iterator::next
+    /// [Break]: ControlFlow::Break
+    fn traverse<F>(&mut self, f: F) -> ControlFlow<()>
     where
-        F: FnMut(usize) -> ControlFlow<()>;        
+        F: FnMut(usize) -> ControlFlow<()>;
     
 /*    // TODO: remove ?
     fn is_empty(&self) -> bool;*/
@@ -72,6 +88,13 @@ impl Iterator for EmptyBitQueue {
     }
 }
 
+impl DoubleEndedIterator for EmptyBitQueue {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        None
+    }
+}
+
 impl BitQueue for EmptyBitQueue{
     fn empty() -> Self {
         Self
@@ -81,13 +104,13 @@ impl BitQueue for EmptyBitQueue{
         Self
     }
 
-    fn trim_to(&mut self, _n: usize) {}
+    fn skip_to(&mut self, _n: usize) {}
 
     fn current(&self) -> usize {
         0
     }
 
-    fn traverse<F>(self, _f: F) -> ControlFlow<()> 
+    fn traverse<F>(&mut self, _f: F) -> ControlFlow<()> 
     where 
         F: FnMut(usize) -> ControlFlow<()> 
     {
@@ -125,7 +148,7 @@ where
     }
 
     #[inline]
-    fn trim_to(&mut self, n: usize) {
+    fn skip_to(&mut self, n: usize) {
         let block: &mut P = unsafe{
             mem::transmute(&mut self.bit_block_iter)
         };
@@ -139,11 +162,11 @@ where
     }
 
     #[inline]
-    fn traverse<F>(self, f: F) -> ControlFlow<()> where F: FnMut(usize) -> ControlFlow<()> {
-        let block: P = unsafe{
-            mem::transmute_copy(&self.bit_block_iter)
+    fn traverse<F>(&mut self, f: F) -> ControlFlow<()> where F: FnMut(usize) -> ControlFlow<()> {
+        let block: &mut P = unsafe{
+            mem::transmute(&mut self.bit_block_iter)
         };
-        bit_utils::traverse_one_bits(block, f)
+        bit_utils::traverse_one_bits_mut(block, f)
     }
 
     /*fn is_empty(&self) -> bool {
@@ -164,6 +187,16 @@ where
     }
 }
 
+impl<P> DoubleEndedIterator for PrimitiveBitQueue<P>
+where
+    P: Primitive
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.bit_block_iter.next_back()
+    }
+}
+
 /// [BitQueue] for array of [Primitive]s.
 #[derive(Clone)]
 pub struct ArrayBitQueue<P, const N: usize>{
@@ -208,7 +241,7 @@ where
     }
 
     #[inline]
-    fn trim_to(&mut self, n: usize) {
+    fn skip_to(&mut self, n: usize) {
         let element_index = n / (size_of::<P>() * 8); // compile-time math optimization
         
         // clamp to empty
@@ -271,7 +304,7 @@ where
     }
 
     #[inline]
-    fn traverse<F>(mut self, mut f: F) -> ControlFlow<()>
+    fn traverse<F>(&mut self, mut f: F) -> ControlFlow<()>
     where
         F: FnMut(usize) -> ControlFlow<()>        
     {
@@ -281,14 +314,27 @@ where
             // compiler should optimize away this for newly constructed BitQueue.
             *self.bit_block_iters.get_unchecked_mut(self.bit_block_index) = self.bit_block_iters[0];
             
-            let slice: &[P] = std::slice::from_raw_parts(
+            let slice: &mut [P] = std::slice::from_raw_parts_mut(
                 // cast is safe because OneBitsIter<P> transmutable to P.
-                self.bit_block_iters.as_ptr().add(self.bit_block_index).cast(),
-                N - self.bit_block_index
+                self.bit_block_iters.as_mut_ptr().cast(),
+                N
             );
             
-            let start_index = self.bit_block_index*size_of::<P>()*8;
-            return bit_utils::traverse_array_one_bits( slice, |i|f(start_index + i));
+            for i in self.bit_block_index..N {
+                let start_index = i*size_of::<P>()*8;
+                let ctrl = bit_utils::traverse_one_bits_mut(
+                    slice.get_unchecked_mut(i), |r|f(start_index + r)
+                );
+                if ctrl.is_break() {
+                    // make interrupted block active
+                    self.bit_block_index = i;
+                    self.bit_block_iters[0] = *self.bit_block_iters.get_unchecked(i);
+                    return ctrl;
+                }
+            }
+            self.bit_block_index = N-1;
+            self.bit_block_iters[0] = one_bits_iter(P::ZERO);
+            ControlFlow::Continue(())
         }
     }
 
@@ -324,7 +370,7 @@ where
     }
 
     #[inline]
-    fn for_each<F>(self, mut f: F)
+    fn for_each<F>(mut self, mut f: F)
     where
         F: FnMut(usize)
     {
@@ -335,4 +381,223 @@ where
     }
 }
 
+impl<P, const N: usize> DoubleEndedIterator for ArrayBitQueue<P, N>
+where
+    P: Primitive
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // Blocks after active one are intact in their places.
+        for i in (self.bit_block_index+1..N).rev() {
+            let bit_block_iter = unsafe{ self.bit_block_iters.get_unchecked_mut(i) };
+            if let Some(index) = bit_block_iter.next_back() {
+                return Some(i * size_of::<P>() * 8 + index);
+            }
+        }
+        self.bit_block_iters[0].next_back()
+            .map(|index| self.bit_block_index * size_of::<P>() * 8 + index)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::BitBlock;
+    use super::*;
+
+    fn do_test<B: BitBlock>(){
+        let mut cases: Vec<Vec<usize>> = vec![
+            vec![],
+            vec![0],
+            vec![B::SIZE.min(64) - 1],
+            vec![B::SIZE - 1],
+            vec![0, B::SIZE - 1],
+            vec![0, B::SIZE.min(64) - 1, B::SIZE - 1],
+        ];
+        if B::SIZE > 64 {
+            cases.push(vec![63, 64]);
+            cases.push(vec![64]);
+        }
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xb17_9e3e);
+        for _ in 0..100 {
+            let mut bits: Vec<_> = (0..rng.gen_range(0..B::SIZE))
+                .map(|_| rng.gen_range(0..B::SIZE)).collect();
+            bits.sort_unstable();
+            bits.dedup();
+            cases.push(bits);
+        }
 
+        for mut bits in cases {
+            bits.dedup();
+            let mut block = B::zero();
+            for &i in &bits {
+                block.set_bit::<true>(i);
+            }
+
+            assert_equal(block.clone().into_bits_iter(), bits.iter().copied());
+            assert_equal(block.clone().into_bits_iter_rev(), bits.iter().copied().rev());
+
+            // Interleaved front/back
+            let mut iter = block.clone().into_bits_iter();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            while let Some(i) = iter.next() {
+                front.push(i);
+                match iter.next_back() {
+                    Some(i) => back.push(i),
+                    None => break,
+                }
+            }
+            assert_eq!(iter.next_back(), None);
+            front.extend(back.iter().rev());
+            assert_eq!(front, bits);
+
+            // After trim
+            if let Some(&mid) = bits.get(bits.len() / 2) {
+                let mut iter = block.clone().into_bits_iter();
+                iter.skip_to(mid);
+                assert_equal(iter.rev(), bits.iter().copied().filter(|&i| i >= mid).rev());
+            }
+        }
+
+        assert_eq!(B::BitsIter::empty().next_back(), None);
+        assert_equal(B::BitsIter::filled().rev(), (0..B::SIZE).rev());
+    }
+
+    #[test]
+    fn test_bits_iter_rev(){
+        do_test::<u16>();
+        do_test::<u32>();
+        do_test::<u64>();
+        do_test::<u128>();
+        do_test::<crate::BitArray<1>>();
+        do_test::<crate::BitArray<2>>();
+        do_test::<crate::BitArray<4>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test::<wide::u64x2>();
+            do_test::<wide::u64x4>();
+            do_test::<crate::U64x8>();
+        }
+    }
+
+    fn do_test_skip_to<B: BitBlock>(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5419_7000);
+        for _ in 0..100 {
+            let mut block = B::zero();
+            for _ in 0..rng.gen_range(0..B::SIZE) {
+                block.set_bit::<true>(rng.gen_range(0..B::SIZE));
+            }
+            let bits: Vec<usize> = block.clone().into_bits_iter().collect();
+            let after = |n: usize| bits.iter().copied().filter(move |&i| i >= n);
+
+            for _ in 0..10 {
+                let n = rng.gen_range(0..=B::SIZE);
+                let mut iter = block.clone().into_bits_iter();
+                iter.skip_to(n);
+                assert_equal(iter.clone(), after(n));
+
+                // skip to current
+                let current = iter.current();
+                iter.skip_to(current);
+                assert_equal(iter.clone(), after(n));
+
+                // skip backwards - no-op
+                iter.skip_to(rng.gen_range(0..=n));
+                assert_equal(iter.clone(), after(n));
+
+                // skip after partial consumption
+                let m = rng.gen_range(n..=B::SIZE);
+                let mut consumed = iter.clone();
+                consumed.next();
+                consumed.skip_to(m);
+                assert_equal(consumed, after(n).skip(1).filter(|&i| i >= m));
+            }
+
+            // skip past last set bit
+            if let Some(&last) = bits.last() {
+                let mut iter = block.clone().into_bits_iter();
+                iter.skip_to(last + 1);
+                assert_eq!(iter.clone().next(), None);
+                assert_eq!(iter.next_back(), None);
+            }
+            // skip past capacity
+            let mut iter = block.clone().into_bits_iter();
+            iter.skip_to(B::SIZE + 100);
+            assert_eq!(iter.clone().next(), None);
+            assert_eq!(iter.next_back(), None);
+        }
+    }
+
+    #[test]
+    fn test_skip_to(){
+        do_test_skip_to::<u16>();
+        do_test_skip_to::<u32>();
+        do_test_skip_to::<u64>();
+        do_test_skip_to::<u128>();
+        do_test_skip_to::<crate::BitArray<1>>();
+        do_test_skip_to::<crate::BitArray<2>>();
+        do_test_skip_to::<crate::BitArray<4>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test_skip_to::<wide::u64x2>();
+            do_test_skip_to::<wide::u64x4>();
+            do_test_skip_to::<crate::U64x8>();
+        }
+    }
+
+    fn do_test_traverse<B: BitBlock>(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x7_4a5e);
+        for _ in 0..100 {
+            let mut block = B::zero();
+            for _ in 0..rng.gen_range(0..B::SIZE) {
+                block.set_bit::<true>(rng.gen_range(0..B::SIZE));
+            }
+            let bits: Vec<usize> = block.clone().into_bits_iter().collect();
+
+            let mut iter = block.clone().into_bits_iter();
+            let mut traversed = Vec::new();
+            assert!(iter.traverse(|i| { traversed.push(i); ControlFlow::Continue(()) }).is_continue());
+            assert_eq!(traversed, bits);
+            assert_eq!(iter.next(), None);
+
+            // Interrupt, and continue
+            if bits.is_empty() {
+                continue;
+            }
+            let mut iter = block.clone().into_bits_iter();
+            let mut traversed = Vec::new();
+            let stop = rng.gen_range(1..=bits.len());
+            let ctrl = iter.traverse(|i| {
+                traversed.push(i);
+                if traversed.len() == stop {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            assert!(ctrl.is_break());
+            if let Some(&first) = bits.get(stop) {
+                assert_eq!(iter.clone().next(), Some(first));
+            }
+            let _ = iter.traverse(|i| { traversed.push(i); ControlFlow::Continue(()) });
+            assert_eq!(traversed, bits);
+        }
+    }
+
+    #[test]
+    fn test_traverse(){
+        do_test_traverse::<u16>();
+        do_test_traverse::<u32>();
+        do_test_traverse::<u64>();
+        do_test_traverse::<u128>();
+        do_test_traverse::<crate::BitArray<1>>();
+        do_test_traverse::<crate::BitArray<3>>();
+        #[cfg(feature = "simd")]
+        {
+            do_test_traverse::<wide::u64x4>();
+            do_test_traverse::<crate::U64x8>();
+        }
+    }
+}
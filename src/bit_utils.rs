@@ -77,12 +77,116 @@ pub unsafe fn get_bit_unchecked<T: Primitive>(block: T, bit_index: usize) -> boo
     !masked_block.is_zero()
 }
 
+/// bzhi/pdep based primitives.
+/// 
+/// Used unconditionally, if `bmi2` target feature enabled at compile time.
+/// With `runtime-dispatch` feature - selected at runtime instead.
+#[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+pub(crate) mod bmi2 {
+    use core::arch::x86_64::{_bzhi_u64, _pdep_u64, _pext_u64};
+
+    /// `bmi2` and `popcnt` can be used.
+    #[inline(always)]
+    pub fn available() -> bool {
+        #[cfg(target_feature = "bmi2")]
+        { true }
+        #[cfg(not(target_feature = "bmi2"))]
+        {
+            use std::sync::atomic::{AtomicU8, Ordering};
+            // 0 - not detected yet, 1 - unavailable, 2 - available.
+            static AVAILABLE: AtomicU8 = AtomicU8::new(0);
+            match AVAILABLE.load(Ordering::Relaxed) {
+                0 => {
+                    let available = std::is_x86_feature_detected!("bmi2") 
+                                 && std::is_x86_feature_detected!("popcnt");
+                    AVAILABLE.store(if available {2} else {1}, Ordering::Relaxed);
+                    available
+                }
+                v => v == 2
+            }
+        }
+    }
+
+    /// # Safety
+    /// 
+    /// Must be [available()].
+    #[inline]
+    #[target_feature(enable = "bmi2,popcnt")]
+    pub unsafe fn rank(block: u64, bit_index: usize) -> usize {
+        _bzhi_u64(block, bit_index as u32).count_ones() as usize
+    }
+
+    /// # Safety
+    /// 
+    /// Must be [available()].
+    #[inline]
+    #[target_feature(enable = "bmi2,popcnt")]
+    pub unsafe fn select(block: u64, n: usize) -> usize {
+        _pdep_u64(1 << n, block).trailing_zeros() as usize
+    }
+
+    /// Scatter low bits of `value` to `mask` bit positions.
+    /// 
+    /// # Safety
+    /// 
+    /// Must be [available()].
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn deposit(value: u64, mask: u64) -> u64 {
+        _pdep_u64(value, mask)
+    }
+
+    /// Gather `mask` bits of `value` into low bits.
+    /// 
+    /// # Safety
+    /// 
+    /// Must be [available()].
+    #[inline]
+    #[target_feature(enable = "bmi2")]
+    pub unsafe fn extract(value: u64, mask: u64) -> u64 {
+        _pext_u64(value, mask)
+    }
+}
+
+/// Number of 1 bits below `bit_index`.
+/// 
+/// `bit_index` must be < `P` bits.
+#[inline]
+pub fn rank<P: Primitive>(block: P, bit_index: usize) -> usize {
+    debug_assert!(bit_index < size_of::<P>()*8);
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if size_of::<P>() == 8 && bmi2::available() {
+        let block: u64 = unsafe{ std::mem::transmute_copy(&block) };
+        return unsafe{ bmi2::rank(block, bit_index) };
+    }
+    (block & !(P::MAX << bit_index)).count_ones() as usize
+}
+
+/// Position of `n`th (from 0) 1 bit.
+/// 
+/// `n` must be < `block.count_ones()`.
+#[inline]
+pub fn select<P: Primitive>(block: P, n: usize) -> usize {
+    debug_assert!(n < block.count_ones() as usize);
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if size_of::<P>() == 8 && bmi2::available() {
+        let block: u64 = unsafe{ std::mem::transmute_copy(&block) };
+        return unsafe{ bmi2::select(block, n) };
+    }
+    let mut block = block;
+    for _ in 0..n {
+        // clear lowest set bit
+        block ^= block & block.wrapping_neg();
+    }
+    block.trailing_zeros() as usize
+}
+
 /// Blocks traversed in the same order as [set_array_bit], [get_array_bit].
 #[inline]
-pub fn traverse_array_one_bits<P, F>(array: &[P], mut f: F) -> ControlFlow<()>
+pub fn traverse_array_one_bits<P, B, F>(array: &[P], mut f: F) -> ControlFlow<B>
 where
     P: Primitive,
-    F: FnMut(usize) -> ControlFlow<()>
+    F: FnMut(usize) -> ControlFlow<B>
 {
     let len = array.len();
     for i in 0..len{
@@ -95,17 +199,17 @@ where
             }
         );
         if control.is_break(){
-            return ControlFlow::Break(());
+            return control;
         }
     }
     ControlFlow::Continue(())
 }
 
 #[inline]
-pub fn traverse_one_bits<P, F>(mut element: P, mut f: F) -> ControlFlow<()>
+pub fn traverse_one_bits<P, B, F>(mut element: P, mut f: F) -> ControlFlow<B>
 where
     P: Primitive,
-    F: FnMut(usize) -> ControlFlow<()>
+    F: FnMut(usize) -> ControlFlow<B>
 {
     // from https://lemire.me/blog/2018/03/08/iterating-over-set-bits-quickly-simd-edition/
     // https://github.com/lemire/Code-used-on-Daniel-Lemire-s-blog/blob/master/2018/03/07/simdbitmapdecode.c#L45
@@ -114,7 +218,7 @@ where
 
         let control = f(index);
         if control.is_break(){
-            return ControlFlow::Break(());
+            return control;
         }
 
         // Returns an integer having just the least significant bit of
@@ -126,6 +230,31 @@ where
     ControlFlow::Continue(())
 }
 
+/// [traverse_one_bits], that consumes traversed bits of `element`.
+/// 
+/// On [Break], the bit that broke traverse is consumed too.
+/// 
+/// [Break]: ControlFlow::Break
+#[inline]
+pub fn traverse_one_bits_mut<P, F>(element: &mut P, mut f: F) -> ControlFlow<()>
+where
+    P: Primitive,
+    F: FnMut(usize) -> ControlFlow<()>
+{
+    // Work on local copy - keep it in register.
+    let mut local = *element;
+    while !local.is_zero() {
+        let index = local.trailing_zeros() as usize;
+        local ^= local & local.wrapping_neg();
+        if f(index).is_break(){
+            *element = local;
+            return ControlFlow::Break(());
+        }
+    }
+    *element = P::ZERO;
+    ControlFlow::Continue(())
+}
+
 /// This is 15% slower then "traverse" version
 #[inline]
 pub fn one_bits_iter<P>(element: P) -> OneBitsIter<P> {
@@ -165,3 +294,19 @@ where
     }
 }
 
+impl<P> DoubleEndedIterator for OneBitsIter<P>
+where
+    P: Primitive,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.element.is_zero() {
+            let index = size_of::<P>()*8 - 1 - self.element.leading_zeros() as usize;
+            self.element ^= P::ONE << index;
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
@@ -1,6 +1,24 @@
 //! Hierarchy configurations for [SparseArray].
 //! 
 //! General rule: use the smallest depth possible. 
+//! 
+//! Index range of each configuration is `0..=MAX_INDEX`:
+//! ```
+//! use hi_sparse_array::config::{self, SparseArrayLevels};
+//! assert_eq!(config::width_64::depth_3::MAX_INDEX, 64*64*64 - 1);
+//! ```
+//! 
+//! Keys are `usize` indices - on 32-bit targets, range of deep 
+//! configurations is cut to `usize::MAX`. For 64-bit ids use `u64` key:
+//! keys, that do not fit `usize`, are reported out of range, never truncated.
+//! ```
+//! use hi_sparse_array::{config, SparseArray};
+//! type Ids = SparseArray<config::width_64::depth_6, Option<u32>, u64>;
+//! let mut ids = Ids::default();
+//! let id: u64 = if cfg!(target_pointer_width = "64") { 1 << 35 } else { 1 << 31 };
+//! ids.insert(id, Some(1));
+//! assert_eq!(ids.iter_keyed().next(), Some((id, &Some(1))));
+//! ```
 
 pub use crate::sparse_array_levels::SparseArrayLevels;
 
@@ -13,17 +31,41 @@ macro_rules! generate {
         type L1 = IntrusiveListLevel<$L1>;
         type LN = IntrusiveListLevel<$LN>;
         
+        /// `width` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_1 = (L0,);
+        /// `width^2` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_2 = (L0, L1);
+        /// `width^3` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_3 = (L0, L1, LN);
+        /// `width^4` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_4 = (L0, L1, LN, LN);
+        /// `width^5` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_5 = (L0, L1, LN, LN, LN);
+        /// `width^6` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_6 = (L0, L1, LN, LN, LN, LN);
+        /// `width^7` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_7 = (L0, L1, LN, LN, LN, LN, LN);
+        /// `width^8` keys. See [SparseArrayLevels::MAX_INDEX].
         pub type depth_8 = (L0, L1, LN, LN, LN, LN, LN, LN);
     };
 }
 
+/// 16 element blocks.
+/// 
+/// For tiny dense keyspaces - `depth_3` covers 4096 keys.
+#[allow(non_camel_case_types)]
+pub mod width_16 {
+    use super::*;
+    generate!(Block<u16, [u8; 16]>, Block<u16, [u16; 16]>, Block<u16, [u32; 16]>);
+}
+
+/// 32 element blocks.
+#[allow(non_camel_case_types)]
+pub mod width_32 {
+    use super::*;
+    generate!(Block<u32, [u8; 32]>, Block<u32, [u16; 32]>, Block<u32, [u32; 32]>);
+}
+
 /// 64 element blocks.
 #[allow(non_camel_case_types)]
 pub mod width_64 {
@@ -40,13 +82,50 @@ pub mod width_128 {
     generate!(Block<wide::u64x2, [u8; 128]>, Block<wide::u64x2, [u16; 128]>, Block<wide::u64x2, [u32; 128]>);
 }
 
+/// 128 element blocks, with [u128] masks.
+///
+/// Same width as [width_128], without `simd` feature.
+#[cfg(target_endian = "little")]
+#[allow(non_camel_case_types)]
+pub mod width_128_u128 {
+    use super::*;
+    generate!(Block<u128, [u8; 128]>, Block<u128, [u16; 128]>, Block<u128, [u32; 128]>);
+}
+
 /// 256 element blocks.
+///
+/// Level 0 uses `u16` and level 1 `u32` indices - 256 and 256x256 children
+/// (plus empty block) do not fit into `u8` and `u16`.
 #[cfg(feature = "simd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
 #[allow(non_camel_case_types)]
 pub mod width_256{
     use super::*;
-    generate!(Block<wide::u64x4, [u8; 256]>, Block<wide::u64x4, [u16; 256]>, Block<wide::u64x4, [u32; 256]>);
+    generate!(Block<wide::u64x4, [u16; 256]>, Block<wide::u64x4, [u32; 256]>, Block<wide::u64x4, [u32; 256]>);
+}
+
+/// 512 element blocks, with [BitArray] masks.
+///
+/// Level 1 uses `u32` indices - 512x512 children do not fit into `u16`.
+#[allow(non_camel_case_types)]
+pub mod width_512{
+    use super::*;
+    use crate::BitArray;
+    generate!(Block<BitArray<8>, [u16; 512]>, Block<BitArray<8>, [u32; 512]>, Block<BitArray<8>, [u32; 512]>);
+}
+
+/// 512 element blocks, with [U64x8] SIMD masks.
+///
+/// Same layout as [width_512].
+///
+/// [U64x8]: crate::U64x8
+#[cfg(feature = "simd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "simd")))]
+#[allow(non_camel_case_types)]
+pub mod width_512_u64x8{
+    use super::*;
+    use crate::U64x8;
+    generate!(Block<U64x8, [u16; 512]>, Block<U64x8, [u32; 512]>, Block<U64x8, [u32; 512]>);
 }
 
 /*pub type _64x1 = w64::d1;
@@ -107,8 +186,8 @@ pub mod sbo {
     pub mod width_256{
         use super::*;
         generate!( 
-            Block<wide::u64x4, [u8; 256]>,      // Use full-sized block for root.
-            SmallBlock<wide::u64x4, [u8;4], [u16;256], [u16;14]>,
+            Block<wide::u64x4, [u16; 256]>,     // Use full-sized block for root.
+            SmallBlock<wide::u64x4, [u8;4], [u32;256], [u32;7]>,
             SmallBlock<wide::u64x4, [u8;4], [u32;256], [u32;15]>
         );
     }
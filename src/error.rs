@@ -0,0 +1,164 @@
+use std::error::Error as StdError;
+use std::fmt;
+
+/// `index` is outside of the hierarchy's [max_range()].
+///
+/// [max_range()]: crate::SparseHierarchy::max_range
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IndexOutOfRange {
+    pub index: usize,
+    /// Max index hierarchy configuration can hold.
+    pub max_range: usize,
+}
+
+impl fmt::Display for IndexOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "index {} is out of range 0..={}", self.index, self.max_range)
+    }
+}
+
+impl StdError for IndexOutOfRange {}
+
+/// Level block index type is too narrow to address one more
+/// block or item.
+///
+/// Configuration level can not hold more blocks/items than its parent's
+/// block index type can address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CapacityExceeded {
+    /// Max block index of the level.
+    pub max_block_index: usize,
+}
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "capacity exceeded - block index can not exceed {}", self.max_block_index)
+    }
+}
+
+impl StdError for CapacityExceeded {}
+
+/// All checked [SparseArray] operations failures.
+/// 
+/// Panicking operations panic with the same message.
+/// 
+/// [SparseArray]: crate::SparseArray
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    IndexOutOfRange(IndexOutOfRange),
+    CapacityExceeded(CapacityExceeded),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange(e)  => fmt::Display::fmt(e, f),
+            Self::CapacityExceeded(e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::IndexOutOfRange(e)  => Some(e),
+            Self::CapacityExceeded(e) => Some(e),
+        }
+    }
+}
+
+impl From<IndexOutOfRange> for Error {
+    #[inline]
+    fn from(e: IndexOutOfRange) -> Self {
+        Self::IndexOutOfRange(e)
+    }
+}
+
+impl From<CapacityExceeded> for Error {
+    #[inline]
+    fn from(e: CapacityExceeded) -> Self {
+        Self::CapacityExceeded(e)
+    }
+}
+
+/// Broken [SparseArray] structure invariant, found by [validate()].
+/// 
+/// `level` is hierarchy level number, `block_index` - block index within that level.
+/// 
+/// [SparseArray]: crate::SparseArray
+/// [validate()]: crate::SparseArray::validate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    /// Data, keys and data back-links storages have different lengths.
+    LengthMismatch { values: usize, keys: usize, back_links: usize },
+    /// Mask bit is set, but child block/data index is 0.
+    MissingChild { level: usize, block_index: usize, in_block_index: usize },
+    /// Mask bit is not set, but child block/data index is not 0.
+    UnexpectedChild { level: usize, block_index: usize, in_block_index: usize, child: usize },
+    /// Child block/data index is outside of the next level/data storage.
+    ChildOutOfRange { level: usize, block_index: usize, in_block_index: usize, child: usize },
+    /// Non-root block is in use, but have empty mask.
+    EmptyBlockInUse { level: usize, block_index: usize },
+    /// Block is child of more than one parent.
+    SharedBlock { level: usize, block_index: usize },
+    /// Block 0 of non-root level is not empty.
+    ZeroBlockNotEmpty { level: usize },
+    /// Free list contains block 0, out-of-storage or repeated block index.
+    FreeListCorrupted { level: usize, block_index: usize },
+    /// Block is in free list, and in use at the same time.
+    FreeBlockInUse { level: usize, block_index: usize },
+    /// Block is in free list, but not empty.
+    FreeBlockNotEmpty { level: usize, block_index: usize },
+    /// Block is neither in use, nor in free list.
+    LostBlock { level: usize, block_index: usize },
+    /// Stored key of data item does not match its position in hierarchy.
+    KeyMismatch { data_index: usize, key: usize, stored_key: usize },
+    /// Data item back-link does not point to its last level position.
+    BackLinkMismatch { data_index: usize },
+    /// Data item is pointed more than once.
+    SharedData { data_index: usize },
+    /// Data item is not pointed by any last level block.
+    UnreachableData { data_index: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use InvariantViolation::*;
+        match *self {
+            LengthMismatch { values, keys, back_links } => write!(f, 
+                "storage length mismatch - values {values}, keys {keys}, back-links {back_links}"),
+            MissingChild { level, block_index, in_block_index } => write!(f, 
+                "level {level} block {block_index}: bit {in_block_index} set, but child is 0"),
+            UnexpectedChild { level, block_index, in_block_index, child } => write!(f, 
+                "level {level} block {block_index}: bit {in_block_index} not set, but child is {child}"),
+            ChildOutOfRange { level, block_index, in_block_index, child } => write!(f, 
+                "level {level} block {block_index}: bit {in_block_index} child {child} is out of storage"),
+            EmptyBlockInUse { level, block_index } => write!(f, 
+                "level {level} block {block_index}: empty block in use"),
+            SharedBlock { level, block_index } => write!(f, 
+                "level {level} block {block_index}: block has more than one parent"),
+            ZeroBlockNotEmpty { level } => write!(f, 
+                "level {level} block 0: not empty"),
+            FreeListCorrupted { level, block_index } => write!(f, 
+                "level {level}: free list corrupted at block {block_index}"),
+            FreeBlockInUse { level, block_index } => write!(f, 
+                "level {level} block {block_index}: free block in use"),
+            FreeBlockNotEmpty { level, block_index } => write!(f, 
+                "level {level} block {block_index}: free block not empty"),
+            LostBlock { level, block_index } => write!(f, 
+                "level {level} block {block_index}: block is neither in use, nor free"),
+            KeyMismatch { data_index, key, stored_key } => write!(f, 
+                "data {data_index}: placed at key {key}, but stored key is {stored_key}"),
+            BackLinkMismatch { data_index } => write!(f, 
+                "data {data_index}: back-link does not match last level position"),
+            SharedData { data_index } => write!(f, 
+                "data {data_index}: pointed more than once"),
+            UnreachableData { data_index } => write!(f, 
+                "data {data_index}: not pointed by any block"),
+        }
+    }
+}
+
+impl StdError for InvariantViolation {}
@@ -56,6 +56,16 @@ pub struct ExactHierarchyState<T>(<T::Borrowed as SparseHierarchy>::State)
 where 
     T: Borrowable<Borrowed: SparseHierarchy>;
 
+impl<T> Clone for ExactHierarchyState<T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy<State: Clone>>
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 impl<T> SparseHierarchyState for ExactHierarchyState<T>
 where
     T: Borrowable<Borrowed: SparseHierarchy>
@@ -71,14 +81,14 @@ where
     unsafe fn select_level_bock<'a, N: ConstInteger>(
         &mut self, this: &'a Self::This, level_n: N, level_index: usize
     ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
-        self.select_level_bock(this, level_n, level_index)
+        self.0.select_level_bock(this.0.borrow(), level_n, level_index)
     }
 
     #[inline]
     unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize) 
         -> <Self::This as SparseHierarchy>::Data<'a> 
     {
-        self.data_block(this, level_index)
+        self.0.data_block(this.0.borrow(), level_index)
     }
 }
 
@@ -1,7 +1,10 @@
 use std::borrow::Borrow;
+use std::iter::Map;
 use std::marker::PhantomData;
-use arrayvec::ArrayVec;
-use crate::BitBlock;
+use std::mem;
+use std::slice;
+use smallvec::SmallVec;
+use crate::{BitBlock, Empty};
 use crate::const_utils::const_bool::ConstBool;
 use crate::const_utils::const_int::ConstInteger;
 use crate::const_utils::const_array::{ConstArray, ConstArrayType};
@@ -16,7 +19,22 @@ pub struct Fold<Op, Init, ArrayIter>{
     pub(crate) array_iter: ArrayIter,
 }
 
+/// [Fold] source iterator over slice of `T` or `&T`.
+///
+/// Yields `&T::Borrowed` - so both `&[T]` and `&[&T]` (`Vec<&T>`)
+/// can be used as `*_fold_slice` source.
+pub type SliceIter<'a, T> = Map<
+    slice::Iter<'a, T>,
+    fn(&'a T) -> &'a <T as Borrowable>::Borrowed
+>;
+
+#[inline]
+pub(crate) fn slice_iter<T: Borrowable>(slice: &[T]) -> SliceIter<'_, T> {
+    slice.iter().map(Borrow::borrow)
+}
+
 type ArrayItem<ArrayIter> = <<ArrayIter as Iterator>::Item as Borrowable>::Borrowed;
+type ArrayItemState<ArrayIter> = <ArrayItem<ArrayIter> as SparseHierarchy>::State;
 
 impl<Op, Init, ArrayIter> SparseHierarchy for Fold<Op, Init, ArrayIter>
 where
@@ -48,10 +66,16 @@ where
     where 
         I: ConstArray<Item=usize> + Copy
     {
+        let terminal = I::Cap::VALUE == Self::LevelCount::VALUE - 1;
         self.array_iter.clone().fold(
             self.init.borrow().level_mask(level_indices).take_or_clone(), 
             |acc, array|{
-                self.op.lvl_op(acc, array.borrow().level_mask(level_indices))
+                let mask = array.borrow().level_mask(level_indices);
+                if terminal {
+                    self.op.terminal_lvl_op(acc, mask)
+                } else {
+                    self.op.lvl_op(acc, mask)
+                }
             }
         )
     }
@@ -64,18 +88,20 @@ where
     where
         I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
     {
-        self.array_iter.clone().fold(
-            self.init.borrow().data_block(level_indices).take_or_clone(),
-            |acc, array|{
-                self.op.data_op(acc, array.borrow().data_block(level_indices))
+        let mut acc = self.init.borrow().data_block(level_indices).take_or_clone();
+        for (position, array) in self.array_iter.clone().enumerate() {
+            if Op::FOLD_SHORT_CIRCUIT && acc.is_empty() {
+                break;
             }
-        )
+            acc = self.op.fold_data_op(acc, array.borrow().data_block(level_indices), position);
+        }
+        acc
     }
     
     type State = FoldState<Op, Init, ArrayIter>;
 }
 
-// TODO: Configurable FoldState storage 
+/// Inline FoldState storage capacity. Falls back to heap above that.
 const N: usize = 32;
 
 pub struct FoldState<Op, Init, ArrayIter>
@@ -85,18 +111,15 @@ where
     Op: BinaryOp
 {
     init_state: <Init::Borrowed as SparseHierarchy>::State,
-    states: ArrayVec<
-        (<ArrayIter as Iterator>::Item, <ArrayItem<ArrayIter> as SparseHierarchy>::State),
-        N
-    >,
+    states: SmallVec<[(<ArrayIter as Iterator>::Item, ArrayItemState<ArrayIter>); N]>,
     
     /// In-use only when `Op::SKIP_EMPTY_HIERARCHIES` raised.
     /// 
-    /// [ArrayVec<usize, N>; Array::LevelCount - 1]
+    /// [SmallVec<[usize; N]>; Array::LevelCount - 1]
     /// 
-    /// [ArrayVec<usize, N>; 0] - otherwise
+    /// [SmallVec<[usize; N]>; 0] - otherwise
     lvls_non_empty_states: ConstArrayType<
-        ArrayVec<usize, N>,
+        SmallVec<[usize; N]>,
         <Op::SKIP_EMPTY_HIERARCHIES as ConstBool>::ConditionalInt<
             <<ArrayItem<ArrayIter> as SparseHierarchy>::LevelCount as ConstInteger>::Dec,
             ConstUsize<0>
@@ -106,6 +129,25 @@ where
     phantom_data: PhantomData<Fold<Op, Init, ArrayIter>>
 }
 
+impl<Op, Init, ArrayIter> Clone for FoldState<Op, Init, ArrayIter>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+    ArrayIter: Iterator<Item: Borrowable<Borrowed: SparseHierarchy<State: Clone>> + Clone>,
+    Op: BinaryOp
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            init_state: self.init_state.clone(),
+            states: self.states.clone(),
+            lvls_non_empty_states: array::Array::from_fn(|i|
+                self.lvls_non_empty_states.as_ref()[i].clone()
+            ),
+            phantom_data: PhantomData
+        }
+    }
+}
+
 impl<Op, Init, ArrayIter> SparseHierarchyState 
 for 
     FoldState<Op, Init, ArrayIter>
@@ -131,7 +173,7 @@ where
 
     #[inline]
     fn new(this: &Self::This) -> Self {
-        let states = ArrayVec::from_iter(
+        let states = SmallVec::from_iter(
             this.array_iter.clone()
                 .map(|array|{
                     let state = SparseHierarchyState::new(array.borrow()); 
@@ -142,7 +184,7 @@ where
         Self{
             init_state: SparseHierarchyState::new(this.init.borrow()),
             states,
-            lvls_non_empty_states: array::Array::from_fn(|_|ArrayVec::new()),
+            lvls_non_empty_states: array::Array::from_fn(|_|SmallVec::new()),
             phantom_data: PhantomData,
         }
     }
@@ -154,22 +196,42 @@ where
         let mut acc_mask = self.init_state
                           .select_level_bock(this.init.borrow(), level_n, level_index)
                           .take_or_clone();
+        let terminal = N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1;
         
         if Op::SKIP_EMPTY_HIERARCHIES::VALUE
         && N::VALUE != 0 
         {
-            let lvl_non_empty_states = self.lvls_non_empty_states.as_mut()
-                                      .get_unchecked_mut(level_n.value()-1); 
+            // Only states non-empty at the previous level are visited.
+            let lvls = self.lvls_non_empty_states.as_mut();
+            let (prev_lvls, lvls) = lvls.split_at_mut(level_n.value()-1);
+            let lvl_non_empty_states = lvls.get_unchecked_mut(0); 
             lvl_non_empty_states.clear();
-            for i in 0..self.states.len(){
-                let (array, array_state) = self.states.get_unchecked_mut(i);
+            
+            let states_len = self.states.len();
+            let states = &mut self.states;
+            let mut visit = |i: usize| {
+                let (array, array_state) = states.get_unchecked_mut(i);
                 let mask = array_state.select_level_bock(
                     (&*array).borrow(), level_n, level_index
                 );
-                acc_mask = this.op.lvl_op(acc_mask, mask);
-                
-                if !acc_mask.is_zero() {
-                    lvl_non_empty_states.push_unchecked(i);
+                if !mask.borrow().is_zero() {
+                    lvl_non_empty_states.push(i);
+                }
+                let acc = mem::replace(&mut acc_mask, BitBlock::zero());
+                acc_mask = if terminal {
+                    this.op.terminal_lvl_op(acc, mask)
+                } else {
+                    this.op.lvl_op(acc, mask)
+                };
+            };
+            
+            if let Some(prev_lvl_non_empty_states) = prev_lvls.last() {
+                for &i in prev_lvl_non_empty_states {
+                    visit(i);
+                }
+            } else {
+                for i in 0..states_len {
+                    visit(i);
                 }
             }
         } else {
@@ -177,7 +239,11 @@ where
                 let mask = array_state.select_level_bock(
                     (&*array).borrow(), level_n, level_index
                 );
-                acc_mask = this.op.lvl_op(acc_mask, mask);
+                acc_mask = if terminal {
+                    this.op.terminal_lvl_op(acc_mask, mask)
+                } else {
+                    this.op.lvl_op(acc_mask, mask)
+                };
             }
         }
         
@@ -194,14 +260,20 @@ where
             let lvl_non_empty_states = self.lvls_non_empty_states.as_ref()
                                        .last().unwrap_unchecked();
             for &i in lvl_non_empty_states {
+                if Op::FOLD_SHORT_CIRCUIT && acc.is_empty() {
+                    break;
+                }
                 let (array, array_state) = self.states.get_unchecked(i);
                 let data = array_state.data_block(array.borrow(), level_index);
-                acc = this.op.data_op(acc, data);
+                acc = this.op.fold_data_op(acc, data, i);
             }
         } else {
-            for (array, array_state) in &self.states {
+            for (i, (array, array_state)) in self.states.iter().enumerate() {
+                if Op::FOLD_SHORT_CIRCUIT && acc.is_empty() {
+                    break;
+                }
                 let data = array_state.data_block(array.borrow(), level_index);
-                acc = this.op.data_op(acc, data);
+                acc = this.op.fold_data_op(acc, data, i);
             }
         }
         
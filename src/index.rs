@@ -0,0 +1,281 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use crate::{BitBlock, IndexOutOfRange};
+use crate::const_utils::const_int::{ConstInteger, ConstUsize};
+
+/// Index, known to be within [max_range()] of [SparseHierarchy]ies
+/// with `Mask` and `LevelCount` configuration.
+///
+/// Accessors that take `Index` skip range validation. Indices returned
+/// by [iter_indexed()] are valid by construction - so can be passed back
+/// to [get_at()] or [insert_at()] as-is.
+///
+/// [max_range()]: crate::SparseHierarchy::max_range
+/// [SparseHierarchy]: crate::SparseHierarchy
+/// [iter_indexed()]: crate::SparseHierarchy::iter_indexed
+/// [get_at()]: crate::SparseHierarchy::get_at
+/// [insert_at()]: crate::SparseArray::insert_at
+///
+/// # From u16/u32
+///
+/// For built-in masks, `Index` is infallibly constructible `From<u16>`/`From<u32>`
+/// when the whole primitive range fits within [MAX] - no range check at all.
+/// Otherwise it is only `TryFrom`. Choosing wrong one is a compile error:
+///
+/// ```
+/// # use hi_sparse_array::Index;
+/// # use hi_sparse_array::const_utils::ConstUsize;
+/// // 64^3 > 2^16
+/// let i: Index<u64, ConstUsize<3>> = 42u16.into();
+/// ```
+/// ```compile_fail
+/// # use hi_sparse_array::Index;
+/// # use hi_sparse_array::const_utils::ConstUsize;
+/// // 64^2 < 2^16
+/// let i: Index<u64, ConstUsize<2>> = 42u16.into();
+/// ```
+///
+/// [MAX]: Self::MAX
+pub struct Index<Mask, LevelCount>{
+    index: usize,
+    phantom_data: PhantomData<fn() -> (Mask, LevelCount)>
+}
+
+impl<Mask, LevelCount> Index<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    /// Max valid index - `Mask::SIZE ^ LevelCount - 1`.
+    ///
+    /// Capacity is `MAX + 1`. If capacity does not fit `usize`, 
+    /// all `usize` values are valid and `MAX` is `usize::MAX`.
+    pub const MAX: usize = match Mask::SIZE.checked_pow(LevelCount::VALUE as _) {
+        Some(capacity) => capacity - 1,
+        None => usize::MAX,
+    };
+    
+    /// Same as [MAX].
+    ///
+    /// [MAX]: Self::MAX
+    #[inline]
+    pub fn max_range() -> usize {
+        Self::MAX
+    }
+
+    /// Returns [IndexOutOfRange], if `index` is outside [MAX].
+    ///
+    /// [MAX]: Self::MAX
+    #[inline]
+    pub fn new(index: usize) -> Result<Self, IndexOutOfRange> {
+        let max_range = Self::MAX;
+        if index > max_range {
+            return Err(IndexOutOfRange{ index, max_range });
+        }
+        Ok(unsafe{ Self::new_unchecked(index) })
+    }
+
+    /// # Safety
+    ///
+    /// `index` must be within [MAX].
+    ///
+    /// [MAX]: Self::MAX
+    #[inline]
+    pub unsafe fn new_unchecked(index: usize) -> Self {
+        debug_assert!(index <= Self::MAX);
+        Self{ index, phantom_data: PhantomData }
+    }
+}
+
+impl<Mask, LevelCount> Index<Mask, LevelCount> {
+    #[inline]
+    pub fn value(self) -> usize {
+        self.index
+    }
+}
+
+impl<Mask, LevelCount> TryFrom<usize> for Index<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    type Error = IndexOutOfRange;
+
+    #[inline]
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        Self::new(index)
+    }
+}
+
+/// `From<$from>` for configurations, where any `$from` value is within
+/// [Index::MAX]. Checked at compile time.
+macro_rules! impl_from_primitive {
+    ($from:ty => $mask:ty: $($level_count:literal),+) => {$(
+        impl From<$from> for Index<$mask, ConstUsize<$level_count>> {
+            #[inline]
+            fn from(index: $from) -> Self {
+                const _: () = assert!(
+                    Index::<$mask, ConstUsize<$level_count>>::MAX >= <$from>::MAX as usize
+                );
+                unsafe{ Self::new_unchecked(index as usize) }
+            }
+        }
+    )+};
+}
+
+/// `TryFrom<$from>` for configurations, too narrow for the whole `$from` range.
+/// Checked at compile time.
+macro_rules! impl_try_from_primitive {
+    ($from:ty => $mask:ty: $($level_count:literal),+) => {$(
+        impl TryFrom<$from> for Index<$mask, ConstUsize<$level_count>> {
+            type Error = IndexOutOfRange;
+
+            #[inline]
+            fn try_from(index: $from) -> Result<Self, Self::Error> {
+                const _: () = assert!(
+                    Index::<$mask, ConstUsize<$level_count>>::MAX < <$from>::MAX as usize
+                );
+                Self::new(index as usize)
+            }
+        }
+    )+};
+}
+
+impl_from_primitive!    (u16 => u16: 4,5,6,7,8,9);
+impl_try_from_primitive!(u16 => u16: 1,2,3);
+impl_from_primitive!    (u32 => u16: 8,9);
+impl_try_from_primitive!(u32 => u16: 1,2,3,4,5,6,7);
+
+impl_from_primitive!    (u16 => u32: 4,5,6,7,8,9);
+impl_try_from_primitive!(u16 => u32: 1,2,3);
+impl_from_primitive!    (u32 => u32: 7,8,9);
+impl_try_from_primitive!(u32 => u32: 1,2,3,4,5,6);
+
+impl_from_primitive!    (u16 => u64: 3,4,5,6,7,8,9);
+impl_try_from_primitive!(u16 => u64: 1,2);
+impl_from_primitive!    (u32 => u64: 6,7,8,9);
+impl_try_from_primitive!(u32 => u64: 1,2,3,4,5);
+
+impl_from_primitive!    (u16 => u128: 3,4,5,6,7,8,9);
+impl_try_from_primitive!(u16 => u128: 1,2);
+impl_from_primitive!    (u32 => u128: 5,6,7,8,9);
+impl_try_from_primitive!(u32 => u128: 1,2,3,4);
+
+#[cfg(feature = "simd")]
+mod simd_from_primitive {
+    use super::*;
+    use crate::U64x8;
+
+    impl_from_primitive!    (u16 => wide::u64x2: 3,4,5,6,7,8,9);
+    impl_try_from_primitive!(u16 => wide::u64x2: 1,2);
+    impl_from_primitive!    (u32 => wide::u64x2: 5,6,7,8,9);
+    impl_try_from_primitive!(u32 => wide::u64x2: 1,2,3,4);
+
+    impl_from_primitive!    (u16 => wide::u64x4: 2,3,4,5,6,7,8,9);
+    impl_try_from_primitive!(u16 => wide::u64x4: 1);
+    impl_from_primitive!    (u32 => wide::u64x4: 4,5,6,7,8,9);
+    impl_try_from_primitive!(u32 => wide::u64x4: 1,2,3);
+
+    impl_from_primitive!    (u16 => U64x8: 2,3,4,5,6,7,8,9);
+    impl_try_from_primitive!(u16 => U64x8: 1);
+    impl_from_primitive!    (u32 => U64x8: 4,5,6,7,8,9);
+    impl_try_from_primitive!(u32 => U64x8: 1,2,3);
+}
+
+impl<Mask, LevelCount> From<Index<Mask, LevelCount>> for usize {
+    #[inline]
+    fn from(index: Index<Mask, LevelCount>) -> Self {
+        index.index
+    }
+}
+
+impl<Mask, LevelCount> Clone for Index<Mask, LevelCount> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Mask, LevelCount> Copy for Index<Mask, LevelCount> {}
+
+impl<Mask, LevelCount> fmt::Debug for Index<Mask, LevelCount> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.index, f)
+    }
+}
+
+impl<Mask, LevelCount> PartialEq for Index<Mask, LevelCount> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<Mask, LevelCount> Eq for Index<Mask, LevelCount> {}
+
+impl<Mask, LevelCount> PartialOrd for Index<Mask, LevelCount> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Mask, LevelCount> Ord for Index<Mask, LevelCount> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<Mask, LevelCount> Hash for Index<Mask, LevelCount> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+/// [SparseArray] key type.
+///
+/// Lets each container speak its own id space - `EntityId(u32)`,
+/// `ChunkId(u16)`, etc. - instead of bare `usize`, so ids of different
+/// spaces can not be mixed up. Implemented for all `Copy` types
+/// (fallibly) convertible to `usize` and back - including `usize` itself.
+///
+/// Keys are converted to `usize` index on the way in, and range-checked
+/// the same way as [Index].
+///
+/// `u64` is a key too - use it for 64-bit ids on 32-bit targets. 
+/// Key, that does not fit `usize`, is out of range - it is never truncated.
+///
+/// [SparseArray]: crate::SparseArray
+pub trait Key: Copy + TryInto<usize> + TryFrom<usize> {}
+
+impl<T> Key for T
+where
+    T: Copy + TryInto<usize> + TryFrom<usize>
+{}
+
+/// `key` as index. `None` if it does not fit `usize`.
+#[inline]
+pub(crate) fn index_from_key<K: Key>(key: K) -> Option<usize> {
+    key.try_into().ok()
+}
+
+/// # Panics
+///
+/// Will panic if `index` is not representable as `K`.
+#[inline]
+pub(crate) fn key_from_index<K: Key>(index: usize) -> K {
+    match K::try_from(index) {
+        Ok(key) => key,
+        Err(_) => key_out_of_range(index),
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn key_out_of_range(index: usize) -> ! {
+    panic!("index {index} is not representable as key")
+}
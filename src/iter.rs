@@ -1,9 +1,12 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
 use std::ops::ControlFlow;
 use crate::sparse_hierarchy::{SparseHierarchy, SparseHierarchyState};
-use crate::{BitBlock, data_block_index};
+use crate::{BitBlock, data_block_index, Empty, Index, Key, SparseArray, SparseArrayLevels};
 use crate::bit_queue::BitQueue;
-use crate::const_utils::const_int::{const_for_rev, ConstInteger, ConstIntVisitor, ConstUsize};
+use crate::const_utils::const_int::{const_for, const_for_rev, ConstInteger, ConstIntVisitor, ConstUsize};
 use crate::const_utils::const_array::ConstArrayType;
+use crate::sparse_array::level_indices;
 use crate::utils::Take;
 use crate::utils::array::Array;
 
@@ -12,134 +15,969 @@ use crate::utils::array::Array;
 type LevelIndices<T: SparseHierarchy> =
     ConstArrayType<
         usize,
-        <T::LevelCount as ConstInteger>::Dec   
+        <T::LevelCount as ConstInteger>::Dec
     >;
 
-/// Each hierarchy level has its own iterator.
-/// 
-/// [T::LevelMaskType::BitsIter; T::LevelCount]
-type LevelIterators<T: SparseHierarchy> =
+/// Each non-terminal hierarchy level has its own iterator.
+///
+/// [T::LevelMaskType::BitsIter; T::LevelCount - 1]
+type UpperLevelIterators<T: SparseHierarchy> =
     ConstArrayType<
         <T::LevelMaskType as BitBlock>::BitsIter,
-        T::LevelCount
+        <T::LevelCount as ConstInteger>::Dec
     >;
 
-/// [SparseHierarchy] iterator.
-/// 
-/// For non-[EXACT_HIERARCHY], iterator may return empty items. 
-pub struct Iter<'a, T>
+/// Terminal (last level) blocks iterator.
+///
+/// Traverse hierarchy, and stops at each terminal block. Returns its mask.
+/// Terminal block's data is accessible through [data_block()], until
+/// the next [next()] call.
+///
+/// All masks are visited as-is, so for non-[EXACT_HIERARCHY] terminal masks
+/// may be empty.
+///
+/// Fused - once exhausted, all level iterators are empty (or root visited,
+/// for single level hierarchy), so [next()] keeps returning `None`.
+pub(crate) struct BlockIter<'a, T>
 where
     T: SparseHierarchy,
 {
     container: &'a T,
-    
-    /// [T::LevelMaskType::BitsIter; T::LevelCount]
-    level_iters: LevelIterators<T>,
-    
+
+    /// [T::LevelMaskType::BitsIter; T::LevelCount - 1]
+    level_iters: UpperLevelIterators<T>,
+
     /// [usize; T::LevelCount::N - 1]
     level_indices: LevelIndices<T>,
 
+    /// Used only with single level hierarchy.
+    root_visited: bool,
+
     state: T::State,
 }
 
-impl<'a, T> Iter<'a, T>
+impl<'a, T> Clone for BlockIter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            container: self.container,
+            level_iters: Array::from_fn(|i| self.level_iters.as_ref()[i].clone()),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            root_visited: self.root_visited,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<'a, T> BlockIter<'a, T>
 where
     T: SparseHierarchy,
 {
     #[inline]
     pub fn new(container: &'a T) -> Self {
-        let mut level_iters: LevelIterators<T> = Array::from_fn(|_| BitQueue::empty());
-        
+        let mut level_iters: UpperLevelIterators<T> = Array::from_fn(|_| BitQueue::empty());
         let mut state = T::State::new(container);
-        
-        let root_mask = unsafe{
-            state.select_level_bock(container, ConstUsize::<0>, 0)
-        };
-        let level0_iter = root_mask.take_or_clone().into_bits_iter();
-        
-        level_iters.as_mut()[0] = level0_iter; 
-        
+
+        if T::LevelCount::VALUE > 1 {
+            let root_mask = unsafe{
+                state.select_level_bock(container, ConstUsize::<0>, 0)
+            };
+            level_iters.as_mut()[0] = root_mask.take_or_clone().into_bits_iter();
+        }
+
         Self{
             container,
             level_iters,
-            
-            // TODO: refactor this
-            // usize::MAX - is marker, that we're in "intial state".
-            // Which means that only level0_iter initialized, and in original state.
-            level_indices: Array::from_fn(|_| usize::MAX),
-
+            level_indices: Array::from_fn(|_| 0),
+            root_visited: false,
             state,
         }
     }
+
+    /// Positions iterator at terminal block of `index`, with all blocks
+    /// before it consumed. Returns that block's bits, starting from `index`.
+    ///
+    /// If there is no such block - returns empty bits, and iterator
+    /// continues from the next existing one.
+    pub fn resume(container: &'a T, index: usize)
+        -> (Self, <T::LevelMaskType as BitBlock>::BitsIter)
+    {
+        let mut this = Self::new(container);
+        if index > T::MAX_INDEX {
+            // Nothing left.
+            this.level_iters.as_mut()[0] = BitQueue::empty();
+            this.root_visited = true;
+            return (this, BitQueue::empty());
+        }
+        let target = level_indices::<T::LevelMaskType, T::LevelCount>(index);
+        let terminal_index = target.as_ref()[T::LevelCount::VALUE - 1];
+
+        if T::LevelCount::VALUE == 1 {
+            this.root_visited = true;
+            let mask = unsafe{
+                this.state.select_level_bock(container, ConstUsize::<0>, 0)
+            };
+            let mut bits = mask.take_or_clone().into_bits_iter();
+            bits.skip_to(terminal_index);
+            return (this, bits);
+        }
+
+        let ctrl = const_for(ConstUsize::<0>, T::LevelCount::DEFAULT.dec(), V(&mut this, target.as_ref()));
+        struct V<'b,'c,'a,T: SparseHierarchy>(&'b mut BlockIter<'a, T>, &'c [usize]);
+        impl<'b,'c,'a,T: SparseHierarchy> ConstIntVisitor for V<'b,'c,'a,T> {
+            type Out = <T::LevelMaskType as BitBlock>::BitsIter;
+            #[inline(always)]
+            fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<Self::Out> {
+                let index = self.1[i.value()];
+                let level_iter = unsafe{
+                    self.0
+                    .level_iters.as_mut()
+                    .get_unchecked_mut(i.value())
+                };
+                // Mask off consumed bits.
+                level_iter.skip_to(index);
+                if level_iter.clone().next() != Some(index) {
+                    // Target block does not exist - continue from
+                    // the next one at this level.
+                    return ControlFlow::Break(BitQueue::empty());
+                }
+                level_iter.next();
+                unsafe{
+                    *self.0
+                        .level_indices.as_mut()
+                        .get_unchecked_mut(i.value())
+                        = index;
+                }
+
+                let level_depth = i.inc();
+                let level_mask = unsafe{
+                    self.0.state.select_level_bock(
+                        self.0.container,
+                        level_depth,
+                        index
+                    )
+                };
+                let mut bits = level_mask.take_or_clone().into_bits_iter();
+                if level_depth.value() == T::LevelCount::VALUE - 1 {
+                    // terminal block
+                    bits.skip_to(self.1[level_depth.value()]);
+                    return ControlFlow::Break(bits);
+                }
+                *unsafe{
+                    self.0
+                    .level_iters.as_mut()
+                    .get_unchecked_mut(level_depth.value())
+                } = bits;
+                ControlFlow::Continue(())
+            }
+        }
+        let bits = match ctrl {
+            ControlFlow::Break(bits) => bits,
+            ControlFlow::Continue(()) => unreachable!(),
+        };
+        (this, bits)
+    }
+
+    /// Index of the first element of current terminal block.
+    #[inline]
+    pub fn base_index(&self) -> usize {
+        data_block_index::<T>(&self.level_indices, 0)
+    }
+
+    /// Index of the element at `level_index` of current terminal block.
+    #[inline]
+    pub fn index(&self, level_index: usize) -> usize {
+        data_block_index::<T>(&self.level_indices, level_index)
+    }
+
+    /// # Safety
+    ///
+    /// - terminal block must be selected ([next()] returned `Some`).
+    /// - `level_index` is not checked.
+    #[inline]
+    pub unsafe fn data_block(&self, level_index: usize) -> T::Data<'a> {
+        self.state.data_block(self.container, level_index)
+    }
+
+    /// # Safety
+    ///
+    /// Same as for [data_block()].
+    #[inline(always)]
+    pub unsafe fn prefetch_data_block(&self, level_index: usize) {
+        self.state.prefetch_data_block(self.container, level_index)
+    }
+
+    #[inline]
+    pub fn state(&self) -> &T::State {
+        &self.state
+    }
 }
 
-impl<'a, T> Iterator for Iter<'a, T>
+impl<'a, T> Iterator for BlockIter<'a, T>
 where
     T: SparseHierarchy,
 {
-    type Item = (usize/*index*/, T::Data<'a>);
+    type Item = T::LevelMask<'a>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let level_index = loop {
-            // We're driven by top-level iterator.
-            let top_level_iter = self.level_iters.as_mut().last_mut().unwrap();
-            if let Some(index) = top_level_iter.next() {
-                break index;
-            } else {
-                let ctrl = const_for_rev(ConstUsize::<0>, T::LevelCount::DEFAULT.dec(), V(self)); 
-                struct V<'b,'a,T: SparseHierarchy>(&'b mut Iter<'a, T>); 
-                impl<'b,'a,T: SparseHierarchy> ConstIntVisitor for V<'b,'a,T> {
-                    type Out = ();
-                    #[inline(always)]
-                    fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<()> {
-                        let level_iter = unsafe{
-                            self.0
-                            .level_iters.as_mut()
-                            .get_unchecked_mut(i.value())
+        if T::LevelCount::VALUE == 1 {
+            if self.root_visited {
+                return None;
+            }
+            self.root_visited = true;
+            return Some(unsafe{
+                self.state.select_level_bock(self.container, ConstUsize::<0>, 0)
+            });
+        }
+
+        loop {
+            let ctrl = const_for_rev(ConstUsize::<0>, T::LevelCount::DEFAULT.dec(), V(self));
+            struct V<'b,'a,T: SparseHierarchy>(&'b mut BlockIter<'a, T>);
+            impl<'b,'a,T: SparseHierarchy> ConstIntVisitor for V<'b,'a,T> {
+                type Out = Option<T::LevelMask<'a>>;
+                #[inline(always)]
+                fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<Self::Out> {
+                    let level_iter = unsafe{
+                        self.0
+                        .level_iters.as_mut()
+                        .get_unchecked_mut(i.value())
+                    };
+                    if let Some(index) = level_iter.next(){
+                        // 1. update level_index
+                        unsafe{
+                            *self.0
+                                .level_indices.as_mut()
+                                .get_unchecked_mut(i.value())
+                                = index;
+                        }
+
+                        // 2. select child block
+                        let level_depth = i.inc();
+                        let level_mask = unsafe{
+                            self.0.state.select_level_bock(
+                                self.0.container,
+                                level_depth,
+                                index
+                            )
                         };
-                        if let Some(index) = level_iter.next(){
-                            // 1. update level_index
-                            unsafe{
-                                *self.0
-                                    .level_indices.as_mut()
-                                    .get_unchecked_mut(i.value()) 
-                                    = index; 
-                            }
-                            
-                            // 2. update level_iter from mask
-                            let level_depth = i.inc();                            
-                            let level_mask = unsafe{
-                                self.0.state.select_level_bock(
-                                    &self.0.container,
-                                    level_depth,
-                                    index
-                                )
-                            };
-                            *unsafe{
-                                self.0
-                                .level_iters.as_mut()
-                                .get_unchecked_mut(level_depth.value())
-                            } = level_mask.take_or_clone().into_bits_iter(); 
-                            
-                            ControlFlow::Break(())
-                        } else {
-                            ControlFlow::Continue(())
+
+                        if level_depth.value() == T::LevelCount::VALUE - 1 {
+                            // terminal block
+                            return ControlFlow::Break(Some(level_mask));
                         }
+
+                        // 3. update level_iter from mask
+                        *unsafe{
+                            self.0
+                            .level_iters.as_mut()
+                            .get_unchecked_mut(level_depth.value())
+                        } = level_mask.take_or_clone().into_bits_iter();
+
+                        ControlFlow::Break(None)
+                    } else {
+                        ControlFlow::Continue(())
                     }
-                }   
-                if ctrl.is_continue(){
-                    // We traversed through whole hierarchy and 
-                    // root iter have nothing more. 
-                    return None;
                 }
             }
-        };
+            match ctrl {
+                ControlFlow::Break(Some(mask)) => return Some(mask),
+                ControlFlow::Break(None) => continue,
+                // We traversed through whole hierarchy and
+                // root iter have nothing more.
+                ControlFlow::Continue(()) => return None,
+            }
+        }
+    }
+}
+
+impl<'a, T> FusedIterator for BlockIter<'a, T>
+where
+    T: SparseHierarchy,
+{}
+
+/// Terminal block, passed by [for_each_block()].
+///
+/// [for_each_block()]: SparseHierarchy::for_each_block
+pub struct TerminalBlock<'b, 'a, T>
+where
+    T: SparseHierarchy,
+{
+    pub(crate) blocks: &'b BlockIter<'a, T>,
+}
+
+impl<'b, 'a, T> TerminalBlock<'b, 'a, T>
+where
+    T: SparseHierarchy,
+{
+    /// Item at `lane` of block.
+    ///
+    /// `lane` must be raised in block mask - otherwise item is
+    /// implementation-defined (but safe).
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lane` is outside block.
+    #[inline]
+    pub fn get(&self, lane: usize) -> T::Data<'a> {
+        assert!(lane < T::LevelMaskType::SIZE);
+        unsafe{ self.blocks.data_block(lane) }
+    }
+}
+
+/// [Iter] position.
+///
+/// Just a key - all keys below are consumed. Can be used with any container
+/// of the same configuration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct IterPos(usize);
+
+impl IterPos {
+    /// Position, from which iteration starts at `index`.
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        Self(index)
+    }
 
+    /// Smallest key, that is not consumed yet.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// [SparseHierarchy] iterator.
+///
+/// For non-[EXACT_HIERARCHY], empty items are skipped - unless
+/// container knows its [iter_len()]. Then all stored items are returned,
+/// and [size_hint()] is exact.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+/// [iter_len()]: SparseHierarchy::iter_len
+/// [size_hint()]: Iterator::size_hint
+pub struct Iter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    blocks: BlockIter<'a, T>,
+
+    /// Terminal level iterator.
+    level_iter: <T::LevelMaskType as BitBlock>::BitsIter,
+
+    /// Items left, if [iter_len()] is known.
+    ///
+    /// [iter_len()]: SparseHierarchy::iter_len
+    remaining: Option<usize>,
+
+    /// All keys below are consumed.
+    next_index: usize,
+}
+
+impl<'a, T> Clone for Iter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            blocks: self.blocks.clone(),
+            level_iter: self.level_iter.clone(),
+            remaining: self.remaining,
+            next_index: self.next_index,
+        }
+    }
+}
+
+impl<'a, T> Iter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    pub fn new(container: &'a T) -> Self {
+        Self{
+            blocks: BlockIter::new(container),
+            level_iter: BitQueue::empty(),
+            remaining: container.iter_len(),
+            next_index: 0,
+        }
+    }
+
+    /// Iteration position. Resume with [resume_at()].
+    ///
+    /// [resume_at()]: Self::resume_at
+    #[inline]
+    pub fn position(&self) -> IterPos {
+        IterPos(self.next_index)
+    }
+
+    /// Continues iteration of `container` from `pos`.
+    ///
+    /// Items before `pos` are never returned again, and none after are skipped -
+    /// even if `container` changed since `pos` was taken. If item at `pos` was
+    /// removed, iteration continues from the next one.
+    ///
+    /// Only hierarchy path to `pos` is visited. But if [iter_len()] is known,
+    /// rest of the hierarchy masks are traversed to count remaining items.
+    ///
+    /// [iter_len()]: SparseHierarchy::iter_len
+    pub fn resume_at(container: &'a T, pos: IterPos) -> Self {
+        let (blocks, level_iter) = BlockIter::resume(container, pos.0);
+        let remaining = container.iter_len().map(|_| {
+            let (rest, bits) = BlockIter::resume(container, pos.0);
+            rest.fold(bits.count(), |acc, mask| acc + mask.borrow().count_ones())
+        });
+        Self{
+            blocks,
+            level_iter,
+            remaining,
+            next_index: pos.0,
+        }
+    }
+
+    /// Collects remaining items, with owned data.
+    ///
+    /// Same as `map(|(i, d)| (i, d.borrow().clone())).collect()`, but
+    /// owned data of lazy hierarchies is moved, not cloned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use hi_sparse_array::{config, intersection, SparseArray, SparseHierarchy};
+    /// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+    /// let mut a1 = Array::default();
+    /// let mut a2 = Array::default();
+    /// a1.insert(1, Some(10));
+    /// a1.insert(7, Some(20));
+    /// a2.insert(7, Some(2));
+    ///
+    /// assert_eq!(a1.iter().to_pairs(), [(1, Some(10)), (7, Some(20))]);
+    ///
+    /// let and = intersection(&a1, &a2, |l, r| Some(l.unwrap() * r.unwrap()));
+    /// assert_eq!(and.iter().to_pairs(), [(7, Some(40))]);
+    /// ```
+    #[inline]
+    pub fn to_pairs(self) -> Vec<(usize, T::DataType)>
+    where
+        T::DataType: Clone
+    {
+        self.cloned().collect()
+    }
+
+    /// Iterator over `(index, data)` with owned data.
+    ///
+    /// Data of containers is cloned, owned data of lazy
+    /// hierarchies is moved as-is.
+    #[inline]
+    pub fn cloned(self) -> ClonedIter<'a, T>
+    where
+        T::DataType: Clone
+    {
+        ClonedIter(self)
+    }
+
+    /// Same as [cloned()], for [Copy] data.
+    ///
+    /// [cloned()]: Self::cloned
+    #[inline]
+    pub fn copied(self) -> ClonedIter<'a, T>
+    where
+        T::DataType: Copy
+    {
+        ClonedIter(self)
+    }
+
+    /// Key of the next item, without consuming it.
+    ///
+    /// Interleaves freely with [next()] - which then returns item with the
+    /// peeked key. At terminal block boundary, upper level iterators are
+    /// advanced to the next block - this is not observable, except that
+    /// empty items before the peeked one (for non-[EXACT_HIERARCHY]) are
+    /// consumed. [position()] is not affected.
+    ///
+    /// Does not construct data - except for non-[EXACT_HIERARCHY] without
+    /// known [iter_len()], where items are checked for emptiness.
+    ///
+    /// [next()]: Iterator::next
+    /// [position()]: Self::position
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    /// [iter_len()]: SparseHierarchy::iter_len
+    #[inline]
+    pub fn peek_index(&mut self) -> Option<usize> {
+        let level_index = self.peek_level_index()?;
+        Some(self.blocks.index(level_index))
+    }
+
+    /// Next item, without consuming it.
+    ///
+    /// Same as [peek_index()], but also returns data. Data is obtained anew
+    /// on each call - for lazy hierarchies it is constructed again by the
+    /// following [next()].
+    ///
+    /// [peek_index()]: Self::peek_index
+    /// [next()]: Iterator::next
+    #[inline]
+    pub fn peek(&mut self) -> Option<(usize, T::Data<'a>)> {
+        let level_index = self.peek_level_index()?;
         let data_block = unsafe {
-            self.state.data_block(&self.container, level_index)
+            self.blocks.data_block(level_index)
         };
-        let block_index = data_block_index::<T>(&self.level_indices, level_index);
-        Some((block_index, data_block))
-    }    
-}
\ No newline at end of file
+        Some((self.blocks.index(level_index), data_block))
+    }
+
+    /// Terminal level index of the next non-skipped element. Not consumed.
+    #[inline]
+    fn peek_level_index(&mut self) -> Option<usize> {
+        loop {
+            let Some(level_index) = self.level_iter.clone().next() else {
+                let mask = self.blocks.next()?;
+                self.level_iter = mask.take_or_clone().into_bits_iter();
+                continue;
+            };
+            // Compile-time branch - exact hierarchy always returns here.
+            if T::EXACT_HIERARCHY
+            || self.remaining.is_some()
+            || !unsafe{ self.blocks.data_block(level_index) }.borrow().is_empty()
+            {
+                return Some(level_index);
+            }
+            self.level_iter.next();
+        }
+    }
+
+    /// Returns terminal level index of the next element.
+    #[inline]
+    fn next_level_index(&mut self) -> Option<usize> {
+        loop {
+            if let Some(index) = self.level_iter.next() {
+                return Some(index);
+            }
+            let mask = self.blocks.next()?;
+            self.level_iter = mask.take_or_clone().into_bits_iter();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    type Item = (usize/*index*/, T::Data<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let level_index = self.next_level_index()?;
+            if cfg!(feature = "prefetch") {
+                if let Some(next) = self.level_iter.clone().next() {
+                    unsafe{ self.blocks.prefetch_data_block(next) };
+                }
+            }
+            let data_block = unsafe {
+                self.blocks.data_block(level_index)
+            };
+            // Compile-time branch - exact hierarchy always returns here.
+            if T::EXACT_HIERARCHY
+            || self.remaining.is_some()
+            || !data_block.borrow().is_empty()
+            {
+                if let Some(remaining) = &mut self.remaining {
+                    *remaining -= 1;
+                }
+                let block_index = self.blocks.index(level_index);
+                // Saturates only if hierarchy capacity does not fit usize.
+                self.next_index = block_index.saturating_add(1);
+                return Some((block_index, data_block));
+            }
+        }
+    }
+
+    /// Traverse terminal blocks bits one by one, without per-item
+    /// [next()] bookkeeping. Prefer over `next()`-ing.
+    ///
+    /// [next()]: Self::next
+    #[inline]
+    fn for_each<F>(self, mut f: F)
+    where
+        F: FnMut(Self::Item)
+    {
+        let skip_empty = !T::EXACT_HIERARCHY && self.remaining.is_none();
+        let mut blocks = self.blocks;
+        let mut level_iter = self.level_iter;
+        loop {
+            let _ = level_iter.traverse(|level_index| {
+                let data = unsafe{ blocks.data_block(level_index) };
+                if !skip_empty || !data.borrow().is_empty() {
+                    f((blocks.index(level_index), data));
+                }
+                ControlFlow::Continue(())
+            });
+            match blocks.next() {
+                Some(mask) => level_iter = mask.take_or_clone().into_bits_iter(),
+                None => return,
+            }
+        }
+    }
+
+    /// Same as [for_each()].
+    ///
+    /// [for_each()]: Self::for_each
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B
+    {
+        let mut acc = Some(init);
+        self.for_each(|item| {
+            acc = Some(f(unsafe{ Option::take(&mut acc).unwrap_unchecked() }, item));
+        });
+        unsafe{ acc.unwrap_unchecked() }
+    }
+
+    /// With [EXACT_HIERARCHY] or known [iter_len()], skips whole terminal
+    /// blocks by mask popcount - skipped items are never touched.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    /// [iter_len()]: SparseHierarchy::iter_len
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if !T::EXACT_HIERARCHY && self.remaining.is_none() {
+            // Each item must be checked for emptiness.
+            for _ in 0..n {
+                self.next()?;
+            }
+            return self.next();
+        }
+
+        let mut skip = n;
+        // Rest of the current block.
+        while skip != 0 {
+            if self.level_iter.next().is_none() {
+                break;
+            }
+            skip -= 1;
+        }
+        // Whole blocks.
+        while skip != 0 {
+            let Some(mask) = self.blocks.next() else {
+                self.remaining = self.remaining.map(|_| 0);
+                // Skipped items are consumed - position past all of them.
+                self.next_index = T::MAX_INDEX.saturating_add(1);
+                return None;
+            };
+            let len = mask.borrow().count_ones();
+            if len <= skip {
+                skip -= len;
+                continue;
+            }
+            self.level_iter = mask.take_or_clone().into_bits_iter();
+            for _ in 0..skip {
+                self.level_iter.next();
+            }
+            break;
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= n;
+        }
+        let item = self.next();
+        if item.is_none() && n != 0 {
+            // Skipped items of the last block are consumed.
+            self.next_index = T::MAX_INDEX.saturating_add(1);
+        }
+        item
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (0, None),
+        }
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T>
+where
+    T: SparseHierarchy,
+{}
+
+impl<'a, Levels, Data, K> ExactSizeIterator for Iter<'a, SparseArray<Levels, Data, K>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{}
+
+/// [SparseHierarchy] keys iterator.
+///
+/// Returns keys of the same items as [Iter], as plain `usize` - so
+/// works as a regular [Iterator] regardless of source data borrowing.
+///
+/// Traverse hierarchy masks only, never touching data - except for
+/// non-[EXACT_HIERARCHY] without known [iter_len()], where items
+/// must be checked for emptiness.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+/// [iter_len()]: SparseHierarchy::iter_len
+pub struct KeysIter<'a, T>(Iter<'a, T>)
+where
+    T: SparseHierarchy;
+
+impl<'a, T> Clone for KeysIter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T> KeysIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    pub fn new(container: &'a T) -> Self {
+        Self(Iter::new(container))
+    }
+}
+
+impl<'a, T> Iterator for KeysIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let iter = &mut self.0;
+        loop {
+            let level_index = iter.next_level_index()?;
+            // Compile-time branch - exact hierarchy always returns here.
+            if T::EXACT_HIERARCHY
+            || iter.remaining.is_some()
+            || !unsafe{ iter.blocks.data_block(level_index) }.borrow().is_empty()
+            {
+                if let Some(remaining) = &mut iter.remaining {
+                    *remaining -= 1;
+                }
+                let index = iter.blocks.index(level_index);
+                iter.next_index = index.saturating_add(1);
+                return Some(index);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> FusedIterator for KeysIter<'a, T>
+where
+    T: SparseHierarchy,
+{}
+
+impl<'a, Levels, Data, K> ExactSizeIterator for KeysIter<'a, SparseArray<Levels, Data, K>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{}
+
+/// [Iter] with owned data.
+///
+/// Constructed by [Iter::cloned()] and [Iter::copied()].
+pub struct ClonedIter<'a, T>(Iter<'a, T>)
+where
+    T: SparseHierarchy;
+
+impl<'a, T> Clone for ClonedIter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T> Iterator for ClonedIter<'a, T>
+where
+    T: SparseHierarchy<DataType: Clone>,
+{
+    type Item = (usize, T::DataType);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, data) = self.0.next()?;
+        Some((index, data.take_or_clone()))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let (index, data) = self.0.nth(n)?;
+        Some((index, data.take_or_clone()))
+    }
+
+    #[inline]
+    fn for_each<F>(self, mut f: F)
+    where
+        F: FnMut(Self::Item)
+    {
+        self.0.for_each(|(index, data)| f((index, data.take_or_clone())))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> FusedIterator for ClonedIter<'a, T>
+where
+    T: SparseHierarchy<DataType: Clone>,
+{}
+
+impl<'a, Levels, Data, K> ExactSizeIterator for ClonedIter<'a, SparseArray<Levels, Data, K>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+{}
+
+/// [Iter] with keys as [Index]es.
+///
+/// Constructed by [iter_indexed()].
+///
+/// [iter_indexed()]: SparseHierarchy::iter_indexed
+pub struct IndexedIter<'a, T>(Iter<'a, T>)
+where
+    T: SparseHierarchy;
+
+impl<'a, T> Clone for IndexedIter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T> IndexedIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    pub fn new(container: &'a T) -> Self {
+        Self(Iter::new(container))
+    }
+}
+
+impl<'a, T> Iterator for IndexedIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    type Item = (Index<T::LevelMaskType, T::LevelCount>, T::Data<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, data) = self.0.next()?;
+        // Came from hierarchy - always in range.
+        Some((unsafe{ Index::new_unchecked(index) }, data))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> FusedIterator for IndexedIter<'a, T>
+where
+    T: SparseHierarchy,
+{}
+
+impl<'a, Levels, Data, K> ExactSizeIterator for IndexedIter<'a, SparseArray<Levels, Data, K>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{}
+
+/// [Iter] with known length.
+///
+/// Length is [count_keys()].
+///
+/// [count_keys()]: SparseHierarchy::count_keys
+pub struct CountedIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    iter: Iter<'a, T>,
+    remaining: usize,
+}
+
+impl<'a, T> Clone for CountedIter<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            iter: self.iter.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<'a, T> CountedIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    pub fn new(container: &'a T) -> Self {
+        Self{
+            iter: Iter::new(container),
+            remaining: container.count_keys(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for CountedIter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    type Item = (usize/*index*/, T::Data<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let item = self.iter.next();
+        debug_assert!(item.is_some());
+        self.remaining -= 1;
+        item
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> FusedIterator for CountedIter<'a, T>
+where
+    T: SparseHierarchy,
+{}
+
+impl<'a, T> ExactSizeIterator for CountedIter<'a, T>
+where
+    T: SparseHierarchy,
+{}
@@ -0,0 +1,194 @@
+/// Level blocks storage.
+///
+/// Blocks are only appended to the end, or truncated.
+pub trait BlockStorage<Block>: Default {
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push(&mut self, block: Block);
+
+    fn truncate(&mut self, len: usize);
+
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Block> where Block: 'a;
+
+    /// # Safety
+    ///
+    /// `index` is not checked.
+    unsafe fn get_unchecked(&self, index: usize) -> &Block;
+
+    /// # Safety
+    ///
+    /// `index` is not checked.
+    unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut Block;
+}
+
+impl<Block> BlockStorage<Block> for Vec<Block> {
+    #[inline]
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    #[inline]
+    fn push(&mut self, block: Block) {
+        Vec::push(self, block)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+
+    #[inline]
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Block> where Block: 'a {
+        self.as_mut_slice().iter_mut()
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> &Block {
+        self.as_slice().get_unchecked(index)
+    }
+
+    #[inline]
+    unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut Block {
+        self.as_mut_slice().get_unchecked_mut(index)
+    }
+}
+
+const CHUNK_SIZE_EXP: usize = 12;
+
+/// Storage with stable block addresses.
+///
+/// Blocks stored in fixed-size chunks of [CHUNK_SIZE](Self::CHUNK_SIZE) blocks, so
+/// growth never moves already allocated blocks.
+/// Block addresses are stable for the lifetime of the level.
+///
+/// Each chunk is allocated at once - so with big blocks,
+/// storage can occupy significantly more memory than `Vec`.
+/// Costs one extra shift/mask per block access.
+pub struct ChunkedStorage<Block> {
+    /// Each chunk have capacity of exactly [Self::CHUNK_SIZE], and never reallocates.
+    chunks: Vec<Vec<Block>>,
+}
+
+impl<Block> ChunkedStorage<Block> {
+    pub const CHUNK_SIZE: usize = 1 << CHUNK_SIZE_EXP;
+
+    #[inline]
+    fn new_chunk() -> Vec<Block> {
+        Vec::with_capacity(Self::CHUNK_SIZE)
+    }
+}
+
+impl<Block> Default for ChunkedStorage<Block> {
+    #[inline]
+    fn default() -> Self {
+        Self{ chunks: Vec::new() }
+    }
+}
+
+impl<Block: Clone> Clone for ChunkedStorage<Block> {
+    #[inline]
+    fn clone(&self) -> Self {
+        // Vec::clone does not preserve capacity.
+        let chunks = self.chunks.iter().map(|chunk| {
+            let mut new_chunk = Self::new_chunk();
+            new_chunk.extend_from_slice(chunk);
+            new_chunk
+        }).collect();
+        Self{ chunks }
+    }
+}
+
+impl<Block> BlockStorage<Block> for ChunkedStorage<Block> {
+    #[inline]
+    fn len(&self) -> usize {
+        if let Some(last_chunk) = self.chunks.last() {
+            ((self.chunks.len() - 1) << CHUNK_SIZE_EXP) + last_chunk.len()
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, block: Block) {
+        match self.chunks.last_mut() {
+            Some(last_chunk) if last_chunk.len() < Self::CHUNK_SIZE => {
+                last_chunk.push(block);
+            }
+            _ => {
+                let mut chunk = Self::new_chunk();
+                chunk.push(block);
+                self.chunks.push(chunk);
+            }
+        }
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        let chunks_len = (len + Self::CHUNK_SIZE - 1) >> CHUNK_SIZE_EXP;
+        self.chunks.truncate(chunks_len);
+        if let Some(last_chunk) = self.chunks.last_mut() {
+            let last_len = len - ((chunks_len - 1) << CHUNK_SIZE_EXP);
+            last_chunk.truncate(last_len);
+        }
+    }
+
+    #[inline]
+    fn iter_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Block> where Block: 'a {
+        self.chunks.iter_mut().flat_map(|chunk| chunk.iter_mut())
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> &Block {
+        let chunk = index >> CHUNK_SIZE_EXP;
+        let slot  = index & (Self::CHUNK_SIZE - 1);
+        self.chunks.get_unchecked(chunk).get_unchecked(slot)
+    }
+
+    #[inline]
+    unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut Block {
+        let chunk = index >> CHUNK_SIZE_EXP;
+        let slot  = index & (Self::CHUNK_SIZE - 1);
+        self.chunks.get_unchecked_mut(chunk).get_unchecked_mut(slot)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use super::{BlockStorage, ChunkedStorage};
+
+    #[test]
+    fn chunked_storage_test(){
+        type Storage = ChunkedStorage<usize>;
+        let mut s = Storage::default();
+        let len = Storage::CHUNK_SIZE * 2 + 5;
+        for i in 0..len {
+            s.push(i);
+        }
+        assert_eq!(s.len(), len);
+
+        // addresses are stable
+        let first: *const usize = unsafe{ s.get_unchecked(0) };
+        for i in 0..Storage::CHUNK_SIZE {
+            s.push(i);
+        }
+        assert_eq!(first, unsafe{ s.get_unchecked(0) } as *const _);
+
+        for i in 0..len {
+            assert_eq!(unsafe{ *s.get_unchecked(i) }, i);
+        }
+
+        s.truncate(Storage::CHUNK_SIZE);
+        assert_eq!(s.len(), Storage::CHUNK_SIZE);
+        s.truncate(1);
+        assert_eq!(s.len(), 1);
+        assert_eq!(s.iter_mut().count(), 1);
+    }
+}
@@ -1,37 +1,47 @@
-use crate::level::ILevel;
+use std::marker::PhantomData;
+use crate::level::{BlockStorage, ILevel};
 use crate::MaybeEmptyIntrusive;
 
 /// Level that uses intrusive list for an empty blocks list.
+///
+/// Use [ChunkedStorage] as `Storage`, if you need stable block addresses.
+///
+/// [ChunkedStorage]: crate::level::ChunkedStorage
 #[derive(Clone)]
-pub struct IntrusiveListLevel<Block: MaybeEmptyIntrusive>{
-    blocks: Vec<Block>,
+pub struct IntrusiveListLevel<Block: MaybeEmptyIntrusive, Storage = Vec<Block>>{
+    blocks: Storage,
     
     /// Single linked list of empty level_block indices.
-    /// Mask of empty level_block used as a "next free level_block".
+    /// Empty level_block stores "next free level_block" in itself.
     /// u64::MAX - terminator.
     root_empty_block: u64,
+
+    phantom: PhantomData<Block>,
 }
 
-impl<Block: MaybeEmptyIntrusive> Default for IntrusiveListLevel<Block> {
+impl<Block, Storage> Default for IntrusiveListLevel<Block, Storage>
+where
+    Block: MaybeEmptyIntrusive,
+    Storage: BlockStorage<Block>,
+{
     #[inline]
     fn default() -> Self {
+        //Always have empty level_block at index 0.
+        let mut blocks = Storage::default();
+        blocks.push(Block::empty());
         Self{
-            //Always have empty level_block at index 0.
-            blocks:vec![Block::empty()],
+            blocks,
             root_empty_block: u64::MAX,
+            phantom: PhantomData,
         }
     }
 }
 
-impl<Block: MaybeEmptyIntrusive> IntrusiveListLevel<Block> {
-    /// Next empty level_block link
-    /// 
-    /// Block's mask used as index to next empty level_block
-    #[inline]
-    unsafe fn next_empty_block_index(block: &mut Block) -> &mut u64 {
-        block.as_u64_mut()
-    }
-    
+impl<Block, Storage> IntrusiveListLevel<Block, Storage>
+where
+    Block: MaybeEmptyIntrusive,
+    Storage: BlockStorage<Block>,
+{
     #[inline]
     fn pop_empty_block(&mut self) -> Option<usize> {
         if self.root_empty_block == u64::MAX {
@@ -41,10 +51,8 @@ impl<Block: MaybeEmptyIntrusive> IntrusiveListLevel<Block> {
         let index = self.root_empty_block as usize;
         unsafe{
             let empty_block = self.blocks.get_unchecked_mut(index);
-            let next_empty_block_index = Self::next_empty_block_index(empty_block); 
-            
             // update list root 
-            self.root_empty_block = *next_empty_block_index;
+            self.root_empty_block = empty_block.next_empty_block();
             
             // restore original level_block zero state
             empty_block.restore_empty();
@@ -58,24 +66,89 @@ impl<Block: MaybeEmptyIntrusive> IntrusiveListLevel<Block> {
     #[inline]
     unsafe fn push_empty_block(&mut self, block_index: usize){
         let empty_block = self.blocks.get_unchecked_mut(block_index);
-        let next_empty_block_index = Self::next_empty_block_index(empty_block);
-        *next_empty_block_index = self.root_empty_block;
+        empty_block.set_next_empty_block(self.root_empty_block);
         
         self.root_empty_block = block_index as u64;
     }
+
+    /// Make all blocks empty, and link all blocks except 0 into free list
+    /// in a single linear pass. Keeps memory.
+    ///
+    /// Blocks will be popped in ascending index order.
+    #[inline]
+    pub fn clear_to_free_list(&mut self) {
+        let len = self.blocks.len();
+        for block in self.blocks.iter_mut() {
+            *block = Block::empty();
+        }
+
+        if len <= 1 {
+            self.root_empty_block = u64::MAX;
+            return;
+        }
+
+        for index in 1..len-1 {
+            unsafe{
+                let block = self.blocks.get_unchecked_mut(index);
+                block.set_next_empty_block((index + 1) as u64);
+            }
+        }
+        unsafe{
+            let last_block = self.blocks.get_unchecked_mut(len - 1);
+            last_block.set_next_empty_block(u64::MAX);
+        }
+        self.root_empty_block = 1;
+    }
+
+    /// Truncate to just the index-0 empty block.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.blocks.truncate(1);
+        unsafe{
+            *self.blocks.get_unchecked_mut(0) = Block::empty();
+        }
+        self.root_empty_block = u64::MAX;
+    }
 }
 
-impl<Block: MaybeEmptyIntrusive> ILevel for IntrusiveListLevel<Block> {
+impl<Block, Storage> ILevel for IntrusiveListLevel<Block, Storage>
+where
+    Block: MaybeEmptyIntrusive,
+    Storage: BlockStorage<Block>,
+{
     type Block = Block;
 
     #[inline]
-    fn blocks(&self) -> &[Self::Block] {
-        self.blocks.as_slice()
+    unsafe fn block_unchecked(&self, index: usize) -> &Self::Block {
+        self.blocks.get_unchecked(index)
+    }
+
+    #[inline]
+    unsafe fn block_unchecked_mut(&mut self, index: usize) -> &mut Self::Block {
+        self.blocks.get_unchecked_mut(index)
     }
 
     #[inline]
-    fn blocks_mut(&mut self) -> &mut [Self::Block] {
-        self.blocks.as_mut_slice()
+    fn blocks_len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn for_each_free_block(&self, mut f: impl FnMut(usize, bool)) {
+        let mut next = self.root_empty_block;
+        // Bounded, in case list is looped.
+        for _ in 0..self.blocks.len() {
+            if next == u64::MAX {
+                return;
+            }
+            let block_index = next as usize;
+            if block_index >= self.blocks.len() {
+                f(block_index, false);
+                return;
+            }
+            let block = unsafe{ self.blocks.get_unchecked(block_index) };
+            f(block_index, block.is_empty_ignoring_link());
+            next = block.next_empty_block();
+        }
     }
 
     #[inline]
@@ -89,8 +162,88 @@ impl<Block: MaybeEmptyIntrusive> ILevel for IntrusiveListLevel<Block> {
         }
     }
 
+    #[inline]
+    fn next_empty_block_index(&self) -> usize {
+        if self.root_empty_block == u64::MAX {
+            self.blocks.len()
+        } else {
+            self.root_empty_block as usize
+        }
+    }
+
     #[inline]
     unsafe fn remove_empty_block_unchecked(&mut self, block_index: usize) {
         self.push_empty_block(block_index);
     }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.clear_to_free_list();
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::HashSet;
+    use rand::{Rng, SeedableRng};
+    use crate::level::{BlockStorage, ChunkedStorage, ILevel, IntrusiveListLevel};
+    use crate::level_block::Block;
+    use crate::{Empty, MaybeEmptyIntrusive};
+
+    type Blk = Block<u64, [u16;64]>;
+
+    #[test]
+    fn clear_insert_stress_test(){
+        do_test::<Vec<Blk>>();
+        do_test::<ChunkedStorage<Blk>>();
+    }
+
+    fn do_test<Storage: BlockStorage<Blk>>(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x9e3779b97f4a7c15);
+        let mut level = IntrusiveListLevel::<Blk, Storage>::default();
+
+        for cycle in 0..200 {
+            let mut in_use = HashSet::new();
+            for _ in 0..rng.gen_range(0..300) {
+                // insert
+                for _ in 0..rng.gen_range(0..8) {
+                    let index = level.insert_empty_block();
+                    assert_ne!(index, 0);
+                    assert!(in_use.insert(index), "in-use block {index} popped");
+                    let block = unsafe{ level.block_unchecked_mut(index) };
+                    assert!(block.is_empty());
+                    // mark as used
+                    block.set_next_empty_block(1);
+                }
+
+                // remove
+                for _ in 0..rng.gen_range(0..4) {
+                    let Some(&index) = in_use.iter().next() else { break };
+                    in_use.remove(&index);
+                    let block = unsafe{ level.block_unchecked_mut(index) };
+                    block.restore_empty();
+                    unsafe{ level.remove_empty_block_unchecked(index); }
+                }
+            }
+
+            let len = level.blocks.len();
+            if cycle % 3 == 0 {
+                level.reset();
+                assert_eq!(level.blocks.len(), 1);
+            } else {
+                level.clear_to_free_list();
+                assert_eq!(level.blocks.len(), len);
+            }
+            assert!(unsafe{ level.block_unchecked(0) }.is_empty());
+
+            // Whole free list reused in ascending order, without growth.
+            let len = level.blocks.len();
+            for expected in 1..len {
+                assert_eq!(level.insert_empty_block(), expected);
+                assert!(unsafe{ level.block_unchecked(expected) }.is_empty());
+            }
+            assert_eq!(level.insert_empty_block(), len);
+            level.clear_to_free_list();
+        }
+    }
 }
\ No newline at end of file
@@ -24,13 +24,25 @@ impl<Block: Empty> ILevel for Level<Block> {
     type Block = Block;
 
     #[inline]
-    fn blocks(&self) -> &[Self::Block] {
-        self.blocks.as_slice()
+    unsafe fn block_unchecked(&self, index: usize) -> &Self::Block {
+        self.blocks.get_unchecked(index)
     }
 
     #[inline]
-    fn blocks_mut(&mut self) -> &mut [Self::Block] {
-        self.blocks.as_mut_slice()
+    unsafe fn block_unchecked_mut(&mut self, index: usize) -> &mut Self::Block {
+        self.blocks.get_unchecked_mut(index)
+    }
+
+    #[inline]
+    fn blocks_len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn for_each_free_block(&self, mut f: impl FnMut(usize, bool)) {
+        for &block_index in &self.empty_block_indices {
+            let empty = self.blocks.get(block_index).is_some_and(|block| block.is_empty());
+            f(block_index, empty);
+        }
     }
 
     #[inline]
@@ -44,8 +56,23 @@ impl<Block: Empty> ILevel for Level<Block> {
         }
     }
 
+    #[inline]
+    fn next_empty_block_index(&self) -> usize {
+        self.empty_block_indices.last().copied().unwrap_or(self.blocks.len())
+    }
+
     #[inline]
     unsafe fn remove_empty_block_unchecked(&mut self, block_index: usize) {
          self.empty_block_indices.push(block_index);
     }
+
+    #[inline]
+    fn clear(&mut self) {
+        for block in self.blocks.iter_mut() {
+            *block = Block::empty();
+        }
+        // Reversed, to pop in ascending index order.
+        self.empty_block_indices.clear();
+        self.empty_block_indices.extend((1..self.blocks.len()).rev());
+    }
 }
\ No newline at end of file
@@ -1,10 +1,12 @@
 mod single_block_level;
 mod intrusive_list_level;
 mod level;
+mod block_storage;
 
 pub use single_block_level::*;
 pub use intrusive_list_level::*;
 pub use level::*;
+pub use block_storage::*;
 
 use crate::Empty;
 
@@ -12,14 +14,39 @@ pub trait ILevel: Default {
     // TODO: Now it is always "HiBlock"
     type Block: Empty;
     
-    fn blocks(&self) -> &[Self::Block];
-    fn blocks_mut(&mut self) -> &mut [Self::Block];
+    /// # Safety
+    ///
+    /// `index` is not checked.
+    unsafe fn block_unchecked(&self, index: usize) -> &Self::Block;
+
+    /// # Safety
+    ///
+    /// `index` is not checked.
+    unsafe fn block_unchecked_mut(&mut self, index: usize) -> &mut Self::Block;
+    
+    /// Number of blocks in storage, including index-0 empty block
+    /// and free blocks.
+    fn blocks_len(&self) -> usize;
+    
+    /// Calls `f(block_index, is_empty)` for each free block.
+    /// 
+    /// Used for validation - must not rely on free blocks bookkeeping
+    /// being consistent. Out-of-storage `block_index` is passed as non-empty.
+    fn for_each_free_block(&self, f: impl FnMut(usize, bool));
     
     fn insert_empty_block(&mut self) -> usize;
     
+    /// Block index, that next [insert_empty_block()] will return.
+    /// 
+    /// [insert_empty_block()]: Self::insert_empty_block
+    fn next_empty_block_index(&self) -> usize;
+    
     /// # Safety
     ///
     /// block_index and level_block emptiness are not checked.
     unsafe fn remove_empty_block_unchecked(&mut self, block_index: usize);
+
+    /// Make all blocks empty, keeping allocated memory for reuse.
+    fn clear(&mut self);
 }
 
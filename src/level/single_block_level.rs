@@ -1,4 +1,3 @@
-use std::slice;
 use crate::level::ILevel;
 use crate::Empty;
 
@@ -11,22 +10,41 @@ impl<Block: Empty> ILevel for SingleBlockLevel<Block>{
     type Block = Block;
 
     #[inline]
-    fn blocks(&self) -> &[Self::Block] {
-        unsafe{ slice::from_raw_parts(&self.block, 1) }
+    unsafe fn block_unchecked(&self, index: usize) -> &Self::Block {
+        debug_assert_eq!(index, 0);
+        &self.block
     }
 
     #[inline]
-    fn blocks_mut(&mut self) -> &mut [Self::Block] {
-        unsafe{ slice::from_raw_parts_mut(&mut self.block, 1) }
+    unsafe fn block_unchecked_mut(&mut self, index: usize) -> &mut Self::Block {
+        debug_assert_eq!(index, 0);
+        &mut self.block
     }
 
+    #[inline]
+    fn blocks_len(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn for_each_free_block(&self, _: impl FnMut(usize, bool)) {}
+
     fn insert_empty_block(&mut self) -> usize {
         unreachable!()
     }
 
+    fn next_empty_block_index(&self) -> usize {
+        unreachable!()
+    }
+
     unsafe fn remove_empty_block_unchecked(&mut self, block_index: usize) {
         unreachable!()
     }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.block = Block::empty();
+    }
 }
 
 impl<Block: Empty> Default for SingleBlockLevel<Block> {
@@ -1,4 +1,5 @@
 use std::mem::{MaybeUninit, size_of};
+use std::{ptr, slice};
 use crate::bit_block::BitBlock;
 use crate::level_block::HiBlock;
 use crate::{Empty, MaybeEmptyIntrusive};
@@ -33,21 +34,51 @@ where
     }
 }
 
+impl<Mask, BlockIndices> Block<Mask, BlockIndices>{
+    /// Intrusive list link storage - mask, or child indices 
+    /// for masks narrower than u64. Both are zero in empty block.
+    #[inline]
+    fn link_ptr(&self) -> *const u64 {
+        if size_of::<Mask>() >= size_of::<u64>() {
+            (&self.mask as *const Mask).cast()
+        } else {
+            const { assert!(size_of::<BlockIndices>() >= size_of::<u64>()) };
+            (&self.block_indices as *const BlockIndices).cast()
+        }
+    }
+}
+
 impl<Mask, BlockIndices> MaybeEmptyIntrusive for Block<Mask, BlockIndices>
 where
     Mask: BitBlock,
     BlockIndices: Array
 {
     #[inline]
-    fn as_u64_mut(&mut self) -> &mut u64 {
-        unsafe{
-            self.mask.as_array_mut().as_mut().get_unchecked_mut(0)
-        }
+    fn next_empty_block(&self) -> u64 {
+        unsafe{ ptr::read_unaligned(self.link_ptr()) }
+    }
+
+    #[inline]
+    fn set_next_empty_block(&mut self, next: u64) {
+        unsafe{ ptr::write_unaligned(self.link_ptr().cast_mut(), next) }
     }
 
     #[inline]
     fn restore_empty(&mut self) {
-        *self.as_u64_mut() = 0;
+        self.set_next_empty_block(0);
+    }
+
+    #[inline]
+    fn is_empty_ignoring_link(&self) -> bool {
+        if size_of::<Mask>() >= size_of::<u64>() {
+            // Link occupies the first 8 mask bytes.
+            let mask = unsafe{ 
+                slice::from_raw_parts((&self.mask as *const Mask).cast::<u8>(), size_of::<Mask>()) 
+            };
+            mask[size_of::<u64>()..].iter().all(|byte| *byte == 0)
+        } else {
+            self.mask.is_zero()
+        }
     }
 }
 
@@ -71,7 +71,7 @@ pub struct ClusterBlock<Mask, SubBlockIndices/*: PrimitiveArray*/, SubBlock/*: P
 
 impl<Mask, SubBlockIndices, SubBlock> ClusterBlock<Mask, SubBlockIndices, SubBlock>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     SubBlockIndices: Array,
     SubBlock: Array
 {
@@ -101,7 +101,7 @@ where
 
 impl<Mask, SubBlockIndices, SubBlock> Empty for ClusterBlock<Mask, SubBlockIndices, SubBlock>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     SubBlockIndices: Array
 {
     // TODO: this should accept Level as arg
@@ -119,31 +119,39 @@ where
 
     #[inline]
     fn is_empty(&self) -> bool {
-        todo!()
+        self.mask.is_zero()
     }
 }
 
 impl<Mask, SubBlockIndices, SubBlock> MaybeEmptyIntrusive for ClusterBlock<Mask, SubBlockIndices, SubBlock>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     SubBlockIndices: Array
 {
     #[inline]
-    fn as_u64_mut(&mut self) -> &mut u64 {
-        unsafe{
-            self.mask.as_array_mut().as_mut().get_unchecked_mut(0)
-        }
+    fn next_empty_block(&self) -> u64 {
+        unsafe{ *self.mask.as_array().as_ref().get_unchecked(0) }
+    }
+
+    #[inline]
+    fn set_next_empty_block(&mut self, next: u64) {
+        unsafe{ *self.mask.as_array_mut().as_mut().get_unchecked_mut(0) = next; }
     }
 
     #[inline]
     fn restore_empty(&mut self) {
-        *self.as_u64_mut() = 0;
+        self.set_next_empty_block(0);
+    }
+
+    #[inline]
+    fn is_empty_ignoring_link(&self) -> bool {
+        self.mask.as_array().as_ref()[1..].iter().all(|word| *word == 0)
     }
 }
 
 impl<Mask, SubBlockIndices, SubBlock> HiBlock for ClusterBlock<Mask, SubBlockIndices, SubBlock>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     SubBlockIndices: Array<Item: Primitive>,
     SubBlock: Array<Item = u16>
 {
@@ -3,6 +3,7 @@ use std::ops::{Deref, DerefMut};
 use std::ops::ControlFlow::Continue;
 use std::ptr;
 use crate::{BitBlock, Empty, MaybeEmptyIntrusive};
+use crate::bit_utils;
 use crate::level_block::HiBlock;
 use crate::utils::{Array, Primitive};
 
@@ -110,7 +111,7 @@ where
 
 impl<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices> SmallBlock<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     BlockIndices: Array + Copy,
     SmallBlockIndices: Array<Item=BlockIndices::Item, UninitArray: Copy>,
     MaskU64Populations: Array<Item=u8> + Copy,
@@ -140,7 +141,7 @@ where
             } else {
                 index % 64
             };
-        let mut block = *self.mask.as_array().as_ref().get_unchecked(u64_index);
+        let block = *self.mask.as_array().as_ref().get_unchecked(u64_index);
         
         if CHECK_FOR_EMPTY {
             let block_mask: u64 = 1 << bit_index;
@@ -150,9 +151,6 @@ where
             }
         }        
         
-        let mask = !(u64::MAX << bit_index);
-        block &= mask;
-        
         let offset = if MaskU64Populations::CAP == 1 {
             // first always zero
             0
@@ -160,7 +158,7 @@ where
             let mask_u64_populations = &self.big_small.small.0;
             *mask_u64_populations.as_ref().get_unchecked(u64_index)
         };
-        Some(offset as usize + block.count_ones() as usize)
+        Some(offset as usize + bit_utils::rank(block, bit_index))
     }
     
     #[inline]
@@ -185,7 +183,7 @@ where
         };
         
         let last_mask_block_population =
-            self.mask.as_array().as_ref().last().unwrap_unchecked().count_ones() as usize;
+            self.mask.as_array().as_ref().last().unwrap_unchecked().count_ones();
         
         population_at_last_mask_block_start + last_mask_block_population
     }    
@@ -214,7 +212,7 @@ where
                     i += 1;
                     
                     *big_array.get_unchecked_mut(index) = value;
-                    Continue(()) 
+                    Continue::<()>(()) 
                 });
                 *big_array.get_unchecked_mut(index) = value;
                 self.big_small = BigSmallArray::from(big);
@@ -243,7 +241,7 @@ where
 
 impl<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices> Empty for SmallBlock<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     BlockIndices: Array + Copy,
     SmallBlockIndices: Array<Item=BlockIndices::Item, UninitArray: Copy>,
     MaskU64Populations: Array<Item=u8> + Copy,
@@ -270,28 +268,36 @@ where
 
 impl<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices> MaybeEmptyIntrusive for SmallBlock<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     BlockIndices: Array + Copy,
     SmallBlockIndices: Array<Item=BlockIndices::Item, UninitArray: Copy>,
     MaskU64Populations: Array<Item=u8> + Copy,
 {
     #[inline]
-    fn as_u64_mut(&mut self) -> &mut u64 {
-        unsafe{
-            self.mask.as_array_mut().as_mut().get_unchecked_mut(0)
-        }
+    fn next_empty_block(&self) -> u64 {
+        unsafe{ *self.mask.as_array().as_ref().get_unchecked(0) }
+    }
+
+    #[inline]
+    fn set_next_empty_block(&mut self, next: u64) {
+        unsafe{ *self.mask.as_array_mut().as_mut().get_unchecked_mut(0) = next; }
     }
 
     #[inline]
     fn restore_empty(&mut self) {
-        *self.as_u64_mut() = 0;
+        self.set_next_empty_block(0);
+    }
+
+    #[inline]
+    fn is_empty_ignoring_link(&self) -> bool {
+        self.mask.as_array().as_ref()[1..].iter().all(|word| *word == 0)
     }
 }
 
 
 impl<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices> HiBlock for SmallBlock<Mask, MaskU64Populations, BlockIndices, SmallBlockIndices>
 where
-    Mask: BitBlock,
+    Mask: BitBlock<Array: Array<Item = u64>>,
     BlockIndices: Array<Item: Primitive> + Copy,
     SmallBlockIndices: Array<Item=BlockIndices::Item, UninitArray: Copy>,
     MaskU64Populations: Array<Item=u8> + Copy,
@@ -26,6 +26,32 @@
 //! we just iterate bitmask population.
 //! Also, bitmasks allows **FAST** container-to-container intersections.
 //! 
+//! ## Index range
+//! 
+//! Indices are `usize`. Configuration with `LevelCount` levels of `Mask::SIZE`
+//! wide blocks holds `Mask::SIZE ^ LevelCount` indices - see
+//! [SparseHierarchy::MAX_INDEX]. If that does not fit `usize`, `MAX_INDEX`
+//! is `usize::MAX` - the rest of the hierarchy range is unreachable.
+//! 
+//! On 32-bit targets this happens already with [config::width_64::depth_6],
+//! so 64-bit keys can not be stored there as-is. Use `u64` [Key] of
+//! [SparseArray] for them - keys above `usize::MAX` are rejected as 
+//! [IndexOutOfRange], not truncated.
+//! 
+//! # Serialization
+//! 
+//! With `rkyv` feature, [SparseArray] can be archived with `rkyv`, and
+//! queried in-place as `ArchivedSparseArray` - without deserialization.
+//! 
+//! # Fuzzing
+//! 
+//! With `arbitrary` feature, [SparseArray] implements `arbitrary::Arbitrary`, and
+//! `ArrayOp` scripts can be checked against `BTreeMap` model. See `fuzz/` for
+//! cargo-fuzz targets.
+//! 
+//! With `testing` feature, `proptest` strategies and `BTreeMap` reference models
+//! are available in `testing` module.
+//! 
 //! # Performance
 //! 
 //! Accessing element by index act as dereferencing N pointers (where N - number
@@ -65,6 +91,27 @@
 //! Due to the fact, that each hierarchy block supplemented with bitmask, finding
 //! intersection is just a matter of ANDing bitmasks.
 //! 
+//! [SparseArray] references and lazy binary ops support `&`, `|`, `-` and `^`
+//! operators, as sugar over [intersection], [union], [difference] and
+//! [symmetric_difference]. Items are taken from the left side, where present:
+//! 
+//! ```
+//! # use hi_sparse_array::{config, materialize, SparseArray, SparseHierarchy};
+//! type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+//! let mut a = Array::default();
+//! let mut b = Array::default();
+//! let mut c = Array::default();
+//! let mut d = Array::default();
+//! a.insert(1, Some(1)); a.insert(2, Some(2));
+//! b.insert(2, Some(20)); b.insert(3, Some(30));
+//! c.insert(4, Some(40)); c.insert(5, Some(50));
+//! d.insert(5, Some(0));
+//! 
+//! let res: Array = materialize(&(&a & &b | &c - &d));
+//! let res = res.iter().filter(|(_, v)| v.is_some());
+//! assert!(res.eq([(2, &Some(2)), (4, &Some(40))]));
+//! ```
+//! 
 //! # Exact hierarchy
 //! 
 //! "Exact hierarchy" - is hierarchy that DOES NOT have nodes pointing to 
@@ -78,7 +125,7 @@
 //! - [is_empty()]
 //! - [contains()]
 //! - TODO From<impl SparseHierarchy>
-//! - iterated elements are guaranteed to be ![is_empty].
+//! - iteration - elements does not need to be checked for [is_empty].
 //! 
 //! N.B. In order to meet "exact hierarchy" constraints, [SparseArray] would have
 //! to check data for emptiness after each mutated access. Since you may never
@@ -92,33 +139,58 @@ mod bit_utils;
 mod bit_block;
 mod apply;
 mod fold;
-//mod empty;
 mod exact_hierarchy;
 mod sparse_hierarchy;
 mod ops;
 mod op;
 mod iter;
+mod point_cursor;
+mod error;
+mod index;
+mod materialize;
+mod morton;
+mod stats;
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(test)]
+mod test_utils;
 
 pub mod bit_queue;
-//mod ref_or_val;
 pub mod level;
 pub mod level_block;
 pub mod const_utils;
 pub mod utils;
 pub mod config;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
 
-//pub use ref_or_val::*;
-pub use bit_block::BitBlock;
-pub use sparse_array::SparseArray;
+pub use bit_block::{BitBlock, BitArray};
+#[cfg(feature = "simd")]
+pub use bit_block::U64x8;
+pub use sparse_array::{SparseArray, DataBlock, DataBlockMut};
 pub use sparse_array_levels::SparseArrayLevels;
 pub use apply::Apply;
-pub use fold::Fold;
-//pub use empty::Empty;
+pub use fold::{Fold, SliceIter};
 pub use sparse_hierarchy::*;
 pub use exact_hierarchy::ExactHierarchy;
 pub use ops::*;
 pub use op::*;
 pub use iter::*;
+pub use point_cursor::PointCursor;
+pub use error::*;
+pub use index::{Index, Key};
+pub use materialize::*;
+pub use morton::{Index2D, Index3D};
+pub use stats::{Stats, LevelStats};
+#[cfg(feature = "rkyv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rkyv")))]
+pub use archive::{ArchivedSparseArray, ArchivedSparseArrayState, ArchiveViolation, SparseArrayResolver};
+#[cfg(feature = "arbitrary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+pub use arbitrary::ArrayOp;
 
 use std::borrow::Borrow;
 use std::ops::BitAnd;
@@ -150,9 +222,22 @@ impl<T> Empty for Option<T>{
 /// Implementing this will allow your [Empty] struct in an empty state 
 /// to be used as a LinkedList node with [IntrusiveListLevel]. 
 pub(crate) trait MaybeEmptyIntrusive: Empty {
-    fn as_u64_mut(&mut self) -> &mut u64;
-    /// Restore [empty()] state, after [as_u64_mut()] mutation.
+    /// Read link, stored by [set_next_empty_block()].
+    /// 
+    /// [set_next_empty_block()]: Self::set_next_empty_block
+    fn next_empty_block(&self) -> u64;
+    /// Store link in block's memory.
+    fn set_next_empty_block(&mut self, next: u64);
+    /// Restore [empty()] state, after [set_next_empty_block()] mutation.
+    /// 
+    /// [empty()]: Empty::empty
+    /// [set_next_empty_block()]: Self::set_next_empty_block
     fn restore_empty(&mut self);
+    /// [is_empty()], ignoring link stored by [set_next_empty_block()].
+    /// 
+    /// [is_empty()]: Empty::is_empty
+    /// [set_next_empty_block()]: Self::set_next_empty_block
+    fn is_empty_ignoring_link(&self) -> bool;
 }
 
 // Compile-time loop inside. Ends up with N ADDs.
@@ -194,6 +279,13 @@ pub fn apply<Op, B1, B2>(op: Op, s1: B1, s2: B2) -> Apply<Op, B1, B2>
 /// * All `LevelMask`s and `LevelCount`s must match (have same hierarchy configurations).
 /// * `init`'s [DataType] must be [Clone]able. This restriction may be lifted in the future.
 /// * `array_iter` will be cloned multiple times. Use cheaply cloneable iterator.
+/// * `array_iter` items can be owned - like lazy adaptors constructed inside 
+///   iterator. Iteration state stores them along with their states.
+/// 
+/// During iteration, with `Op`::[SKIP_EMPTY_HIERARCHIES] raised, sources with empty
+/// block on the current branch are not visited on the levels below.
+/// 
+/// [SKIP_EMPTY_HIERARCHIES]: BinaryOp::SKIP_EMPTY_HIERARCHIES
 #[inline]
 pub fn fold<Op, Init, ArrayIter>(op: Op, init: Init, array_iter: ArrayIter) 
     -> Fold<Op, Init, ArrayIter>
@@ -0,0 +1,118 @@
+use std::borrow::Borrow;
+use crate::{Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
+use crate::sparse_array::InsertBranch;
+use crate::utils::Take;
+
+/// Store all `src` elements in `dst`.
+///
+/// `dst` is cleared first, its memory is reused - refilling `dst` with
+/// the same elements again does not allocate.
+/// Elements are stored as [iter()] returns them - for non-[EXACT_HIERARCHY]
+/// `src` that includes empty ones.
+///
+/// [iter()]: SparseHierarchy::iter
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub fn collect_into<H, Levels, Data, K>(src: &H, dst: &mut SparseArray<Levels, Data, K>)
+where
+    H: SparseHierarchy<
+        LevelMaskType = Levels::Mask,
+        LevelCount = Levels::LevelCount,
+        DataType = Data
+    >,
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+{
+    dst.clear();
+    // Indices are ascending - most inserts hit the cached upper levels path.
+    let mut branch = InsertBranch::default();
+    for (index, data) in src.iter() {
+        unsafe{ dst.insert_with_branch(index, data.take_or_clone(), &mut branch); }
+    }
+}
+
+/// Store all `src` elements in new [SparseArray].
+///
+/// Faster than [insert()]ing [iter()] items one by one.
+/// `src` and resulting [SparseArray] must have the same `(Mask, LevelCount)`
+/// hierarchy configuration.
+///
+/// See [collect_into()].
+///
+/// [insert()]: SparseArray::insert
+/// [iter()]: SparseHierarchy::iter
+#[inline]
+pub fn materialize<H, Levels, Data, K>(src: &H) -> SparseArray<Levels, Data, K>
+where
+    H: SparseHierarchy<
+        LevelMaskType = Levels::Mask,
+        LevelCount = Levels::LevelCount,
+        DataType = Data
+    >,
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+{
+    let mut dst = SparseArray::default();
+    collect_into(src, &mut dst);
+    dst
+}
+
+/// Merge all non-empty `src` elements into `dst`.
+///
+/// For each non-empty `src` element, calls `f` with `dst` item at the same
+/// index - missing ones are passed as [empty]. `f` can update or fill it,
+/// and the item left [empty] is removed from `dst`.
+///
+/// `src` is traversed in ascending index order, so inserts
+/// reuse upper levels path of the previous one.
+///
+/// `dst` can not be one of `src`'s sources - borrow checker does not allow
+/// that. [materialize()] `src` first, if you need that.
+///
+/// [empty]: Empty::empty
+pub fn merge_into<'a, H, Levels, Data, K, F>(src: &'a H, dst: &mut SparseArray<Levels, Data, K>, mut f: F)
+where
+    H: SparseHierarchy<
+        LevelMaskType = Levels::Mask,
+        LevelCount = Levels::LevelCount,
+    >,
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+    F: FnMut(&mut Data, H::Data<'a>)
+{
+    let mut branch = InsertBranch::default();
+    for (index, data) in src.iter() {
+        if data.borrow().is_empty() {
+            continue;
+        }
+        let slot = unsafe{ dst.get_mut_with_branch(index, &mut branch) };
+        f(slot, data);
+        if slot.is_empty() {
+            dst.remove_index(index);
+            // remove() may free cached path blocks.
+            branch = InsertBranch::default();
+        }
+    }
+}
+
+impl<Levels0, Levels1, Data, K> From<&SparseArray<Levels0, Data, K>> for SparseArray<Levels1, Data, K>
+where
+    Levels0: SparseArrayLevels,
+    Levels1: SparseArrayLevels<
+        Mask = Levels0::Mask,
+        LevelCount = Levels0::LevelCount
+    >,
+    Data: Empty + Clone,
+    K: Key,
+{
+    /// Convert between [SparseArray]s of the same `(Mask, LevelCount)`
+    /// hierarchy configuration.
+    #[inline]
+    fn from(src: &SparseArray<Levels0, Data, K>) -> Self {
+        let mut dst = Self::default();
+        collect_into(src, &mut dst);
+        dst
+    }
+}
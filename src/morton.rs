@@ -0,0 +1,467 @@
+//! Morton (Z-order) encoded multidimensional indices.
+//!
+//! Interleaving coordinate bits keeps spatially close points close in
+//! index space - so they end up in the same hierarchy blocks.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use crate::{BitBlock, Index, IndexOutOfRange};
+use crate::const_utils::const_int::ConstInteger;
+#[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+use crate::bit_utils::bmi2;
+
+/// Every 2nd bit, starting from 0.
+const MASK_2D: u64 = 0x5555_5555_5555_5555;
+/// Every 3rd bit, starting from 0.
+const MASK_3D: u64 = 0x1249_2492_4924_9249;
+
+/// Low 32 bits of `x` to even bit positions.
+#[inline]
+fn spread_2d(x: u64) -> u64 {
+    let x = x & 0xFFFF_FFFF;
+    let x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    let x = (x | (x <<  8)) & 0x00FF_00FF_00FF_00FF;
+    let x = (x | (x <<  4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    let x = (x | (x <<  2)) & 0x3333_3333_3333_3333;
+            (x | (x <<  1)) & MASK_2D
+}
+
+/// Inverse of [spread_2d].
+#[inline]
+fn compact_2d(x: u64) -> u64 {
+    let x = x & MASK_2D;
+    let x = (x | (x >>  1)) & 0x3333_3333_3333_3333;
+    let x = (x | (x >>  2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    let x = (x | (x >>  4)) & 0x00FF_00FF_00FF_00FF;
+    let x = (x | (x >>  8)) & 0x0000_FFFF_0000_FFFF;
+            (x | (x >> 16)) & 0xFFFF_FFFF
+}
+
+/// Low 21 bits of `x` to every 3rd bit position.
+#[inline]
+fn spread_3d(x: u64) -> u64 {
+    let x = x & 0x1F_FFFF;
+    let x = (x | (x << 32)) & 0x001F_0000_0000_FFFF;
+    let x = (x | (x << 16)) & 0x001F_0000_FF00_00FF;
+    let x = (x | (x <<  8)) & 0x100F_00F0_0F00_F00F;
+    let x = (x | (x <<  4)) & 0x10C3_0C30_C30C_30C3;
+            (x | (x <<  2)) & MASK_3D
+}
+
+/// Inverse of [spread_3d].
+#[inline]
+fn compact_3d(x: u64) -> u64 {
+    let x = x & MASK_3D;
+    let x = (x | (x >>  2)) & 0x10C3_0C30_C30C_30C3;
+    let x = (x | (x >>  4)) & 0x100F_00F0_0F00_F00F;
+    let x = (x | (x >>  8)) & 0x001F_0000_FF00_00FF;
+    let x = (x | (x >> 16)) & 0x001F_0000_0000_FFFF;
+            (x | (x >> 32)) & 0x1F_FFFF
+}
+
+#[inline]
+fn encode_2d(x: u64, y: u64) -> u64 {
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if bmi2::available() {
+        return unsafe{ bmi2::deposit(x, MASK_2D) | bmi2::deposit(y, MASK_2D << 1) };
+    }
+    spread_2d(x) | (spread_2d(y) << 1)
+}
+
+#[inline]
+fn decode_2d(index: u64) -> (u64, u64) {
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if bmi2::available() {
+        return unsafe{ (bmi2::extract(index, MASK_2D), bmi2::extract(index, MASK_2D << 1)) };
+    }
+    (compact_2d(index), compact_2d(index >> 1))
+}
+
+#[inline]
+fn encode_3d(x: u64, y: u64, z: u64) -> u64 {
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if bmi2::available() {
+        return unsafe{
+            bmi2::deposit(x, MASK_3D)
+          | bmi2::deposit(y, MASK_3D << 1)
+          | bmi2::deposit(z, MASK_3D << 2)
+        };
+    }
+    spread_3d(x) | (spread_3d(y) << 1) | (spread_3d(z) << 2)
+}
+
+#[inline]
+fn decode_3d(index: u64) -> (u64, u64, u64) {
+    #[cfg(all(target_arch = "x86_64", any(target_feature = "bmi2", feature = "runtime-dispatch")))]
+    if bmi2::available() {
+        return unsafe{(
+            bmi2::extract(index, MASK_3D),
+            bmi2::extract(index, MASK_3D << 1),
+            bmi2::extract(index, MASK_3D << 2)
+        )};
+    }
+    (compact_3d(index), compact_3d(index >> 1), compact_3d(index >> 2))
+}
+
+/// Max index, that `axis_bits * dims` interleaved bits can produce.
+const fn interleaved_max(axis_bits: u32, dims: u32) -> usize {
+    let bits = axis_bits * dims;
+    if bits >= usize::BITS {
+        usize::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Morton encoded `(x, y)` [Index].
+///
+/// Each axis gets half of [Index::MAX] bits - so any `Index2D` fits
+/// `(Mask, LevelCount)` configuration by construction. With odd index
+/// bit count, the top index bit stays unused.
+///
+/// Uses `pdep`/`pext` with `bmi2` - see `runtime-dispatch` feature.
+pub struct Index2D<Mask, LevelCount>{
+    index: Index<Mask, LevelCount>,
+}
+
+impl<Mask, LevelCount> Index2D<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    /// Bits per coordinate.
+    pub const AXIS_BITS: u32 = Index::<Mask, LevelCount>::MAX.count_ones() / 2;
+
+    /// Max valid coordinate.
+    pub const AXIS_MAX: usize = interleaved_max(Self::AXIS_BITS, 1);
+
+    /// Max index, `Index2D` can be constructed from.
+    pub const MAX: usize = interleaved_max(Self::AXIS_BITS, 2);
+
+    /// Returns [IndexOutOfRange] with offending coordinate,
+    /// if `x` or `y` is outside [AXIS_MAX].
+    ///
+    /// [AXIS_MAX]: Self::AXIS_MAX
+    #[inline]
+    pub fn from_xy(x: usize, y: usize) -> Result<Self, IndexOutOfRange> {
+        for c in [x, y] {
+            if c > Self::AXIS_MAX {
+                return Err(IndexOutOfRange{ index: c, max_range: Self::AXIS_MAX });
+            }
+        }
+        let index = encode_2d(x as u64, y as u64) as usize;
+        Ok(Self{ index: unsafe{ Index::new_unchecked(index) } })
+    }
+
+    #[inline]
+    pub fn to_xy(self) -> (usize, usize) {
+        let (x, y) = decode_2d(self.index.value() as u64);
+        (x as usize, y as usize)
+    }
+
+    #[inline]
+    pub fn index(self) -> Index<Mask, LevelCount> {
+        self.index
+    }
+}
+
+impl<Mask, LevelCount> TryFrom<usize> for Index2D<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    type Error = IndexOutOfRange;
+
+    /// Returns [IndexOutOfRange], if `index` is outside [MAX].
+    ///
+    /// [MAX]: Self::MAX
+    #[inline]
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        if index > Self::MAX {
+            return Err(IndexOutOfRange{ index, max_range: Self::MAX });
+        }
+        Ok(Self{ index: unsafe{ Index::new_unchecked(index) } })
+    }
+}
+
+/// Morton encoded `(x, y, z)` [Index].
+///
+/// Each axis gets third of [Index::MAX] bits - so any `Index3D` fits
+/// `(Mask, LevelCount)` configuration by construction.
+/// Remainder index bits stay unused.
+///
+/// Uses `pdep`/`pext` with `bmi2` - see `runtime-dispatch` feature.
+pub struct Index3D<Mask, LevelCount>{
+    index: Index<Mask, LevelCount>,
+}
+
+impl<Mask, LevelCount> Index3D<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    /// Bits per coordinate.
+    pub const AXIS_BITS: u32 = Index::<Mask, LevelCount>::MAX.count_ones() / 3;
+
+    /// Max valid coordinate.
+    pub const AXIS_MAX: usize = interleaved_max(Self::AXIS_BITS, 1);
+
+    /// Max index, `Index3D` can be constructed from.
+    pub const MAX: usize = interleaved_max(Self::AXIS_BITS, 3);
+
+    /// Returns [IndexOutOfRange] with offending coordinate,
+    /// if `x`, `y` or `z` is outside [AXIS_MAX].
+    ///
+    /// [AXIS_MAX]: Self::AXIS_MAX
+    #[inline]
+    pub fn from_xyz(x: usize, y: usize, z: usize) -> Result<Self, IndexOutOfRange> {
+        for c in [x, y, z] {
+            if c > Self::AXIS_MAX {
+                return Err(IndexOutOfRange{ index: c, max_range: Self::AXIS_MAX });
+            }
+        }
+        let index = encode_3d(x as u64, y as u64, z as u64) as usize;
+        Ok(Self{ index: unsafe{ Index::new_unchecked(index) } })
+    }
+
+    #[inline]
+    pub fn to_xyz(self) -> (usize, usize, usize) {
+        let (x, y, z) = decode_3d(self.index.value() as u64);
+        (x as usize, y as usize, z as usize)
+    }
+
+    #[inline]
+    pub fn index(self) -> Index<Mask, LevelCount> {
+        self.index
+    }
+}
+
+impl<Mask, LevelCount> TryFrom<usize> for Index3D<Mask, LevelCount>
+where
+    Mask: BitBlock,
+    LevelCount: ConstInteger,
+{
+    type Error = IndexOutOfRange;
+
+    /// Returns [IndexOutOfRange], if `index` is outside [MAX].
+    ///
+    /// [MAX]: Self::MAX
+    #[inline]
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        if index > Self::MAX {
+            return Err(IndexOutOfRange{ index, max_range: Self::MAX });
+        }
+        Ok(Self{ index: unsafe{ Index::new_unchecked(index) } })
+    }
+}
+
+macro_rules! impl_index_traits {
+    ($t: ident) => {
+        impl<Mask, LevelCount> From<$t<Mask, LevelCount>> for Index<Mask, LevelCount> {
+            #[inline]
+            fn from(index: $t<Mask, LevelCount>) -> Self {
+                index.index
+            }
+        }
+
+        impl<Mask, LevelCount> From<$t<Mask, LevelCount>> for usize {
+            #[inline]
+            fn from(index: $t<Mask, LevelCount>) -> Self {
+                index.index.value()
+            }
+        }
+
+        impl<Mask, LevelCount> Clone for $t<Mask, LevelCount> {
+            #[inline]
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<Mask, LevelCount> Copy for $t<Mask, LevelCount> {}
+
+        impl<Mask, LevelCount> fmt::Debug for $t<Mask, LevelCount> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.index, f)
+            }
+        }
+
+        impl<Mask, LevelCount> PartialEq for $t<Mask, LevelCount> {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.index == other.index
+            }
+        }
+
+        impl<Mask, LevelCount> Eq for $t<Mask, LevelCount> {}
+
+        /// Z-order.
+        impl<Mask, LevelCount> PartialOrd for $t<Mask, LevelCount> {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        /// Z-order.
+        impl<Mask, LevelCount> Ord for $t<Mask, LevelCount> {
+            #[inline]
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.index.cmp(&other.index)
+            }
+        }
+
+        impl<Mask, LevelCount> Hash for $t<Mask, LevelCount> {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.index.hash(state)
+            }
+        }
+    };
+}
+impl_index_traits!(Index2D);
+impl_index_traits!(Index3D);
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::{config, SparseArray, SparseArrayLevels, SparseHierarchy};
+    use super::*;
+
+    type I2<Levels> = Index2D<<Levels as SparseArrayLevels>::Mask, <Levels as SparseArrayLevels>::LevelCount>;
+    type I3<Levels> = Index3D<<Levels as SparseArrayLevels>::Mask, <Levels as SparseArrayLevels>::LevelCount>;
+
+    #[test]
+    fn portable_matches_dispatched(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x2d3d);
+        for _ in 0..10_000 {
+            let (x, y) = (rng.gen::<u32>() as u64, rng.gen::<u32>() as u64);
+            let i = encode_2d(x, y);
+            assert_eq!(i, spread_2d(x) | (spread_2d(y) << 1));
+            assert_eq!(decode_2d(i), (x, y));
+            assert_eq!((compact_2d(i), compact_2d(i >> 1)), (x, y));
+
+            let axis = 0..1 << 21;
+            let (x, y, z) = (rng.gen_range(axis.clone()), rng.gen_range(axis.clone()), rng.gen_range(axis));
+            let i = encode_3d(x, y, z);
+            assert_eq!(i, spread_3d(x) | (spread_3d(y) << 1) | (spread_3d(z) << 2));
+            assert_eq!(decode_3d(i), (x, y, z));
+            assert_eq!((compact_3d(i), compact_3d(i >> 1), compact_3d(i >> 2)), (x, y, z));
+        }
+        assert_eq!(encode_2d(0b11, 0b01), 0b0111);
+        assert_eq!(encode_3d(0b1, 0b1, 0b0), 0b011);
+        assert_eq!(encode_3d(0b10, 0, 0b10), 0b100_000 | 0b001_000);
+    }
+
+    fn do_test_2d<Levels: SparseArrayLevels>(axis_bits: u32){
+        assert_eq!(I2::<Levels>::AXIS_BITS, axis_bits);
+        let max = I2::<Levels>::AXIS_MAX;
+        assert!(I2::<Levels>::MAX <= Levels::MAX_INDEX);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x2d);
+        for _ in 0..1000 {
+            let (x, y) = (rng.gen_range(0..=max), rng.gen_range(0..=max));
+            let i = I2::<Levels>::from_xy(x, y).unwrap();
+            assert_eq!(i.to_xy(), (x, y));
+            assert_eq!(I2::<Levels>::try_from(usize::from(i)), Ok(i));
+        }
+
+        let i = I2::<Levels>::from_xy(max, max).unwrap();
+        assert_eq!(usize::from(i), I2::<Levels>::MAX);
+        let err = IndexOutOfRange{ index: max + 1, max_range: max };
+        assert_eq!(I2::<Levels>::from_xy(max + 1, 0), Err(err));
+        assert_eq!(I2::<Levels>::from_xy(0, max + 1), Err(err));
+    }
+
+    fn do_test_3d<Levels: SparseArrayLevels>(axis_bits: u32){
+        assert_eq!(I3::<Levels>::AXIS_BITS, axis_bits);
+        let max = I3::<Levels>::AXIS_MAX;
+        assert!(I3::<Levels>::MAX <= Levels::MAX_INDEX);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x3d);
+        for _ in 0..1000 {
+            let (x, y, z) = (rng.gen_range(0..=max), rng.gen_range(0..=max), rng.gen_range(0..=max));
+            let i = I3::<Levels>::from_xyz(x, y, z).unwrap();
+            assert_eq!(i.to_xyz(), (x, y, z));
+            assert_eq!(I3::<Levels>::try_from(usize::from(i)), Ok(i));
+        }
+
+        let i = I3::<Levels>::from_xyz(max, max, max).unwrap();
+        assert_eq!(usize::from(i), I3::<Levels>::MAX);
+        let err = IndexOutOfRange{ index: max + 1, max_range: max };
+        assert_eq!(I3::<Levels>::from_xyz(0, 0, max + 1), Err(err));
+    }
+
+    #[test]
+    fn round_trip_test(){
+        do_test_2d::<config::width_64::depth_1>(3);
+        do_test_2d::<config::width_64::depth_3>(9);
+        do_test_2d::<config::width_64::depth_4>(12);
+        // Odd index bits count - top bit unused.
+        do_test_2d::<config::width_128_u128::depth_3>(10);
+        do_test_2d::<config::width_16::depth_2>(4);
+
+        do_test_3d::<config::width_64::depth_3>(6);
+        do_test_3d::<config::width_64::depth_4>(8);
+        do_test_3d::<config::width_16::depth_3>(4);
+        do_test_3d::<config::width_32::depth_2>(3);
+
+        type I = I2<config::width_128_u128::depth_3>;
+        assert_eq!(I::try_from(I::MAX + 1), Err(IndexOutOfRange{ index: I::MAX + 1, max_range: I::MAX }));
+    }
+
+    /// Aligned `2^k` squares/cubes occupy contiguous index ranges,
+    /// and index grows with each coordinate.
+    #[test]
+    fn locality_test(){
+        type Xy  = I2<config::width_64::depth_2>;
+        type Xyz = I3<config::width_64::depth_3>;
+
+        for k in 0..=3 {
+            let side = 1 << k;
+            for (bx, by) in [(0, 0), (1, 0), (0, 1), (3, 5)] {
+                let mut indices: Vec<usize> = (0..side*side)
+                    .map(|i| Xy::from_xy(bx*side + i % side, by*side + i / side).unwrap().into())
+                    .collect();
+                indices.sort();
+                let first = indices[0];
+                assert_eq!(first % (side*side), 0);
+                assert!(indices.into_iter().eq(first..first + side*side));
+            }
+        }
+
+        let side = 4;
+        let mut indices: Vec<usize> = (0..side*side*side)
+            .map(|i| Xyz::from_xyz(4 + i % side, 8 + i / side % side, 12 + i / (side*side)).unwrap().into())
+            .collect();
+        indices.sort();
+        let first = indices[0];
+        assert!(indices.into_iter().eq(first..first + side*side*side));
+
+        let max = Xy::AXIS_MAX;
+        for x in 0..max {
+            for y in 0..max {
+                let i = Xy::from_xy(x, y).unwrap();
+                assert!(i < Xy::from_xy(x + 1, y).unwrap());
+                assert!(i < Xy::from_xy(x, y + 1).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_array_test(){
+        type Levels = config::width_64::depth_3;
+        type I = I2<Levels>;
+        let mut a: SparseArray<Levels, Option<(usize, usize)>> = Default::default();
+        let points = [(0, 0), (1, 1), (511, 511), (7, 300), (300, 7)];
+        for (x, y) in points {
+            a.insert_at(I::from_xy(x, y).unwrap().index(), Some((x, y)));
+        }
+        for (index, data) in a.iter() {
+            let xy = I::try_from(index).unwrap().to_xy();
+            assert_eq!(*data, Some(xy));
+        }
+        assert_eq!(a.get(I::from_xy(300, 7).unwrap().into()), &Some((300, 7)));
+    }
+}
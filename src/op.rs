@@ -48,13 +48,52 @@ pub trait BinaryOp {
         right: impl Borrow<Self::LevelMask> + Take<Self::LevelMask>
     ) -> Self::LevelMask;
     
+    /// Operation applied to terminal (last) level masks.
+    /// 
+    /// Same as [lvl_op] by default. Override, if terminal level 
+    /// can be computed tighter than upper ones.
+    /// 
+    /// [lvl_op]: Self::lvl_op
+    #[inline]
+    fn terminal_lvl_op(&self,
+        left : impl Borrow<Self::LevelMask> + Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask> + Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        self.lvl_op(left, right)
+    }
+    
     type Left;
     type Right;
     type Out: Empty;
     
+    /// Stop [Fold] data resolve at the first [empty] accumulator, 
+    /// without visiting the rest of hierarchies. 
+    /// 
+    /// Raise only if [empty] accumulator stays [empty] 
+    /// through all subsequent [fold_data_op]s.
+    /// 
+    /// [empty]: Empty::is_empty
+    /// [fold_data_op]: Self::fold_data_op
+    const FOLD_SHORT_CIRCUIT: bool = false;
+    
     /// Operation applied to data items.
     fn data_op(&self,
        left : impl Borrow<Self::Left>  + Take<Self::Left>,
        right: impl Borrow<Self::Right> + Take<Self::Right>
     ) -> Self::Out;
+    
+    /// Operation applied to data items in [Fold].
+    /// 
+    /// `position` is `right`'s position in [Fold]'s hierarchy iterator.
+    /// Same as [data_op] by default. 
+    /// 
+    /// [data_op]: Self::data_op
+    #[inline]
+    fn fold_data_op(&self,
+       left : impl Borrow<Self::Left>  + Take<Self::Left>,
+       right: impl Borrow<Self::Right> + Take<Self::Right>,
+       _position: usize
+    ) -> Self::Out {
+        self.data_op(left, right)
+    }
 }
@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use crate::{data_block_index, BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::{Borrowable, Take};
+
+/// Cached items of one terminal block.
+type CachedBlock<T> = Box<[Option<T>]>;
+
+/// Lazy [SparseHierarchy] adaptor, that memoizes source items.
+///
+/// Each non-empty source item is computed at most once - on first access.
+/// Subsequent `get`s and iterations are served from the cache. Cache is
+/// populated by terminal blocks - one block is allocated on first non-empty
+/// item in it. Empty items are not cached, and computed on each access.
+/// Hierarchy is passed as-is.
+///
+/// Cache is never invalidated - source must not change while `Cached` is alive.
+/// Uses interior mutability, and thus is not [Sync].
+///
+/// Constructed by [cached()].
+pub struct Cached<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    h: H,
+    
+    /// Terminal block number -> block items.
+    cache: RefCell<HashMap<usize, CachedBlock<<H::Borrowed as SparseHierarchy>::DataType>>>,
+}
+
+impl<H> Cached<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    #[inline]
+    fn get_or_compute<D>(&self, index: usize, f: impl FnOnce() -> D)
+        -> <H::Borrowed as SparseHierarchy>::DataType
+    where
+        D: Take<<H::Borrowed as SparseHierarchy>::DataType>
+    {
+        let block_size = <H::Borrowed as SparseHierarchy>::LevelMaskType::SIZE;
+        let (block, lane) = (index / block_size, index % block_size);
+        if let Some(Some(data)) = self.cache.borrow().get(&block).map(|items| &items[lane]) {
+            return data.clone();
+        }
+        // Cache is not borrowed while computing.
+        let data = f().take_or_clone();
+        if !data.is_empty() {
+            let mut cache = self.cache.borrow_mut();
+            let items = cache.entry(block).or_insert_with(|| 
+                (0..block_size).map(|_| None).collect()
+            );
+            items[lane] = Some(data.clone());
+        }
+        data
+    }
+}
+
+impl<H> SparseHierarchy for Cached<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    const EXACT_HIERARCHY: bool = <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.h.borrow().level_mask(level_indices)
+    }
+
+    type DataType = <H::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let (upper_indices, data_index) = level_indices.split_last();
+        let index = data_block_index::<Self>(&upper_indices, data_index);
+        self.get_or_compute(index, || self.h.borrow().data_block(level_indices))
+    }
+
+    type State = CachedState<H>;
+}
+
+pub struct CachedState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// [usize; LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <<H::Borrowed as SparseHierarchy>::LevelCount as ConstInteger>::Dec
+    >,
+
+    phantom_data: PhantomData<Cached<H>>
+}
+
+impl<H> Clone for CachedState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H> SparseHierarchyState for CachedState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    type This = Cached<H>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            level_indices: Array::from_fn(|_| 0),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        if /*const*/ N::VALUE != 0 {
+            *self.level_indices.as_mut().get_unchecked_mut(N::VALUE - 1) = level_index;
+        }
+        self.state.select_level_bock(this.h.borrow(), level_n, level_index)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let index = data_block_index::<Self::This>(&self.level_indices, level_index);
+        this.get_or_compute(index, || self.state.data_block(this.h.borrow(), level_index))
+    }
+}
+
+impl<H> Borrowable for Cached<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    type Borrowed = Cached<H>;
+}
+
+/// Memoize items of expensive lazy [SparseHierarchy].
+///
+/// # Example
+///
+/// ```
+/// # use std::cell::Cell;
+/// # use hi_sparse_array::{cached, config, map_indexed, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut a = Array::default();
+/// a.insert(10, Some(1));
+///
+/// let calls = Cell::new(0);
+/// let expensive = map_indexed(&a, |_, v| { calls.set(calls.get() + 1); v.map(|v| v * 2) });
+/// let c = cached(&expensive);
+/// assert_eq!(c.get(10), Some(2));
+/// assert_eq!(c.get(10), Some(2));
+/// assert_eq!(calls.get(), 1);
+/// ```
+#[inline]
+pub fn cached<H>(h: H) -> Cached<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    Cached{h, cache: Default::default()}
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use std::collections::HashSet;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, Empty, intersection, map_indexed, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_cached(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xcac4_ed00);
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for _ in 0..1000 {
+            let v = rng.gen_range(0..5000);
+            a1.insert(v, Data(v + 1));
+            let v = rng.gen_range(0..5000);
+            a2.insert(v, Data(v + 1));
+        }
+
+        let reference = intersection(&a1, &a2, |d1, d2| Data(d1.0 + d2.0));
+        let calls = Cell::new(0);
+        let non_empty_calls = Cell::new(0);
+        let lazy = map_indexed(
+            &reference,
+            |_, d: &Data| { 
+                calls.set(calls.get() + 1);
+                if !d.is_empty() {
+                    non_empty_calls.set(non_empty_calls.get() + 1);
+                }
+                d.clone() 
+            }
+        );
+        let c = cached(&lazy);
+
+        let expected: Vec<_> = reference.iter().collect();
+
+        let mut queried = HashSet::new();
+        for _ in 0..3 {
+            assert_equal(c.iter(), expected.iter().cloned());
+            queried.extend(expected.iter().map(|(i, _)| *i));
+            for _ in 0..1000 {
+                let i = rng.gen_range(0..5000);
+                assert_eq!(c.get(i), reference.get(i));
+                if !reference.get(i).is_empty() {
+                    queried.insert(i);
+                }
+            }
+        }
+        assert_eq!(non_empty_calls.get(), queried.len());
+        
+        // Empty items are computed on each access.
+        let empty = (0..5000).find(|&i| reference.get(i).is_empty()).unwrap();
+        calls.set(0);
+        assert!(c.get(empty).is_empty());
+        assert!(c.get(empty).is_empty());
+        assert_eq!(calls.get(), 2);
+        
+        // Cache is stored by terminal blocks.
+        let blocks: HashSet<_> = queried.iter().map(|i| i / 64).collect();
+        assert_eq!(c.cache.borrow().len(), blocks.len());
+    }
+}
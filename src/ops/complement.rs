@@ -0,0 +1,271 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+use crate::{BitBlock, data_block_index, Empty, Primitive, range_view, RangeView, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::Borrowable;
+
+/// Lazy [SparseHierarchy] with keys, that are absent in the source.
+///
+/// Item is `Some(())` for each empty source item, across the whole range.
+/// Upper level masks are all raised. Terminal masks are inverted source ones
+/// for [EXACT_HIERARCHY] source, and all raised otherwise.
+///
+/// Use [complement()] to limit it to a range.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub struct Complement<H>{
+    h: H,
+}
+
+/// Mask with all bits raised.
+#[inline]
+fn full_mask<M: BitBlock>() -> M {
+    let mut mask = M::zero();
+    for word in mask.as_array_mut().as_mut() {
+        *word = Primitive::MAX;
+    }
+    mask
+}
+
+#[inline]
+fn inverted_mask<M: BitBlock>(mask: &M) -> M {
+    let mut out = mask.clone();
+    for word in out.as_array_mut().as_mut() {
+        *word = !*word;
+    }
+    out
+}
+
+impl<H> SparseHierarchy for Complement<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    // Full upper masks.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let terminal = I::Cap::VALUE == Self::LevelCount::VALUE - 1;
+        if terminal && <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY {
+            let mask = self.h.borrow().level_mask(level_indices);
+            inverted_mask(mask.borrow())
+        } else {
+            full_mask()
+        }
+    }
+
+    type DataType = Option<()>;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let h = self.h.borrow();
+        let contains = if <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY {
+            let (upper_indices, data_index) = level_indices.split_last();
+            let index = data_block_index::<Self>(&upper_indices, data_index);
+            h.may_contain_unchecked(index)
+        } else {
+            !h.data_block(level_indices).borrow().is_empty()
+        };
+        (!contains).then_some(())
+    }
+
+    type State = ComplementState<H>;
+}
+
+pub struct ComplementState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// Source terminal mask. In-use only with [EXACT_HIERARCHY] source.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    terminal_mask: <H::Borrowed as SparseHierarchy>::LevelMaskType,
+
+    phantom_data: PhantomData<Complement<H>>
+}
+
+impl<H> Clone for ComplementState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            terminal_mask: self.terminal_mask.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H> SparseHierarchyState for ComplementState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    type This = Complement<H>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            terminal_mask: BitBlock::zero(),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        // Source blocks outside its hierarchy are empty ones.
+        let mask = self.state.select_level_bock(this.h.borrow(), level_n, level_index);
+        let terminal = N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1;
+        if terminal && <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY {
+            self.terminal_mask = mask.borrow().clone();
+            inverted_mask(&self.terminal_mask)
+        } else {
+            full_mask()
+        }
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let contains = if <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY {
+            self.terminal_mask.get_bit(level_index)
+        } else {
+            !self.state.data_block(this.h.borrow(), level_index).borrow().is_empty()
+        };
+        (!contains).then_some(())
+    }
+}
+
+impl<H> Borrowable for Complement<H>{
+    type Borrowed = Complement<H>;
+}
+
+/// Keys within `bounds`, that are absent (empty) in [SparseHierarchy].
+///
+/// Item is `Some(())` for each key. Traverse all terminal blocks within
+/// `bounds`, but never constructs source data with [EXACT_HIERARCHY] source.
+///
+/// # Example
+///
+/// Find free ids:
+/// ```
+/// # use hi_sparse_array::{complement, config, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut used = Array::default();
+/// used.insert(0, Some(0));
+/// used.insert(1, Some(1));
+/// used.insert(3, Some(3));
+///
+/// let free = complement(&used, 0..6);
+/// let free = free.iter().filter(|(_, v)| v.is_some()).map(|(i, _)| i);
+/// assert!(free.eq([2, 4, 5]));
+/// ```
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn complement<H>(h: H, bounds: impl RangeBounds<usize>) -> RangeView<Complement<H>>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    range_view(Complement{h}, bounds)
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, intersection, SparseArray, union_fold};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    fn keys(h: &impl SparseHierarchy<DataType = Option<()>>) -> Vec<usize> {
+        h.iter().filter(|(_, d)| d.borrow().is_some()).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_complement(){
+        // Empty source.
+        let empty = Array::default();
+        assert_equal(keys(&complement(&empty, 10..100)), 10..100);
+        assert_eq!(complement(&empty, ..).count_keys(), Array::max_range() + 1);
+
+        // Full range.
+        let full = Array::from_keys(0..5000, |i| Data(i+1));
+        assert!(keys(&complement(&full, 0..5000)).is_empty());
+        assert_equal(keys(&complement(&full, 4000..5100)), 5000..5100);
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x00c0_4e7e);
+        let mut a = Array::default();
+        for _ in 0..2000 {
+            let v = rng.gen_range(0..10_000);
+            a.insert(v, Data(v+1));
+        }
+        // Empty item is absent.
+        let removed = a.iter().nth(5).unwrap().0;
+        a.insert(removed, Data(0));
+
+        let expected: Vec<_> = (100..9000).filter(|&i| !a.contains(i)).collect();
+        let c = complement(&a, 100..9000);
+        assert_eq!(keys(&c), expected);
+        for i in 0..10_000 {
+            assert_eq!(c.get(i).is_some(), expected.binary_search(&i).is_ok());
+        }
+
+        // Exact source.
+        a.remove(removed);
+        let exact = unsafe{ ExactHierarchy::new_unchecked(&a) };
+        let c = complement(&exact, 100..9000);
+        assert_eq!(keys(&c), expected);
+        for i in 0..10_000 {
+            assert_eq!(c.get(i).is_some(), expected.binary_search(&i).is_ok());
+        }
+    }
+
+    /// Allocatable ids, not reserved by any of N arrays.
+    #[test]
+    fn test_free_ids(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x0f4e_e1d5);
+        let reserved: Vec<Array> = (0..4).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..1000 {
+                let v = rng.gen_range(0..8000);
+                a.insert(v, Data(1));
+            }
+            a
+        }).collect();
+        let mut allocatable = Array::default();
+        for i in (0..8000).step_by(3) {
+            allocatable.insert(i, Data(1));
+        }
+
+        let any_reserved = union_fold(&reserved[0], reserved[1..].iter(), |acc, d| Data(acc.0 + d.0));
+        let free = intersection(&allocatable, complement(&any_reserved, ..), |a, f|
+            (!a.is_empty() && f.is_some()).then_some(())
+        );
+        let expected: Vec<_> = (0..8000).step_by(3)
+            .filter(|&i| reserved.iter().all(|r| !r.contains(i)))
+            .collect();
+        assert_eq!(keys(&free), expected);
+    }
+}
@@ -0,0 +1,231 @@
+use std::borrow::Borrow;
+use std::mem;
+use crate::{Empty, Iter, SparseHierarchy};
+
+/// Item change between two [SparseHierarchy] snapshots.
+///
+/// Produced by [diff()].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Change<Old, New>{
+    /// Item present only in new snapshot.
+    Added(usize, New),
+    /// Item present only in old snapshot.
+    Removed(usize, Old),
+    /// Item present in both, but with different values.
+    Modified(usize, Old, New),
+}
+
+impl<Old, New> Change<Old, New>{
+    #[inline]
+    pub fn index(&self) -> usize {
+        match self {
+            Change::Added(index, _)
+            | Change::Removed(index, _)
+            | Change::Modified(index, _, _) => *index
+        }
+    }
+}
+
+/// Next non-empty item.
+#[inline]
+fn next_non_empty<'a, H: SparseHierarchy>(iter: &mut Iter<'a, H>) -> Option<(usize, H::Data<'a>)> {
+    iter.find(|(_, data)| H::EXACT_HIERARCHY || !data.borrow().is_empty())
+}
+
+/// [Change]s iterator, in index order.
+///
+/// Constructed by [diff()].
+pub struct Diff<'a, H1, H2>
+where
+    H1: SparseHierarchy,
+    H2: SparseHierarchy,
+{
+    old_iter: Iter<'a, H1>,
+    new_iter: Iter<'a, H2>,
+    /// Current non-empty items.
+    old: Option<(usize, H1::Data<'a>)>,
+    new: Option<(usize, H2::Data<'a>)>,
+}
+
+impl<'a, H1, H2> Iterator for Diff<'a, H1, H2>
+where
+    H1: SparseHierarchy,
+    H2: SparseHierarchy<DataType = H1::DataType>,
+    H1::DataType: PartialEq,
+{
+    type Item = Change<H1::Data<'a>, H2::Data<'a>>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let old_index = self.old.as_ref().map(|(index, _)| *index);
+            let new_index = self.new.as_ref().map(|(index, _)| *index);
+            let change = match (old_index, new_index) {
+                (None, None) => return None,
+                (Some(old_index), Some(new_index)) if old_index == new_index => {
+                    let (index, old) = self.take_old()?;
+                    let (_, new) = self.take_new()?;
+                    if old.borrow() == new.borrow() {
+                        continue;
+                    }
+                    Change::Modified(index, old, new)
+                }
+                (Some(old_index), Some(new_index)) if new_index < old_index => {
+                    let (index, new) = self.take_new()?;
+                    Change::Added(index, new)
+                }
+                (None, Some(_)) => {
+                    let (index, new) = self.take_new()?;
+                    Change::Added(index, new)
+                }
+                _ => {
+                    let (index, old) = self.take_old()?;
+                    Change::Removed(index, old)
+                }
+            };
+            return Some(change);
+        }
+    }
+}
+
+impl<'a, H1, H2> Diff<'a, H1, H2>
+where
+    H1: SparseHierarchy,
+    H2: SparseHierarchy,
+{
+    #[inline]
+    fn take_old(&mut self) -> Option<(usize, H1::Data<'a>)> {
+        mem::replace(&mut self.old, next_non_empty(&mut self.old_iter))
+    }
+
+    #[inline]
+    fn take_new(&mut self) -> Option<(usize, H2::Data<'a>)> {
+        mem::replace(&mut self.new, next_non_empty(&mut self.new_iter))
+    }
+}
+
+/// Changes between `old` and `new` [SparseHierarchy] snapshots.
+///
+/// Dual walk over both hierarchies in index order. Empty items are treated
+/// as absent. Items present in both, with equal values - are skipped.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same data type.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{Change, config, diff, SparseArray};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut old = Array::default();
+/// old.insert(1, Some(1));
+/// old.insert(2, Some(2));
+/// let mut new = Array::default();
+/// new.insert(2, Some(20));
+/// new.insert(3, Some(3));
+///
+/// let changes: Vec<_> = diff(&old, &new).collect();
+/// assert_eq!(changes, [
+///     Change::Removed(1, &Some(1)),
+///     Change::Modified(2, &Some(2), &Some(20)),
+///     Change::Added(3, &Some(3)),
+/// ]);
+/// ```
+#[inline]
+pub fn diff<'a, H1, H2>(old: &'a H1, new: &'a H2) -> Diff<'a, H1, H2>
+where
+    H1: SparseHierarchy,
+    H2: SparseHierarchy<DataType = H1::DataType>,
+    H1::DataType: PartialEq,
+{
+    let mut old_iter = old.iter();
+    let mut new_iter = new.iter();
+    Diff{
+        old: next_non_empty(&mut old_iter),
+        new: next_non_empty(&mut new_iter),
+        old_iter,
+        new_iter,
+    }
+}
+
+/// Calls `f` for each of [diff()] changes.
+#[inline]
+pub fn diff_for_each<'a, H1, H2, F>(old: &'a H1, new: &'a H2, f: F)
+where
+    H1: SparseHierarchy,
+    H2: SparseHierarchy<DataType = H1::DataType>,
+    H1::DataType: PartialEq,
+    F: FnMut(Change<H1::Data<'a>, H2::Data<'a>>),
+{
+    diff(old, new).for_each(f)
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::BTreeMap;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    fn brute_force_diff(old: &BTreeMap<usize, usize>, new: &BTreeMap<usize, usize>)
+        -> Vec<Change<usize, usize>>
+    {
+        let mut keys: Vec<_> = old.keys().chain(new.keys()).copied().collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter().filter_map(|k| match (old.get(&k), new.get(&k)) {
+            (Some(&o), None) => Some(Change::Removed(k, o)),
+            (None, Some(&n)) => Some(Change::Added(k, n)),
+            (Some(&o), Some(&n)) if o != n => Some(Change::Modified(k, o, n)),
+            _ => None
+        }).collect()
+    }
+
+    #[test]
+    fn test_diff(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xd1ff_d1ff);
+        for _ in 0..50 {
+            let mut old = Array::default();
+            let mut old_map = BTreeMap::new();
+            for _ in 0..rng.gen_range(0..500) {
+                let (k, v) = (rng.gen_range(0..5000), rng.gen_range(1..4));
+                old.insert(k, Data(v));
+                old_map.insert(k, v);
+            }
+            let mut new = Array::default();
+            let mut new_map = old_map.clone();
+            for (&k, &v) in &old_map {
+                new.insert(k, Data(v));
+            }
+            for _ in 0..rng.gen_range(0..100) {
+                let k = rng.gen_range(0..5000);
+                if rng.gen_bool(0.3) {
+                    // Empty item is absent.
+                    new.insert(k, Data(0));
+                    new_map.remove(&k);
+                } else {
+                    let v = rng.gen_range(1..4);
+                    new.insert(k, Data(v));
+                    new_map.insert(k, v);
+                }
+            }
+
+            let expected = brute_force_diff(&old_map, &new_map);
+            let map = |c: Change<&Data, &Data>| match c {
+                Change::Added(i, n) => Change::Added(i, n.0),
+                Change::Removed(i, o) => Change::Removed(i, o.0),
+                Change::Modified(i, o, n) => Change::Modified(i, o.0, n.0),
+            };
+            let changes: Vec<_> = diff(&old, &new).map(map).collect();
+            assert_eq!(changes, expected);
+
+            let mut changes = Vec::new();
+            diff_for_each(&old, &new, |c| changes.push(map(c)));
+            assert_eq!(changes, expected);
+            
+            assert!(diff(&new, &new).next().is_none());
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::Sub;
+use crate::{Apply, apply, BitBlock, Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
+use crate::const_utils::ConstFalse;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+pub struct DifferenceOp<L, R, M>{
+    /// Right hierarchy is [EXACT_HIERARCHY].
+    ///
+    /// Known at compile-time - will be optimized away.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    right_exact: bool,
+    phantom_data: PhantomData<(L, R, M)>
+}
+impl<Left, Right, Mask> BinaryOp for DifferenceOp<Left, Right, Mask>
+where
+    Left: Empty + Clone,
+    Right: Empty,
+    Mask: BitBlock,
+{
+    // Upper level bits can't be cleared - we don't know what's
+    // in the terminal levels below.
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        _    : impl Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take_or_clone()
+    }
+
+    #[inline]
+    fn terminal_lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut mask = left.take_or_clone();
+        if self.right_exact {
+            // m0 & !m1
+            let right = right.borrow().as_array().as_ref();
+            for (l, r) in mask.as_array_mut().as_mut().iter_mut().zip(right) {
+                *l &= !*r;
+            }
+        }
+        mask
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Left;
+
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Take<Self::Left>,
+        right: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        if right.borrow().is_empty() {
+            left.take_or_clone()
+        } else {
+            Left::empty()
+        }
+    }
+}
+
+pub type Difference<H1, H2> = Apply<
+    DifferenceOp<
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H2 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    H1,
+    H2
+>;
+
+/// Difference between two [SparseHierarchy]ies.
+///
+/// Contains `h1` items, that are not present (empty) in `h2`.
+///
+/// Terminal level masks are tightened only if `h2` is [EXACT_HIERARCHY].
+/// Otherwise, removed items are checked and skipped in iteration.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn difference<H1, H2>(h1: H1, h2: H2) -> Difference<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+{
+    let op = DifferenceOp{
+        right_exact: <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY,
+        phantom_data: PhantomData
+    };
+    apply(op, h1, h2)
+}
+
+/// [difference].
+impl<Levels, Data, K, Rhs> Sub<Rhs> for &SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = Levels::LevelCount,
+        LevelMaskType = Levels::Mask,
+        DataType      = Data,
+    >>,
+{
+    type Output = Difference<Self, Rhs>;
+
+    #[inline]
+    fn sub(self, rhs: Rhs) -> Self::Output {
+        difference(self, rhs)
+    }
+}
+
+/// [difference].
+impl<Op, B1, B2, Rhs> Sub<Rhs> for Apply<Op, B1, B2>
+where
+    Self: SparseHierarchy<DataType: Clone>,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Self as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Self as SparseHierarchy>::LevelMaskType,
+        DataType      = <Self as SparseHierarchy>::DataType,
+    >>,
+{
+    type Output = Difference<Self, Rhs>;
+
+    #[inline]
+    fn sub(self, rhs: Rhs) -> Self::Output {
+        difference(self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::BTreeSet;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_difference(){
+        const RANGE: usize = 20_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+
+        for _ in 0..10 {
+            let mut a1 = Array::default();
+            let mut a2 = Array::default();
+            let mut s1 = BTreeSet::new();
+            let mut s2 = BTreeSet::new();
+            for _ in 0..rng.gen_range(0..2000) {
+                let v = rng.gen_range(0..RANGE);
+                a1.insert(v, Data(v+1));
+                s1.insert(v);
+            }
+            for _ in 0..rng.gen_range(0..2000) {
+                let v = rng.gen_range(0..RANGE);
+                a2.insert(v, Data(v+1));
+                s2.insert(v);
+            }
+            let expected: Vec<_> = s1.difference(&s2).copied().collect();
+
+            // non-exact right
+            {
+                let diff = difference(&a1, &a2);
+                assert_equal(
+                    diff.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+                    expected.iter().copied()
+                );
+                for i in 0..RANGE {
+                    let d = diff.get(i);
+                    assert_eq!(d, unsafe{ diff.get_unchecked(i) });
+                    assert_eq!(!d.is_empty(), s1.contains(&i) && !s2.contains(&i));
+                }
+            }
+
+            // exact right
+            {
+                let a2 = unsafe{ ExactHierarchy::new_unchecked(&a2) };
+                let diff = difference(&a1, &a2);
+                assert_equal(
+                    diff.iter().map(|(i, d)| { assert!(!d.is_empty()); i }),
+                    expected.iter().copied()
+                );
+                for &i in &expected {
+                    assert_eq!(diff.get(i), Data(i+1));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, Empty, fold, Fold, SparseHierarchy};
+use crate::const_utils::ConstFalse;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+pub struct DifferenceFoldOp<Acc, Data, Mask>{
+    /// Subtrahend hierarchies are [EXACT_HIERARCHY].
+    ///
+    /// Known at compile-time - will be optimized away.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    right_exact: bool,
+    phantom_data: PhantomData<(Acc, Data, Mask)>
+}
+impl<Acc, Data, Mask> BinaryOp for DifferenceFoldOp<Acc, Data, Mask>
+where
+    Acc: Empty,
+    Data: Empty,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        _    : impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take()
+    }
+
+    #[inline]
+    fn terminal_lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut acc = left.take();
+        if self.right_exact {
+            // acc & !m
+            let right = right.borrow().as_array().as_ref();
+            for (l, r) in acc.as_array_mut().as_mut().iter_mut().zip(right) {
+                *l &= !*r;
+            }
+        }
+        acc
+    }
+
+    type Left  = Acc;
+    type Right = Data;
+    type Out   = Acc;
+
+    #[inline]
+    fn data_op(
+        &self,
+        acc  : impl Take<Self::Left>,
+        right: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        if right.borrow().is_empty() {
+            acc.take()
+        } else {
+            Acc::empty()
+        }
+    }
+}
+
+pub type DifferenceFold<Init, Iter> = Fold<
+    DifferenceFoldOp<
+        <<Init as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<<Iter as Iterator>::Item as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<Init as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    Init,
+    Iter
+>;
+
+/// Difference between `init` and union of `iter` [SparseHierarchy]ies in fold-style.
+///
+/// Contains `init` items, that are not present (empty) in any of `iter`'s.
+/// With empty `iter` - same as `init`.
+///
+/// `Init`'s type may differ, but all [SparseHierarchy]ies
+/// must have the same configuration.
+#[inline]
+pub fn difference_fold<Init, Iter>(init: Init, iter: Iter)
+    -> DifferenceFold<Init, Iter>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy>,
+    Iter: Iterator<Item: Borrowable<Borrowed: SparseHierarchy>>,
+{
+    let op = DifferenceFoldOp{
+        right_exact: <<Iter::Item as Borrowable>::Borrowed as SparseHierarchy>::EXACT_HIERARCHY,
+        phantom_data: PhantomData
+    };
+    fold(op, init, iter)
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::BTreeSet;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_difference_fold(){
+        const RANGE: usize = 20_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+
+        let mut random_array = |count: usize| {
+            let mut a = Array::default();
+            let mut s = BTreeSet::new();
+            for _ in 0..count {
+                let v = rng.gen_range(0..RANGE);
+                a.insert(v, Data(v+1));
+                s.insert(v);
+            }
+            (a, s)
+        };
+
+        let (a0, s0) = random_array(4000);
+        for rest_len in [0, 1, 3] {
+            let (rest, rest_sets): (Vec<_>, Vec<_>) = (0..rest_len).map(|_| random_array(1000)).unzip();
+            let expected: Vec<_> = s0.iter().copied()
+                .filter(|i| rest_sets.iter().all(|s| !s.contains(i)))
+                .collect();
+
+            // non-exact
+            {
+                let diff = difference_fold(&a0, rest.iter());
+                assert_equal(
+                    diff.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+                    expected.iter().copied()
+                );
+                for i in 0..RANGE {
+                    let d = diff.get(i);
+                    assert_eq!(!d.is_empty(), expected.binary_search(&i).is_ok());
+                }
+            }
+
+            // exact
+            {
+                let rest: Vec<_> = rest.iter()
+                    .map(|a| unsafe{ ExactHierarchy::new_unchecked(a) })
+                    .collect();
+                let diff = difference_fold(&a0, rest.iter());
+                assert_equal(
+                    diff.iter().map(|(i, d)| { assert_eq!(d, Data(i+1)); i }),
+                    expected.iter().copied()
+                );
+            }
+        }
+    }
+}
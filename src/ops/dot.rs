@@ -0,0 +1,79 @@
+use std::ops::{Add, Mul};
+use crate::{intersection, SparseHierarchy};
+use crate::utils::Borrowable;
+
+/// Sparse dot product - sum of products of common items.
+///
+/// Walks intersection hierarchy, skipping subtrees with zero masks, and
+/// accumulates with [fold_data]. No intermediate storage is allocated.
+/// `T::default()` is used as zero.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, dot, SparseArray};
+/// type Vector = SparseArray<config::width_64::depth_2, Option<f64>>;
+/// let mut a = Vector::default();
+/// let mut b = Vector::default();
+/// a.insert(1, Some(2.0));
+/// a.insert(5, Some(3.0));
+/// b.insert(5, Some(4.0));
+/// b.insert(9, Some(5.0));
+/// assert_eq!(dot(&a, &b), 12.0);
+/// ```
+///
+/// [fold_data]: SparseHierarchy::fold_data
+pub fn dot<H1, H2, T>(h1: H1, h2: H2) -> T
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType = Option<T>>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType,
+        DataType      = Option<T>,
+    >>,
+    T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    let products = intersection(h1, h2, |l: &Option<T>, r: &Option<T>|
+        match (l, r) {
+            (Some(l), Some(r)) => Some(*l * *r),
+            _ => None
+        }
+    );
+    products.fold_data(T::default(), |acc, _, product| match product {
+        Some(product) => acc + product,
+        None => acc
+    })
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::HashMap;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, SparseArray};
+    use super::*;
+
+    #[test]
+    fn test_dot(){
+        type Vector = SparseArray<config::width_64::depth_3, Option<i64>>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xd07_d07);
+        let mut a = Vector::default();
+        let mut b = Vector::default();
+        let mut ma = HashMap::new();
+        let mut mb = HashMap::new();
+        for _ in 0..2000 {
+            let (k, v) = (rng.gen_range(0..10_000), rng.gen_range(-100..100));
+            a.insert(k, Some(v));
+            ma.insert(k, v);
+            let (k, v) = (rng.gen_range(0..10_000), rng.gen_range(-100..100));
+            b.insert(k, Some(v));
+            mb.insert(k, v);
+        }
+        let expected: i64 = ma.iter()
+            .filter_map(|(k, va)| mb.get(k).map(|vb| va * vb))
+            .sum();
+        assert_eq!(dot(&a, &b), expected);
+        assert_eq!(dot(&a, Vector::default()), 0);
+    }
+}
@@ -0,0 +1,368 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use crate::{BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::const_int::{const_for, ConstInteger, ConstIntVisitor, ConstUsize};
+use crate::const_utils::const_array::{ConstArray, ConstCopyArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::{Borrowable, Take};
+
+/// Lazy [SparseHierarchy] adaptor, that presents source as a deeper one.
+///
+/// Extra levels are added on top. Their masks have only bit 0 raised
+/// (if source is non-empty) - so keys are the same as in source.
+/// Source levels are passed as-is.
+///
+/// Constructed by [expand_depth()].
+pub struct ExpandDepth<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    h: H,
+    /// Mask of extra levels blocks.
+    upper_mask: <H::Borrowed as SparseHierarchy>::LevelMaskType,
+    phantom_data: PhantomData<Depth>
+}
+
+impl<H, Depth> ExpandDepth<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    Depth: ConstInteger,
+{
+    /// Number of extra levels.
+    const EXTRA: usize = Depth::VALUE - <H::Borrowed as SparseHierarchy>::LevelCount::VALUE;
+}
+
+type SourceLevelCount<H> = <<H as Borrowable>::Borrowed as SparseHierarchy>::LevelCount;
+
+/// Calls source `level_mask` for source level `level`.
+///
+/// Source level is known at run-time only, so we look for matching
+/// compile-time one.
+struct SourceLevelMask<'a, H, I>{
+    h: &'a H,
+    level: usize,
+    /// Source level indices starts at `EXTRA`.
+    level_indices: I,
+    extra: usize,
+}
+impl<'a, H, I> ConstIntVisitor for SourceLevelMask<'a, H, I>
+where
+    H: SparseHierarchy,
+    I: ConstArray<Item=usize>,
+{
+    type Out = H::LevelMaskType;
+
+    #[inline(always)]
+    fn visit<J: ConstInteger>(&mut self, _: J) -> ControlFlow<Self::Out> {
+        if J::VALUE != self.level {
+            return ControlFlow::Continue(());
+        }
+        let indices: ConstCopyArrayType<usize, J> = Array::from_fn(|i| unsafe{
+            *self.level_indices.as_ref().get_unchecked(self.extra + i)
+        });
+        let mask = unsafe{ self.h.level_mask(indices) };
+        ControlFlow::Break(mask.take_or_clone())
+    }
+}
+
+/// Calls source state `select_level_bock` for source level `level`.
+struct SelectSourceLevel<'a, 'b, H: SparseHierarchy>{
+    h: &'a H,
+    state: &'b mut H::State,
+    level: usize,
+    level_index: usize,
+}
+impl<'a, 'b, H> ConstIntVisitor for SelectSourceLevel<'a, 'b, H>
+where
+    H: SparseHierarchy,
+{
+    type Out = H::LevelMaskType;
+
+    #[inline(always)]
+    fn visit<J: ConstInteger>(&mut self, level_n: J) -> ControlFlow<Self::Out> {
+        if J::VALUE != self.level {
+            return ControlFlow::Continue(());
+        }
+        let mask = unsafe{ self.state.select_level_bock(self.h, level_n, self.level_index) };
+        ControlFlow::Break(mask.take_or_clone())
+    }
+}
+
+impl<H, Depth> SparseHierarchy for ExpandDepth<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    Depth: ConstInteger,
+{
+    const EXACT_HIERARCHY: bool = <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    type LevelCount = Depth;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let level = I::Cap::VALUE;
+        let extra = Self::EXTRA;
+        let upper_indices = level_indices.as_ref().get_unchecked(..level.min(extra));
+        if upper_indices.iter().any(|&i| i != 0) {
+            return BitBlock::zero();
+        }
+        if level < extra {
+            return self.upper_mask.clone();
+        }
+        let ctrl = const_for(
+            ConstUsize::<0>,
+            SourceLevelCount::<H>::DEFAULT,
+            SourceLevelMask{ h: self.h.borrow(), level: level - extra, level_indices, extra }
+        );
+        match ctrl {
+            ControlFlow::Break(mask) => mask,
+            ControlFlow::Continue(()) => unreachable!(),
+        }
+    }
+
+    type DataType = <H::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let extra = Self::EXTRA;
+        if level_indices.as_ref().get_unchecked(..extra).iter().any(|&i| i != 0) {
+            return Empty::empty();
+        }
+        let indices: ConstCopyArrayType<usize, SourceLevelCount<H>> = Array::from_fn(|i|
+            *level_indices.as_ref().get_unchecked(extra + i)
+        );
+        self.h.borrow().data_block(indices).take_or_clone()
+    }
+
+    type State = ExpandDepthState<H, Depth>;
+}
+
+pub struct ExpandDepthState<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// First selected level, with non-zero index within extra levels block.
+    /// `usize::MAX` if none.
+    ///
+    /// Composed hierarchies can select blocks outside of source this way.
+    outside_level: usize,
+
+    phantom_data: PhantomData<ExpandDepth<H, Depth>>
+}
+
+impl<H, Depth> Clone for ExpandDepthState<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            outside_level: self.outside_level,
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H, Depth> SparseHierarchyState for ExpandDepthState<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    Depth: ConstInteger,
+{
+    type This = ExpandDepth<H, Depth>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            outside_level: usize::MAX,
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, _: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        let extra = Self::This::EXTRA;
+        if N::VALUE <= extra && self.outside_level >= N::VALUE {
+            self.outside_level = if level_index == 0 { usize::MAX } else { N::VALUE };
+        }
+        if self.outside_level <= N::VALUE {
+            return BitBlock::zero();
+        }
+        if N::VALUE < extra {
+            return this.upper_mask.clone();
+        }
+        // Source root is selected with 0 index - and that's what `level_index`
+        // is for non-outside block.
+        let ctrl = const_for(
+            ConstUsize::<0>,
+            SourceLevelCount::<H>::DEFAULT,
+            SelectSourceLevel{
+                h: this.h.borrow(),
+                state: &mut self.state,
+                level: N::VALUE - extra,
+                level_index,
+            }
+        );
+        match ctrl {
+            ControlFlow::Break(mask) => mask,
+            ControlFlow::Continue(()) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        if self.outside_level != usize::MAX {
+            return Empty::empty();
+        }
+        self.state.data_block(this.h.borrow(), level_index).take_or_clone()
+    }
+}
+
+impl<H, Depth> Borrowable for ExpandDepth<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    type Borrowed = ExpandDepth<H, Depth>;
+}
+
+/// View of [SparseHierarchy] as one with `Depth` levels.
+///
+/// Keys stay the same. Allows to compose hierarchies of different depths,
+/// without copying data.
+///
+/// # Panics
+///
+/// If `Depth` is less than source [LevelCount].
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, expand_depth, SparseArray, SparseHierarchy, union};
+/// # use hi_sparse_array::const_utils::ConstUsize;
+/// type Shallow = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// type Deep    = SparseArray<config::width_64::depth_3, Option<u32>>;
+/// let mut a = Shallow::default();
+/// a.insert(10, Some(1));
+/// let mut b = Deep::default();
+/// b.insert(10, Some(2));
+/// b.insert(100_000, Some(3));
+///
+/// let a = expand_depth::<ConstUsize<3>, _>(&a);
+/// let u = union(&a, &b, |x, y| x.or(*y));
+/// assert!(u.iter().eq([(10, Some(1)), (100_000, Some(3))]));
+/// ```
+///
+/// [LevelCount]: SparseHierarchy::LevelCount
+#[inline]
+pub fn expand_depth<Depth, H>(h: H) -> ExpandDepth<H, Depth>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    Depth: ConstInteger,
+{
+    assert!(
+        Depth::VALUE >= SourceLevelCount::<H>::VALUE,
+        "Depth must not be less than source LevelCount!"
+    );
+    let mut upper_mask = BitBlock::zero();
+    let is_empty = unsafe{ h.borrow().level_mask([]).borrow().is_zero() };
+    if !is_empty {
+        BitBlock::set_bit::<true>(&mut upper_mask, 0);
+    }
+    ExpandDepth{h, upper_mask, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::BTreeMap;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection, SparseArray, union};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Shallow = SparseArray<config::width_64::depth_2, Data>;
+    type Deep    = SparseArray<config::width_64::depth_4, Data>;
+
+    #[test]
+    fn test_expand_depth(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xde_e9e4);
+        let max = Shallow::max_range();
+        let mut a = Shallow::default();
+        let mut b = Deep::default();
+        let mut ma = BTreeMap::new();
+        let mut mb = BTreeMap::new();
+        for _ in 0..1000 {
+            let v = rng.gen_range(0..=max);
+            a.insert(v, Data(v + 1));
+            ma.insert(v, v + 1);
+            let v = rng.gen_range(0..2 * max);
+            b.insert(v, Data(v + 1));
+            mb.insert(v, v + 1);
+        }
+        for v in [0, max - 1, max, max + 1] {
+            b.insert(v, Data(v + 1));
+            mb.insert(v, v + 1);
+        }
+        a.insert(max, Data(max + 1));
+        ma.insert(max, max + 1);
+
+        let ea = expand_depth::<ConstUsize<4>, _>(&a);
+        assert_equal(ea.iter(), a.iter().map(|(i, d)| (i, d.clone())));
+        assert_eq!(ea.get(max), Data(max + 1));
+        assert_eq!(ea.get(max + 1), Data(0));
+        assert_eq!(ea.get(Deep::max_range()), Data(0));
+        for i in max - 100..max + 100 {
+            assert_eq!(ea.get(i).0, ma.get(&i).copied().unwrap_or(0));
+        }
+
+        // Union
+        let u = union(&ea, &b, |x, y| Data(x.0 + y.0));
+        let mut expected = mb.clone();
+        for (&k, &v) in &ma {
+            *expected.entry(k).or_insert(0) += v;
+        }
+        assert_equal(u.iter().map(|(i, d)| (i, d.0)), expected.iter().map(|(&k, &v)| (k, v)));
+
+        // Intersection
+        let and = intersection(&ea, &b, |x, y| Data(x.0 + y.0));
+        let expected: Vec<_> = ma.iter()
+            .filter_map(|(k, v)| mb.get(k).map(|v2| (*k, v + v2)))
+            .collect();
+        assert_equal(
+            and.iter().filter(|(_, d)| !d.is_empty()).map(|(i, d)| (i, d.0)),
+            expected.iter().copied()
+        );
+        for (k, v) in &expected {
+            assert_eq!(and.get(*k).0, *v);
+        }
+
+        // Same depth
+        let same = expand_depth::<ConstUsize<2>, _>(&a);
+        assert_equal(same.iter(), a.iter().map(|(i, d)| (i, d.clone())));
+
+        // Empty source
+        let empty = Shallow::default();
+        assert_eq!(expand_depth::<ConstUsize<3>, _>(&empty).iter().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Depth")]
+    fn test_expand_depth_shallower(){
+        expand_depth::<ConstUsize<1>, _>(Shallow::default());
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::{Borrowable, Take};
+
+/// Lazy [SparseHierarchy] adaptor, that hides items rejected by predicate.
+///
+/// Constructed by [filter()].
+pub struct Filter<H, F>{
+    h: H,
+    f: F,
+}
+
+impl<H, F> SparseHierarchy for Filter<H, F>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> bool,
+{
+    // Predicate needs data - so hierarchy can't be tightened.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.h.borrow().level_mask(level_indices)
+    }
+
+    type DataType = <H::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let data = self.h.borrow().data_block(level_indices);
+        filter_data(&self.f, data)
+    }
+
+    type State = FilterState<H, F>;
+}
+
+#[inline]
+fn filter_data<T, F>(f: &F, data: impl Borrow<T> + Take<T>) -> T
+where
+    T: Empty + Clone,
+    F: Fn(&T) -> bool,
+{
+    if f(data.borrow()) {
+        data.take_or_clone()
+    } else {
+        T::empty()
+    }
+}
+
+pub struct FilterState<H, F>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+    phantom_data: PhantomData<Filter<H, F>>
+}
+
+impl<H, F> Clone for FilterState<H, F>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H, F> SparseHierarchyState for FilterState<H, F>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> bool,
+{
+    type This = Filter<H, F>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        self.state.select_level_bock(this.h.borrow(), level_n, level_index)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let data = self.state.data_block(this.h.borrow(), level_index);
+        filter_data(&this.f, data)
+    }
+}
+
+impl<H, F> Borrowable for Filter<H, F>{
+    type Borrowed = Filter<H, F>;
+}
+
+/// Hide [SparseHierarchy] items, for which `f` returns false.
+///
+/// Rejected items read as empty. Hierarchy is passed as-is, so result
+/// is not [EXACT_HIERARCHY] - rejected items are skipped in iteration,
+/// after being checked.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn filter<H, F>(h: H, f: F) -> Filter<H, F>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> bool,
+{
+    Filter{h, f}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, intersection, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_filter(){
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for i in 0..3000 {
+            a1.insert(i, Data(i+1));
+        }
+        for i in (0..3000).step_by(3) {
+            a2.insert(i, Data(1));
+        }
+
+        let even = filter(&a1, |d: &Data| d.0 % 2 == 0);
+        assert_equal(
+            even.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+            (1..3000).step_by(2)
+        );
+        assert_eq!(even.get(1), Data(2));
+        assert!(even.get(2).is_empty());
+        assert_eq!(unsafe{ even.get_unchecked(3) }, Data(4));
+
+        // Half of terminal bits are rejected.
+        assert_equal(
+            even.iter(),
+            (1..3000).step_by(2).map(|i| (i, Data(i+1)))
+        );
+        assert_eq!(even.iter().count(), 1500);
+
+        let res = intersection(even, &a2, |l, r| Data(l.0 * r.0));
+        assert_equal(
+            res.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+            (3..3000).step_by(6)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::Borrowable;
+
+/// Lazy [SparseHierarchy] adaptor, that maps items and hides rejected ones.
+///
+/// Constructed by [filter_map()].
+pub struct FilterMap<H, F, Out>{
+    h: H,
+    f: F,
+    phantom_data: PhantomData<Out>,
+}
+
+impl<H, F, Out> SparseHierarchy for FilterMap<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> Option<Out>,
+    Out: Empty,
+{
+    // Closure needs data - so hierarchy can't be tightened.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.h.borrow().level_mask(level_indices)
+    }
+
+    type DataType = Out;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let data = self.h.borrow().data_block(level_indices);
+        filter_map_data(&self.f, data)
+    }
+
+    type State = FilterMapState<H, F, Out>;
+}
+
+#[inline]
+fn filter_map_data<T, F, Out>(f: &F, data: impl Borrow<T>) -> Out
+where
+    F: Fn(&T) -> Option<Out>,
+    Out: Empty,
+{
+    f(data.borrow()).unwrap_or_else(Out::empty)
+}
+
+pub struct FilterMapState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+    phantom_data: PhantomData<FilterMap<H, F, Out>>
+}
+
+impl<H, F, Out> Clone for FilterMapState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H, F, Out> SparseHierarchyState for FilterMapState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> Option<Out>,
+    Out: Empty,
+{
+    type This = FilterMap<H, F, Out>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        self.state.select_level_bock(this.h.borrow(), level_n, level_index)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let data = self.state.data_block(this.h.borrow(), level_index);
+        filter_map_data(&this.f, data)
+    }
+}
+
+impl<H, F, Out> Borrowable for FilterMap<H, F, Out>{
+    type Borrowed = FilterMap<H, F, Out>;
+}
+
+/// Map [SparseHierarchy] items with `f`, hiding ones for which `f` returns `None`.
+///
+/// Rejected items read as empty. Hierarchy is passed as-is, so result
+/// is not [EXACT_HIERARCHY] - rejected items are skipped in iteration,
+/// after being checked.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn filter_map<H, F, Out>(h: H, f: F) -> FilterMap<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(&<H::Borrowed as SparseHierarchy>::DataType) -> Option<Out>,
+    Out: Empty,
+{
+    FilterMap{h, f, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, intersection, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_filter_map(){
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for i in 0..3000 {
+            a1.insert(i, Data(i+1));
+        }
+        for i in (0..3000).step_by(3) {
+            a2.insert(i, Data(1));
+        }
+
+        let and = intersection(&a1, &a2, |l, r| Data(l.0 + r.0));
+        // Keep even sums only.
+        let res = filter_map(and, |d: &Data| (d.0 % 2 == 0).then_some(Data(d.0)));
+        assert_equal(
+            res.iter().map(|(i, d)| (i, d.0)),
+            (0..3000).step_by(6).map(|i| (i, i+2))
+        );
+        assert_eq!(res.get(0), Data(2));
+        assert_eq!(unsafe{ res.get_unchecked(6) }, Data(8));
+        
+        // Rejected - present in source, odd sum.
+        assert!(res.get(3).is_empty());
+        // Not in source.
+        assert!(res.get(5000).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+//! Fold-style ops over heterogeneous [SparseHierarchy]ies.
+//!
+//! [intersection_fold] / [union_fold] accept only same-typed sources.
+//! Macros here unroll to the chain of binary fold steps instead - so each
+//! source can be of its own type, as long as all share the same configuration.
+//!
+//! [SparseHierarchy]: crate::SparseHierarchy
+//! [intersection_fold]: crate::intersection_fold
+//! [union_fold]: crate::union_fold
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fold_tuple {
+    ($step:path, $f:expr, $acc:expr) => { $acc };
+    ($step:path, $f:expr, $acc:expr, $h:expr $(, $rest:expr)*) => {
+        $crate::__fold_tuple!($step, $f, $step($acc, $h, $f) $(, $rest)*)
+    };
+}
+
+/// [intersection_fold] over sources of different types.
+///
+/// `intersection_fold_tuple!(init, (a, b, c), f)` is the same as
+/// `intersection_fold(init, [a, b, c].iter(), f)`, but `a`, `b`, `c`
+/// can be of distinct [SparseHierarchy] types: concrete arrays and lazy views
+/// can be mixed. Unrolls to the chain of binary steps, so `f` expression is
+/// instantiated per source.
+///
+/// All [SparseHierarchy]ies must have the same configuration.
+/// Accumulator type must be [Clone].
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, intersection_fold_tuple, map_indexed, SparseArray, SparseHierarchy};
+/// type Array<T> = SparseArray<config::width_64::depth_2, Option<T>>;
+/// let mut a: Array<u32> = Default::default();
+/// let mut b: Array<u8>  = Default::default();
+/// a.insert(10, Some(1));
+/// a.insert(20, Some(2));
+/// b.insert(20, Some(3));
+/// let c = map_indexed(&b, |i, v| v.map(|v| v as usize + i));
+///
+/// let res = intersection_fold_tuple!(&a, (&b, &c), |acc: Option<u32>, v|
+///     acc.zip(*v).map(|(acc, v)| acc + v as u32)
+/// );
+/// assert_eq!(res.get(20), Some(2 + 3 + 23));
+/// ```
+///
+/// [intersection_fold]: crate::intersection_fold
+/// [SparseHierarchy]: crate::SparseHierarchy
+#[macro_export]
+macro_rules! intersection_fold_tuple {
+    ($init:expr, ($($h:expr),+ $(,)?), $f:expr) => {
+        $crate::__fold_tuple!($crate::__intersection_fold_step, $f, $init $(, $h)+)
+    };
+}
+
+/// [union_fold] over sources of different types.
+///
+/// Same as [intersection_fold_tuple], but with [union_fold] semantics.
+///
+/// [union_fold]: crate::union_fold
+/// [intersection_fold_tuple]: crate::intersection_fold_tuple
+#[macro_export]
+macro_rules! union_fold_tuple {
+    ($init:expr, ($($h:expr),+ $(,)?), $f:expr) => {
+        $crate::__fold_tuple!($crate::__union_fold_step, $f, $init $(, $h)+)
+    };
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, Empty, map_indexed, SparseArray, SparseHierarchy};
+    use crate::test_utils::Data;
+
+    trait Value{
+        fn value(&self) -> usize;
+    }
+    impl Value for Data{
+        fn value(&self) -> usize { self.0 }
+    }
+    impl Value for Option<u8>{
+        fn value(&self) -> usize { self.unwrap_or(0) as usize }
+    }
+
+    type Array<T> = SparseArray<config::width_64::depth_2, T>;
+
+    #[test]
+    fn test_fold_tuple(){
+        let mut a = Array::<Data>::default();
+        let mut b = Array::<Option<u8>>::default();
+        a.insert(1, Data(1));
+        a.insert(2, Data(1));
+        a.insert(300, Data(1));
+        b.insert(2, Some(10));
+        b.insert(300, Some(20));
+        b.insert(4000, Some(30));
+        let c = map_indexed(&a, |i, d: &Data| Data(d.0 * i));
+
+        let and = intersection_fold_tuple!(&a, (&b, &c), |acc: Data, d|
+            if acc.is_empty() || d.is_empty() {
+                Data::empty()
+            } else {
+                Data(acc.0 + d.value())
+            }
+        );
+        assert_eq!(and.get(300), Data(1 + 20 + 300));
+        assert_eq!(and.get(1), Data(0));
+        assert_equal(
+            and.iter().filter(|(_, d)| !d.is_empty()),
+            [(2, Data(1 + 10 + 2)), (300, Data(1 + 20 + 300))]
+        );
+
+        let or = union_fold_tuple!(&a, (&b, c), |acc: Data, d|
+            Data(acc.0 + d.value())
+        );
+        assert_eq!(or.get(4000), Data(30));
+        assert_equal(
+            or.iter().filter(|(_, d)| !d.is_empty()),
+            [(1, Data(1 + 1)), (2, Data(1 + 10 + 2)), (300, Data(1 + 20 + 300)), (4000, Data(30))]
+        );
+    }
+}
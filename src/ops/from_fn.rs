@@ -0,0 +1,233 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, data_block_index, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::Borrowable;
+
+/// Lazy [SparseHierarchy] with keys of other [SparseHierarchy],
+/// and items computed from index.
+///
+/// With [EXACT_HIERARCHY] keys source - its data is never touched.
+///
+/// Constructed by [from_fn()].
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub struct FromFn<H, F, Out>{
+    keys: H,
+    f: F,
+    phantom_data: PhantomData<Out>,
+}
+
+impl<H, F, Out> SparseHierarchy for FromFn<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize) -> Out,
+    Out: Empty,
+{
+    const EXACT_HIERARCHY: bool = <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.keys.borrow().level_mask(level_indices)
+    }
+
+    type DataType = Out;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let keys = self.keys.borrow();
+        let (upper_indices, data_index) = level_indices.split_last();
+        let index = data_block_index::<Self>(&upper_indices, data_index);
+        let contains = if Self::EXACT_HIERARCHY {
+            keys.may_contain_unchecked(index)
+        } else {
+            !keys.data_block(level_indices).borrow().is_empty()
+        };
+        if contains {
+            (self.f)(index)
+        } else {
+            Out::empty()
+        }
+    }
+
+    type State = FromFnState<H, F, Out>;
+}
+
+pub struct FromFnState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// [usize; LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <<H::Borrowed as SparseHierarchy>::LevelCount as ConstInteger>::Dec
+    >,
+
+    /// In-use only with [EXACT_HIERARCHY] keys source.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    terminal_mask: <H::Borrowed as SparseHierarchy>::LevelMaskType,
+
+    phantom_data: PhantomData<FromFn<H, F, Out>>
+}
+
+impl<H, F, Out> Clone for FromFnState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            terminal_mask: self.terminal_mask.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H, F, Out> SparseHierarchyState for FromFnState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize) -> Out,
+    Out: Empty,
+{
+    type This = FromFn<H, F, Out>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.keys.borrow()),
+            level_indices: Array::from_fn(|_| 0),
+            terminal_mask: BitBlock::zero(),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        if /*const*/ N::VALUE != 0 {
+            *self.level_indices.as_mut().get_unchecked_mut(N::VALUE - 1) = level_index;
+        }
+        let mask = self.state.select_level_bock(this.keys.borrow(), level_n, level_index);
+        if <Self::This as SparseHierarchy>::EXACT_HIERARCHY
+        && N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1
+        {
+            self.terminal_mask = mask.borrow().clone();
+        }
+        mask
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let contains = if <Self::This as SparseHierarchy>::EXACT_HIERARCHY {
+            self.terminal_mask.get_bit(level_index)
+        } else {
+            !self.state.data_block(this.keys.borrow(), level_index).borrow().is_empty()
+        };
+        if contains {
+            let index = data_block_index::<Self::This>(&self.level_indices, level_index);
+            (this.f)(index)
+        } else {
+            Out::empty()
+        }
+    }
+}
+
+impl<H, F, Out> Borrowable for FromFn<H, F, Out>{
+    type Borrowed = FromFn<H, F, Out>;
+}
+
+/// [SparseHierarchy] with `keys`' keys, and items computed by `f` from index.
+///
+/// `f` is called on each item access, for non-empty `keys` items only.
+/// Hierarchy of `keys` is passed as-is.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, from_fn, keys_view, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<String>>;
+/// let mut names = Array::default();
+/// names.insert(3, Some("a".into()));
+/// names.insert(7, Some("b".into()));
+///
+/// let weights = from_fn(keys_view(&names), |i| Some(i * 10));
+/// assert!(weights.iter().eq([(3, Some(30)), (7, Some(70))]));
+/// assert_eq!(weights.get(4), None);
+/// ```
+#[inline]
+pub fn from_fn<H, F, Out>(keys: H, f: F) -> FromFn<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize) -> Out,
+    Out: Empty,
+{
+    FromFn{keys, f, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, SparseArray, union};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_from_fn(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xf4_0f4);
+        let mut keys = Array::default();
+        let mut a = Array::default();
+        for _ in 0..1000 {
+            let v = rng.gen_range(0..10_000);
+            keys.insert(v, Data(1));
+            let v = rng.gen_range(0..10_000);
+            a.insert(v, Data(v + 1));
+        }
+        // Empty item is absent.
+        let removed = keys.iter().nth(10).unwrap().0;
+        keys.insert(removed, Data(0));
+
+        let computed = from_fn(&keys, |i| Data(i * 2 + 1));
+        let exact_keys = unsafe{ ExactHierarchy::new_unchecked(&keys) };
+        let exact_computed = from_fn(&exact_keys, |i| Data(i * 2 + 1));
+        for i in 0..10_000 {
+            let expected = if keys.contains(i) { Data(i * 2 + 1) } else { Data(0) };
+            assert_eq!(computed.get(i), expected);
+            if i != removed {
+                assert_eq!(exact_computed.get(i), expected);
+            }
+        }
+
+        let u = union(&a, &computed, |l, r| Data(l.0 + r.0));
+        let expected: Vec<_> = (0..10_000)
+            .map(|i| (i, Data(a.get(i).0 + computed.get(i).0)))
+            .filter(|(_, d)| !d.is_empty())
+            .collect();
+        assert_equal(u.iter().filter(|(_, d)| !d.is_empty()), expected.iter().cloned());
+        for (i, d) in &expected {
+            assert_eq!(&u.get(*i), d);
+        }
+    }
+}
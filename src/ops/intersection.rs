@@ -1,20 +1,21 @@
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 use std::ops::BitAnd;
-use crate::{Apply, apply, BitBlock, Empty, SparseHierarchy};
+use crate::{Apply, apply, BitBlock, Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
 use crate::const_utils::ConstFalse;
 use crate::level_block::Block;
 use crate::op::BinaryOp;
+use super::{Resolve, ResolveLeftIfBoth};
 use crate::utils::{Borrowable, Take};
 
-pub(crate) struct IntersectionOp<F, L, R, O, M>{
+pub struct IntersectionOp<F, L, R, O, M>{
     f: F,
     phantom_data: PhantomData<(L, R, O, M)>
 }
 impl<F, Left, Right, Out, Mask> BinaryOp for IntersectionOp<F, Left, Right, Out, Mask>
 where
     Out: Empty,
-    F: Fn(&Left, &Right) -> Out,
+    F: Resolve<Left, Right, Out>,
     Mask: BitBlock,
 {
     const EXACT_HIERARCHY: bool = false;
@@ -37,10 +38,10 @@ where
     #[inline]
     fn data_op(
         &self,
-        left : impl Borrow<Self::Left>,
-        right: impl Borrow<Self::Right>
+        left : impl Borrow<Self::Left>  + Take<Self::Left>,
+        right: impl Borrow<Self::Right> + Take<Self::Right>
     ) -> Self::Out {
-        (self.f)(left.borrow(), right.borrow())
+        self.f.resolve(left, right)
     }
 }
 
@@ -64,6 +65,21 @@ pub type Intersection<H1, H2, F, Res> = Apply<
 /// to each pair of intersected items.
 /// 
 /// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+/// 
+/// # Example
+/// 
+/// Join two component arrays into computed value:
+/// ```
+/// # use hi_sparse_array::{config, intersection, SparseArray, SparseHierarchy};
+/// let mut positions: SparseArray<config::width_64::depth_2, Option<f32>> = Default::default();
+/// let mut speeds   : SparseArray<config::width_64::depth_2, Option<f32>> = Default::default();
+/// positions.insert(10, Some(1.0));
+/// positions.insert(20, Some(2.0));
+/// speeds.insert(20, Some(0.5));
+/// 
+/// let next_positions = intersection(&positions, &speeds, |p, s| Some(p.unwrap() + s.unwrap()));
+/// assert_eq!(next_positions.get(20), Some(2.5));
+/// ```
 #[inline]
 pub fn intersection<H1, H2, F, R>(h1: H1, h2: H2, f: F)
     -> Intersection<H1, H2, F, R>
@@ -79,6 +95,46 @@ where
     apply(IntersectionOp{ f, phantom_data: PhantomData }, h1, h2)
 }
 
+/// [intersection], that takes items from the left side.
+impl<Levels, Data, K, Rhs> BitAnd<Rhs> for &SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = Levels::LevelCount,
+        LevelMaskType = Levels::Mask,
+        DataType      = Data,
+    >>,
+{
+    type Output = Intersection<Self, Rhs, ResolveLeftIfBoth, Data>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        apply(IntersectionOp{ f: ResolveLeftIfBoth, phantom_data: PhantomData }, self, rhs)
+    }
+}
+
+/// [intersection], that takes items from the left side.
+impl<Op, B1, B2, Rhs> BitAnd<Rhs> for Apply<Op, B1, B2>
+where
+    Self: SparseHierarchy<DataType: Clone>,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Self as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Self as SparseHierarchy>::LevelMaskType,
+        DataType      = <Self as SparseHierarchy>::DataType,
+    >>,
+{
+    type Output = Intersection<
+        Self, Rhs, ResolveLeftIfBoth, <Self as SparseHierarchy>::DataType
+    >;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        apply(IntersectionOp{ f: ResolveLeftIfBoth, phantom_data: PhantomData }, self, rhs)
+    }
+}
+
 #[cfg(test)]
 mod test{
     use crate::level::{IntrusiveListLevel, SingleBlockLevel};
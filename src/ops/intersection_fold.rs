@@ -1,23 +1,25 @@
 use std::any::Any;
 use std::borrow::Borrow;
 use std::marker::PhantomData;
-use std::ops::{BitAnd, BitAndAssign, BitOrAssign};
-use crate::{BitBlock, Empty, fold, Fold, SparseHierarchy};
+use crate::{Apply, apply, BitBlock, Empty, fold, Fold, SliceIter, SparseHierarchy};
+use crate::fold::slice_iter;
 use crate::const_utils::ConstFalse;
 use crate::level_block::Block;
 use crate::op::BinaryOp;
 use crate::utils::{Borrowable, Take};
 
-pub(crate) struct IntersectionFoldOp<F, Acc, Data, Mask>{
+pub struct IntersectionFoldOp<F, Acc, Data, Mask>{
     f: F,
     phantom_data: PhantomData<(Acc, Data, Mask)>
 }
 impl<F, Acc, Data, Mask> BinaryOp for IntersectionFoldOp<F, Acc, Data, Mask>
 where
-    Acc: Empty,
+    Acc: Empty + Clone,
     F: Fn(Acc, &Data) -> Acc,
     Mask: BitBlock,
 {
+    // Even with all sources exact, upper level bits can't be cleared -
+    // intersection of children may end up empty.
     const EXACT_HIERARCHY: bool = false;
     type SKIP_EMPTY_HIERARCHIES = ConstFalse;
     type LevelMask = Mask;
@@ -28,7 +30,7 @@ where
         left : impl Take<Self::LevelMask>, 
         right: impl Borrow<Self::LevelMask>
     ) -> Self::LevelMask {
-        let mut acc = left.take();
+        let mut acc = left.take_or_clone();
         acc &= right.borrow();
         acc
     }
@@ -43,10 +45,42 @@ where
         acc  : impl Take<Self::Left>,
         right: impl Borrow<Self::Right>
     ) -> Self::Out {
-        (self.f)(acc.take(), right.borrow())
+        (self.f)(acc.take_or_clone(), right.borrow())
     }
 }
 
+#[doc(hidden)]
+pub type IntersectionFoldStep<H1, H2, F> = Apply<
+    IntersectionFoldOp<
+        F,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H2 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    H1,
+    H2
+>;
+
+/// Single [intersection_fold] step between two [SparseHierarchy]ies
+/// of different types. Used by [intersection_fold_tuple](crate::intersection_fold_tuple).
+#[doc(hidden)]
+#[inline]
+pub fn __intersection_fold_step<H1, H2, F>(h1: H1, h2: H2, f: F)
+    -> IntersectionFoldStep<H1, H2, F>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(
+        <H1::Borrowed as SparseHierarchy>::DataType,
+        &<H2::Borrowed as SparseHierarchy>::DataType
+    ) -> <H1::Borrowed as SparseHierarchy>::DataType,
+{
+    apply(IntersectionFoldOp { f, phantom_data: PhantomData }, h1, h2)
+}
+
 pub type IntersectionFold<Init, Iter, F> = Fold<
     IntersectionFoldOp<
         F, 
@@ -60,8 +94,14 @@ pub type IntersectionFold<Init, Iter, F> = Fold<
 
 /// Intersection between N [SparseHierarchy]ies in fold-style.
 /// 
+/// Sources items are folded with `f` into `init`'s item one by one, as
+/// they are resolved - both on [get()] and iteration. Resulting item is a single
+/// value - no per-source items are collected in between.
+/// 
 /// `Init`'s type may differ, but all [SparseHierarchy]ies 
 /// must have the same configuration.
+/// 
+/// [get()]: SparseHierarchy::get
 #[inline]
 pub fn intersection_fold<Init, Iter, F>(init: Init, iter: Iter, f: F)
     -> IntersectionFold<Init, Iter, F>
@@ -77,6 +117,24 @@ where
     fold(IntersectionFoldOp { f, phantom_data: PhantomData }, init, iter)
 }
 
+/// [intersection_fold] over slice of [SparseHierarchy]ies or references to them.
+///
+/// Accepts both `&[T]` and `&[&T]` (e.g. `&Vec<&T>`).
+#[inline]
+pub fn intersection_fold_slice<'a, Init, T, F>(init: Init, slice: &'a [T], f: F)
+    -> IntersectionFold<Init, SliceIter<'a, T>, F>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy>,
+    T: Borrowable<Borrowed: SparseHierarchy>,
+
+    F: Fn(
+        <Init::Borrowed as SparseHierarchy>::DataType, 
+        &<T::Borrowed as SparseHierarchy>::DataType
+    ) -> <Init::Borrowed as SparseHierarchy>::DataType
+{
+    intersection_fold(init, slice_iter(slice), f)
+}
+
 #[cfg(test)]
 mod test{
     use crate::level::{IntrusiveListLevel, SingleBlockLevel};
@@ -135,4 +193,162 @@ mod test{
         );
         assert_eq!(res.get(12).0, 1300);
     }
-}
\ No newline at end of file
+    
+    /// More sources than inline FoldState capacity.
+    #[test]
+    fn test_64_sources(){
+        use crate::{config, union_fold};
+        use crate::test_utils::Data;
+        
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let init = {
+            let mut a = Array::default();
+            a.insert(10, Data(1));
+            a.insert(5000, Data(1));
+            a
+        };
+        let arrays: Vec<_> = (0..64).map(|i| {
+            let mut a = Array::default();
+            a.insert(10, Data(1));
+            a.insert(100 + i, Data(1));
+            a
+        }).collect();
+        
+        let and = intersection_fold(&init, arrays.iter(), |acc, d| Data(acc.0 + d.0));
+        assert_eq!(and.get(10), Data(65));
+        assert_eq!(
+            and.iter().filter(|(_, d)| !d.is_empty()).collect::<Vec<_>>(), 
+            vec![(10, Data(65))]
+        );
+        
+        let or = union_fold(&init, arrays.iter(), |acc, d| Data(acc.0 + d.0));
+        assert_eq!(or.iter().count(), 2 + 64);
+        assert_eq!(or.get(10), Data(65));
+        for (i, d) in or.iter() {
+            assert_eq!(d, or.get(i));
+        }
+    }
+    
+    #[test]
+    fn test_slice(){
+        use crate::{config, union_fold_slice};
+        use crate::test_utils::Data;
+        
+        type Array = SparseArray<config::width_64::depth_2, Data>;
+        let arrays: Vec<_> = (0..3).map(|i| {
+            let mut a = Array::default();
+            a.insert(10, Data(1));
+            a.insert(100 + i, Data(1));
+            a
+        }).collect();
+        let init = Array::from(&arrays[0]);
+        
+        // &[T]
+        let and = intersection_fold_slice(&init, &arrays, |acc, d| Data(acc.0 + d.0));
+        assert_eq!(and.get(10), Data(4));
+        
+        // &[&T]
+        let refs: Vec<&Array> = arrays.iter().collect();
+        let and = intersection_fold_slice(&init, &refs, |acc, d| Data(acc.0 + d.0));
+        assert_eq!(
+            and.iter().filter(|(_, d)| !d.is_empty()).collect::<Vec<_>>(), 
+            vec![(10, Data(4))]
+        );
+        
+        let or = union_fold_slice(&init, &refs[1..], |acc, d| Data(acc.0 + d.0));
+        assert_eq!(
+            or.iter().filter(|(_, d)| !d.is_empty()).collect::<Vec<_>>(), 
+            vec![(10, Data(3)), (100, Data(1)), (101, Data(1)), (102, Data(1))]
+        );
+    }
+    
+    /// Consumers over intersection of [EXACT_HIERARCHY] sources.
+    /// 
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    #[test]
+    fn test_exact_sources(){
+        use itertools::assert_equal;
+        use rand::{Rng, SeedableRng};
+        use crate::{config, is_disjoint, union};
+        use crate::test_utils::Data;
+        
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe8ac_7000);
+        let arrays: Vec<Array> = (0..6).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..3000 {
+                let v = rng.gen_range(0..20_000);
+                a.insert(v, Data(1));
+            }
+            a
+        }).collect();
+        let add = |d1: &Data, d2: &Data| Data(d1.0 + d2.0);
+        let u0 = union(&arrays[0], &arrays[1], add);
+        let u1 = union(&arrays[2], &arrays[3], add);
+        let u2 = union(&arrays[4], &arrays[5], add);
+        let sources = [&u1, &u2];
+        
+        let and = intersection_fold(&u0, sources.iter().copied(), |acc, d| 
+            if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+        );
+        let expected: Vec<usize> = (0..20_000)
+            .filter(|&i| u0.contains(i) && u1.contains(i) && u2.contains(i))
+            .collect();
+        
+        assert_eq!(and.count_keys(), expected.len());
+        assert_equal(
+            and.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+            expected.iter().copied()
+        );
+        
+        let mut none = Array::default();
+        for i in 0..20_000 {
+            if !expected.contains(&i) {
+                none.insert(i, Data(1));
+            }
+        }
+        assert!(is_disjoint(&and, &none));
+    }
+    
+    /// Owned lazy sources, constructed inside iterator.
+    #[test]
+    fn test_owned_sources(){
+        use rand::{Rng, SeedableRng};
+        use crate::{config, map_indexed, union_fold};
+        use crate::test_utils::Data;
+        
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x0e7e_d500);
+        let layers: Vec<Array> = (0..4).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..3000 {
+                let v = rng.gen_range(0..10_000);
+                a.insert(v, Data(v + 1));
+            }
+            a
+        }).collect();
+        let double = |_: usize, d: &Data| Data(d.0 * 2);
+        
+        let and = intersection_fold(
+            &layers[0],
+            layers[1..].iter().map(|l| map_indexed(l, double)),
+            |acc, d| if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+        );
+        let or = union_fold(
+            &layers[0],
+            layers[1..].iter().map(|l| map_indexed(l, double)),
+            |acc, d| Data(acc.0 + d.0)
+        );
+        for i in 0..10_000 {
+            let in_all = layers.iter().all(|l| l.contains(i));
+            let expected = if in_all { Data((i + 1) * 7) } else { Data(0) };
+            assert_eq!(and.get(i), expected);
+            
+            let expected = layers[0].get(i).0 
+                + layers[1..].iter().map(|l| l.get(i).0 * 2).sum::<usize>();
+            assert_eq!(or.get(i), Data(expected));
+        }
+        assert!(and.iter().all(|(i, d)| d == and.get(i)));
+        assert!(or.iter().all(|(i, d)| d == or.get(i)));
+    }
+}
@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+use crate::{BitBlock, Empty, intersection_fold, IntersectionFold, Primitive, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::DefaultHierarchyState;
+use crate::utils::Borrowable;
+
+/// [SparseHierarchy] that contains each index, with `f()` as item.
+///
+/// Constructed by [intersection_fold_with()], as fold accumulator source.
+pub struct FullHierarchy<F, Acc, LevelCount, Mask>{
+    f: F,
+    phantom_data: PhantomData<(Acc, LevelCount, Mask)>
+}
+
+impl<F, Acc, LevelCount, Mask> SparseHierarchy for FullHierarchy<F, Acc, LevelCount, Mask>
+where
+    F: Fn() -> Acc,
+    Acc: Empty,
+    LevelCount: ConstInteger,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = LevelCount;
+
+    type LevelMaskType = Mask;
+    type LevelMask<'a> = Mask where Self: 'a;
+
+    #[inline]
+    unsafe fn level_mask<I>(&self, _: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let mut mask = Mask::zero();
+        mask.as_array_mut().as_mut().fill(Primitive::MAX);
+        mask
+    }
+
+    type DataType = Acc;
+    type Data<'a> = Acc where Self: 'a;
+
+    #[inline]
+    unsafe fn data_block<I>(&self, _: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        (self.f)()
+    }
+
+    type State = DefaultHierarchyState<Self>;
+}
+
+impl<F, Acc, LevelCount, Mask> Borrowable for FullHierarchy<F, Acc, LevelCount, Mask>{
+    type Borrowed = Self;
+}
+
+type ItemHierarchy<Iter> = <<Iter as Iterator>::Item as Borrowable>::Borrowed;
+
+// `Acc` should be deducible from `Init`, but RUST still
+// not dealt with Fn's.
+pub type IntersectionFoldWith<Iter, Init, Acc, F> = IntersectionFold<
+    FullHierarchy<
+        Init,
+        Acc,
+        <ItemHierarchy<Iter> as SparseHierarchy>::LevelCount,
+        <ItemHierarchy<Iter> as SparseHierarchy>::LevelMaskType,
+    >,
+    Iter,
+    F
+>;
+
+/// Intersection between N [SparseHierarchy]ies in fold-style, with
+/// accumulator initialized by `init()` for each index.
+///
+/// Unlike [intersection_fold()], does not need an accumulator [SparseHierarchy] -
+/// result's items are of `Acc` type, which may differ from the sources' one.
+/// With empty `iter` - every index is present.
+///
+/// # Example
+///
+/// Sum matching values:
+/// ```
+/// # use hi_sparse_array::{config, intersection_fold_with, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut a1 = Array::default();
+/// let mut a2 = Array::default();
+/// let mut a3 = Array::default();
+/// a1.insert(10, Some(1));
+/// a1.insert(20, Some(1));
+/// a2.insert(20, Some(2));
+/// a3.insert(10, Some(3));
+/// a3.insert(20, Some(3));
+///
+/// let arrays = [a1, a2, a3];
+/// let sum = intersection_fold_with(arrays.iter(), || Some(0u64), |acc, v|
+///     acc.zip(*v).map(|(acc, v)| acc + v as u64)
+/// );
+/// assert_eq!(sum.get(20), Some(6));
+/// assert_eq!(sum.get(10), None);
+/// ```
+#[inline]
+pub fn intersection_fold_with<Iter, Init, Acc, F>(iter: Iter, init: Init, f: F)
+    -> IntersectionFoldWith<Iter, Init, Acc, F>
+where
+    Iter: Iterator<Item: Borrowable<Borrowed: SparseHierarchy>>,
+    Init: Fn() -> Acc,
+    Acc: Empty,
+    F: Fn(Acc, &<ItemHierarchy<Iter> as SparseHierarchy>::DataType) -> Acc,
+{
+    let init = FullHierarchy{ f: init, phantom_data: PhantomData };
+    intersection_fold(init, iter, f)
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_intersection_fold_with(){
+        type Array = SparseArray<config::width_64::depth_2, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed_f01d);
+        let arrays: Vec<Array> = (0..3).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..200 {
+                let i = rng.gen_range(0..500);
+                a.insert(i, Data(i + 1));
+            }
+            a
+        }).collect();
+
+        let sum = intersection_fold_with(
+            arrays.iter(),
+            || Some(0),
+            |acc: Option<usize>, d| acc.filter(|_| !d.is_empty()).map(|acc| acc + d.0)
+        );
+
+        let expected = arrays[0].iter()
+            .filter(|(i, _)| arrays[1..].iter().all(|a| a.contains(*i)))
+            .map(|(i, _)| (i, Some(arrays.iter().map(|a| a.get(i).0).sum())));
+        assert_equal(
+            sum.iter().filter(|(_, d)| d.is_some()),
+            expected
+        );
+
+        assert_eq!(sum.get(Array::max_range()), None);
+    }
+}
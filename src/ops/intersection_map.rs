@@ -0,0 +1,256 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::{Borrowable, Take};
+
+type Source<H> = <H as Borrowable>::Borrowed;
+
+/// Lazy intersection between two [SparseHierarchy]ies, that maps
+/// intersected items with closure.
+///
+/// Constructed by [intersection_map()].
+pub struct IntersectionMap<H1, H2, F, Out>{
+    h1: H1,
+    h2: H2,
+    f: F,
+    phantom_data: PhantomData<Out>
+}
+
+impl<H1, H2, F, Out> IntersectionMap<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(&<Source<H1> as SparseHierarchy>::DataType, &<Source<H2> as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    /// Right item is fetched only if left one is not empty.
+    /// `f` is called only if both are not empty.
+    #[inline]
+    fn resolve<D1, D2>(&self, d1: D1, d2: impl FnOnce() -> D2) -> Out
+    where
+        D1: Borrow<<Source<H1> as SparseHierarchy>::DataType>,
+        D2: Borrow<<Source<H2> as SparseHierarchy>::DataType>,
+    {
+        let d1 = d1.borrow();
+        if d1.is_empty() {
+            return Out::empty();
+        }
+        let d2 = d2();
+        let d2 = d2.borrow();
+        if d2.is_empty() {
+            return Out::empty();
+        }
+        (self.f)(d1, d2)
+    }
+}
+
+impl<H1, H2, F, Out> SparseHierarchy for IntersectionMap<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Source<H1> as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Source<H1> as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(&<Source<H1> as SparseHierarchy>::DataType, &<Source<H2> as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    // Intersection of children may end up empty.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <Source<H1> as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <Source<H1> as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let mut mask = self.h1.borrow().level_mask(level_indices).take_or_clone();
+        mask &= self.h2.borrow().level_mask(level_indices).borrow();
+        mask
+    }
+
+    type DataType = Out;
+    type Data<'a> = Out where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        self.resolve(
+            self.h1.borrow().data_block(level_indices),
+            || self.h2.borrow().data_block(level_indices)
+        )
+    }
+
+    type State = IntersectionMapState<H1, H2, F, Out>;
+}
+
+pub struct IntersectionMapState<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy>,
+{
+    s1: <Source<H1> as SparseHierarchy>::State,
+    s2: <Source<H2> as SparseHierarchy>::State,
+    phantom_data: PhantomData<IntersectionMap<H1, H2, F, Out>>
+}
+
+impl<H1, H2, F, Out> Clone for IntersectionMapState<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            s1: self.s1.clone(),
+            s2: self.s2.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H1, H2, F, Out> SparseHierarchyState for IntersectionMapState<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Source<H1> as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Source<H1> as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(&<Source<H1> as SparseHierarchy>::DataType, &<Source<H2> as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    type This = IntersectionMap<H1, H2, F, Out>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            s1: SparseHierarchyState::new(this.h1.borrow()),
+            s2: SparseHierarchyState::new(this.h2.borrow()),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        let mut mask = self.s1.select_level_bock(this.h1.borrow(), level_n, level_index).take_or_clone();
+        mask &= self.s2.select_level_bock(this.h2.borrow(), level_n, level_index).borrow();
+        mask
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        this.resolve(
+            self.s1.data_block(this.h1.borrow(), level_index),
+            || self.s2.data_block(this.h2.borrow(), level_index)
+        )
+    }
+}
+
+impl<H1, H2, F, Out> Borrowable for IntersectionMap<H1, H2, F, Out>{
+    type Borrowed = IntersectionMap<H1, H2, F, Out>;
+}
+
+/// Intersection between two [SparseHierarchy]ies, with intersected items
+/// mapped by `f`.
+///
+/// Unlike [intersection()], item access short-circuits: `h1` item is
+/// fetched first, and if it is empty - `h2` is not touched at all.
+/// `f` is called only for pairs of non-empty items, once per item access -
+/// so it does not need to handle empty ones.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// # Example
+///
+/// Join two component arrays into computed value:
+/// ```
+/// # use hi_sparse_array::{config, intersection_map, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<f32>>;
+/// let mut positions = Array::default();
+/// let mut speeds    = Array::default();
+/// positions.insert(10, Some(1.0));
+/// positions.insert(20, Some(2.0));
+/// speeds.insert(20, Some(0.5));
+/// speeds.insert(30, Some(1.5));
+///
+/// let next_positions = intersection_map(&positions, &speeds, |p, s| Some(p.unwrap() + s.unwrap()));
+/// assert_eq!(next_positions.get(20), Some(2.5));
+/// assert_eq!(next_positions.get(10), None);
+/// assert!(next_positions.iter().eq([(20, Some(2.5))]));
+/// ```
+///
+/// [intersection()]: crate::intersection
+#[inline]
+pub fn intersection_map<H1, H2, F, Out>(h1: H1, h2: H2, f: F) -> IntersectionMap<H1, H2, F, Out>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Source<H1> as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Source<H1> as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(&<Source<H1> as SparseHierarchy>::DataType, &<Source<H2> as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    IntersectionMap{h1, h2, f, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection, map_indexed, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_intersection_map(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x1a7e_4a90);
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for _ in 0..3000 {
+            let v = rng.gen_range(0..5000);
+            a1.insert(v, Data(v + 1));
+            let v = rng.gen_range(0..5000);
+            a2.insert(v, Data(v + 1));
+        }
+
+        let reference = intersection(&a1, &a2, |d1, d2|
+            if d1.is_empty() || d2.is_empty() { Data(0) } else { Data(d1.0 * d2.0) }
+        );
+        let m = intersection_map(&a1, &a2, |d1, d2| Data(d1.0 * d2.0));
+        for i in 0..5000 {
+            assert_eq!(m.get(i), reference.get(i));
+        }
+        let non_empty = |(_, d): &(usize, Data)| !d.is_empty();
+        assert_equal(m.iter().filter(non_empty), reference.iter().filter(non_empty));
+
+        // Right side and `f` are not touched for empty left items.
+        let right_calls = Cell::new(0);
+        let f_calls = Cell::new(0);
+        let right = map_indexed(&a2, |_, d: &Data| { right_calls.set(right_calls.get() + 1); d.clone() });
+        let m = intersection_map(&a1, &right, |d1, d2| { f_calls.set(f_calls.get() + 1); Data(d1.0 * d2.0) });
+        let absent = (0..5000).find(|&i| a1.get(i).is_empty() && !a2.get(i).is_empty()).unwrap();
+        assert_eq!(m.get(absent), Data(0));
+        assert_eq!((right_calls.get(), f_calls.get()), (0, 0));
+
+        let left_only = (0..5000).find(|&i| !a1.get(i).is_empty() && a2.get(i).is_empty()).unwrap();
+        assert_eq!(m.get(left_only), Data(0));
+        assert_eq!((right_calls.get(), f_calls.get()), (1, 0));
+
+        let both = reference.iter().find(non_empty).unwrap();
+        assert_eq!(m.get(both.0), both.1);
+        assert_eq!((right_calls.get(), f_calls.get()), (2, 1));
+    }
+}
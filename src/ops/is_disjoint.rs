@@ -0,0 +1,144 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use crate::{apply, BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::ConstFalse;
+use crate::iter::BlockIter;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+/// Intersection, that only checks items existence.
+struct IsDisjointOp<L, R, M>{
+    phantom_data: PhantomData<(L, R, M)>
+}
+impl<Left, Right, Mask> BinaryOp for IsDisjointOp<Left, Right, Mask>
+where
+    Left: Empty,
+    Right: Empty,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take_or_clone() & right.take_or_clone()
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Option<()>;
+
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Self::Left>,
+        right: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        (!left.borrow().is_empty() && !right.borrow().is_empty()).then_some(())
+    }
+}
+
+/// Returns `true` if [SparseHierarchy]ies have no common non-empty items.
+///
+/// Traverse intersection hierarchy, skipping subtrees with zero masks, 
+/// and stops at first common item. Never constructs data items.
+/// If both are [EXACT_HIERARCHY] - does not touch data at all.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub fn is_disjoint<H1, H2>(h1: H1, h2: H2) -> bool
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+{
+    let exact = <H1::Borrowed as SparseHierarchy>::EXACT_HIERARCHY
+             && <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    
+    let and = apply(IsDisjointOp{ phantom_data: PhantomData }, h1, h2);
+    let mut blocks = BlockIter::new(&and);
+    while let Some(mask) = blocks.next() {
+        let mask = mask.borrow();
+        if exact {
+            if !mask.is_zero() {
+                return false;
+            }
+        } else {
+            let ctrl = mask.traverse_bits(|level_index| {
+                let data = unsafe{ blocks.data_block(level_index) };
+                if data.is_some() {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            if ctrl.is_break() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, intersection, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_is_disjoint(){
+        // Upper level masks intersect, terminal ones - don't.
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for i in 0..1000 {
+            a1.insert(i*2,   Data(1));
+            a2.insert(i*2+1, Data(1));
+        }
+        assert!(is_disjoint(&a1, &a2));
+        {
+            let e1 = unsafe{ ExactHierarchy::new_unchecked(&a1) };
+            let e2 = unsafe{ ExactHierarchy::new_unchecked(&a2) };
+            assert!(is_disjoint(&e1, &e2));
+        }
+
+        // Common key.
+        a2.insert(1500, Data(1));
+        assert!(!is_disjoint(&a1, &a2));
+
+        // Non-exact: terminal bits intersect, but item is empty.
+        a1.insert(1501, Data(0));
+        a2.remove(1500);
+        assert!(is_disjoint(&a1, &a2));
+        assert!(is_disjoint(&a2, &a1));
+    }
+
+    #[test]
+    fn test_is_disjoint_random(){
+        const RANGE: usize = 20_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        for _ in 0..100 {
+            let mut a1 = Array::default();
+            let mut a2 = Array::default();
+            for _ in 0..rng.gen_range(0..50) {
+                a1.insert(rng.gen_range(0..RANGE), Data(1));
+                a2.insert(rng.gen_range(0..RANGE), Data(1));
+            }
+            let expected = intersection(&a1, &a2, |l, r| Data(l.0 * r.0))
+                .iter().all(|(_, d)| d.is_empty());
+            assert_eq!(is_disjoint(&a1, &a2), expected);
+        }
+    }
+}
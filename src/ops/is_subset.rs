@@ -0,0 +1,190 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use crate::{apply, BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::ConstFalse;
+use crate::iter::BlockIter;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+/// Difference, that only checks items existence.
+struct IsSubsetOp<L, R, M>{
+    /// Big hierarchy is [EXACT_HIERARCHY].
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    right_exact: bool,
+    phantom_data: PhantomData<(L, R, M)>
+}
+impl<Left, Right, Mask> BinaryOp for IsSubsetOp<Left, Right, Mask>
+where
+    Left: Empty,
+    Right: Empty,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        _    : impl Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take_or_clone()
+    }
+
+    #[inline]
+    fn terminal_lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut mask = left.take_or_clone();
+        if self.right_exact {
+            // m0 & !m1
+            let right = right.borrow().as_array().as_ref();
+            for (l, r) in mask.as_array_mut().as_mut().iter_mut().zip(right) {
+                *l &= !*r;
+            }
+        }
+        mask
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Option<()>;
+
+    /// `Some` - if item missing in big hierarchy.
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Self::Left>,
+        right: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        (!left.borrow().is_empty() && right.borrow().is_empty()).then_some(())
+    }
+}
+
+/// Returns `true` if all non-empty items of `small` are present in `big`.
+///
+/// Traverse `small`'s hierarchy, and stops at first item missing in `big`.
+/// Never constructs data items. If both are [EXACT_HIERARCHY] - 
+/// does not touch data at all.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub fn is_subset<H1, H2>(small: H1, big: H2) -> bool
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+{
+    let big_exact = <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    let exact = <H1::Borrowed as SparseHierarchy>::EXACT_HIERARCHY && big_exact;
+    
+    let diff = apply(IsSubsetOp{ right_exact: big_exact, phantom_data: PhantomData }, small, big);
+    let mut blocks = BlockIter::new(&diff);
+    while let Some(mask) = blocks.next() {
+        let mask = mask.borrow();
+        if exact {
+            if !mask.is_zero() {
+                return false;
+            }
+        } else {
+            let ctrl = mask.traverse_bits(|level_index| {
+                let data = unsafe{ blocks.data_block(level_index) };
+                if data.is_some() {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            });
+            if ctrl.is_break() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Returns `true` if all non-empty items of `small` are present in `big`.
+/// 
+/// Same as [is_subset()] with swapped arguments.
+#[inline]
+pub fn is_superset<H1, H2>(big: H1, small: H2) -> bool
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+{
+    is_subset(small, big)
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_is_subset(){
+        let mut small = Array::default();
+        let mut big = Array::default();
+        for i in 0..1000 {
+            big.insert(i*3, Data(1));
+            if i % 2 == 0 {
+                small.insert(i*3, Data(1));
+            }
+        }
+        assert!(is_subset(&small, &big));
+        assert!(is_superset(&big, &small));
+        assert!(!is_subset(&big, &small));
+        assert!(is_subset(&Array::default(), &small));
+        {
+            let s = unsafe{ ExactHierarchy::new_unchecked(&small) };
+            let b = unsafe{ ExactHierarchy::new_unchecked(&big) };
+            assert!(is_subset(&s, &b));
+            assert!(!is_subset(&b, &s));
+        }
+
+        // Same terminal block, missing key.
+        small.insert(1, Data(1));
+        assert!(!is_subset(&small, &big));
+
+        // Non-exact: empty item in small does not count.
+        small.insert(1, Data(0));
+        assert!(is_subset(&small, &big));
+
+        // Non-exact: empty item in big does not count.
+        big.insert(1, Data(0));
+        small.insert(1, Data(1));
+        assert!(!is_subset(&small, &big));
+    }
+
+    #[test]
+    fn test_is_subset_random(){
+        const RANGE: usize = 2_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        for _ in 0..100 {
+            let mut small = Array::default();
+            let mut big = Array::default();
+            for _ in 0..rng.gen_range(0..1000) {
+                big.insert(rng.gen_range(0..RANGE), Data(1));
+            }
+            for _ in 0..rng.gen_range(0..10) {
+                small.insert(rng.gen_range(0..RANGE), Data(1));
+            }
+            let expected = small.iter().all(|(i, _)| big.contains(i));
+            assert_eq!(is_subset(&small, &big), expected);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use crate::{apply, BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::ConstFalse;
+use crate::iter::BlockIter;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+/// Dual walk, that only checks keys equality.
+struct KeysEqOp<L, R, M>{
+    /// Both hierarchies are [EXACT_HIERARCHY].
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    exact: bool,
+    /// In-use only when `exact`. Raised at first mismatched masks.
+    differ: Cell<bool>,
+    phantom_data: PhantomData<(L, R, M)>
+}
+impl<Left, Right, Mask> BinaryOp for KeysEqOp<Left, Right, Mask>
+where
+    Left: Empty,
+    Right: Empty,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstFalse;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Borrow<Self::LevelMask> + Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask> + Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        if self.exact {
+            // Exact masks are equal for equal key sets -
+            // walk left, and check right along the way.
+            if left.borrow() != right.borrow() {
+                self.differ.set(true);
+            }
+            left.take_or_clone()
+        } else {
+            left.take_or_clone() | right.take_or_clone()
+        }
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Option<()>;
+
+    /// `Some` - if item present only in one hierarchy.
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Self::Left>,
+        right: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        (left.borrow().is_empty() != right.borrow().is_empty()).then_some(())
+    }
+}
+
+/// Returns `true` if [SparseHierarchy]ies have the same non-empty items keys.
+///
+/// If both are [EXACT_HIERARCHY] - compares masks level by level, and stops
+/// at the first mismatch, never touching data. Otherwise - traverse 
+/// union hierarchy, and checks items emptiness.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub fn keys_eq<H1, H2>(h1: H1, h2: H2) -> bool
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+{
+    let exact = <H1::Borrowed as SparseHierarchy>::EXACT_HIERARCHY
+             && <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    
+    let op = KeysEqOp{ exact, differ: Cell::new(false), phantom_data: PhantomData };
+    let walk = apply(op, h1, h2);
+    let mut blocks = BlockIter::new(&walk);
+    if exact {
+        // Root mask is checked at BlockIter construction.
+        while !walk.op.differ.get() {
+            if blocks.next().is_none() {
+                return true;
+            }
+        }
+        return false;
+    }
+    
+    while let Some(mask) = blocks.next() {
+        let ctrl = mask.borrow().traverse_bits(|level_index| {
+            let data = unsafe{ blocks.data_block(level_index) };
+            if data.is_some() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        if ctrl.is_break() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    fn exact(a: &Array) -> ExactHierarchy<&Array> {
+        unsafe{ ExactHierarchy::new_unchecked(a) }
+    }
+
+    #[test]
+    fn test_keys_eq(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x6b65_7973);
+        for _ in 0..100 {
+            let mut a1 = Array::default();
+            let mut a2 = Array::default();
+            for _ in 0..rng.gen_range(0..1000) {
+                let v = rng.gen_range(0..Array::max_range());
+                a1.insert(v, Data(1));
+                a2.insert(v, Data(2));
+            }
+            assert!(keys_eq(&a1, &a2));
+            assert!(keys_eq(exact(&a1), exact(&a2)));
+            
+            // Differ at the end / in the middle.
+            let v = rng.gen_range(0..=Array::max_range());
+            let expected = a1.contains(v);
+            a2.insert(v, Data(1));
+            assert_eq!(keys_eq(&a1, &a2), expected);
+            assert_eq!(keys_eq(exact(&a1), exact(&a2)), expected);
+            assert_eq!(keys_eq(exact(&a2), exact(&a1)), expected);
+            assert!(a1 != a2);
+        }
+        
+        // Non-exact: empty items does not count.
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        a1.insert(10, Data(1));
+        a2.insert(10, Data(1));
+        a2.insert(Array::max_range(), Data(0));
+        assert!(keys_eq(&a1, &a2));
+        assert!(!keys_eq(exact(&a1), exact(&a2)));
+        assert!(a1 == a2);
+        
+        a2.insert(10, Data(2));
+        assert!(keys_eq(&a1, &a2));
+        assert!(a1 != a2);
+        
+        assert!(keys_eq(&Array::default(), &Array::default()));
+        assert!(Array::default() == Array::default());
+    }
+}
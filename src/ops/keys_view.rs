@@ -0,0 +1,183 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, data_block_index, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::Borrowable;
+
+/// Lazy [SparseHierarchy] adaptor, that exposes only keys of the source.
+///
+/// Item is `Some(())` for each non-empty source item.
+/// With [EXACT_HIERARCHY] source - data is never touched.
+///
+/// Constructed by [keys_view()].
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub struct KeysView<H>{
+    h: H,
+}
+
+impl<H> SparseHierarchy for KeysView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    const EXACT_HIERARCHY: bool = <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.h.borrow().level_mask(level_indices)
+    }
+
+    type DataType = Option<()>;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let h = self.h.borrow();
+        let contains = if Self::EXACT_HIERARCHY {
+            let (upper_indices, data_index) = level_indices.split_last();
+            let index = data_block_index::<Self>(&upper_indices, data_index);
+            h.may_contain_unchecked(index)
+        } else {
+            !h.data_block(level_indices).borrow().is_empty()
+        };
+        contains.then_some(())
+    }
+
+    type State = KeysViewState<H>;
+}
+
+pub struct KeysViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// In-use only with [EXACT_HIERARCHY] source.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    terminal_mask: <H::Borrowed as SparseHierarchy>::LevelMaskType,
+
+    phantom_data: PhantomData<KeysView<H>>
+}
+
+impl<H> Clone for KeysViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            terminal_mask: self.terminal_mask.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H> SparseHierarchyState for KeysViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    type This = KeysView<H>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            terminal_mask: BitBlock::zero(),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        let mask = self.state.select_level_bock(this.h.borrow(), level_n, level_index);
+        if <Self::This as SparseHierarchy>::EXACT_HIERARCHY
+        && N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1
+        {
+            self.terminal_mask = mask.borrow().clone();
+        }
+        mask
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let contains = if <Self::This as SparseHierarchy>::EXACT_HIERARCHY {
+            self.terminal_mask.get_bit(level_index)
+        } else {
+            !self.state.data_block(this.h.borrow(), level_index).borrow().is_empty()
+        };
+        contains.then_some(())
+    }
+}
+
+impl<H> Borrowable for KeysView<H>{
+    type Borrowed = KeysView<H>;
+}
+
+/// Keys of [SparseHierarchy], with `Some(())` as item.
+///
+/// Hierarchy is passed as-is. Useful for composing key sets
+/// of heavy-payload hierarchies.
+#[inline]
+pub fn keys_view<H>(h: H) -> KeysView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    KeysView{h}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::SeedableRng;
+    use crate::{intersection_fold, is_subset, union};
+    use crate::test_utils::{Data, random_array};
+    use super::*;
+
+    #[test]
+    fn test_keys_view(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x6b65_7973);
+        let a1 = random_array(&mut rng);
+        let a2 = random_array(&mut rng);
+
+        // Non-exact source.
+        let k1 = keys_view(&a1);
+        assert_equal(k1.iter(), a1.iter().map(|(i, _)| (i, Some(()))));
+        for i in 0..10_000 {
+            assert_eq!(k1.get(i).is_some(), a1.contains(i));
+        }
+
+        // Exact source.
+        let u = union(&a1, &a2, |d1, d2| Data(d1.0.max(d2.0)));
+        let ku = keys_view(&u);
+        assert_equal(ku.iter(), u.iter().map(|(i, _)| (i, Some(()))));
+        for i in 0..10_000 {
+            assert_eq!(ku.get(i).is_some(), u.contains(i));
+        }
+        assert!(is_subset(&k1, &ku));
+        assert_eq!(ku.count_keys(), u.count_keys());
+
+        // Composition.
+        let k2 = keys_view(&a2);
+        let and = intersection_fold(&k1, [&k2].into_iter(), |acc, d| acc.and(*d));
+        assert_equal(
+            and.iter().filter(|(_, d)| d.is_some()).map(|(i, _)| i),
+            a1.iter().map(|(i, _)| i).filter(|&i| a2.contains(i))
+        );
+    }
+}
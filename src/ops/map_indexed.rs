@@ -0,0 +1,169 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{data_block_index, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::Borrowable;
+
+/// Lazy [SparseHierarchy] adaptor, that maps items with their indices.
+///
+/// Constructed by [map_indexed()].
+pub struct MapIndexed<H, F, Out>{
+    h: H,
+    f: F,
+    phantom_data: PhantomData<Out>,
+}
+
+impl<H, F, Out> SparseHierarchy for MapIndexed<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize, &<H::Borrowed as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    const EXACT_HIERARCHY: bool = <H::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = <H::Borrowed as SparseHierarchy>::LevelMask<'a> where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        self.h.borrow().level_mask(level_indices)
+    }
+
+    type DataType = Out;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let data = self.h.borrow().data_block(level_indices);
+        let (upper_indices, data_index) = level_indices.split_last();
+        let index = data_block_index::<Self>(&upper_indices, data_index);
+        (self.f)(index, data.borrow())
+    }
+
+    type State = MapIndexedState<H, F, Out>;
+}
+
+pub struct MapIndexedState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// [usize; LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <<H::Borrowed as SparseHierarchy>::LevelCount as ConstInteger>::Dec
+    >,
+
+    phantom_data: PhantomData<MapIndexed<H, F, Out>>
+}
+
+impl<H, F, Out> Clone for MapIndexedState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H, F, Out> SparseHierarchyState for MapIndexedState<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize, &<H::Borrowed as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    type This = MapIndexed<H, F, Out>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            level_indices: Array::from_fn(|_| 0),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        if /*const*/ N::VALUE != 0 {
+            *self.level_indices.as_mut().get_unchecked_mut(N::VALUE - 1) = level_index;
+        }
+        self.state.select_level_bock(this.h.borrow(), level_n, level_index)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let data = self.state.data_block(this.h.borrow(), level_index);
+        let index = data_block_index::<Self::This>(&self.level_indices, level_index);
+        (this.f)(index, data.borrow())
+    }
+}
+
+impl<H, F, Out> Borrowable for MapIndexed<H, F, Out>{
+    type Borrowed = MapIndexed<H, F, Out>;
+}
+
+/// Map [SparseHierarchy] items with `f`, which receives item index as well.
+///
+/// Hierarchy is passed as-is.
+#[inline]
+pub fn map_indexed<H, F, Out>(h: H, f: F) -> MapIndexed<H, F, Out>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+    F: Fn(usize, &<H::Borrowed as SparseHierarchy>::DataType) -> Out,
+    Out: Empty,
+{
+    MapIndexed{h, f, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_map_indexed(){
+        fn do_test<Levels: crate::SparseArrayLevels>(){
+            let range = SparseArray::<Levels, Data>::max_range();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+            let mut a = SparseArray::<Levels, Data>::default();
+            for _ in 0..1000 {
+                let v = rng.gen_range(0..=range);
+                a.insert(v, Data(1));
+            }
+
+            let m = map_indexed(&a, |i, d: &Data| Some((i, d.0)));
+            assert_equal(
+                m.iter(),
+                a.iter().map(|(i, d)| (i, Some((i, d.0))))
+            );
+            for (i, _) in a.iter() {
+                assert_eq!(m.get(i), Some((i, 1)));
+                assert_eq!(unsafe{ m.get_unchecked(i) }, Some((i, 1)));
+            }
+        }
+        do_test::<config::width_64::depth_3>();
+        do_test::<config::width_64::depth_4>();
+    }
+}
@@ -0,0 +1,308 @@
+use std::borrow::Borrow;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::slice;
+use smallvec::SmallVec;
+use crate::{BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstCopyArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::{Borrowable, Take};
+
+type Source<T> = <T as Borrowable>::Borrowed;
+
+/// Lazy intersection between N [SparseHierarchy]ies, that maps
+/// per-source items into a single value.
+///
+/// Constructed by [map_multi()].
+pub struct MapMulti<'a, T, F, Out>{
+    sources: &'a [T],
+    f: F,
+    phantom_data: PhantomData<Out>,
+}
+
+/// Per-source items of one index, passed to [map_multi()]'s closure.
+///
+/// Yields items of all sources in slice order. Each item is resolved
+/// on [next()] - nothing is collected beforehand.
+///
+/// [next()]: Iterator::next
+pub struct MultiItems<'s, 'a, T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy>
+{
+    sources: slice::Iter<'a, T>,
+    at: MultiItemsAt<'s, T>,
+}
+
+enum MultiItemsAt<'s, T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy>
+{
+    /// Point access - [SparseHierarchy::data_block].
+    Point(ConstCopyArrayType<usize, <Source<T> as SparseHierarchy>::LevelCount>),
+
+    /// Iteration - [SparseHierarchyState::data_block].
+    State{
+        states: slice::Iter<'s, <Source<T> as SparseHierarchy>::State>,
+        level_index: usize
+    },
+}
+
+impl<'s, 'a, T> Iterator for MultiItems<'s, 'a, T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>
+{
+    type Item = <Source<T> as SparseHierarchy>::Data<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let source = self.sources.next()?.borrow();
+        Some(unsafe{
+            match &mut self.at {
+                MultiItemsAt::Point(level_indices) =>
+                    source.data_block(*level_indices),
+                MultiItemsAt::State{states, level_index} =>
+                    states.next().unwrap_unchecked().data_block(source, *level_index),
+            }
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.sources.size_hint()
+    }
+}
+
+impl<'s, 'a, T> ExactSizeIterator for MultiItems<'s, 'a, T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>
+{}
+
+impl<'s, 'a, T> FusedIterator for MultiItems<'s, 'a, T>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>
+{}
+
+impl<'a, T, F, Out> SparseHierarchy for MapMulti<'a, T, F, Out>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>,
+    F: Fn(MultiItems<'_, 'a, T>) -> Out,
+    Out: Empty,
+{
+    // Even with all sources exact, upper level bits can't be cleared -
+    // intersection of children may end up empty.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <Source<T> as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <Source<T> as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'b> = Self::LevelMaskType where Self: 'b;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let mut sources = self.sources.iter();
+        let Some(first) = sources.next() else {
+            return BitBlock::zero();
+        };
+        sources.fold(
+            first.borrow().level_mask(level_indices).take_or_clone(),
+            |mut acc, source| {
+                acc &= source.borrow().level_mask(level_indices).borrow();
+                acc
+            }
+        )
+    }
+
+    type DataType = Out;
+    type Data<'b> = Self::DataType where Self: 'b;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        (self.f)(MultiItems{
+            sources: self.sources.iter(),
+            at: MultiItemsAt::Point(Array::from_fn(|i| level_indices.as_ref()[i])),
+        })
+    }
+
+    type State = MapMultiState<'a, T, F, Out>;
+}
+
+/// Inline MapMultiState storage capacity. Falls back to heap above that.
+const N: usize = 32;
+
+pub struct MapMultiState<'a, T, F, Out>
+where
+    T: Borrowable<Borrowed: SparseHierarchy>,
+{
+    states: SmallVec<[<Source<T> as SparseHierarchy>::State; N]>,
+    phantom_data: PhantomData<MapMulti<'a, T, F, Out>>
+}
+
+impl<'a, T, F, Out> Clone for MapMultiState<'a, T, F, Out>
+where
+    T: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            states: self.states.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<'a, T, F, Out> SparseHierarchyState for MapMultiState<'a, T, F, Out>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>,
+    F: Fn(MultiItems<'_, 'a, T>) -> Out,
+    Out: Empty,
+{
+    type This = MapMulti<'a, T, F, Out>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            states: this.sources.iter()
+                .map(|source| SparseHierarchyState::new(source.borrow()))
+                .collect(),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'b, L: ConstInteger>(
+        &mut self, this: &'b Self::This, level_n: L, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'b> {
+        let mut states = self.states.iter_mut().zip(this.sources);
+        let Some((state, source)) = states.next() else {
+            return BitBlock::zero();
+        };
+        states.fold(
+            state.select_level_bock(source.borrow(), level_n, level_index).take_or_clone(),
+            |mut acc, (state, source)| {
+                acc &= state.select_level_bock(source.borrow(), level_n, level_index).borrow();
+                acc
+            }
+        )
+    }
+
+    #[inline]
+    unsafe fn data_block<'b>(&self, this: &'b Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'b>
+    {
+        (this.f)(MultiItems{
+            sources: this.sources.iter(),
+            at: MultiItemsAt::State{states: self.states.iter(), level_index},
+        })
+    }
+}
+
+impl<'a, T, F, Out> Borrowable for MapMulti<'a, T, F, Out>{
+    type Borrowed = MapMulti<'a, T, F, Out>;
+}
+
+/// Intersection between N [SparseHierarchy]ies, mapped into a single value.
+///
+/// `f` receives [MultiItems] - iterator over per-source items of the same index -
+/// and returns the resulting item. Used for both [get()] and iteration.
+/// Items are resolved one by one, while `f` consumes them - so folding closure
+/// does not collect anything.
+///
+/// Works with slice of [SparseHierarchy]ies or references to them.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, map_multi, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut a = Array::default();
+/// let mut b = Array::default();
+/// let mut c = Array::default();
+/// a.insert(1, Some(1)); a.insert(2, Some(1));
+/// b.insert(1, Some(2)); b.insert(2, Some(2));
+/// c.insert(1, Some(3));
+///
+/// let sources = [&a, &b, &c];
+/// let sum = map_multi(&sources, |items| items.map(|v| *v).sum::<Option<u32>>());
+/// assert_eq!(sum.get(1), Some(6));
+/// assert_eq!(sum.get(2), None);
+/// assert!(sum.iter().eq([(1, Some(6))]));
+/// ```
+///
+/// [get()]: SparseHierarchy::get
+#[inline]
+pub fn map_multi<'a, T, F, Out>(sources: &'a [T], f: F) -> MapMulti<'a, T, F, Out>
+where
+    T: Borrowable<Borrowed: SparseHierarchy + 'a>,
+    F: Fn(MultiItems<'_, 'a, T>) -> Out,
+    Out: Empty,
+{
+    MapMulti{sources, f, phantom_data: PhantomData}
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection_fold_slice, map_indexed, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_map_multi(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x3a9_3017);
+        let arrays: Vec<_> = (0..4).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..3000 {
+                let v = rng.gen_range(0..5000);
+                a.insert(v, Data(v+1));
+            }
+            a
+        }).collect();
+
+        let reference = intersection_fold_slice(&arrays[0], &arrays[1..], |acc, d|
+            if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+        );
+        let m = map_multi(&arrays, |items| {
+            let mut acc = 0;
+            for d in items {
+                if d.is_empty() {
+                    return Data(0);
+                }
+                acc += d.0;
+            }
+            Data(acc)
+        });
+        for i in 0..5000 {
+            assert_eq!(m.get(i), reference.get(i));
+        }
+        let non_empty = |(_, d): &(usize, Data)| !d.is_empty();
+        assert!(m.iter().filter(non_empty).eq(reference.iter().filter(non_empty)));
+
+        let refs: Vec<_> = arrays.iter().collect();
+        let m = map_multi(&refs, |items| Data(items.len()));
+        assert!(m.iter().map(|(i, _)| i).eq(reference.iter().filter(non_empty).map(|(i, _)| i)));
+        assert!(m.iter().all(|(_, d)| d == Data(4)));
+
+        // Items are resolved lazily.
+        let visits = Cell::new(0);
+        let sources: Vec<_> = arrays.iter()
+            .map(|a| map_indexed(a, |_, d: &Data| { visits.set(visits.get() + 1); d.clone() }))
+            .collect();
+        let first = map_multi(&sources, |mut items| items.next().unwrap());
+        let i = reference.iter().find(non_empty).unwrap().0;
+        assert_eq!(first.get(i), arrays[0].get(i).clone());
+        assert_eq!(visits.get(), 1);
+
+        let empty: [&Array; 0] = [];
+        let m = map_multi(&empty, |items| Data(items.len() + 1));
+        assert_eq!(m.iter().count(), 0);
+    }
+}
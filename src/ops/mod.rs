@@ -1,11 +1,94 @@
+mod resolve;
+pub use resolve::*;
+
 mod intersection;
 pub use intersection::*;
 
+mod intersection_map;
+pub use intersection_map::*;
+
 mod intersection_fold;
 pub use intersection_fold::*;
 
+mod intersection_fold_with;
+pub use intersection_fold_with::*;
+
+mod intersection_fold_streaming;
+pub use intersection_fold_streaming::*;
+
+mod map_multi;
+pub use map_multi::*;
+
+mod is_disjoint;
+pub use is_disjoint::*;
+
+mod is_subset;
+pub use is_subset::*;
+
+mod keys_eq;
+pub use keys_eq::*;
+
+mod dot;
+pub use dot::*;
+
+mod diff;
+pub use diff::*;
+
 mod union;
 pub use union::*;
 
+mod union_items;
+pub use union_items::*;
+
 mod union_fold;
 pub use union_fold::*;
+
+mod union_fold_enumerate;
+pub use union_fold_enumerate::*;
+
+mod union_fold_present;
+pub use union_fold_present::*;
+
+mod fold_tuple;
+
+mod difference;
+pub use difference::*;
+
+mod symmetric_difference;
+pub use symmetric_difference::*;
+
+mod difference_fold;
+pub use difference_fold::*;
+
+mod filter;
+pub use filter::*;
+
+mod filter_map;
+pub use filter_map::*;
+
+mod map_indexed;
+pub use map_indexed::*;
+
+mod keys_view;
+pub use keys_view::*;
+
+mod from_fn;
+pub use from_fn::*;
+
+mod offset_view;
+pub use offset_view::*;
+
+mod range_view;
+pub use range_view::*;
+
+mod complement;
+pub use complement::*;
+
+mod expand_depth;
+pub use expand_depth::*;
+
+mod select;
+pub use select::*;
+
+mod cached;
+pub use cached::*;
@@ -0,0 +1,402 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use crate::{BitBlock, data_block_index, SparseHierarchy};
+use crate::const_utils::const_int::{const_for, ConstInteger, ConstIntVisitor, ConstUsize};
+use crate::const_utils::const_array::{ConstArray, ConstArrayType, ConstCopyArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::{Borrowable, Take};
+
+type Mask<H> = <<H as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType;
+
+/// Lazy [SparseHierarchy] adaptor, that shifts keys by offset.
+///
+/// Each view block covers parts of at most two source blocks of the
+/// same level - its mask is put together from shifted source masks.
+/// If offset is not a multiple of block child span, view child covers
+/// parts of two source children - and its bit is raised if any of them is.
+/// Thus, view is not [EXACT_HIERARCHY].
+///
+/// Masks are fetched by point access. Traverse state does not use source
+/// state.
+///
+/// Constructed by [offset_view()].
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub struct OffsetView<H>{
+    h: H,
+    offset: usize,
+}
+
+impl<H> OffsetView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    const WIDTH: usize = Mask::<H>::SIZE;
+    const WIDTH_EXP: usize = Self::WIDTH.ilog2() as usize;
+
+    /// Mask of source level `I::Cap` block, with level-wide number `block`.
+    ///
+    /// `indices` - any level indices array of that level.
+    #[inline]
+    unsafe fn source_mask<I>(&self, mut indices: I, block: usize) -> Mask<H>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let level = I::Cap::VALUE;
+        let w = Self::WIDTH_EXP;
+        if block.checked_shr((w * level) as u32).unwrap_or(0) != 0 {
+            return BitBlock::zero();
+        }
+        for (i, index) in indices.as_mut().iter_mut().enumerate() {
+            *index = (block >> (w * (level - 1 - i))) & (Self::WIDTH - 1);
+        }
+        self.h.borrow().level_mask(indices).take_or_clone()
+    }
+
+    /// Raises view block child bits, whose source child with level-wide
+    /// number `view child - child_offset` is raised.
+    ///
+    /// `level_indices` - view block.
+    #[inline]
+    unsafe fn or_shifted_mask<I>(&self, out: &mut Mask<H>, level_indices: I, child_offset: usize)
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let width = Self::WIDTH as i128;
+        let block = level_indices.as_ref().iter()
+            .fold(0i128, |acc, &i| (acc << Self::WIDTH_EXP) | i as i128);
+
+        // Source child of view child 0.
+        let first = block * width - child_offset as i128;
+        let first_block = first.div_euclid(width);
+        let blocks = if first.rem_euclid(width) == 0 { 1 } else { 2 };
+        for source_block in first_block..first_block + blocks {
+            if source_block < 0 {
+                continue;
+            }
+            let mask = self.source_mask(level_indices, source_block as usize);
+            let _: ControlFlow<()> = mask.traverse_bits(|i| {
+                let child = source_block * width + i as i128 - first;
+                if (0..width).contains(&child) {
+                    BitBlock::set_bit::<true>(out, child as usize);
+                }
+                ControlFlow::Continue(())
+            });
+        }
+    }
+}
+
+impl<H> SparseHierarchy for OffsetView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    // Partially covered children are raised in full.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = Mask<H>;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let levels = Self::LevelCount::VALUE;
+        let child_span_exp = Self::WIDTH_EXP * (levels - 1 - I::Cap::VALUE);
+        let child_offset = self.offset >> child_span_exp;
+        let remainder = self.offset & ((1 << child_span_exp) - 1);
+
+        let mut mask = BitBlock::zero();
+        self.or_shifted_mask(&mut mask, level_indices, child_offset);
+        if remainder != 0 {
+            // View child starts in the previous source child.
+            self.or_shifted_mask(&mut mask, level_indices, child_offset + 1);
+        }
+        mask
+    }
+
+    type DataType = <H::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = <H::Borrowed as SparseHierarchy>::Data<'a> where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let (upper_indices, data_index) = level_indices.split_last();
+        let index = data_block_index::<Self>(&upper_indices, data_index);
+        // Wraps around for indices below offset - to the top of the source
+        // range, which is checked to be empty at construction.
+        let source_index = index.wrapping_sub(self.offset) & Self::max_range();
+        self.h.borrow().get_unchecked(source_index)
+    }
+
+    type State = OffsetViewState<H>;
+}
+
+pub struct OffsetViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    /// [usize; LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <<H::Borrowed as SparseHierarchy>::LevelCount as ConstInteger>::Dec
+    >,
+    phantom_data: PhantomData<OffsetView<H>>
+}
+
+impl<H> Clone for OffsetViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H> SparseHierarchyState for OffsetViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    type This = OffsetView<H>;
+
+    #[inline]
+    fn new(_: &Self::This) -> Self {
+        Self{
+            level_indices: Array::from_fn(|_| 0),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, _: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        if /*const*/ N::VALUE != 0 {
+            *self.level_indices.as_mut().get_unchecked_mut(N::VALUE - 1) = level_index;
+        }
+        let prefix: ConstCopyArrayType<usize, N> = Array::from_fn(|i|
+            *self.level_indices.as_ref().get_unchecked(i)
+        );
+        this.level_mask(prefix)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let upper = <Self::This as SparseHierarchy>::LevelCount::VALUE - 1;
+        let indices: ConstCopyArrayType<usize, <Self::This as SparseHierarchy>::LevelCount> = Array::from_fn(|i|
+            if i < upper { *self.level_indices.as_ref().get_unchecked(i) } else { level_index }
+        );
+        this.data_block(indices)
+    }
+}
+
+impl<H> Borrowable for OffsetView<H>{
+    type Borrowed = OffsetView<H>;
+}
+
+/// Upper bound of `h` keys - exact for [EXACT_HIERARCHY].
+/// `None` if `h` is empty.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+fn last_key_bound<H: SparseHierarchy>(h: &H) -> Option<usize> {
+    struct V<'a, H: SparseHierarchy>{
+        h: &'a H,
+        state: H::State,
+        level_index: usize,
+        base: usize,
+        bound: Option<usize>,
+    }
+    impl<'a, H: SparseHierarchy> ConstIntVisitor for V<'a, H> {
+        type Out = Option<usize>;
+        #[inline(always)]
+        fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<Self::Out> {
+            let mask = unsafe{ self.state.select_level_bock(self.h, i, self.level_index) };
+            let Some(last) = mask.borrow().last_set_bit() else {
+                return ControlFlow::Break(self.bound);
+            };
+            let levels = H::LevelCount::VALUE;
+            let span_exp = H::LevelMaskType::SIZE.ilog2() as usize * (levels - 1 - I::VALUE);
+            self.base += last << span_exp;
+            self.bound = Some(self.base.saturating_add((1 << span_exp) - 1));
+            self.level_index = last;
+            if I::VALUE == levels - 1 {
+                ControlFlow::Break(self.bound)
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+    let v = V{ h, state: SparseHierarchyState::new(h), level_index: 0, base: 0, bound: None };
+    match const_for(ConstUsize::<0>, H::LevelCount::DEFAULT, v) {
+        ControlFlow::Break(bound) => bound,
+        ControlFlow::Continue(()) => unreachable!(),
+    }
+}
+
+/// View of [SparseHierarchy] with all keys shifted by `offset`.
+///
+/// Any `offset` is accepted. Terminal masks are always shifted exactly.
+/// Upper level blocks, whose child span `offset` is not a multiple of, may
+/// point to empty children - iteration skips them.
+///
+/// # Panics
+///
+/// If shifted keys would not fit [max_range()]. Checked against source
+/// hierarchy - which may be coarser than actual keys for non-[EXACT_HIERARCHY].
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, offset_view, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut chunk = Array::default();
+/// chunk.insert(5, Some(1));
+/// chunk.insert(63, Some(2));
+///
+/// let view = offset_view(&chunk, 100);
+/// assert_eq!(view.get(100 + 5), &Some(1));
+/// assert_eq!(view.get(5), &None);
+/// assert!(view.iter().map(|(i, _)| i).eq([105, 163]));
+/// ```
+///
+/// [max_range()]: SparseHierarchy::max_range
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn offset_view<H>(h: H, offset: usize) -> OffsetView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    let max_range = <H::Borrowed as SparseHierarchy>::max_range();
+    if let Some(last) = last_key_bound(h.borrow()) {
+        assert!(offset <= max_range - last, "offset shifts keys out of range!");
+    }
+    OffsetView{h, offset}
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, Empty, SparseArray, union};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_offset_view(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        const SPAN: usize = 64 * 64;
+
+        let mut chunk0 = Array::default();
+        chunk0.insert(0, Data(1));
+        chunk0.insert(100, Data(2));
+        chunk0.insert(SPAN + 7, Data(3));
+
+        let mut chunk1 = Array::default();
+        chunk1.insert(1, Data(10));
+        chunk1.insert(100, Data(20));
+
+        let v0 = offset_view(&chunk0, 2 * SPAN);
+        let v1 = offset_view(&chunk1, 3 * SPAN);
+        assert_equal(
+            v0.iter(),
+            [(2*SPAN, &Data(1)), (2*SPAN + 100, &Data(2)), (3*SPAN + 7, &Data(3))]
+        );
+        assert_eq!(v0.get(100), &Data(0));
+        assert_eq!(v0.get(2*SPAN + 100), &Data(2));
+
+        let u = union(&v0, &v1, |d0, d1| Data(d0.0 + d1.0));
+        assert_equal(
+            u.iter(),
+            [
+                (2*SPAN, Data(1)), (2*SPAN + 100, Data(2)),
+                (3*SPAN + 1, Data(10)), (3*SPAN + 7, Data(3)), (3*SPAN + 100, Data(20))
+            ]
+        );
+        for (i, d) in u.iter() {
+            assert_eq!(u.get(i), d);
+        }
+        assert_eq!(u.get(SPAN + 7), Data(0));
+    }
+
+    #[test]
+    fn test_offset_view_unaligned(){
+        fn do_test<Levels: crate::SparseArrayLevels>(rng: &mut impl Rng){
+            type Array<Levels> = SparseArray<Levels, Data>;
+            let max = Array::<Levels>::max_range();
+            let range = max / 4;
+            let mut a = Array::<Levels>::default();
+            for _ in 0..300 {
+                let v = rng.gen_range(0..range);
+                a.insert(v, Data(v + 1));
+            }
+            let width = <Levels::Mask as BitBlock>::SIZE;
+            let offsets = [
+                0, 1, 37, width - 1, width, width + 3,
+                width * width - 1, width * width + 1,
+                rng.gen_range(0..=max - range), max - range
+            ];
+            for offset in offsets.into_iter().filter(|&o| o <= max - range) {
+                let view = offset_view(&a, offset);
+                assert_equal(
+                    view.iter().filter(|(_, d)| !d.is_empty()),
+                    a.iter().map(|(i, d)| (i + offset, d))
+                );
+                for (i, d) in a.iter() {
+                    assert_eq!(view.get(i + offset), d);
+                }
+                for _ in 0..100 {
+                    let i = rng.gen_range(0..=max);
+                    let expected = i.checked_sub(offset).map_or(Data(0), |i| a.get(i).clone());
+                    assert_eq!(view.get(i), &expected);
+                }
+            }
+        }
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x0ff5_e700);
+        do_test::<config::width_64::depth_2>(&mut rng);
+        do_test::<config::width_64::depth_3>(&mut rng);
+        do_test::<config::width_32::depth_3>(&mut rng);
+        do_test::<config::width_16::depth_4>(&mut rng);
+        do_test::<config::width_512::depth_2>(&mut rng);
+        // max_range() saturates to usize::MAX.
+        do_test::<config::width_512::depth_8>(&mut rng);
+    }
+
+    #[test]
+    fn test_offset_view_unaligned_union(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut chunk0 = Array::default();
+        let mut chunk1 = Array::default();
+        for i in 0..100 {
+            chunk0.insert(i * 3, Data(1));
+            chunk1.insert(i * 5, Data(10));
+        }
+        let u = union(offset_view(&chunk0, 1000), offset_view(&chunk1, 1001), |d0, d1| Data(d0.0 + d1.0));
+        let expected = (1000..1500).filter_map(|i| {
+            let d = (i - 1000) % 3 == 0 && i < 1300;
+            let d1 = i > 1000 && (i - 1001) % 5 == 0 && i < 1501;
+            match (d, d1) {
+                (false, false) => None,
+                _ => Some((i, Data(d as usize + 10 * d1 as usize)))
+            }
+        });
+        assert_equal(u.iter().filter(|(_, d)| !d.is_empty()), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_offset_view_out_of_range(){
+        type Array = SparseArray<config::width_64::depth_2, Data>;
+        let mut a = Array::default();
+        a.insert(Array::max_range(), Data(1));
+        offset_view(a, 64);
+    }
+}
@@ -0,0 +1,402 @@
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use crate::{BitBlock, data_block_index, Empty, Primitive, SparseHierarchy};
+use crate::bit_block::word_bits;
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::{ConstArray, ConstArrayType};
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::array::Array;
+use crate::utils::{Borrowable, Take};
+
+/// Lazy [SparseHierarchy] adaptor, that exposes only keys within range.
+///
+/// Blocks on the range boundary are masked, interior ones are passed as-is.
+///
+/// Constructed by [range_view()].
+pub struct RangeView<H>{
+    h: H,
+    /// Inclusive.
+    start: usize,
+    /// Inclusive - so that range can reach `usize::MAX`.
+    /// Empty range has `start > last`.
+    last: usize,
+}
+
+impl<H> RangeView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    /// `mask` of level `prefix.len()` block, that `prefix` points to,
+    /// restricted to range.
+    #[inline]
+    fn restrict_mask(
+        &self,
+        prefix: &[usize],
+        mask: impl Take<<H::Borrowed as SparseHierarchy>::LevelMaskType>
+    ) -> <H::Borrowed as SparseHierarchy>::LevelMaskType {
+        let width = <H::Borrowed as SparseHierarchy>::LevelMaskType::SIZE;
+        let width_exp = width.ilog2() as usize;
+        let levels = <H::Borrowed as SparseHierarchy>::LevelCount::VALUE;
+        let span_exp = |level: usize| width_exp * (levels - 1 - level);
+
+        let base = prefix.iter().enumerate()
+            .map(|(level, &i)| i << span_exp(level))
+            .sum::<usize>();
+        let child_span_exp = span_exp(prefix.len());
+
+        if self.start > self.last {
+            return BitBlock::zero();
+        }
+        let lo = self.start.saturating_sub(base) >> child_span_exp;
+        let hi = match self.last.checked_sub(base) {
+            Some(last) => (last >> child_span_exp) + 1,
+            None => 0,
+        };
+        let (lo, hi) = (lo.min(width), hi.min(width));
+
+        if lo == 0 && hi == width {
+            mask.take_or_clone()
+        } else {
+            mask.take_or_clone() & range_mask(lo, hi)
+        }
+    }
+
+    #[inline]
+    fn contains_index(&self, index: usize) -> bool {
+        self.start <= index && index <= self.last
+    }
+}
+
+/// Mask with bits `lo..hi` raised.
+#[inline]
+fn range_mask<M: BitBlock>(lo: usize, hi: usize) -> M {
+    let word_bits = word_bits::<M>();
+    let mut mask = M::zero();
+    for (i, word) in mask.as_array_mut().as_mut().iter_mut().enumerate() {
+        let word_start = i * word_bits;
+        let lo = lo.clamp(word_start, word_start + word_bits) - word_start;
+        let hi = hi.clamp(word_start, word_start + word_bits) - word_start;
+        if lo < hi {
+            let max: <M::Array as Array>::Item = Primitive::MAX;
+            let hi_bits = if hi == word_bits { max } else { !(max << hi) };
+            *word = hi_bits & (max << lo);
+        }
+    }
+    mask
+}
+
+impl<H> SparseHierarchy for RangeView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    // Boundary blocks can end up empty after masking.
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let mask = self.h.borrow().level_mask(level_indices);
+        self.restrict_mask(level_indices.as_ref(), mask)
+    }
+
+    type DataType = <H::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let (upper_indices, data_index) = level_indices.split_last();
+        let index = data_block_index::<Self>(&upper_indices, data_index);
+        if self.contains_index(index) {
+            self.h.borrow().data_block(level_indices).take_or_clone()
+        } else {
+            Empty::empty()
+        }
+    }
+
+    type State = RangeViewState<H>;
+}
+
+pub struct RangeViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy>,
+{
+    state: <H::Borrowed as SparseHierarchy>::State,
+
+    /// [usize; LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <<H::Borrowed as SparseHierarchy>::LevelCount as ConstInteger>::Dec
+    >,
+
+    phantom_data: PhantomData<RangeView<H>>
+}
+
+impl<H> Clone for RangeViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            state: self.state.clone(),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H> SparseHierarchyState for RangeViewState<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    type This = RangeView<H>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            state: SparseHierarchyState::new(this.h.borrow()),
+            level_indices: Array::from_fn(|_| 0),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        if /*const*/ N::VALUE != 0 {
+            *self.level_indices.as_mut().get_unchecked_mut(N::VALUE - 1) = level_index;
+        }
+        let mask = self.state.select_level_bock(this.h.borrow(), level_n, level_index);
+        let prefix = self.level_indices.as_ref().get_unchecked(..N::VALUE);
+        this.restrict_mask(prefix, mask)
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let index = data_block_index::<Self::This>(&self.level_indices, level_index);
+        if this.contains_index(index) {
+            self.state.data_block(this.h.borrow(), level_index).take_or_clone()
+        } else {
+            Empty::empty()
+        }
+    }
+}
+
+impl<H> Borrowable for RangeView<H>{
+    type Borrowed = RangeView<H>;
+}
+
+/// View of [SparseHierarchy] items with keys within `range`.
+///
+/// Parts of `range` outside [max_range()] are ignored.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, range_view, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut a = Array::default();
+/// a.insert(5, Some(1));
+/// a.insert(50, Some(2));
+/// a.insert(500, Some(3));
+///
+/// let view = range_view(&a, 10..500);
+/// assert!(view.iter().map(|(i, _)| i).eq([50]));
+/// ```
+///
+/// [max_range()]: SparseHierarchy::max_range
+#[inline]
+pub fn range_view<H>(h: H, range: impl RangeBounds<usize>) -> RangeView<H>
+where
+    H: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+{
+    let max_range = <H::Borrowed as SparseHierarchy>::max_range();
+    let empty = RangeView{h, start: 1, last: 0};
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => match i.checked_add(1) {
+            Some(i) => i,
+            None => return empty,
+        },
+        Bound::Unbounded    => 0,
+    };
+    let last = match range.end_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => match i.checked_sub(1) {
+            Some(i) => i,
+            None => return empty,
+        },
+        Bound::Unbounded    => max_range,
+    };
+    RangeView{start, last: last.min(max_range), ..empty}
+}
+
+/// One [range_view()] per raised root mask bit - each covers
+/// root block child span.
+///
+/// Views are independent, and can be iterated in parallel, each with
+/// its own traverse state. Views are in index order.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, split_at_root, SparseArray, SparseHierarchy};
+/// type Array = SparseArray<config::width_64::depth_2, Option<u32>>;
+/// let mut a = Array::default();
+/// a.insert(5, Some(1));
+/// a.insert(50, Some(2));
+/// a.insert(500, Some(3));
+///
+/// // Root block child span is 64.
+/// let counts: Vec<_> = split_at_root(&a).map(|view| view.iter().count()).collect();
+/// assert_eq!(counts, [2, 1]);
+/// ```
+#[inline]
+pub fn split_at_root<H>(h: &H) -> impl Iterator<Item = RangeView<&H>>
+where
+    H: SparseHierarchy<DataType: Clone>,
+{
+    let width = H::LevelMaskType::SIZE;
+    let span_exp = width.ilog2() as usize * (H::LevelCount::VALUE - 1);
+    let root_mask = h.root_mask().take_or_clone();
+    root_mask.into_bits_iter().map(move |i| {
+        let start = i << span_exp;
+        range_view(h, start..=start + ((1 << span_exp) - 1))
+    })
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_range_view(){
+        fn do_test<Levels: crate::SparseArrayLevels>(){
+            type Array<Levels> = SparseArray<Levels, Data>;
+            let max = Array::<Levels>::max_range();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0x7261_6e67);
+            let mut a = Array::<Levels>::default();
+            for _ in 0..1000 {
+                let v = rng.gen_range(0..=max);
+                a.insert(v, Data(rng.gen_range(1..1000)));
+            }
+            a.insert(max, Data(1));
+
+            use Bound::*;
+            let check = |range: (Bound<usize>, Bound<usize>)| {
+                let view = range_view(&a, range);
+                let expected = a.iter()
+                    .filter(|(i, _)| range.contains(i))
+                    .map(|(i, d)| (i, d.clone()))
+                    .collect::<Vec<_>>();
+                assert_equal(
+                    view.iter().filter(|(_, d)| !d.is_empty()),
+                    expected.iter().cloned()
+                );
+                for (i, d) in &expected {
+                    assert_eq!(&view.get(*i), d);
+                }
+                assert_eq!(view.count_keys(), expected.len());
+            };
+
+            // Empty
+            check((Included(10), Excluded(10)));
+            check((Included(20), Excluded(10)));
+            check((Included(0), Excluded(0)));
+            check((Excluded(max), Unbounded));
+            // Whole
+            check((Included(0), Included(max)));
+            check((Unbounded, Unbounded));
+            // Inside a single terminal block
+            check((Included(3), Excluded(40)));
+            check((Included(64*3 + 1), Excluded(64*3 + 63)));
+            // Last key
+            check((Included(max), Unbounded));
+            check((Included(max - 10), Included(usize::MAX)));
+            for _ in 0..50 {
+                let start = rng.gen_range(0..=max);
+                let last = rng.gen_range(start..=max);
+                check((Included(start), Included(last)));
+                check((Included(start), Excluded(last)));
+            }
+
+            assert_eq!(range_view(&a, ..).count_keys(), a.iter().count());
+        }
+        do_test::<config::width_64::depth_2>();
+        do_test::<config::width_64::depth_3>();
+        #[cfg(feature = "simd")]
+        do_test::<config::width_128::depth_2>();
+        do_test::<config::width_128_u128::depth_2>();
+        do_test::<config::width_32::depth_3>();
+        do_test::<config::width_16::depth_4>();
+        do_test::<config::width_512::depth_2>();
+        // max_range() saturates to usize::MAX.
+        do_test::<config::width_512::depth_8>();
+        #[cfg(feature = "simd")]
+        do_test::<config::width_512_u64x8::depth_2>();
+        #[cfg(feature = "simd")]
+        do_test::<config::width_256::depth_2>();
+    }
+
+    #[test]
+    fn test_split_at_root(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5b11_7007);
+        let mut a = Array::default();
+        let mut b = Array::default();
+        for _ in 0..3000 {
+            let v = rng.gen_range(0..Array::max_range() / 2);
+            a.insert(v, Data(v + 1));
+            let v = rng.gen_range(0..Array::max_range() / 2);
+            b.insert(v, Data(v + 1));
+        }
+        let and = intersection(&a, &b, |x, y| Data(x.0 + y.0));
+
+        let views: Vec<_> = split_at_root(&and).collect();
+        assert_eq!(views.len(), BitBlock::count_ones(&and.root_mask()));
+        assert_equal(
+            views.iter().flat_map(|view| view.iter()),
+            and.iter()
+        );
+
+        assert_eq!(split_at_root(&Array::default()).count(), 0);
+
+        // Last root child span ends at usize::MAX.
+        type BigArray = SparseArray<config::width_512::depth_8, Data>;
+        let mut a = BigArray::default();
+        a.insert(0, Data(1));
+        a.insert(usize::MAX, Data(2));
+        let counts: Vec<_> = split_at_root(&a).map(|view| view.iter().count()).collect();
+        assert_eq!(counts, [1, 1]);
+    }
+
+    #[test]
+    fn test_range_view_intersection(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut a = Array::default();
+        let mut b = Array::default();
+        for i in 0..500 {
+            a.insert(i * 7, Data(1));
+            b.insert(i * 3, Data(1));
+        }
+        let and = intersection(range_view(&a, 100..1000), &b, |x, y| Data(x.0 + y.0));
+        assert_equal(
+            and.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+            (100..1000).filter(|i| i % 21 == 0)
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::borrow::Borrow;
+use crate::Empty;
+use crate::utils::Take;
+
+/// Data resolve of [union()], [intersection()] and [symmetric_difference()].
+///
+/// Implemented for `Fn(&L, &R) -> O` closures, and for operators'
+/// (`|`, `&`, `^`) resolves. The latter take items by value, whenever they
+/// are passed by value - so operator chains move items from one
+/// operation to another, instead of cloning them at each one.
+///
+/// [union()]: crate::union
+/// [intersection()]: crate::intersection
+/// [symmetric_difference()]: crate::symmetric_difference
+pub trait Resolve<L, R, O> {
+    fn resolve(
+        &self,
+        left : impl Borrow<L> + Take<L>,
+        right: impl Borrow<R> + Take<R>
+    ) -> O;
+}
+
+impl<F, L, R, O> Resolve<L, R, O> for F
+where
+    F: Fn(&L, &R) -> O
+{
+    #[inline]
+    fn resolve(
+        &self,
+        left : impl Borrow<L> + Take<L>,
+        right: impl Borrow<R> + Take<R>
+    ) -> O {
+        self(left.borrow(), right.borrow())
+    }
+}
+
+/// Operators data resolve - `left`, if present. `right` otherwise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolveLeftOrRight;
+
+impl<D: Empty + Clone> Resolve<D, D, D> for ResolveLeftOrRight {
+    #[inline]
+    fn resolve(
+        &self,
+        left : impl Borrow<D> + Take<D>,
+        right: impl Borrow<D> + Take<D>
+    ) -> D {
+        if !left.borrow().is_empty() {
+            left.take_or_clone()
+        } else if !right.borrow().is_empty() {
+            right.take_or_clone()
+        } else {
+            D::empty()
+        }
+    }
+}
+
+/// Operators data resolve - `left`, if both present.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResolveLeftIfBoth;
+
+impl<D: Empty + Clone> Resolve<D, D, D> for ResolveLeftIfBoth {
+    #[inline]
+    fn resolve(
+        &self,
+        left : impl Borrow<D> + Take<D>,
+        right: impl Borrow<D> + Take<D>
+    ) -> D {
+        if right.borrow().is_empty() {
+            D::empty()
+        } else {
+            left.take_or_clone()
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, data_block_index, Empty, SparseHierarchy};
+use crate::const_utils::const_int::ConstInteger;
+use crate::const_utils::const_array::ConstArray;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::{Borrowable, Take};
+
+/// Lazy [SparseHierarchy] adaptor, that keeps `data`'s items with keys
+/// present in `keys`.
+///
+/// Like intersection, but `keys` items are never constructed -
+/// with [EXACT_HIERARCHY] `keys` its data is not touched at all.
+///
+/// Constructed by [select()].
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+pub struct Select<H1, H2>{
+    data: H1,
+    keys: H2,
+}
+
+impl<H1, H2> Select<H1, H2>
+where
+    H2: Borrowable<Borrowed: SparseHierarchy>,
+{
+    const KEYS_EXACT: bool = <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY;
+}
+
+impl<H1, H2> SparseHierarchy for Select<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType,
+    >>,
+{
+    const EXACT_HIERARCHY: bool = false;
+    type LevelCount = <H1::Borrowed as SparseHierarchy>::LevelCount;
+
+    type LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType;
+    type LevelMask<'a> = Self::LevelMaskType where Self: 'a;
+    #[inline]
+    unsafe fn level_mask<I>(&self, level_indices: I) -> Self::LevelMask<'_>
+    where
+        I: ConstArray<Item=usize> + Copy
+    {
+        let mask1 = self.data.borrow().level_mask(level_indices);
+        let mask2 = self.keys.borrow().level_mask(level_indices);
+        mask1.take_or_clone() & mask2.take_or_clone()
+    }
+
+    type DataType = <H1::Borrowed as SparseHierarchy>::DataType;
+    type Data<'a> = Self::DataType where Self: 'a;
+    #[inline]
+    unsafe fn data_block<I>(&self, level_indices: I) -> Self::Data<'_>
+    where
+        I: ConstArray<Item=usize, Cap=Self::LevelCount> + Copy
+    {
+        let keys = self.keys.borrow();
+        let contains = if Self::KEYS_EXACT {
+            let (upper_indices, data_index) = level_indices.split_last();
+            let index = data_block_index::<Self>(&upper_indices, data_index);
+            keys.may_contain_unchecked(index)
+        } else {
+            !keys.data_block(level_indices).borrow().is_empty()
+        };
+        if contains {
+            self.data.borrow().data_block(level_indices).take_or_clone()
+        } else {
+            Empty::empty()
+        }
+    }
+
+    type State = SelectState<H1, H2>;
+}
+
+pub struct SelectState<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy>,
+{
+    data_state: <H1::Borrowed as SparseHierarchy>::State,
+    keys_state: <H2::Borrowed as SparseHierarchy>::State,
+
+    /// `keys` terminal mask. In-use only with [EXACT_HIERARCHY] `keys`.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    keys_terminal_mask: <H2::Borrowed as SparseHierarchy>::LevelMaskType,
+
+    phantom_data: PhantomData<Select<H1, H2>>
+}
+
+impl<H1, H2> Clone for SelectState<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<State: Clone>>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            data_state: self.data_state.clone(),
+            keys_state: self.keys_state.clone(),
+            keys_terminal_mask: self.keys_terminal_mask.clone(),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<H1, H2> SparseHierarchyState for SelectState<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType,
+    >>,
+{
+    type This = Select<H1, H2>;
+
+    #[inline]
+    fn new(this: &Self::This) -> Self {
+        Self{
+            data_state: SparseHierarchyState::new(this.data.borrow()),
+            keys_state: SparseHierarchyState::new(this.keys.borrow()),
+            keys_terminal_mask: BitBlock::zero(),
+            phantom_data: PhantomData
+        }
+    }
+
+    #[inline]
+    unsafe fn select_level_bock<'a, N: ConstInteger>(
+        &mut self, this: &'a Self::This, level_n: N, level_index: usize
+    ) -> <Self::This as SparseHierarchy>::LevelMask<'a> {
+        let mask1 = self.data_state.select_level_bock(this.data.borrow(), level_n, level_index);
+        let mask2 = self.keys_state.select_level_bock(this.keys.borrow(), level_n, level_index);
+        if Self::This::KEYS_EXACT
+        && N::VALUE == <Self::This as SparseHierarchy>::LevelCount::VALUE - 1
+        {
+            self.keys_terminal_mask = mask2.borrow().clone();
+        }
+        mask1.take_or_clone() & mask2.take_or_clone()
+    }
+
+    #[inline]
+    unsafe fn data_block<'a>(&self, this: &'a Self::This, level_index: usize)
+        -> <Self::This as SparseHierarchy>::Data<'a>
+    {
+        let contains = if Self::This::KEYS_EXACT {
+            self.keys_terminal_mask.get_bit(level_index)
+        } else {
+            !self.keys_state.data_block(this.keys.borrow(), level_index).borrow().is_empty()
+        };
+        if contains {
+            self.data_state.data_block(this.data.borrow(), level_index).take_or_clone()
+        } else {
+            Empty::empty()
+        }
+    }
+}
+
+impl<H1, H2> Borrowable for Select<H1, H2>{
+    type Borrowed = Select<H1, H2>;
+}
+
+/// `data` items, with keys present in `keys`.
+///
+/// Same as [intersection] that takes left items, but never
+/// constructs `keys` items.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [intersection]: crate::intersection
+#[inline]
+pub fn select<H1, H2>(data: H1, keys: H2) -> Select<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType,
+    >>,
+{
+    Select{data, keys}
+}
+
+#[cfg(test)]
+mod test{
+    use std::cell::Cell;
+    use itertools::assert_equal;
+    use rand::SeedableRng;
+    use crate::{keys_view, map_indexed, union};
+    use crate::test_utils::{Data, random_array};
+    use super::*;
+
+    #[test]
+    fn test_select(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x5e1e_c7ed);
+        let a1 = random_array(&mut rng);
+        let a2 = random_array(&mut rng);
+        let a3 = random_array(&mut rng);
+
+        let expected: Vec<_> = a1.iter()
+            .filter(|(i, _)| a2.contains(*i))
+            .map(|(i, d)| (i, d.clone()))
+            .collect();
+
+        // Non-exact keys.
+        let s = select(&a1, &a2);
+        assert_equal(s.iter().filter(|(_, d)| !d.is_empty()), expected.iter().cloned());
+        for i in 0..10_000 {
+            let d = if a2.contains(i) { a1.get(i).clone() } else { Data(0) };
+            assert_eq!(s.get(i), d);
+        }
+
+        // Exact keys - never touched.
+        let calls = Cell::new(0);
+        let keys = map_indexed(
+            union(&a2, &a3, |_, _| Data(1)),
+            |_, d: &Data| { calls.set(calls.get() + 1); d.clone() }
+        );
+        let expected: Vec<_> = a1.iter()
+            .filter(|(i, _)| a2.contains(*i) || a3.contains(*i))
+            .map(|(i, d)| (i, d.clone()))
+            .collect();
+        let s = select(&a1, &keys);
+        assert_equal(s.iter().filter(|(_, d)| !d.is_empty()), expected.iter().cloned());
+        for (i, d) in &expected {
+            assert_eq!(&s.get(*i), d);
+        }
+        assert_eq!(calls.get(), 0);
+
+        // With keys view.
+        let s = select(&a1, keys_view(&a2));
+        assert_eq!(s.count_keys(), a1.iter().filter(|(i, _)| a2.contains(*i)).count());
+    }
+}
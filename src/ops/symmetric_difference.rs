@@ -0,0 +1,258 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use std::ops::BitXor;
+use crate::{Apply, apply, BitBlock, Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
+use crate::const_utils::ConstTrue;
+use crate::op::BinaryOp;
+use super::{Resolve, ResolveLeftOrRight};
+use crate::utils::{Borrowable, Take};
+
+pub struct SymmetricDifferenceOp<F, L, R, O, M>{
+    f: F,
+    /// Both hierarchies are [EXACT_HIERARCHY].
+    ///
+    /// Known at compile-time - will be optimized away.
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    exact: bool,
+    phantom_data: PhantomData<(L, R, O, M)>
+}
+impl<F, Left, Right, Out, Mask> BinaryOp for SymmetricDifferenceOp<F, Left, Right, Out, Mask>
+where
+    Left: Empty,
+    Right: Empty,
+    Out: Empty,
+    F: Resolve<Left, Right, Out>,
+    Mask: BitBlock,
+{
+    // Upper level bits can't be cleared - we don't know what's
+    // in the terminal levels below.
+    const EXACT_HIERARCHY: bool = false;
+    type SKIP_EMPTY_HIERARCHIES = ConstTrue;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take_or_clone() | right.take_or_clone()
+    }
+
+    #[inline]
+    fn terminal_lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut mask = left.take_or_clone();
+        let right = right.borrow().as_array().as_ref();
+        for (l, r) in mask.as_array_mut().as_mut().iter_mut().zip(right) {
+            if self.exact {
+                // m0 ^ m1
+                *l ^= *r;
+            } else {
+                *l |= *r;
+            }
+        }
+        mask
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Out;
+
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Self::Left>  + Take<Self::Left>,
+        right: impl Borrow<Self::Right> + Take<Self::Right>
+    ) -> Self::Out {
+        if left.borrow().is_empty() != right.borrow().is_empty() {
+            self.f.resolve(left, right)
+        } else {
+            Out::empty()
+        }
+    }
+}
+
+// `Res` should be deducible from `F`, but RUST still
+// not dealt with Fn's.
+pub type SymmetricDifference<H1, H2, F, Res> = Apply<
+    SymmetricDifferenceOp<
+        F,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H2 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        Res,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    H1,
+    H2
+>;
+
+/// Symmetric difference between two [SparseHierarchy]ies.
+///
+/// Contains items present in exactly one of [SparseHierarchy]ies, and applies `f`
+/// to each of them. One item-argument is always in empty state - the one from
+/// [SparseHierarchy] that does not have an item at that index.
+///
+/// Terminal level masks are tightened only if both are [EXACT_HIERARCHY].
+/// Otherwise, items present in both are checked and skipped in iteration.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+#[inline]
+pub fn symmetric_difference<H1, H2, F, R>(h1: H1, h2: H2, f: F)
+    -> SymmetricDifference<H1, H2, F, R>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(&<H1::Borrowed as SparseHierarchy>::DataType, &<H2::Borrowed as SparseHierarchy>::DataType) -> R,
+    R: Empty,
+{
+    apply_symmetric_difference(h1, h2, f)
+}
+
+#[inline]
+fn apply_symmetric_difference<H1, H2, F, R>(h1: H1, h2: H2, f: F)
+    -> SymmetricDifference<H1, H2, F, R>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Resolve<
+        <H1::Borrowed as SparseHierarchy>::DataType,
+        <H2::Borrowed as SparseHierarchy>::DataType,
+        R
+    >,
+    R: Empty,
+{
+    let op = SymmetricDifferenceOp{
+        f,
+        exact: <H1::Borrowed as SparseHierarchy>::EXACT_HIERARCHY
+            && <H2::Borrowed as SparseHierarchy>::EXACT_HIERARCHY,
+        phantom_data: PhantomData
+    };
+    apply(op, h1, h2)
+}
+
+/// [symmetric_difference].
+impl<Levels, Data, K, Rhs> BitXor<Rhs> for &SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = Levels::LevelCount,
+        LevelMaskType = Levels::Mask,
+        DataType      = Data,
+    >>,
+{
+    type Output = SymmetricDifference<Self, Rhs, ResolveLeftOrRight, Data>;
+
+    #[inline]
+    fn bitxor(self, rhs: Rhs) -> Self::Output {
+        apply_symmetric_difference(self, rhs, ResolveLeftOrRight)
+    }
+}
+
+/// [symmetric_difference].
+impl<Op, B1, B2, Rhs> BitXor<Rhs> for Apply<Op, B1, B2>
+where
+    Self: SparseHierarchy<DataType: Clone>,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Self as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Self as SparseHierarchy>::LevelMaskType,
+        DataType      = <Self as SparseHierarchy>::DataType,
+    >>,
+{
+    type Output = SymmetricDifference<
+        Self, Rhs, ResolveLeftOrRight, <Self as SparseHierarchy>::DataType
+    >;
+
+    #[inline]
+    fn bitxor(self, rhs: Rhs) -> Self::Output {
+        apply_symmetric_difference(self, rhs, ResolveLeftOrRight)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::collections::BTreeSet;
+    use itertools::assert_equal;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, ExactHierarchy, intersection, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+
+    #[test]
+    fn test_symmetric_difference(){
+        const RANGE: usize = 20_000;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+
+        for _ in 0..10 {
+            let mut a1 = Array::default();
+            let mut a2 = Array::default();
+            let mut s1 = BTreeSet::new();
+            let mut s2 = BTreeSet::new();
+            for _ in 0..rng.gen_range(0..2000) {
+                let v = rng.gen_range(0..RANGE);
+                a1.insert(v, Data(v+1));
+                s1.insert(v);
+            }
+            for _ in 0..rng.gen_range(0..2000) {
+                let v = rng.gen_range(0..RANGE);
+                a2.insert(v, Data(v+1));
+                s2.insert(v);
+            }
+            let expected: Vec<_> = s1.symmetric_difference(&s2).copied().collect::<BTreeSet<_>>()
+                                   .into_iter().collect();
+            let f = |l: &Data, r: &Data| Data(l.0 + r.0);
+
+            // non-exact
+            {
+                let xor = symmetric_difference(&a1, &a2, f);
+                assert_equal(
+                    xor.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+                    expected.iter().copied()
+                );
+                for i in 0..RANGE {
+                    let d = xor.get(i);
+                    assert_eq!(d, unsafe{ xor.get_unchecked(i) });
+                    if s1.contains(&i) != s2.contains(&i) {
+                        assert_eq!(d, Data(i+1));
+                    } else {
+                        assert!(d.is_empty());
+                    }
+                }
+            }
+
+            // exact
+            {
+                let e1 = unsafe{ ExactHierarchy::new_unchecked(&a1) };
+                let e2 = unsafe{ ExactHierarchy::new_unchecked(&a2) };
+                let xor = symmetric_difference(&e1, &e2, f);
+                assert_equal(
+                    xor.iter().map(|(i, d)| { assert_eq!(d, Data(i+1)); i }),
+                    expected.iter().copied()
+                );
+
+                // composition
+                let xor_and_a1 = intersection(xor, &a1, |x, a| Data(x.0 + a.0));
+                assert_equal(
+                    xor_and_a1.iter().filter(|(_, d)| !d.is_empty()).map(|(i, _)| i),
+                    s1.difference(&s2).copied()
+                );
+            }
+        }
+    }
+}
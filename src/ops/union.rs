@@ -1,18 +1,20 @@
 use std::borrow::Borrow;
 use std::marker::PhantomData;
-use crate::{Apply, apply, BitBlock, Empty, SparseHierarchy};
+use std::ops::BitOr;
+use crate::{Apply, apply, BitBlock, Empty, Key, SparseArray, SparseArrayLevels, SparseHierarchy};
 use crate::const_utils::ConstTrue;
 use crate::op::BinaryOp;
+use super::{Resolve, ResolveLeftOrRight};
 use crate::utils::{Borrowable, Take};
 
-pub(crate) struct UnionOp<F, L, R, O, M>{
+pub struct UnionOp<F, L, R, O, M>{
     f: F,
     phantom_data: PhantomData<(L, R, O, M)>
 }
 impl<F, Left, Right, Out, Mask> BinaryOp for UnionOp<F, Left, Right, Out, Mask>
 where
     Out: Empty,
-    F: Fn(&Left, &Right) -> Out,
+    F: Resolve<Left, Right, Out>,
     Mask: BitBlock,
 {
     const EXACT_HIERARCHY: bool = true;
@@ -35,10 +37,10 @@ where
     #[inline]
     fn data_op(
         &self,
-        left : impl Borrow<Self::Left>,
-        right: impl Borrow<Self::Right>
+        left : impl Borrow<Self::Left>  + Take<Self::Left>,
+        right: impl Borrow<Self::Right> + Take<Self::Right>
     ) -> Self::Out {
-        (self.f)(left.borrow(), right.borrow())
+        self.f.resolve(left, right)
     }
 }
 
@@ -76,4 +78,78 @@ where
     R: Empty,
 {
     apply(UnionOp { f, phantom_data: PhantomData }, h1, h2)
-}
\ No newline at end of file
+}
+
+/// [union], that prefers items from the left side.
+impl<Levels, Data, K, Rhs> BitOr<Rhs> for &SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Clone,
+    K: Key,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = Levels::LevelCount,
+        LevelMaskType = Levels::Mask,
+        DataType      = Data,
+    >>,
+{
+    type Output = Union<Self, Rhs, ResolveLeftOrRight, Data>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        apply(UnionOp{ f: ResolveLeftOrRight, phantom_data: PhantomData }, self, rhs)
+    }
+}
+
+/// [union], that prefers items from the left side.
+impl<Op, B1, B2, Rhs> BitOr<Rhs> for Apply<Op, B1, B2>
+where
+    Self: SparseHierarchy<DataType: Clone>,
+    Rhs: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <Self as SparseHierarchy>::LevelCount,
+        LevelMaskType = <Self as SparseHierarchy>::LevelMaskType,
+        DataType      = <Self as SparseHierarchy>::DataType,
+    >>,
+{
+    type Output = Union<
+        Self, Rhs, ResolveLeftOrRight, <Self as SparseHierarchy>::DataType
+    >;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        apply(UnionOp{ f: ResolveLeftOrRight, phantom_data: PhantomData }, self, rhs)
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_2, Data>;
+
+    #[test]
+    fn test_union(){
+        let mut a1 = Array::default();
+        a1.insert(1, Data(1));
+        a1.insert(200, Data(2));
+
+        let mut a2 = Array::default();
+        a2.insert(200, Data(20));
+        a2.insert(3000, Data(30));
+
+        // Resolve closure is captured by op - plain lambda,
+        // without type annotations.
+        let res = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        assert_equal(res.iter(), [(1, Data(1)), (200, Data(22)), (3000, Data(30))]);
+        assert_eq!(res.get(200), Data(22));
+        assert!(res.get(2).is_empty());
+
+        type MaxUnion<'a> = Union<&'a Array, &'a Array, fn(&Data, &Data) -> Data, Data>;
+        fn nameable<'a>(a1: &'a Array, a2: &'a Array) -> MaxUnion<'a> {
+            union(a1, a2, |l, r| Data(l.0.max(r.0)))
+        }
+        assert_eq!(nameable(&a1, &a2).get(200), Data(20));
+    }
+}
@@ -1,17 +1,18 @@
 use std::borrow::Borrow;
 use std::marker::PhantomData;
-use crate::{BitBlock, Empty, fold, Fold, SparseHierarchy};
+use crate::{Apply, apply, BitBlock, Empty, fold, Fold, SliceIter, SparseHierarchy};
+use crate::fold::slice_iter;
 use crate::const_utils::ConstTrue;
 use crate::op::BinaryOp;
 use crate::utils::{Borrowable, Take};
 
-pub(crate) struct UnionFoldOp<F, Acc, Data, Mask>{
+pub struct UnionFoldOp<F, Acc, Data, Mask>{
     f: F,
     phantom_data: PhantomData<(Acc, Data, Mask)>
 }
 impl<F, Acc, Data, Mask> BinaryOp for UnionFoldOp<F, Acc, Data, Mask>
 where
-    Acc: Empty,
+    Acc: Empty + Clone,
     F: Fn(Acc, &Data) -> Acc,
     Mask: BitBlock,
 {
@@ -25,7 +26,7 @@ where
         left : impl Take<Self::LevelMask>, 
         right: impl Borrow<Self::LevelMask>
     ) -> Self::LevelMask {
-        let mut acc = left.take();
+        let mut acc = left.take_or_clone();
         acc |= right.borrow();
         acc
     }
@@ -40,10 +41,42 @@ where
         acc  : impl Take<Self::Left>,
         right: impl Borrow<Self::Right>
     ) -> Self::Out {
-        (self.f)(acc.take(), right.borrow())
+        (self.f)(acc.take_or_clone(), right.borrow())
     }
 }
 
+#[doc(hidden)]
+pub type UnionFoldStep<H1, H2, F> = Apply<
+    UnionFoldOp<
+        F,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H2 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    H1,
+    H2
+>;
+
+/// Single [union_fold] step between two [SparseHierarchy]ies
+/// of different types. Used by [union_fold_tuple](crate::union_fold_tuple).
+#[doc(hidden)]
+#[inline]
+pub fn __union_fold_step<H1, H2, F>(h1: H1, h2: H2, f: F)
+    -> UnionFoldStep<H1, H2, F>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType
+    >>,
+    F: Fn(
+        <H1::Borrowed as SparseHierarchy>::DataType,
+        &<H2::Borrowed as SparseHierarchy>::DataType
+    ) -> <H1::Borrowed as SparseHierarchy>::DataType,
+{
+    apply(UnionFoldOp { f, phantom_data: PhantomData }, h1, h2)
+}
+
 pub type UnionFold<Init, Iter, F> = Fold<
     UnionFoldOp<
         F, 
@@ -72,4 +105,62 @@ where
     ) -> <Init::Borrowed as SparseHierarchy>::DataType
 {
     fold(UnionFoldOp { f, phantom_data: PhantomData }, init, iter)
-}
\ No newline at end of file
+}
+
+/// [union_fold] over slice of [SparseHierarchy]ies or references to them.
+///
+/// Accepts both `&[T]` and `&[&T]` (e.g. `&Vec<&T>`).
+#[inline]
+pub fn union_fold_slice<'a, Init, T, F>(init: Init, slice: &'a [T], f: F)
+    -> UnionFold<Init, SliceIter<'a, T>, F>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy>,
+    T: Borrowable<Borrowed: SparseHierarchy>,
+
+    F: Fn(
+        <Init::Borrowed as SparseHierarchy>::DataType, 
+        &<T::Borrowed as SparseHierarchy>::DataType
+    ) -> <Init::Borrowed as SparseHierarchy>::DataType
+{
+    union_fold(init, slice_iter(slice), f)
+}
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, SparseArray, SparseHierarchy};
+    use crate::test_utils::Data;
+    use super::*;
+
+    /// Items do not borrow iterator - regular [Iterator] adapters compose.
+    #[test]
+    fn test_union_fold_adapters(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let arrays: Vec<_> = (1..4).map(|k| {
+            let mut a = Array::default();
+            for i in (0..1000).step_by(k) {
+                a.insert(i, Data(1));
+            }
+            a
+        }).collect();
+        let or = union_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+
+        let expected = |i: usize| 1 + (i % 2 == 0) as usize + (i % 3 == 0) as usize;
+        assert_equal(
+            Iterator::take(
+                or.iter()
+                    .map(|(i, d)| (i, d.0))
+                    .filter(|(_, n)| *n > 1)
+                    .step_by(2),
+                10
+            ),
+            Iterator::take(
+                (0..1000).map(|i| (i, expected(i))).filter(|(_, n)| *n > 1).step_by(2),
+                10
+            )
+        );
+        assert_equal(
+            or.iter().take_while(|(i, _)| *i < 10).map(|(_, d)| d),
+            (0..10).map(|i| Data(expected(i)))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, Empty, fold, Fold, SparseHierarchy};
+use crate::const_utils::ConstTrue;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+pub struct UnionFoldEnumerateOp<F, Acc, Data, Mask>{
+    f: F,
+    phantom_data: PhantomData<(Acc, Data, Mask)>
+}
+impl<F, Acc, Data, Mask> BinaryOp for UnionFoldEnumerateOp<F, Acc, Data, Mask>
+where
+    Acc: Empty,
+    F: Fn(Acc, usize, &Data) -> Acc,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = true;
+    type SKIP_EMPTY_HIERARCHIES = ConstTrue;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut acc = left.take();
+        acc |= right.borrow();
+        acc
+    }
+
+    type Left  = Acc;
+    type Right = Data;
+    type Out   = Acc;
+
+    /// Never called - [Fold] uses [fold_data_op] only.
+    ///
+    /// [fold_data_op]: Self::fold_data_op
+    #[inline]
+    fn data_op(
+        &self,
+        _: impl Take<Self::Left>,
+        _: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        unreachable!()
+    }
+
+    #[inline]
+    fn fold_data_op(
+        &self,
+        acc  : impl Take<Self::Left>,
+        right: impl Borrow<Self::Right>,
+        position: usize
+    ) -> Self::Out {
+        (self.f)(acc.take(), position, right.borrow())
+    }
+}
+
+pub type UnionFoldEnumerate<Init, Iter, F> = Fold<
+    UnionFoldEnumerateOp<
+        F,
+        <<Init as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<<Iter as Iterator>::Item as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<Init as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    Init,
+    Iter
+>;
+
+/// Same as [union_fold()], but `f` also receives the position of
+/// the source [SparseHierarchy] in `iter`.
+///
+/// Like in [union_fold()], `f` may be called with empty items.
+///
+/// [union_fold()]: crate::union_fold
+#[inline]
+pub fn union_fold_enumerate<Init, Iter, F>(init: Init, iter: Iter, f: F)
+     -> UnionFoldEnumerate<Init, Iter, F>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy>,
+    Iter: Iterator<Item: Borrowable<Borrowed: SparseHierarchy>>,
+
+    F: Fn(
+        <Init::Borrowed as SparseHierarchy>::DataType,
+        usize,
+        &<<Iter::Item as Borrowable>::Borrowed as SparseHierarchy>::DataType
+    ) -> <Init::Borrowed as SparseHierarchy>::DataType
+{
+    fold(UnionFoldEnumerateOp { f, phantom_data: PhantomData }, init, iter)
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    /// Bitset of source positions.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Sources(u32);
+    impl Empty for Sources{
+        fn empty() -> Self {
+            Self(0)
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+
+    type Array = SparseArray<config::width_64::depth_2, Data>;
+    type SourcesArray = SparseArray<config::width_64::depth_2, Sources>;
+
+    #[test]
+    fn test_union_fold_enumerate(){
+        let mut a0 = Array::default();
+        a0.insert(1,   Data(1));
+        a0.insert(100, Data(1));
+        a0.insert(200, Data(1));
+
+        let mut a1 = Array::default();
+        a1.insert(100, Data(1));
+        a1.insert(300, Data(1));
+
+        let mut a2 = Array::default();
+        a2.insert(100,  Data(1));
+        a2.insert(200,  Data(1));
+        a2.insert(4000, Data(1));
+
+        let init = SourcesArray::default();
+        let arrays = [a0, a1, a2];
+        let u = union_fold_enumerate(&init, arrays.iter(), |acc, position, d| {
+            if d.is_empty() {
+                acc
+            } else {
+                Sources(acc.0 | (1 << position))
+            }
+        });
+
+        let expected = [
+            (1,    Sources(0b001)),
+            (100,  Sources(0b111)),
+            (200,  Sources(0b101)),
+            (300,  Sources(0b010)),
+            (4000, Sources(0b100)),
+        ];
+        assert_equal(u.iter(), expected.clone());
+        for (i, sources) in expected {
+            assert_eq!(u.get(i), sources);
+            assert_eq!(unsafe{ u.get_unchecked(i) }, sources);
+        }
+        assert!(u.get(2).is_empty());
+    }
+}
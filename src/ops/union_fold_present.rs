@@ -0,0 +1,201 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{BitBlock, Empty, fold, Fold, SparseHierarchy};
+use crate::const_utils::ConstTrue;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+/// [union_fold_present()] item.
+///
+/// Folded data, along with bitmask of sources that have an item at that index.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Present<T>{
+    mask: u64,
+    data: T,
+}
+
+impl<T> Present<T>{
+    #[inline]
+    pub fn new(data: T) -> Self {
+        Self{ mask: 0, data }
+    }
+
+    /// Bitmask of source positions, that have an item at that index.
+    #[inline]
+    pub fn present_mask(&self) -> u64 {
+        self.mask
+    }
+
+    #[inline]
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> T {
+        self.data
+    }
+}
+
+impl<T: Empty> Empty for Present<T>{
+    #[inline]
+    fn empty() -> Self {
+        Self::new(T::empty())
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.mask == 0 && self.data.is_empty()
+    }
+}
+
+pub struct UnionFoldPresentOp<F, Acc, Data, Mask>{
+    f: F,
+    phantom_data: PhantomData<(Acc, Data, Mask)>
+}
+impl<F, Acc, Data, Mask> BinaryOp for UnionFoldPresentOp<F, Acc, Data, Mask>
+where
+    Acc: Empty,
+    Data: Empty,
+    F: Fn(Acc, usize, &Data) -> Acc,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = true;
+    type SKIP_EMPTY_HIERARCHIES = ConstTrue;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Borrow<Self::LevelMask>
+    ) -> Self::LevelMask {
+        let mut acc = left.take();
+        acc |= right.borrow();
+        acc
+    }
+
+    type Left  = Present<Acc>;
+    type Right = Data;
+    type Out   = Present<Acc>;
+
+    /// Never called - [Fold] uses [fold_data_op] only.
+    ///
+    /// [fold_data_op]: Self::fold_data_op
+    #[inline]
+    fn data_op(
+        &self,
+        _: impl Take<Self::Left>,
+        _: impl Borrow<Self::Right>
+    ) -> Self::Out {
+        unreachable!()
+    }
+
+    #[inline]
+    fn fold_data_op(
+        &self,
+        acc  : impl Take<Self::Left>,
+        right: impl Borrow<Self::Right>,
+        position: usize
+    ) -> Self::Out {
+        let mut acc = acc.take();
+        let right = right.borrow();
+        if !right.is_empty() {
+            acc.mask |= 1 << position;
+            acc.data = (self.f)(acc.data, position, right);
+        }
+        acc
+    }
+}
+
+// `Acc` should be deducible from `Init`, but RUST still
+// not dealt with associated type equality in aliases.
+pub type UnionFoldPresent<Init, Iter, F, Acc> = Fold<
+    UnionFoldPresentOp<
+        F,
+        Acc,
+        <<<Iter as Iterator>::Item as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<Init as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    Init,
+    Iter
+>;
+
+/// Union between up to 64 [SparseHierarchy]ies in fold-style,
+/// with [Present] items.
+///
+/// Each item carries [present_mask()] - bitmask of `iter` positions that
+/// have an item at that index. Unlike [union_fold_enumerate()], `f` is called
+/// only with non-empty items.
+///
+/// `init` is usually an empty container of [Present]s.
+/// Its `Init`'s type may differ, but all [SparseHierarchy]ies
+/// must have the same configuration.
+///
+/// # Panics
+///
+/// Panics in debug, if `iter` has more than 64 [SparseHierarchy]ies.
+///
+/// [present_mask()]: Present::present_mask
+/// [union_fold_enumerate()]: crate::union_fold_enumerate
+#[inline]
+pub fn union_fold_present<Init, Iter, F, Acc>(init: Init, iter: Iter, f: F)
+     -> UnionFoldPresent<Init, Iter, F, Acc>
+where
+    Init: Borrowable<Borrowed: SparseHierarchy<DataType = Present<Acc>>>,
+    Iter: Iterator<Item: Borrowable<Borrowed: SparseHierarchy>> + Clone,
+    F: Fn(Acc, usize, &<<Iter::Item as Borrowable>::Borrowed as SparseHierarchy>::DataType) -> Acc
+{
+    debug_assert!(iter.clone().count() <= 64);
+    fold(UnionFoldPresentOp { f, phantom_data: PhantomData }, init, iter)
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_2, Data>;
+    type PresentArray = SparseArray<config::width_64::depth_2, Present<Data>>;
+
+    #[test]
+    fn test_union_fold_present(){
+        let mut arrays = [Array::default(), Array::default(), Array::default()];
+        let keys: [&[usize]; 3] = [
+            &[1, 100, 200],
+            &[100, 300],
+            &[100, 200, 4000],
+        ];
+        for (position, keys) in keys.iter().enumerate() {
+            for &k in *keys {
+                arrays[position].insert(k, Data(10usize.pow(position as u32)));
+            }
+        }
+
+        let init = PresentArray::default();
+        let u = union_fold_present(&init, arrays.iter(), |acc, _, d| Data(acc.0 + d.0));
+
+        assert_equal(
+            u.iter().map(|(i, p)| (i, p.present_mask(), p.into_data())),
+            [
+                (1,    0b001, Data(1)),
+                (100,  0b111, Data(111)),
+                (200,  0b101, Data(101)),
+                (300,  0b010, Data(10)),
+                (4000, 0b100, Data(100)),
+            ]
+        );
+
+        // Mask matches actual set of sources.
+        for (i, p) in u.iter() {
+            for (position, keys) in keys.iter().enumerate() {
+                let bit = p.present_mask() & (1 << position) != 0;
+                assert_eq!(bit, keys.contains(&i));
+            }
+            assert_eq!(u.get(i), p);
+        }
+        assert!(u.get(2).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use crate::{Apply, apply, BitBlock, Empty, SparseHierarchy};
+use crate::const_utils::ConstTrue;
+use crate::op::BinaryOp;
+use crate::utils::{Borrowable, Take};
+
+/// [union_items()] item.
+///
+/// Tells which of [SparseHierarchy]ies have an item at that index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum UnionItem<L, R>{
+    Left(L),
+    Right(R),
+    Both(L, R),
+}
+
+impl<L, R> UnionItem<L, R>{
+    #[inline]
+    pub fn left(&self) -> Option<&L> {
+        match self {
+            UnionItem::Left(l) | UnionItem::Both(l, _) => Some(l),
+            UnionItem::Right(_) => None,
+        }
+    }
+
+    #[inline]
+    pub fn right(&self) -> Option<&R> {
+        match self {
+            UnionItem::Right(r) | UnionItem::Both(_, r) => Some(r),
+            UnionItem::Left(_) => None,
+        }
+    }
+}
+
+pub struct UnionItemsOp<L, R, M>{
+    phantom_data: PhantomData<(L, R, M)>
+}
+impl<Left, Right, Mask> BinaryOp for UnionItemsOp<Left, Right, Mask>
+where
+    Left: Empty + Clone,
+    Right: Empty + Clone,
+    Mask: BitBlock,
+{
+    const EXACT_HIERARCHY: bool = true;
+    type SKIP_EMPTY_HIERARCHIES = ConstTrue;
+    type LevelMask = Mask;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<Self::LevelMask>,
+        right: impl Take<Self::LevelMask>
+    ) -> Self::LevelMask {
+        left.take_or_clone() | right.take_or_clone()
+    }
+
+    type Left  = Left;
+    type Right = Right;
+    type Out   = Option<UnionItem<Left, Right>>;
+
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Self::Left>  + Take<Self::Left>,
+        right: impl Borrow<Self::Right> + Take<Self::Right>
+    ) -> Self::Out {
+        let left  = (!left.borrow().is_empty()).then(|| left.take_or_clone());
+        let right = (!right.borrow().is_empty()).then(|| right.take_or_clone());
+        match (left, right) {
+            (Some(l), Some(r)) => Some(UnionItem::Both(l, r)),
+            (Some(l), None)    => Some(UnionItem::Left(l)),
+            (None, Some(r))    => Some(UnionItem::Right(r)),
+            (None, None)       => None,
+        }
+    }
+}
+
+pub type UnionItems<H1, H2> = Apply<
+    UnionItemsOp<
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H2 as Borrowable>::Borrowed as SparseHierarchy>::DataType,
+        <<H1 as Borrowable>::Borrowed as SparseHierarchy>::LevelMaskType
+    >,
+    H1,
+    H2
+>;
+
+/// Union between two [SparseHierarchy]ies, with [UnionItem] items.
+///
+/// Same as [union()], but instead of resolving each pair of merged items
+/// with closure - tells which side(s) have an item at that index.
+/// Items are `None` only at indices, that are absent in both.
+///
+/// [SparseHierarchy]ies can be of different types, but must have the same configuration.
+///
+/// [union()]: crate::union
+#[inline]
+pub fn union_items<H1, H2>(h1: H1, h2: H2) -> UnionItems<H1, H2>
+where
+    H1: Borrowable<Borrowed: SparseHierarchy<DataType: Clone>>,
+    H2: Borrowable<Borrowed: SparseHierarchy<
+        LevelCount    = <H1::Borrowed as SparseHierarchy>::LevelCount,
+        LevelMaskType = <H1::Borrowed as SparseHierarchy>::LevelMaskType,
+        DataType: Clone
+    >>,
+{
+    apply(UnionItemsOp{ phantom_data: PhantomData }, h1, h2)
+}
+
+#[cfg(test)]
+mod test{
+    use itertools::assert_equal;
+    use crate::{config, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_2, Data>;
+
+    #[test]
+    fn test_union_items(){
+        let mut a1 = Array::default();
+        a1.insert(1, Data(1));
+        a1.insert(200, Data(2));
+
+        let mut a2 = Array::default();
+        a2.insert(200, Data(20));
+        a2.insert(3000, Data(30));
+
+        let res = union_items(&a1, &a2);
+        assert_equal(res.iter(), [
+            (1,    Some(UnionItem::Left(Data(1)))),
+            (200,  Some(UnionItem::Both(Data(2), Data(20)))),
+            (3000, Some(UnionItem::Right(Data(30)))),
+        ]);
+        assert_eq!(res.get(200), Some(UnionItem::Both(Data(2), Data(20))));
+        assert_eq!(unsafe{ res.get_unchecked(3000) }, Some(UnionItem::Right(Data(30))));
+        assert_eq!(res.get(2), None);
+
+        let sum: usize = res.iter()
+            .map(|(_, item)| match item.unwrap() {
+                UnionItem::Left(l)    => l.0,
+                UnionItem::Right(r)   => r.0,
+                UnionItem::Both(l, r) => l.0 * r.0,
+            })
+            .sum();
+        assert_eq!(sum, 1 + 40 + 30);
+
+        let item = res.get(200).unwrap();
+        assert_eq!(item.left(), Some(&Data(2)));
+        assert_eq!(item.right(), Some(&Data(20)));
+    }
+}
@@ -0,0 +1,197 @@
+use std::ops::ControlFlow;
+use crate::{BitBlock, SparseHierarchy};
+use crate::const_utils::const_int::{const_for, ConstInteger, ConstIntVisitor, ConstUsize};
+use crate::const_utils::const_array::ConstArrayType;
+use crate::sparse_array::level_indices;
+use crate::sparse_hierarchy::SparseHierarchyState;
+use crate::utils::Take;
+use crate::utils::array::Array;
+
+/// Point query cursor.
+///
+/// Remembers the last descended hierarchy path, and on each [get()] re-selects
+/// only levels, whose index changed. For sorted or clustered queries,
+/// that's mostly terminal level only - instead of all levels per [SparseHierarchy::get].
+///
+/// Uses [SparseHierarchyState], so works the same for containers and lazy ops.
+/// Pays off mostly for lazy ops - container's [SparseHierarchy::get] is
+/// already just a few loads.
+///
+/// Constructed by [SparseHierarchy::point_cursor].
+///
+/// [get()]: Self::get
+pub struct PointCursor<'a, T>
+where
+    T: SparseHierarchy,
+{
+    container: &'a T,
+    state: T::State,
+
+    /// [T::LevelMaskType; T::LevelCount]
+    ///
+    /// Masks of selected blocks.
+    masks: ConstArrayType<T::LevelMaskType, T::LevelCount>,
+
+    /// [usize; T::LevelCount - 1]
+    level_indices: ConstArrayType<
+        usize,
+        <T::LevelCount as ConstInteger>::Dec
+    >,
+
+    /// Number of selected levels below root.
+    selected: usize,
+}
+
+impl<'a, T> Clone for PointCursor<'a, T>
+where
+    T: SparseHierarchy<State: Clone>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            container: self.container,
+            state: self.state.clone(),
+            masks: Array::from_fn(|i| self.masks.as_ref()[i].clone()),
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i]),
+            selected: self.selected,
+        }
+    }
+}
+
+impl<'a, T> PointCursor<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    pub fn new(container: &'a T) -> Self {
+        let mut state = T::State::new(container);
+        let mut masks: ConstArrayType<T::LevelMaskType, T::LevelCount> =
+            Array::from_fn(|_| BitBlock::zero());
+        masks.as_mut()[0] = unsafe{
+            state.select_level_bock(container, ConstUsize::<0>, 0)
+        }.take_or_clone();
+        Self{
+            container,
+            state,
+            masks,
+            level_indices: Array::from_fn(|_| 0),
+            selected: 0,
+        }
+    }
+
+    /// Item at `index`.
+    ///
+    /// Returns `None` if `index` is not in hierarchy. Non-[EXACT_HIERARCHY]
+    /// can return empty items.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `index` is outside [max_range()].
+    ///
+    /// [EXACT_HIERARCHY]: SparseHierarchy::EXACT_HIERARCHY
+    /// [max_range()]: SparseHierarchy::max_range
+    #[inline]
+    pub fn get(&mut self, index: usize) -> Option<T::Data<'a>> {
+        assert!(index <= T::MAX_INDEX, "index out of range!");
+        let target = level_indices::<T::LevelMaskType, T::LevelCount>(index);
+        let target = target.as_ref();
+
+        // First level, whose block must be re-selected.
+        let mut from = 0;
+        while from < self.selected && self.level_indices.as_ref()[from] == target[from] {
+            from += 1;
+        }
+
+        let ctrl = const_for(ConstUsize::<0>, T::LevelCount::DEFAULT.dec(), V{
+            this: self, target, from
+        });
+        struct V<'b,'c,'a,T: SparseHierarchy>{
+            this: &'b mut PointCursor<'a, T>,
+            target: &'c [usize],
+            from: usize,
+        }
+        impl<'b,'c,'a,T: SparseHierarchy> ConstIntVisitor for V<'b,'c,'a,T> {
+            type Out = ();
+            #[inline(always)]
+            fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<Self::Out> {
+                if i.value() < self.from {
+                    return ControlFlow::Continue(());
+                }
+                let this = &mut *self.this;
+                let index = self.target[i.value()];
+                if !this.masks.as_ref()[i.value()].get_bit(index) {
+                    this.selected = i.value();
+                    return ControlFlow::Break(());
+                }
+                let level_depth = i.inc();
+                let mask = unsafe{
+                    this.state.select_level_bock(this.container, level_depth, index)
+                };
+                this.masks.as_mut()[level_depth.value()] = mask.take_or_clone();
+                this.level_indices.as_mut()[i.value()] = index;
+                this.selected = level_depth.value();
+                ControlFlow::Continue(())
+            }
+        }
+        if ctrl.is_break() {
+            return None;
+        }
+
+        let terminal_index = target[T::LevelCount::VALUE - 1];
+        if !self.masks.as_ref()[T::LevelCount::VALUE - 1].get_bit(terminal_index) {
+            return None;
+        }
+        Some(unsafe{ self.state.data_block(self.container, terminal_index) })
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use std::borrow::Borrow;
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection, SparseArray, union};
+    use crate::test_utils::Data;
+    use super::*;
+
+    #[test]
+    fn test_point_cursor(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x9017_c0a5);
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for _ in 0..4000 {
+            let v = rng.gen_range(0..100_000);
+            a1.insert(v, Data(v+1));
+            let v = rng.gen_range(0..100_000);
+            a2.insert(v, Data(v+1));
+        }
+
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H, rng: &mut impl Rng){
+            let expected = |i| {
+                let d = h.get(i).borrow().clone();
+                if h.may_contain(i) { Some(d) } else { None }
+            };
+            let mut cursor = h.point_cursor();
+            // Sorted
+            for i in 0..100_000 {
+                assert_eq!(cursor.get(i).map(|d| d.borrow().clone()), expected(i));
+            }
+            // Random
+            for _ in 0..10_000 {
+                let i = rng.gen_range(0..=H::max_range());
+                assert_eq!(cursor.get(i).map(|d| d.borrow().clone()), expected(i));
+            }
+        }
+        check(&a1, &mut rng);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+        check(&union(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+
+        // Single level
+        type Small = SparseArray<config::width_64::depth_1, Data>;
+        let mut a = Small::default();
+        a.insert(5, Data(6));
+        let mut cursor = a.point_cursor();
+        assert_eq!(cursor.get(5), Some(&Data(6)));
+        assert_eq!(cursor.get(6), None);
+    }
+}
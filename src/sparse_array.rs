@@ -1,3 +1,5 @@
+use std::borrow::Borrow;
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::ControlFlow;
 use std::ops::ControlFlow::{Break, Continue};
@@ -7,10 +9,12 @@ use crate::utils::Borrowable;
 use crate::level_block::HiBlock;
 use crate::level::{ILevel, IntrusiveListLevel};
 use crate::sparse_hierarchy::{SparseHierarchy, SparseHierarchyState};
-use crate::const_utils::const_int::{ConstUsize, ConstInteger, ConstIntVisitor};
+use crate::const_utils::const_int::{const_for, ConstUsize, ConstInteger, ConstIntVisitor};
 use crate::const_utils::const_array::{ConstArray, ConstArrayType, ConstCopyArrayType};
 use crate::const_utils::{ConstBool, ConstFalse, ConstTrue};
-use crate::Empty;
+use crate::{CapacityExceeded, Empty, Error, Index, IndexOutOfRange, InvariantViolation, Key, LevelStats, Stats, keys_eq};
+use crate::index::{index_from_key, key_from_index};
+use crate::iter::{BlockIter, KeysIter};
 use crate::utils::primitive::Primitive;
 use crate::utils::array::{Array};
 use crate::sparse_array_levels::{FoldMutVisitor, FoldVisitor, MutVisitor, SparseArrayLevels, Visitor};
@@ -43,11 +47,44 @@ where
         level_indices.as_mut()[level] = level_index; 
     }
     
-    *level_indices.as_mut().last_mut().unwrap() = level_remainder; 
-    
+    *level_indices.as_mut().last_mut().unwrap() = level_remainder;
+
     level_indices
 }
 
+/// Checked version of [level_indices].
+///
+/// Returns [IndexOutOfRange] if `index` does not fit `LevelMask::SIZE^LevelsCount` range.
+#[inline]
+pub(crate) fn try_level_indices<LevelMask, LevelsCount>(index: usize)
+     -> Result<ConstCopyArrayType<usize, LevelsCount>, IndexOutOfRange>
+where
+    LevelMask: BitBlock,
+    LevelsCount: ConstInteger,
+{
+    let index = Index::<LevelMask, LevelsCount>::new(index)?;
+    Ok(level_indices::<LevelMask, LevelsCount>(index.value()))
+}
+
+/// `index` as block index `P`.
+/// 
+/// # Panics
+/// 
+/// Will panic with [CapacityExceeded], if `index` does not fit `P`.
+#[inline(always)]
+fn to_block_index<P: Primitive>(index: usize) -> P {
+    match P::try_from_usize(index) {
+        Some(block_index) => block_index,
+        None => capacity_exceeded(P::MAX.as_usize())
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn capacity_exceeded(max_block_index: usize) -> ! {
+    panic!("{}", Error::from(CapacityExceeded{ max_block_index }))
+}
+
 #[cfg(test)]
 #[test]
 fn test_level_indices_new(){
@@ -76,23 +113,146 @@ fn test_level_indices_new(){
     }
 }
 
-pub struct SparseArray<Levels, Data> {
+#[cfg(test)]
+#[test]
+fn test_validate(){
+    use InvariantViolation::*;
+    type Array = SparseArray<crate::config::width_64::depth_3, Option<usize>>;
+    
+    let mut a = Array::default();
+    assert_eq!(a.validate(), Ok(()));
+    for i in [0, 1, 63, 64, 4096, 100_000] {
+        a.insert(i, Some(i));
+    }
+    a.remove(63);
+    assert_eq!(a.validate(), Ok(()));
+    
+    {
+        let mut a = Array::from(&a);
+        a.keys[2] = 7;
+        assert_eq!(a.validate(), Err(KeyMismatch{ data_index: 2, key: 1, stored_key: 7 }));
+    }
+    {
+        let mut a = Array::from(&a);
+        a.last_level_block_indices[1].1 += 1;
+        assert_eq!(a.validate(), Err(BackLinkMismatch{ data_index: 1 }));
+    }
+    {
+        let mut a = Array::from(&a);
+        a.values.push(None);
+        assert_eq!(a.validate(), Err(LengthMismatch{ values: 7, keys: 6, back_links: 6 }));
+        a.keys.push(0);
+        a.last_level_block_indices.push((0, 0));
+        assert_eq!(a.validate(), Err(UnreachableData{ data_index: 6 }));
+    }
+    
+    a.clear();
+    assert_eq!(a.validate(), Ok(()));
+}
+
+/// Upper levels path cache for sequential inserts.
+pub(crate) trait BranchCache<LevelCount: ConstInteger> {
+    /// Remember `level_indices` as the current path.
+    /// 
+    /// Returns number of upper levels, whose next-level block indices 
+    /// are still valid.
+    fn update(&mut self, level_indices: ConstCopyArrayType<usize, LevelCount>) -> usize;
+    
+    fn block_index(&self, level: usize) -> usize;
+    
+    fn set_block_index(&mut self, level: usize, block_index: usize);
+}
+
+/// No cache.
+impl<LevelCount: ConstInteger> BranchCache<LevelCount> for () {
+    #[inline(always)]
+    fn update(&mut self, _: ConstCopyArrayType<usize, LevelCount>) -> usize {
+        0
+    }
+
+    #[inline(always)]
+    fn block_index(&self, _: usize) -> usize {
+        unreachable!()
+    }
+
+    #[inline(always)]
+    fn set_block_index(&mut self, _: usize, _: usize) {}
+}
+
+/// Previously inserted index path: its level indices, and block indices 
+/// at each level.
+pub(crate) struct InsertBranch<LevelCount: ConstInteger>{
+    level_indices: ConstCopyArrayType<usize, LevelCount>,
+    block_indices: ConstCopyArrayType<usize, LevelCount>,
+    /// Number of valid upper levels in `level_indices`.
+    len: usize,
+}
+
+impl<LevelCount: ConstInteger> Default for InsertBranch<LevelCount>{
+    #[inline]
+    fn default() -> Self {
+        Self{
+            level_indices: Array::from_fn(|_| 0),
+            block_indices: Array::from_fn(|_| 0),
+            len: 0
+        }
+    }
+}
+
+impl<LevelCount: ConstInteger> BranchCache<LevelCount> for InsertBranch<LevelCount> {
+    #[inline]
+    fn update(&mut self, level_indices: ConstCopyArrayType<usize, LevelCount>) -> usize {
+        let upper_levels = LevelCount::VALUE - 1;
+        let mut cached = 0;
+        while cached < self.len 
+           && level_indices.as_ref()[cached] == self.level_indices.as_ref()[cached]
+        {
+            cached += 1;
+        }
+        self.level_indices = level_indices;
+        self.len = upper_levels;
+        cached
+    }
+
+    #[inline]
+    fn block_index(&self, level: usize) -> usize {
+        unsafe{ *self.block_indices.as_ref().get_unchecked(level) }
+    }
+
+    #[inline]
+    fn set_block_index(&mut self, level: usize, block_index: usize) {
+        unsafe{ *self.block_indices.as_mut().get_unchecked_mut(level) = block_index; }
+    }
+}
+
+/// Sparse array container.
+///
+/// `K` is the key type, accepted and returned by [SparseArray] own
+/// methods - see [Key]. [SparseHierarchy] interface always speaks `usize`
+/// indices.
+pub struct SparseArray<Levels, Data, K = usize> {
     levels: Levels,
     
     // TODO: some kind of multi-vec, to reduce allocation count? 
-    values: Vec<Data>,
+    pub(crate) values: Vec<Data>,
+    /// Key of each `values` item. Element 0 belongs to the empty item,
+    /// and is never read - so every in-range key, including `usize::MAX`,
+    /// is storable.
     keys  : Vec<usize>,
     
     // TODO: can be pair of u32's
     // Used only in remove().
     /// Coordinates in last level of pointer to value with this vec index.  
     last_level_block_indices: Vec<(usize/*block_index*/, usize/*in-block index*/)>, 
+    
+    phantom_data: PhantomData<fn(K) -> K>
 }
-impl<Levels, Data> Default for
-    SparseArray<Levels, Data>
+impl<Levels, Data, K> Default for
+    SparseArray<Levels, Data, K>
 where
     Levels: SparseArrayLevels,
     Data: Empty,
+    K: Key,
 {
     #[inline]
     fn default() -> Self {        
@@ -100,20 +260,43 @@ where
             levels: Levels::default(),
             
             values: vec![Data::empty()], 
-            keys  : vec![usize::MAX /*doesn't matter*/],
-            last_level_block_indices: vec![(0,0)]
+            keys  : vec![0 /*never read*/],
+            last_level_block_indices: vec![(0,0)],
+            phantom_data: PhantomData
         }
     }
 }
 
-impl<Levels, Data> SparseArray<Levels, Data>
+impl<Levels, Data, K> SparseArray<Levels, Data, K>
 where
     Levels: SparseArrayLevels,
     Data: Empty,
+    K: Key,
 {
     #[inline(always)]
     fn check_index_range(index: usize){
-        assert!(index <= Self::max_range(), "index out of range!");
+        if index > Self::MAX_INDEX {
+            Self::index_out_of_range(index);
+        }
+    }
+    
+    /// `key` as index. Not range-checked.
+    /// 
+    /// # Panics
+    /// 
+    /// Will panic with [IndexOutOfRange], if `key` does not fit `usize`.
+    #[inline]
+    fn key_index(key: K) -> usize {
+        match index_from_key(key) {
+            Some(index) => index,
+            None => Self::index_out_of_range(usize::MAX)
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn index_out_of_range(index: usize) -> ! {
+        panic!("{}", Error::from(IndexOutOfRange{ index, max_range: Self::MAX_INDEX }))
     }
     
     #[inline(always)]
@@ -127,7 +310,7 @@ where
                 L: ILevel 
             {
                 unsafe {
-                    level.blocks().get_unchecked(self.0) as *const _ as *const u8
+                    level.block_unchecked(self.0) as *const _ as *const u8
                 }
             }
         }
@@ -201,7 +384,7 @@ where
                 L: ILevel, L::Block: HiBlock
             {
                 unsafe{
-                    let block = level.blocks().get_unchecked(level_block_index);
+                    let block = level.block_unchecked(level_block_index);
                     let in_block_index = self.level_indices.as_ref().get_unchecked(I::VALUE).as_usize();
                     let block_index = block.get_or_zero(in_block_index).as_usize();
                     *self.out.as_mut().get_unchecked_mut(I::VALUE) = block_index; 
@@ -220,13 +403,46 @@ where
         self.fetch_block_indices(level_indices).1
     }
     
-    /// Returns `Some(item)` if there is an element at `index` in container.
+    /// Remove all elements.
+    ///
+    /// Level blocks memory is kept, and reused by subsequent inserts.
+    pub fn clear(&mut self) {
+        struct V;
+        impl<M> FoldMutVisitor<M> for V {
+            type Acc = ();
+
+            #[inline(always)]
+            fn visit<I: ConstInteger, L>(&mut self, _: I, level: &mut L, _: Self::Acc)
+                -> ControlFlow<Self::Acc, Self::Acc>
+            where
+                L: ILevel, L::Block: HiBlock<Mask=M>
+            {
+                level.clear();
+                Continue(())
+            }
+        }
+        self.levels.fold_mut((), V);
+
+        self.values.truncate(1);
+        self.keys.truncate(1);
+        self.last_level_block_indices.truncate(1);
+    }
+
+    /// Returns `Some(item)` if there is an element at `key` in container.
     /// `None` otherwise. 
     /// 
     /// # Panics
     /// 
-    /// Will panic if `index` is outside [max_range()].
-    pub fn remove(&mut self, index: usize) -> Option<Data> {
+    /// Will panic if `key` is outside [max_range()].
+    #[inline]
+    pub fn remove(&mut self, key: K) -> Option<Data> {
+        self.remove_index(Self::key_index(key))
+    }
+    
+    /// [remove()] by raw `index`.
+    ///
+    /// [remove()]: Self::remove
+    pub(crate) fn remove_index(&mut self, index: usize) -> Option<Data> {
         Self::check_index_range(index);
 
         let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
@@ -262,7 +478,7 @@ where
                 } else {
                     self.levels_block_indices.as_ref()[level_number.dec().value()]
                 };
-                let level_block = unsafe{ level.blocks_mut().get_unchecked_mut(block_index) };
+                let level_block = unsafe{ level.block_unchecked_mut(block_index) };
                 unsafe{
                     let inner_index = self.level_indices.as_ref()[level_number.value()];
                     level_block.remove_unchecked(inner_index);
@@ -302,7 +518,7 @@ where
                         L: ILevel<Block: HiBlock>,                
                     {
                         unsafe{
-                            let level_block = level.blocks_mut().get_unchecked_mut(self.level_block_index);                            
+                            let level_block = level.block_unchecked_mut(self.level_block_index);                            
                             level_block.set_unchecked(self.inner_index, Primitive::from_usize(self.data_block_index));
                         }
                     }
@@ -317,66 +533,373 @@ where
         Some(value)
     }
     
-    /// Returns mutable reference to item at `index`, if exists.
+    /// Returns mutable reference to item at `key`, if exists.
     /// Inserts and return [empty] level_block, otherwise.
     /// 
     /// # Panics
     ///
-    /// Will panic if `index` is outside [max_range()].
+    /// Will panic if `key` is outside [max_range()].
     ///
     /// # Tip
     /// 
     /// Even though this container is ![EXACT_HIERARCHY], if you end up 
     /// with a value in empty state - consider calling [remove()].
-    pub fn get_mut(&mut self, index: usize) -> &mut Data {
-        self.get_or_insert(index, ConstFalse, ||Data::empty())
+    pub fn get_mut(&mut self, key: K) -> &mut Data {
+        self.get_or_insert(Self::key_index(key), ConstFalse, ||Data::empty())
     }
 
-    /// Inserts `value` at `index`.
+    /// Inserts `value` at `key`.
     /// If there was a value - it will be replaced.
     ///
     /// Somewhat faster than *[get_mut()] = `value`, since it will not insert intermediate
-    /// [empty] value [^1], if `index` unoccupied.
+    /// [empty] value [^1], if `key` unoccupied.
     ///
     /// [^1]: Thou, if empty constructor is not complex - compiler may be 
     /// able to optimize away intermediate value anyway. But better safe then sorry.
     /// 
     /// # Panics
     ///
-    /// Will panic if `index` is outside [max_range()].
+    /// Will panic if `key` is outside [max_range()].
     ///
     /// # Tip
     /// 
     /// Even though this container is ![EXACT_HIERARCHY], try not to insert empty 
     /// `value`, as it will appear in iteration. 
-    pub fn insert(&mut self, index: usize, value: Data) {
-        self.get_or_insert(index, ConstTrue, ||value);
+    pub fn insert(&mut self, key: K, value: Data) {
+        self.get_or_insert(Self::key_index(key), ConstTrue, ||value);
+    }
+
+    /// [insert()] with pre-validated `index` - no range check.
+    #[inline]
+    pub fn insert_at(&mut self, index: Index<Levels::Mask, Levels::LevelCount>, value: Data) {
+        let index = index.value();
+        let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
+        self.get_or_insert_impl(index, level_indices, ConstTrue, ||value, &mut ());
+    }
+
+    /// Same as [insert()], but returns [Error] instead of panic:
+    /// - [IndexOutOfRange] if `key` is outside [max_range()].
+    /// - [CapacityExceeded] if any level can not address one more 
+    ///   block, or last level - one more item.
+    /// 
+    /// On error, container is left unchanged.
+    #[inline]
+    pub fn try_insert(&mut self, key: K, value: Data) -> Result<(), Error> {
+        let index = index_from_key(key).ok_or(
+            IndexOutOfRange{ index: usize::MAX, max_range: Self::MAX_INDEX }
+        )?;
+        let level_indices = try_level_indices::<Levels::Mask, Levels::LevelCount>(index)?;
+        self.check_insert_capacity(level_indices)?;
+        self.get_or_insert_impl(index, level_indices, ConstTrue, ||value, &mut ());
+        Ok(())
     }
     
+    /// Checks that each block and item, that insert at `level_indices` 
+    /// would add, fits its parent block index type. Nothing is changed.
+    fn check_insert_capacity(
+        &self, 
+        level_indices: ConstCopyArrayType<usize, Levels::LevelCount>
+    ) -> Result<(), CapacityExceeded> {
+        struct V<LevelIndices>(LevelIndices);
+        impl<LevelIndices: Array<Item=usize>, M> FoldVisitor<M> for V<LevelIndices> {
+            /// (block index - `None` if it will be inserted, max index parent block can address)
+            type Acc = Result<(Option<usize>, usize), CapacityExceeded>;
+            
+            #[inline(always)]
+            fn visit<I: ConstInteger, L>(&mut self, _: I, level: &L, acc: Self::Acc) 
+                -> ControlFlow<Self::Acc, Self::Acc> 
+            where 
+                L: ILevel, L::Block: HiBlock
+            {
+                let Ok((block_index, max_block_index)) = acc else {
+                    return Break(acc);
+                };
+                let max_child_index = <L::Block as HiBlock>::Item::MAX.as_usize();
+                let Some(block_index) = block_index else {
+                    // Whole path below is new.
+                    if level.next_empty_block_index() > max_block_index {
+                        return Break(Err(CapacityExceeded{ max_block_index }));
+                    }
+                    return Continue(Ok((None, max_child_index)));
+                };
+                let child = unsafe{
+                    let block = level.block_unchecked(block_index);
+                    block.get_or_zero(self.0.as_ref()[I::VALUE]).as_usize()
+                };
+                Continue(Ok(((child != 0).then_some(child), max_child_index)))
+            }
+        }
+        let (data_index, max_data_index) = self.levels.fold(Ok((Some(0), usize::MAX)), V(level_indices))?;
+        if data_index.is_none() && self.values.len() > max_data_index {
+            return Err(CapacityExceeded{ max_block_index: max_data_index });
+        }
+        Ok(())
+    }
+
     /// insert:
     /// true  - for insert
     /// false - for get_mut
     #[inline]
     fn get_or_insert(&mut self, index: usize, insert: impl ConstBool, value_fn: impl FnOnce() -> Data)
-        -> &mut Data 
+        -> &mut Data
     {
         Self::check_index_range(index);
+        let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
+        self.get_or_insert_impl(index, level_indices, insert, value_fn, &mut ())
+    }
 
+    /// Same as [insert()], but reuses upper levels path of the previous
+    /// `branch` insert. Most effective with ascending `index`es.
+    ///
+    /// # Safety
+    ///
+    /// `branch` must be used with this container only, without [remove()]s
+    /// or [clear()]s in between.
+    ///
+    /// [insert()]: Self::insert
+    /// [remove()]: Self::remove
+    /// [clear()]: Self::clear
+    #[inline]
+    pub(crate) unsafe fn insert_with_branch(
+        &mut self,
+        index: usize,
+        value: Data,
+        branch: &mut InsertBranch<Levels::LevelCount>
+    ) {
+        Self::check_index_range(index);
         let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
+        self.get_or_insert_impl(index, level_indices, ConstTrue, ||value, branch);
+    }
+
+    /// [get_mut()] version of [insert_with_branch()].
+    ///
+    /// # Safety
+    ///
+    /// Same as in [insert_with_branch()].
+    ///
+    /// [get_mut()]: Self::get_mut
+    /// [insert_with_branch()]: Self::insert_with_branch
+    #[inline]
+    pub(crate) unsafe fn get_mut_with_branch(
+        &mut self,
+        index: usize,
+        branch: &mut InsertBranch<Levels::LevelCount>
+    ) -> &mut Data {
+        Self::check_index_range(index);
+        let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
+        self.get_or_insert_impl(index, level_indices, ConstFalse, ||Data::empty(), branch)
+    }
+
+    /// Construct with `keys`, and items computed by `value` from key.
+    ///
+    /// Faster than [insert()]ing one by one, when `keys` are ascending - 
+    /// inserts reuse upper levels path of the previous one. 
+    /// Works with any order though. Repeated keys are overwritten.
+    ///
+    /// Use to build payload-carrying container from index set.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if any key is outside [max_range()].
+    ///
+    /// [insert()]: Self::insert
+    /// [max_range()]: SparseHierarchy::max_range
+    pub fn from_keys<I, F>(keys: I, mut value: F) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        F: FnMut(K) -> Data
+    {
+        let keys = keys.into_iter();
+        let mut this = Self::default();
+        let (len, _) = keys.size_hint();
+        this.values.reserve(len);
+        this.keys.reserve(len);
+        this.last_level_block_indices.reserve(len);
+        
+        let mut branch = InsertBranch::default();
+        for key in keys {
+            unsafe{ this.insert_with_branch(Self::key_index(key), value(key), &mut branch); }
+        }
+        this
+    }
+
+    /// Merge `other` into self.
+    ///
+    /// For each non-empty `other` item, calls `f` with the item at the same
+    /// index in self. Missing items are inserted as [empty] first.
+    ///
+    /// `other` is traversed in ascending index order, so inserts
+    /// reuse upper levels path of the previous one.
+    ///
+    /// [empty]: Empty::empty
+    pub fn union_with<H, F>(&mut self, other: &H, mut f: F)
+    where
+        H: SparseHierarchy<LevelMaskType = Levels::Mask, LevelCount = Levels::LevelCount>,
+        F: FnMut(&mut Data, &H::DataType)
+    {
+        let mut branch = InsertBranch::default();
+        for (index, data) in other.iter() {
+            let data = data.borrow();
+            if data.is_empty() {
+                continue;
+            }
+            let slot = unsafe{ self.get_mut_with_branch(index, &mut branch) };
+            f(slot, data);
+        }
+    }
+
+    /// Remove items, that are not present (empty) in `other`.
+    ///
+    /// For each retained item, calls `f` with the item at the same
+    /// index in `other`.
+    pub fn intersect_with<H, F>(&mut self, other: &H, mut f: F)
+    where
+        H: SparseHierarchy<LevelMaskType = Levels::Mask, LevelCount = Levels::LevelCount>,
+        F: FnMut(&mut Data, &H::DataType)
+    {
+        // Traverse dense storage backwards - remove() swaps the last
+        // element into removed place, and that one is already visited.
+        for i in (1..self.values.len()).rev() {
+            let index = unsafe{ *self.keys.get_unchecked(i) };
+            let data = other.get(index);
+            let data = data.borrow();
+            if data.is_empty() {
+                self.remove_index(index);
+            } else {
+                f(unsafe{ self.values.get_unchecked_mut(i) }, data);
+            }
+        }
+    }
+
+    /// Remove items, that are present (non-empty) in `other`.
+    ///
+    /// Returns number of removed items.
+    pub fn subtract<H>(&mut self, other: &H) -> usize
+    where
+        H: SparseHierarchy<LevelMaskType = Levels::Mask, LevelCount = Levels::LevelCount>,
+    {
+        let len = self.len();
+        // Same as in intersect_with().
+        for i in (1..self.values.len()).rev() {
+            let index = unsafe{ *self.keys.get_unchecked(i) };
+            if other.contains(index) {
+                self.remove_index(index);
+            }
+        }
+        len - self.len()
+    }
+
+    /// Remove items present in both, and insert clones of items
+    /// present only in `other`.
+    ///
+    /// Single ordered pass over `other`.
+    pub fn symmetric_difference_with<H>(&mut self, other: &H)
+    where
+        H: SparseHierarchy<
+            LevelMaskType = Levels::Mask,
+            LevelCount = Levels::LevelCount,
+            DataType = Data
+        >,
+        Data: Clone
+    {
+        let mut branch = InsertBranch::default();
+        for (index, data) in other.iter() {
+            let data = data.borrow();
+            if data.is_empty() {
+                continue;
+            }
+            if self.remove_index(index).is_some() {
+                // remove() may free cached path blocks.
+                branch = InsertBranch::default();
+            } else {
+                unsafe{ self.insert_with_branch(index, data.clone(), &mut branch); }
+            }
+        }
+    }
+
+    /// Split into items for which `pred` returns `true`, and the rest.
+    ///
+    /// Items are moved, never cloned. Linear pass over contiguous storage.
+    ///
+    /// Use [subtract()]/[intersect_with()] when only one half is needed.
+    ///
+    /// [subtract()]: Self::subtract
+    /// [intersect_with()]: Self::intersect_with
+    pub fn partition<F>(self, mut pred: F) -> (Self, Self)
+    where
+        F: FnMut(K, &Data) -> bool
+    {
+        let mut matching = Self::default();
+        let mut rest = Self::default();
+        let mut matching_branch = InsertBranch::default();
+        let mut rest_branch = InsertBranch::default();
+        let items = self.keys.into_iter().zip(self.values).skip(1);
+        for (index, data) in items {
+            // Each branch is used with its own container only.
+            unsafe{
+                if pred(key_from_index(index), &data) {
+                    matching.insert_with_branch(index, data, &mut matching_branch);
+                } else {
+                    rest.insert_with_branch(index, data, &mut rest_branch);
+                }
+            }
+        }
+        (matching, rest)
+    }
+
+    /// Same as [union_with()], but moves `other` items into `f`.
+    ///
+    /// [union_with()]: Self::union_with
+    pub fn union_with_into<OtherLevels, OtherData, F>(
+        &mut self,
+        other: SparseArray<OtherLevels, OtherData, K>,
+        mut f: F
+    )
+    where
+        OtherLevels: SparseArrayLevels<Mask = Levels::Mask, LevelCount = Levels::LevelCount>,
+        OtherData: Empty,
+        F: FnMut(&mut Data, OtherData)
+    {
+        let mut branch = InsertBranch::default();
+        let items = other.keys.into_iter().zip(other.values).skip(1);
+        for (index, data) in items {
+            if data.is_empty() {
+                continue;
+            }
+            let slot = unsafe{ self.get_mut_with_branch(index, &mut branch) };
+            f(slot, data);
+        }
+    }
+
+    /// `level_indices` must be `level_indices(index)`.
+    #[inline]
+    fn get_or_insert_impl(
+        &mut self,
+        index: usize,
+        level_indices: ConstCopyArrayType<usize, Levels::LevelCount>,
+        insert: impl ConstBool,
+        value_fn: impl FnOnce() -> Data,
+        branch: &mut impl BranchCache<Levels::LevelCount>
+    ) -> &mut Data {
         let last_level_inner_index = unsafe{ *level_indices.as_ref().last().unwrap_unchecked() }; 
         
         let this = NonNull::new(self).unwrap();
-        let last_level_block_index = self.levels.fold_mut(0, V{this, level_indices, index});
-        struct V<Levels, Data, LevelIndices> {
-            this: NonNull<SparseArray<Levels, Data>>,
+        let cached_levels = branch.update(level_indices);
+        let last_level_block_index = self.levels.fold_mut(0, V{this, level_indices, index, branch, cached_levels});
+        struct V<'a, Levels, Data, K, LevelIndices, B> {
+            this: NonNull<SparseArray<Levels, Data, K>>,
             level_indices: LevelIndices,
-            index: usize
+            index: usize,
+            branch: &'a mut B,
+            cached_levels: usize,
         }
-        impl<Levels, Data, LevelIndices, M> FoldMutVisitor<M> for V<Levels, Data, LevelIndices>
+        impl<'a, Levels, Data, K, LevelIndices, B, M> FoldMutVisitor<M> for V<'a, Levels, Data, K, LevelIndices, B>
         where
             Levels: SparseArrayLevels,
             Data: Empty,
-            LevelIndices: Array<Item=usize>
+            K: Key,
+            LevelIndices: Array<Item=usize>,
+            B: BranchCache<Levels::LevelCount>
         {
             type Acc = usize;
             
@@ -392,7 +915,11 @@ where
                     return Continue(level_block_index);
                 }
                 
-                let block = level.blocks_mut().get_unchecked_mut(level_block_index);
+                if I::VALUE < self.cached_levels {
+                    return Continue(self.branch.block_index(I::VALUE + 1));
+                }
+                
+                let block = level.block_unchecked_mut(level_block_index);
                 let inner_index = self.level_indices.as_ref()[I::VALUE];
                 let (block_index, _) = block.get_or_insert(inner_index, ||{
                     struct Insert;
@@ -404,8 +931,9 @@ where
                         }
                     }
                     let block_index = self.this.as_mut().levels.visit_mut(i.inc(), Insert);
-                    Primitive::from_usize(block_index)
+                    to_block_index(block_index)
                 });
+                self.branch.set_block_index(I::VALUE + 1, block_index.as_usize());
                 Continue(block_index.as_usize())
             }
             }
@@ -421,15 +949,15 @@ where
                 insert, value_fn, index
             }
         );
-        struct LastLevelVisitor<Levels, Data, Insert, ValueFn>{
-            this: NonNull<SparseArray<Levels, Data>>,
+        struct LastLevelVisitor<Levels, Data, K, Insert, ValueFn>{
+            this: NonNull<SparseArray<Levels, Data, K>>,
             level_block_index: usize,
             block_inner_index: usize,
             insert: Insert,
             value_fn: ValueFn,
             index: usize
         }
-        impl<Levels, Data, Insert, ValueFn, M> MutVisitor<M> for LastLevelVisitor<Levels, Data, Insert, ValueFn>
+        impl<Levels, Data, K, Insert, ValueFn, M> MutVisitor<M> for LastLevelVisitor<Levels, Data, K, Insert, ValueFn>
         where
             Insert: ConstBool,
             ValueFn: FnOnce() -> Data
@@ -442,18 +970,18 @@ where
                 L: ILevel, L::Block: HiBlock<Mask=M> 
             {
             unsafe{ 
-                let block = level.blocks_mut().get_unchecked_mut(self.level_block_index);
+                let block = level.block_unchecked_mut(self.level_block_index);
                 
                 let this = self.this.as_mut();
                 let (block_index, inserted) = block.get_or_insert(self.block_inner_index, ||{
-                    let i = this.values.len();
+                    let i = to_block_index(this.values.len());
                     // Make RUST happy, push value latter
                     //this.values.push(value);
                     this.keys.push(self.index);
                     this.last_level_block_indices.push(
                         (self.level_block_index, self.block_inner_index)
                     );
-                    Primitive::from_usize(i)                        
+                    i
                 });
                 let block_index = block_index.as_usize();
                 
@@ -479,14 +1007,15 @@ where
         }  
     }
     
-    /// Returns `Some`, if an element with `index` exists in container.
+    /// Returns `Some`, if an element with `key` exists in container.
     /// `None` - otherwise.
     /// 
     /// # Panics
     ///
-    /// Will panic if `index` is outside [max_range()].
+    /// Will panic if `key` is outside [max_range()].
     #[inline]
-    pub fn try_get(&self, index: usize) -> Option<&Data> {
+    pub fn try_get(&self, key: K) -> Option<&Data> {
+        let index = Self::key_index(key);
         Self::check_index_range(index);
         let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
         let data_block_index = unsafe{ self.fetch_block_index(level_indices) };
@@ -498,14 +1027,15 @@ where
         }
     }    
     
-    /// Returns `Some`, if element with `index` exists in container.
+    /// Returns `Some`, if element with `key` exists in container.
     /// `None` - otherwise.
     /// 
     /// # Panics
     ///
-    /// Will panic if `index` is outside [max_range()].
+    /// Will panic if `key` is outside [max_range()].
     #[inline]
-    pub fn try_get_mut(&mut self, index: usize) -> Option<&mut Data> {
+    pub fn try_get_mut(&mut self, key: K) -> Option<&mut Data> {
+        let index = Self::key_index(key);
         Self::check_index_range(index);
         let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
         let data_block_index = unsafe{ self.fetch_block_index(level_indices) };
@@ -519,13 +1049,13 @@ where
     
     /// # Safety
     /// 
-    /// Element at `index` must exist in container [^1].
+    /// Element at `key` must exist in container [^1].
     /// 
     /// [^1]: Pay attention that this is a stricter requirement than
     /// [SparseHierarchy::get_unchecked]'s.
     #[inline]
-    pub unsafe fn get_mut_unchecked(&mut self, index: usize) -> &mut Data {
-        let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(index);
+    pub unsafe fn get_mut_unchecked(&mut self, key: K) -> &mut Data {
+        let level_indices = level_indices::<Levels::Mask, Levels::LevelCount>(Self::key_index(key));
         let data_block_index = self.fetch_block_index(level_indices);
         debug_assert!(data_block_index != 0);
         self.values.get_unchecked_mut(data_block_index)
@@ -535,12 +1065,94 @@ where
     // TODO: concrete type in return
     /// Return keys and values as contiguous array iterator. 
     #[inline]
-    pub fn unordered_iter(&self) -> impl ExactSizeIterator<Item = (usize, &Data)>{
-        self.keys[1..].iter().copied().zip(
+    pub fn unordered_iter(&self) -> impl ExactSizeIterator<Item = (K, &Data)>{
+        self.keys[1..].iter().map(|&index| key_from_index(index)).zip(
             self.values[1..].iter()
         )
     }
-    
+
+    /// Fold all items in storage order.
+    /// 
+    /// Linear pass over contiguous storage, without touching hierarchy.
+    /// Prefer over [fold_data] when order does not matter.
+    /// 
+    /// [fold_data]: SparseHierarchy::fold_data
+    #[inline]
+    pub fn fold_data_unordered<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, K, &Data) -> B
+    {
+        self.unordered_iter().fold(init, |acc, (index, data)| f(acc, index, data))
+    }
+
+    /// Ordered keys iterator.
+    ///
+    /// Walks hierarchy blocks only, data is never touched.
+    #[inline]
+    pub fn keys(&self) -> KeysIter<'_, Self> {
+        KeysIter::new(self)
+    }
+
+    /// [iter()] with `usize` indices converted to `K`.
+    ///
+    /// [iter()]: SparseHierarchy::iter
+    #[inline]
+    pub fn iter_keyed(&self) -> impl ExactSizeIterator<Item = (K, &Data)> {
+        self.iter().map(|(index, data)| (key_from_index(index), data))
+    }
+
+    /// Number of stored elements. O(1).
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len() - 1
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Terminal blocks iterator.
+    ///
+    /// Returns `(base_index, mask, data_block)` for each allocated terminal block.
+    /// Element at `base_index + lane` is present if `mask` have `lane` bit set.
+    ///
+    /// Values are not stored per-block, so lanes are accessed through [DataBlock].
+    /// Gather present lanes into local buffer to run vectorized kernel over them.
+    #[inline]
+    pub fn data_blocks(&self)
+        -> impl Iterator<Item = (usize, &Levels::Mask, DataBlock<'_, Levels, Data, K>)>
+    {
+        let mut blocks = BlockIter::new(self);
+        std::iter::from_fn(move || {
+            let mask = blocks.next()?;
+            let block_ptr = unsafe{ blocks.state().terminal_block_ptr(self) };
+            let data_block = DataBlock{ array: self, block_ptr };
+            Some((blocks.base_index(), mask, data_block))
+        })
+    }
+
+    /// Mutable version of [data_blocks()].
+    ///
+    /// [data_blocks()]: Self::data_blocks
+    #[inline]
+    pub fn data_blocks_mut(&mut self)
+        -> impl Iterator<Item = (usize, &Levels::Mask, DataBlockMut<'_, Levels, Data, K>)>
+    {
+        // Hierarchy traversal never touches values - so we can hand out
+        // mutable access to them, while traversing.
+        // Each value belongs to exactly one terminal block lane.
+        let values = self.values.as_mut_ptr();
+        let this: &Self = self;
+        let mut blocks = BlockIter::new(this);
+        std::iter::from_fn(move || {
+            let mask = blocks.next()?;
+            let block_ptr = unsafe{ blocks.state().terminal_block_ptr(this) };
+            let data_block = DataBlockMut{ array: this, values, block_ptr };
+            Some((blocks.base_index(), mask, data_block))
+        })
+    }
+
     /*#[inline]
     pub fn keys(&self) -> &[usize]{
         &self.keys[1..]
@@ -558,10 +1170,351 @@ where
 }
 
 
-impl<Levels, Data> SparseHierarchy for SparseArray<Levels, Data>
+/// Level, as seen by [SparseArray::dump()] / [SparseArray::validate()].
+pub(crate) struct LevelInfo {
+    blocks_len: usize,
+    block_size: usize,
+    /// Block 0 of non-root level must stay empty.
+    zero_block_empty: bool,
+    /// (block index, is empty)
+    free_blocks: Vec<(usize, bool)>,
+    /// Reachable blocks, in walk order.
+    pub(crate) blocks: Vec<BlockInfo>,
+}
+
+/// Block, as seen by [SparseArray::dump()] / [SparseArray::validate()].
+pub(crate) struct BlockInfo {
+    block_index: usize,
+    /// Index prefix, formed by level indices of the path to this block.
+    prefix: usize,
+    /// Mask words, least significant first.
+    /// `None` if `block_index` does not point to a live block storage.
+    mask: Option<Vec<usize>>,
+    /// (in-block index, child block index / data index)
+    pub(crate) children: Vec<(usize, usize)>,
+    /// Non-zero children of unset mask bits.
+    stray_children: Vec<(usize, usize)>,
+}
+
+/// Mask as hex words, most significant first.
+fn fmt_mask<M: BitBlock>(words: &[usize]) -> String {
+    let width = crate::bit_block::word_bits::<M>() / 4;
+    let words: Vec<String> = words.iter().rev()
+        .map(|word| format!("{word:0width$x}"))
+        .collect();
+    format!("0x{}", words.join("_"))
+}
+
+impl<Levels, Data, K> SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{
+    /// Walk hierarchy level by level. Block indices are bounds-checked,
+    /// so this is safe to call on a corrupted container.
+    pub(crate) fn walk_levels(&self) -> Vec<LevelInfo> {
+        struct LevelInfoV(Vec<(usize, usize)>);
+        impl<M> Visitor<M> for LevelInfoV {
+            type Out = LevelInfo;
+            fn visit<I: ConstInteger, L>(self, i: I, level: &L) -> LevelInfo
+            where 
+                L: ILevel<Block: HiBlock> 
+            {
+                let blocks_len = level.blocks_len();
+                let mut free_blocks = Vec::new();
+                level.for_each_free_block(|block_index, empty| free_blocks.push((block_index, empty)));
+                let zero_block_empty = i.value() == 0 
+                    || unsafe{ level.block_unchecked(0) }.mask().is_zero();
+                
+                let mut blocks = Vec::new();
+                for (block_index, prefix) in self.0 {
+                    let mut info = BlockInfo{
+                        block_index, prefix, mask: None, 
+                        children: Vec::new(), stray_children: Vec::new()
+                    };
+                    // Only root level may use block 0.
+                    if block_index < blocks_len && (i.value() == 0 || block_index != 0) {
+                        let block = unsafe{ level.block_unchecked(block_index) };
+                        let mask = block.mask();
+                        for index in 0..<L::Block as HiBlock>::Mask::SIZE {
+                            let child = unsafe{ block.get_or_zero(index) }.as_usize();
+                            if mask.get_bit(index) {
+                                info.children.push((index, child));
+                            } else if child != 0 {
+                                info.stray_children.push((index, child));
+                            }
+                        }
+                        info.mask = Some(
+                            mask.as_array().as_ref().iter().map(|w| w.as_usize()).collect()
+                        );
+                    }
+                    blocks.push(info);
+                }
+                let block_size = std::mem::size_of::<L::Block>();
+                LevelInfo{ blocks_len, block_size, zero_block_empty, free_blocks, blocks }
+            }
+        }
+        
+        struct LevelsV<'a, Levels>{
+            levels: &'a Levels,
+            frontier: Vec<(usize, usize)>,
+            out: &'a mut Vec<LevelInfo>,
+        }
+        impl<'a, Levels: SparseArrayLevels> ConstIntVisitor for LevelsV<'a, Levels> {
+            type Out = ();
+            fn visit<I: ConstInteger>(&mut self, i: I) -> ControlFlow<()> {
+                let frontier = std::mem::take(&mut self.frontier);
+                let level = self.levels.visit(i, LevelInfoV(frontier));
+                for block in &level.blocks {
+                    for &(index, child) in &block.children {
+                        let prefix = block.prefix.wrapping_mul(Levels::Mask::SIZE) + index;
+                        self.frontier.push((child, prefix));
+                    }
+                }
+                self.out.push(level);
+                Continue(())
+            }
+        }
+        
+        let mut out = Vec::new();
+        let _ = const_for(ConstUsize::<0>, Levels::LevelCount::default(), LevelsV{
+            levels: &self.levels,
+            frontier: vec![(0, 0)],
+            out: &mut out,
+        });
+        out
+    }
+    
+    /// Exhaustively checks container structure invariants.
+    /// 
+    /// Checks that set mask bits and only them have live children, 
+    /// each block and data item is pointed exactly once, keys and 
+    /// back-links of data items match their hierarchy position, 
+    /// and free blocks are empty and unreachable.
+    /// 
+    /// O(N) of allocated blocks. Intended for tests, fuzzing and debugging -
+    /// container should never fail validation, unless memory was corrupted,
+    /// or there is a bug.
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        use InvariantViolation::*;
+        
+        let values_len = self.values.len();
+        if self.keys.len() != values_len || self.last_level_block_indices.len() != values_len {
+            return Err(LengthMismatch{
+                values: values_len, 
+                keys: self.keys.len(), 
+                back_links: self.last_level_block_indices.len()
+            });
+        }
+        
+        let levels = self.walk_levels();
+        let last_level = levels.len() - 1;
+        let mut data_used = vec![false; values_len];
+        for (level, info) in levels.iter().enumerate() {
+            if !info.zero_block_empty {
+                return Err(ZeroBlockNotEmpty{ level });
+            }
+            
+            let mut used = vec![false; info.blocks_len];
+            for block in &info.blocks {
+                let block_index = block.block_index;
+                // Invalid block indices are reported by parent.
+                debug_assert!(block.mask.is_some());
+                if std::mem::replace(&mut used[block_index], true) {
+                    return Err(SharedBlock{ level, block_index });
+                }
+                if level != 0 && block.children.is_empty() {
+                    return Err(EmptyBlockInUse{ level, block_index });
+                }
+                if let Some(&(in_block_index, child)) = block.stray_children.first() {
+                    return Err(UnexpectedChild{ level, block_index, in_block_index, child });
+                }
+                
+                let children_len = if level == last_level {
+                    values_len
+                } else {
+                    levels[level + 1].blocks_len
+                };
+                for &(in_block_index, child) in &block.children {
+                    if child == 0 {
+                        return Err(MissingChild{ level, block_index, in_block_index });
+                    }
+                    if child >= children_len {
+                        return Err(ChildOutOfRange{ level, block_index, in_block_index, child });
+                    }
+                    if level != last_level {
+                        continue;
+                    }
+                    
+                    let data_index = child;
+                    if std::mem::replace(&mut data_used[data_index], true) {
+                        return Err(SharedData{ data_index });
+                    }
+                    let key = block.prefix.wrapping_mul(Levels::Mask::SIZE) + in_block_index;
+                    let stored_key = self.keys[data_index];
+                    if stored_key != key {
+                        return Err(KeyMismatch{ data_index, key, stored_key });
+                    }
+                    if self.last_level_block_indices[data_index] != (block_index, in_block_index) {
+                        return Err(BackLinkMismatch{ data_index });
+                    }
+                }
+            }
+            
+            let mut free = vec![false; info.blocks_len];
+            for &(block_index, empty) in &info.free_blocks {
+                if block_index == 0 || block_index >= info.blocks_len 
+                    || std::mem::replace(&mut free[block_index], true) 
+                {
+                    return Err(FreeListCorrupted{ level, block_index });
+                }
+                if used[block_index] {
+                    return Err(FreeBlockInUse{ level, block_index });
+                }
+                if !empty {
+                    return Err(FreeBlockNotEmpty{ level, block_index });
+                }
+            }
+            
+            for block_index in 1..info.blocks_len {
+                if !used[block_index] && !free[block_index] {
+                    return Err(LostBlock{ level, block_index });
+                }
+            }
+        }
+        
+        if let Some(data_index) = (1..values_len).find(|&i| !data_used[i]) {
+            return Err(UnreachableData{ data_index });
+        }
+        Ok(())
+    }
+
+    /// Memory and shape statistics.
+    /// 
+    /// Walks all blocks in use - O(N).
+    pub fn stats(&self) -> Stats {
+        use std::mem::size_of;
+        
+        let levels = self.walk_levels().into_iter().map(|level| {
+            let children = level.blocks.iter().map(|block| block.children.len());
+            let total_children: usize = children.clone().sum();
+            let blocks = level.blocks.len();
+            LevelStats{
+                blocks,
+                allocated_blocks: level.blocks_len,
+                avg_children: if blocks == 0 { 0.0 } else { total_children as f64 / blocks as f64 },
+                max_children: children.max().unwrap_or(0),
+                bytes: level.blocks_len * level.block_size,
+            }
+        }).collect();
+        
+        let data_bytes = 
+              self.values.capacity() * size_of::<Data>()
+            + self.keys.capacity() * size_of::<usize>()
+            + self.last_level_block_indices.capacity() * size_of::<(usize, usize)>();
+        Stats{ len: self.len(), levels, data_bytes }
+    }
+    
+    /// Describe data item `data_index`, pointed by `key`. 
+    /// Reports out-of-range data index and key mismatch.
+    fn dump_data(&self, key: usize, data_index: usize) -> String {
+        match self.keys.get(data_index) {
+            _ if data_index == 0 => format!("data {data_index} (invalid)"),
+            None => format!("data {data_index} (out of range)"),
+            Some(&stored) if stored != key => format!("data {data_index} (stored key {stored})"),
+            Some(_) => format!("data {data_index}"),
+        }
+    }
+    
+    /// Writes hierarchy structure, level by level.
+    /// 
+    /// For each block prints its index, mask, and children - child block 
+    /// indices, or keys with data indices for the last level. 
+    /// Structure inconsistencies are marked in place, instead of panic.
+    /// 
+    /// Intended for debugging.
+    /// 
+    /// ```
+    /// # use hi_sparse_array::{config, SparseArray};
+    /// let mut a: SparseArray<config::width_64::depth_2, Option<u32>> = Default::default();
+    /// a.insert(3, Some(30));
+    /// a.insert(200, Some(2000));
+    /// let mut s = String::new();
+    /// a.dump(&mut s).unwrap();
+    /// assert!(s.contains("key 200 -> data 2"));
+    /// ```
+    pub fn dump(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let levels = self.walk_levels();
+        let last_level = levels.len() - 1;
+        for (level_n, level) in levels.iter().enumerate() {
+            writeln!(w, "level {level_n}:")?;
+            for block in &level.blocks {
+                let Some(mask) = &block.mask else {
+                    writeln!(w, "  block {}: invalid block index", block.block_index)?;
+                    continue;
+                };
+                let mask = fmt_mask::<Levels::Mask>(mask);
+                writeln!(w, "  block {}: mask {mask}, {} children", block.block_index, block.children.len())?;
+                for &(index, child) in &block.children {
+                    if level_n == last_level {
+                        let key = block.prefix.wrapping_mul(Levels::Mask::SIZE) + index;
+                        writeln!(w, "    key {key} -> {}", self.dump_data(key, child))?;
+                    } else {
+                        writeln!(w, "    {index} -> block {child}")?;
+                    }
+                }
+                for &(index, child) in &block.stray_children {
+                    writeln!(w, "    {index} -> {child} (mask bit not set)")?;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Hierarchy structure as Graphviz DOT graph.
+    /// 
+    /// Same information as [dump()], for visualization of small containers.
+    /// 
+    /// [dump()]: Self::dump
+    pub fn to_dot(&self) -> String {
+        use fmt::Write;
+        
+        let levels = self.walk_levels();
+        let last_level = levels.len() - 1;
+        let mut s = String::new();
+        s += "digraph SparseArray {\n";
+        s += "    node [shape=box, fontname=monospace];\n";
+        for (level_n, level) in levels.iter().enumerate() {
+            for block in &level.blocks {
+                let node = format!("L{level_n}_{}", block.block_index);
+                let mask = match &block.mask {
+                    Some(mask) => fmt_mask::<Levels::Mask>(mask),
+                    None => "invalid block index".into(),
+                };
+                let _ = writeln!(s, "    {node} [label=\"L{level_n} #{}\\n{mask}\"];", block.block_index);
+                for &(index, child) in &block.children {
+                    if level_n == last_level {
+                        let key = block.prefix.wrapping_mul(Levels::Mask::SIZE) + index;
+                        let _ = writeln!(s, "    D{child} [label=\"{}\", shape=ellipse];", self.dump_data(key, child));
+                        let _ = writeln!(s, "    {node} -> D{child} [label=\"{key}\"];");
+                    } else {
+                        let _ = writeln!(s, "    {node} -> L{}_{child} [label=\"{index}\"];", level_n + 1);
+                    }
+                }
+            }
+        }
+        s += "}\n";
+        s
+    }
+}
+
+
+impl<Levels, Data, K> SparseHierarchy for SparseArray<Levels, Data, K>
 where
     Levels: SparseArrayLevels,
-    Data: Empty
+    Data: Empty,
+    K: Key,
 {
     const EXACT_HIERARCHY: bool = false;
     
@@ -584,11 +1537,25 @@ where
         let data_block_index = self.fetch_block_index(level_indices);
         self.values.get_unchecked(data_block_index)
     }
+    
+    /// Same as [len()] - O(1). All stored items are counted, even
+    /// empty ones.
+    /// 
+    /// [len()]: Self::len
+    #[inline]
+    fn count_keys(&self) -> usize {
+        self.len()
+    }
 
-    type State = SparseArrayState<Levels, Data>;
+    #[inline]
+    fn iter_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    type State = SparseArrayState<Levels, Data, K>;
 }
 
-pub struct SparseArrayState<Levels, Data>
+pub struct SparseArrayState<Levels, Data, K = usize>
 where
     Levels: SparseArrayLevels
 {
@@ -599,15 +1566,44 @@ where
         *const u8, 
         <Levels::LevelCount as ConstInteger>::Dec
     >,
-    phantom_data: PhantomData<SparseArray<Levels, Data>>
+    phantom_data: PhantomData<SparseArray<Levels, Data, K>>
 }
 
-impl<Levels, Data> SparseHierarchyState for SparseArrayState<Levels, Data>
+// Block pointers address blocks of the array state was created for, 
+// and are dereferenced only for reading, through `&SparseArray` passed 
+// alongside. So state is thread-safe as much as `&SparseArray` is.
+unsafe impl<Levels, Data, K> Send for SparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    SparseArray<Levels, Data, K>: Sync
+{}
+
+unsafe impl<Levels, Data, K> Sync for SparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    SparseArray<Levels, Data, K>: Sync
+{}
+
+impl<Levels, Data, K> Clone for SparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            level_block_ptrs: Array::from_fn(|i| self.level_block_ptrs.as_ref()[i]),
+            phantom_data: PhantomData
+        }
+    }
+}
+
+impl<Levels, Data, K> SparseHierarchyState for SparseArrayState<Levels, Data, K>
 where
     Levels: SparseArrayLevels,
     Data: Empty,
+    K: Key,
 {
-    type This = SparseArray<Levels, Data>;
+    type This = SparseArray<Levels, Data, K>;
 
     #[inline]
     fn new(_: &Self::This) -> Self {
@@ -655,23 +1651,169 @@ where
         -> <Self::This as SparseHierarchy>::Data<'a> 
     {
         let last_level_index = Levels::LevelCount::default().dec();
-        
-        let level_block_ptr = 
-            if Levels::LevelCount::VALUE == 1{
-                this.get_block_ptr(ConstUsize::<0>, 0)
-            } else {
-                // We do not store the root level's block.
-                let level_block_ptrs_index = last_level_index.dec();
-                let level_block_ptr = *self.level_block_ptrs.as_ref()
-                                      .get_unchecked(level_block_ptrs_index.value());
-                level_block_ptr
-            };
-        
+        let level_block_ptr = self.terminal_block_ptr(this);
         let data_block_index = this.get_block_index(last_level_index, level_block_ptr, level_index);
         this.values.get_unchecked(data_block_index)
     }
+
+    #[cfg(feature = "prefetch")]
+    #[inline(always)]
+    unsafe fn prefetch_data_block(&self, this: &Self::This, level_index: usize) {
+        let last_level_index = Levels::LevelCount::default().dec();
+        let level_block_ptr = self.terminal_block_ptr(this);
+        let data_block_index = this.get_block_index(last_level_index, level_block_ptr, level_index);
+        crate::utils::prefetch_read(this.values.as_ptr().add(data_block_index));
+    }
+}
+
+impl<Levels, Data, K> SparseArrayState<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{
+    /// # Safety
+    ///
+    /// Terminal block must be selected.
+    #[inline(always)]
+    unsafe fn terminal_block_ptr(&self, this: &SparseArray<Levels, Data, K>) -> *const u8 {
+        if Levels::LevelCount::VALUE == 1{
+            this.get_block_ptr(ConstUsize::<0>, 0)
+        } else {
+            // We do not store the root level's block.
+            let level_block_ptrs_index = Levels::LevelCount::default().dec().dec();
+            *self.level_block_ptrs.as_ref()
+                .get_unchecked(level_block_ptrs_index.value())
+        }
+    }
+}
+
+/// Terminal block data, produced by [SparseArray::data_blocks].
+pub struct DataBlock<'a, Levels, Data, K = usize>{
+    array: &'a SparseArray<Levels, Data, K>,
+    block_ptr: *const u8,
+}
+
+// Read-only view into `array` - same as `&SparseArray`.
+unsafe impl<'a, Levels, Data, K> Send for DataBlock<'a, Levels, Data, K>
+where
+    SparseArray<Levels, Data, K>: Sync
+{}
+
+unsafe impl<'a, Levels, Data, K> Sync for DataBlock<'a, Levels, Data, K>
+where
+    SparseArray<Levels, Data, K>: Sync
+{}
+
+impl<'a, Levels, Data, K> DataBlock<'a, Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{
+    /// Returns [Empty] data, if `lane` is not present in block mask.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lane` is outside block.
+    #[inline]
+    pub fn get(&self, lane: usize) -> &'a Data {
+        assert!(lane < Levels::Mask::SIZE);
+        unsafe{
+            let last_level_index = Levels::LevelCount::default().dec();
+            let data_block_index = self.array.get_block_index(last_level_index, self.block_ptr, lane);
+            self.array.values.get_unchecked(data_block_index)
+        }
+    }
+}
+
+/// Terminal block mutable data, produced by [SparseArray::data_blocks_mut].
+pub struct DataBlockMut<'a, Levels, Data, K = usize>{
+    array: &'a SparseArray<Levels, Data, K>,
+    values: *mut Data,
+    block_ptr: *const u8,
+}
+
+// Reads levels through `&SparseArray`, and mutates only own block items -
+// different blocks never share items. Like `(&SparseArray, &mut [Data])`.
+unsafe impl<'a, Levels, Data, K> Send for DataBlockMut<'a, Levels, Data, K>
+where
+    SparseArray<Levels, Data, K>: Sync,
+    Data: Send,
+{}
+
+unsafe impl<'a, Levels, Data, K> Sync for DataBlockMut<'a, Levels, Data, K>
+where
+    SparseArray<Levels, Data, K>: Sync,
+    Data: Sync,
+{}
+
+impl<'a, Levels, Data, K> DataBlockMut<'a, Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+    K: Key,
+{
+    /// Returns `None`, if `lane` is not present in block mask.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lane` is outside block.
+    #[inline]
+    pub fn get_mut(&mut self, lane: usize) -> Option<&mut Data> {
+        assert!(lane < Levels::Mask::SIZE);
+        unsafe{
+            let last_level_index = Levels::LevelCount::default().dec();
+            let data_block_index = self.array.get_block_index(last_level_index, self.block_ptr, lane);
+            if data_block_index == 0 {
+                return None;
+            }
+            Some(&mut *self.values.add(data_block_index))
+        }
+    }
+}
+
+/// Non-empty items equality. Empty items are treated as absent.
+///
+/// Keys are compared first with [keys_eq], then values of shared keys.
+impl<Levels, Data, K> PartialEq for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + PartialEq,
+    K: Key,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        if !keys_eq(self, other) {
+            return false;
+        }
+        let non_empty = |(_, d): &(usize, &Data)| !d.is_empty();
+        self.iter().filter(non_empty)
+            .zip(other.iter().filter(non_empty))
+            .all(|((_, l), (_, r))| l == r)
+    }
+}
+
+impl<Levels, Data, K> Eq for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Eq,
+    K: Key,
+{}
+
+/// Formats as map of stored items, by index.
+impl<Levels, Data, K> fmt::Debug for SparseArray<Levels, Data, K>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + fmt::Debug,
+    K: Key,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
 }
 
-impl<Levels, Data> Borrowable for SparseArray<Levels, Data>{
-    type Borrowed = SparseArray<Levels, Data>; 
+impl<Levels, Data, K> Borrowable for SparseArray<Levels, Data, K>{
+    type Borrowed = SparseArray<Levels, Data, K>; 
 }
\ No newline at end of file
@@ -44,6 +44,14 @@ pub trait SparseArrayLevels: Default {
     type LevelCount: ConstInteger;
     type Mask: BitBlock;
     
+    /// Max index of [SparseArray] with this configuration.
+    /// 
+    /// See [Index::MAX].
+    /// 
+    /// [SparseArray]: crate::SparseArray
+    /// [Index::MAX]: crate::Index::MAX
+    const MAX_INDEX: usize = crate::Index::<Self::Mask, Self::LevelCount>::MAX;
+    
     fn visit<I: ConstInteger, V: Visitor<Self::Mask>>(&self, i: I, visitor: V) -> V::Out;
     fn visit_mut<I: ConstInteger, V: MutVisitor<Self::Mask>>(&mut self, i: I, visitor: V) -> V::Out;
     
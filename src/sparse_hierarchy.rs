@@ -1,6 +1,8 @@
 use std::borrow::Borrow;
+use std::ops::ControlFlow;
 use crate::{Array, BitBlock};
-use crate::iter::Iter;
+use crate::iter::{BlockIter, CountedIter, IndexedIter, Iter, KeysIter, TerminalBlock};
+use crate::{Index, PointCursor};
 use crate::sparse_array::level_indices;
 use crate::const_utils::const_int::ConstInteger;
 use crate::const_utils::const_array::{ConstArray, ConstArrayType, ConstCopyArrayType};
@@ -47,6 +49,14 @@ pub trait SparseHierarchy: Sized + Borrowable<Borrowed=Self> {
     where
         I: ConstArray<Item=usize> + Copy;
     
+    /// Root level mask.
+    /// 
+    /// Computed without traverse state - cheap even for lazy ops.
+    #[inline]
+    fn root_mask(&self) -> Self::LevelMask<'_> {
+        unsafe{ self.level_mask([]) }
+    }
+    
     type DataType: Empty;
     type Data<'a>: Borrow<Self::DataType> + Take<Self::DataType>
         where Self: 'a;
@@ -94,7 +104,7 @@ pub trait SparseHierarchy: Sized + Borrowable<Borrowed=Self> {
         if Self::EXACT_HIERARCHY {
             self.may_contain_unchecked(index)
         } else {
-            self.get_unchecked(index).borrow().is_empty()
+            !self.get_unchecked(index).borrow().is_empty()
         }
     }
     
@@ -130,21 +140,272 @@ pub trait SparseHierarchy: Sized + Borrowable<Borrowed=Self> {
         unsafe{ self.get_unchecked(index) }
     }    
     
+    /// [get] with pre-validated `index` - no range check.
+    /// 
+    /// [get]: Self::get
+    #[inline]
+    fn get_at(&self, index: Index<Self::LevelMaskType, Self::LevelCount>) -> Self::Data<'_>{
+        unsafe{ self.get_unchecked(index.value()) }
+    }
+    
     #[inline]
     fn iter(&self) -> Iter<Self>{
         Iter::new(self)
     }
     
+    /// [iter()] with keys as [Index]es.
+    /// 
+    /// Returned indices are valid by construction - pass them to
+    /// [get_at()] and alike to skip range validation.
+    /// 
+    /// [iter()]: Self::iter
+    /// [get_at()]: Self::get_at
+    #[inline]
+    fn iter_indexed(&self) -> IndexedIter<'_, Self>{
+        IndexedIter::new(self)
+    }
+    
+    /// Cursor for a series of [get]-like queries.
+    /// 
+    /// Re-selects only hierarchy levels, that changed since the previous
+    /// query - much faster than [get] for sorted or clustered queries.
+    /// 
+    /// [get]: Self::get
+    #[inline]
+    fn point_cursor(&self) -> PointCursor<'_, Self>{
+        PointCursor::new(self)
+    }
+    
+    /// Ordered keys iterator.
+    /// 
+    /// Same keys as [iter()] returns. Items are never constructed
+    /// for [EXACT_HIERARCHY].
+    /// 
+    /// [iter()]: Self::iter
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn keys(&self) -> KeysIter<'_, Self>{
+        KeysIter::new(self)
+    }
+    
+    /// Number of items [iter()] yields, if known without traversal.
+    /// 
+    /// When `Some`, [iter()] yields every raised terminal mask bit - 
+    /// without checking items for emptiness - and reports exact
+    /// [size_hint()]. Containers override this.
+    /// 
+    /// [iter()]: Self::iter
+    /// [size_hint()]: Iterator::size_hint
+    #[inline]
+    fn iter_len(&self) -> Option<usize> {
+        None
+    }
+
+    /// [iter()] with exact [size_hint()], and [ExactSizeIterator] impl.
+    /// 
+    /// Length is taken from [count_keys()] upfront - which is O(1) for
+    /// containers, and masks-only traversal for [EXACT_HIERARCHY]
+    /// lazy ones.
+    /// 
+    /// [iter()]: Self::iter
+    /// [size_hint()]: Iterator::size_hint
+    /// [count_keys()]: Self::count_keys
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn iter_counted(&self) -> CountedIter<'_, Self>{
+        CountedIter::new(self)
+    }
+    
+    /// Number of keys - items [iter()] yields.
+    /// 
+    /// If [EXACT_HIERARCHY] - counts terminal masks bits, without 
+    /// touching data. Each raised bit is a key, even if its item is empty -
+    /// containers count stored empty items too.
+    /// Otherwise - checks each item for emptiness.
+    /// 
+    /// [iter()]: Self::iter
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn count_keys(&self) -> usize {
+        let mut blocks = BlockIter::new(self);
+        let mut count = 0;
+        while let Some(mask) = blocks.next() {
+            if Self::EXACT_HIERARCHY {
+                count += mask.borrow().count_ones();
+            } else {
+                let _: ControlFlow<()> = mask.borrow().traverse_bits(|level_index| {
+                    let data = unsafe{ blocks.data_block(level_index) };
+                    if !data.borrow().is_empty() {
+                        count += 1;
+                    }
+                    ControlFlow::Continue(())
+                });
+            }
+        }
+        count
+    }
+    
+    /// Upper bound of [count_keys()].
+    /// 
+    /// Sums terminal masks bits, never touching data. Exact for 
+    /// [EXACT_HIERARCHY]. Cheap way to decide whether resolving items
+    /// worth it, or how much to reserve.
+    /// 
+    /// [count_keys()]: Self::count_keys
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn estimate_max_len(&self) -> usize {
+        BlockIter::new(self)
+            .map(|mask| mask.borrow().count_ones())
+            .sum()
+    }
+    
+    /// [estimate_max_len()], that stops as soon as it exceeds `limit`.
+    /// 
+    /// Returns `None` if upper bound exceeds `limit`.
+    /// 
+    /// [estimate_max_len()]: Self::estimate_max_len
+    #[inline]
+    fn bounded_len(&self, limit: usize) -> Option<usize> {
+        let mut len = 0;
+        for mask in BlockIter::new(self) {
+            len += mask.borrow().count_ones();
+            if len > limit {
+                return None;
+            }
+        }
+        Some(len)
+    }
+    
+    /// Fold all non-empty items in index order.
+    /// 
+    /// Walks terminal masks block by block, which is faster than 
+    /// folding [iter()]. Empty items of non-[EXACT_HIERARCHY] are skipped.
+    /// 
+    /// [iter()]: Self::iter
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn fold_data<'a, B, F>(&'a self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, usize, Self::Data<'a>) -> B
+    {
+        let mut blocks = BlockIter::new(self);
+        let mut acc = init;
+        while let Some(mask) = blocks.next() {
+            for level_index in mask.take_or_clone().into_bits_iter() {
+                let data = unsafe{ blocks.data_block(level_index) };
+                if !Self::EXACT_HIERARCHY && data.borrow().is_empty() {
+                    continue;
+                }
+                acc = f(acc, blocks.index(level_index), data);
+            }
+        }
+        acc
+    }
+    
+    /// Call `f` for each non-empty item in index order, until it returns
+    /// [Break].
+    /// 
+    /// Returns `Some` with [Break] value, if traverse was interrupted.
+    /// Internal iteration - usually faster than [iter()].
+    /// Empty items of non-[EXACT_HIERARCHY] are skipped.
+    /// 
+    /// [Break]: ControlFlow::Break
+    /// [iter()]: Self::iter
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn traverse<'a, B, F>(&'a self, mut f: F) -> Option<B>
+    where
+        F: FnMut(usize, Self::Data<'a>) -> ControlFlow<B>
+    {
+        let mut blocks = BlockIter::new(self);
+        while let Some(mask) = blocks.next() {
+            let ctrl = mask.borrow().traverse_bits(|level_index| {
+                let data = unsafe{ blocks.data_block(level_index) };
+                if !Self::EXACT_HIERARCHY && data.borrow().is_empty() {
+                    return ControlFlow::Continue(());
+                }
+                f(blocks.index(level_index), data)
+            });
+            if let ControlFlow::Break(b) = ctrl {
+                return Some(b);
+            }
+        }
+        None
+    }
+    
+    /// Call `f` with `(base_index, mask, block)` for each terminal block.
+    /// 
+    /// Item with index `base_index + lane` is accessible through `block`,
+    /// if `mask` have `lane` bit raised. Process all mask lanes at once,
+    /// to avoid per-item iteration overhead - e.g. with vectorized kernel.
+    /// 
+    /// For non-[EXACT_HIERARCHY], masks may contain empty items
+    /// (and even be empty).
+    /// 
+    /// [EXACT_HIERARCHY]: Self::EXACT_HIERARCHY
+    #[inline]
+    fn for_each_block<'a, F>(&'a self, mut f: F)
+    where
+        F: FnMut(usize, &Self::LevelMaskType, TerminalBlock<'_, 'a, Self>)
+    {
+        let mut blocks = BlockIter::new(self);
+        while let Some(mask) = blocks.next() {
+            f(blocks.base_index(), mask.borrow(), TerminalBlock{ blocks: &blocks });
+        }
+    }
+    
+    /// First non-empty item, that satisfies `pred`.
+    /// 
+    /// Stops traverse as soon as item found.
+    #[inline]
+    fn find_first<P>(&self, mut pred: P) -> Option<(usize, Self::Data<'_>)>
+    where
+        P: FnMut(usize, &Self::DataType) -> bool
+    {
+        self.iter().find(|(index, data)| {
+            let data = data.borrow();
+            (Self::EXACT_HIERARCHY || !data.is_empty()) && pred(*index, data)
+        })
+    }
+    
+    /// Is there any non-empty item, that satisfies `pred`?
+    /// 
+    /// Stops traverse at the first match.
+    #[inline]
+    fn any<P>(&self, pred: P) -> bool
+    where
+        P: FnMut(usize, &Self::DataType) -> bool
+    {
+        self.find_first(pred).is_some()
+    }
+    
+    /// Do all non-empty items satisfy `pred`?
+    /// 
+    /// Stops traverse at the first mismatch. `true` for empty hierarchy.
+    #[inline]
+    fn all<P>(&self, mut pred: P) -> bool
+    where
+        P: FnMut(usize, &Self::DataType) -> bool
+    {
+        !self.any(|index, data| !pred(index, data))
+    }
+    
     /// Use [DefaultHierarchyState] as default, if you don't want to implement 
     /// stateful SparseHierarchy.
     type State: SparseHierarchyState<This = Self>;
     
     /// Max index this SparseHierarchy can contain.
     /// 
-    /// Act as `const` - noop.
+    /// Derived from `(LevelMaskType, LevelCount)` - see [Index::MAX].
+    const MAX_INDEX: usize = Index::<Self::LevelMaskType, Self::LevelCount>::MAX;
+    
+    /// Same as [MAX_INDEX].
+    /// 
+    /// [MAX_INDEX]: Self::MAX_INDEX
     #[inline]
-    /*const*/ fn max_range() -> usize {
-        Self::LevelMaskType::SIZE.pow(Self::LevelCount::VALUE as _) - 1 
+    fn max_range() -> usize {
+        Self::MAX_INDEX
     }
 }
 
@@ -204,6 +465,23 @@ pub trait SparseHierarchyState{
         this: &'a Self::This,
         level_index: usize
     ) -> <Self::This as SparseHierarchy>::Data<'a>;    
+    
+    /// Hint, that [data_block] at `level_index` will be accessed soon.
+    /// 
+    /// Containers may issue software prefetch of item's memory. 
+    /// Does nothing by default.
+    /// 
+    /// # Safety
+    /// 
+    /// Same as for [data_block].
+    /// 
+    /// [data_block]: Self::data_block
+    #[inline(always)]
+    unsafe fn prefetch_data_block(
+        &self,
+        _this: &Self::This,
+        _level_index: usize
+    ) {}
 }
 
 /// [SparseHierarchyState] that use [SparseHierarchy] stateless methods.
@@ -218,6 +496,15 @@ where
     >
 }
 
+impl<This: SparseHierarchy> Clone for DefaultHierarchyState<This>{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self{
+            level_indices: Array::from_fn(|i| self.level_indices.as_ref()[i])
+        }
+    }
+}
+
 impl<This: SparseHierarchy> SparseHierarchyState for DefaultHierarchyState<This>{
     type This = This;
 
@@ -264,3 +551,614 @@ impl<This: SparseHierarchy> SparseHierarchyState for DefaultHierarchyState<This>
         this.data_block(indices)
     }
 }
+
+#[cfg(test)]
+mod test{
+    use rand::{Rng, SeedableRng};
+    use crate::{config, intersection, SparseArray, union};
+    use crate::test_utils::Data;
+    use super::*;
+
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+    
+    /// Two arrays with 4000 random keys each in `0..max`, valued `key + 1`.
+    fn random_pair(rng: &mut impl Rng, max: usize) -> (Array, Array) {
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        for _ in 0..4000 {
+            let v = rng.gen_range(0..max);
+            a1.insert(v, Data(v+1));
+            let v = rng.gen_range(0..max);
+            a2.insert(v, Data(v+1));
+        }
+        (a1, a2)
+    }
+
+    #[test]
+    fn count_keys_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (mut a1, mut a2) = random_pair(&mut rng, Array::max_range());
+        assert_eq!(a1.count_keys(), a1.iter().count());
+        
+        // Stored empty items are keys.
+        let len = a1.len();
+        a1.insert(Array::max_range(), Data::empty());
+        a2.insert(Array::max_range(), Data::empty());
+        assert_eq!(a1.count_keys(), len + 1);
+        assert_eq!(a1.count_keys(), a1.iter().count());
+
+        // non-exact
+        let and = intersection(&a1, &a2, |l, r| Data(l.0 + r.0));
+        assert!(and.get(Array::max_range()).is_empty());
+        assert_eq!(
+            and.count_keys(),
+            and.iter().filter(|(_, d)| !d.is_empty()).count()
+        );
+        assert_eq!(and.count_keys(), and.iter().count());
+
+        // exact
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        assert_eq!(or.count_keys(), or.iter().count());
+    }
+    
+    #[test]
+    fn iter_nth_test(){
+        use std::cell::Cell;
+        use crate::map_indexed;
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H, rng: &mut impl Rng){
+            let all: Vec<_> = h.iter().to_pairs();
+            let nth = |iter: &mut Iter<H>, n| 
+                iter.nth(n).map(|(i, d)| (i, d.borrow().clone()));
+            
+            for n in [0, 1, 63, 64, 65, all.len() - 1, all.len(), all.len() + 100] {
+                assert_eq!(nth(&mut h.iter(), n), all.get(n).cloned());
+            }
+            
+            // Pagination
+            let mut iter = h.iter();
+            let mut pos = 0;
+            loop {
+                let n = rng.gen_range(0..300);
+                let item = nth(&mut iter, n);
+                pos += n;
+                assert_eq!(item, all.get(pos).cloned());
+                if item.is_none() {
+                    break;
+                }
+                pos += 1;
+            }
+            
+            let start = all.len() / 3;
+            let page = Iterator::take(h.iter().skip(start), 50).map(|(i, _)| i);
+            let end = (start + 50).min(all.len());
+            assert!(page.eq(all[start..end].iter().map(|(i, _)| *i)));
+        }
+        check(&a1, &mut rng);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        check(&or, &mut rng);
+        
+        // Skipped items are not touched.
+        let calls = Cell::new(0);
+        let lazy = map_indexed(&or, |_, d: &Data| { calls.set(calls.get() + 1); d.clone() });
+        let item = lazy.iter().nth(5000).map(|(i, _)| i);
+        assert_eq!(item, or.iter().nth(5000).map(|(i, _)| i));
+        assert_eq!(calls.get(), 1);
+    }
+    
+    #[test]
+    fn keys_test(){
+        use std::cell::Cell;
+        use itertools::Itertools;
+        use crate::map_indexed;
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy>(h: &H){
+            assert!(SparseHierarchy::keys(h).eq(h.iter().map(|(i, _)| i)));
+            assert!(SparseHierarchy::keys(h).tuple_windows().all(|(l, r)| l < r));
+        }
+        check(&a1);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)));
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        check(&or);
+        
+        let keys = a1.keys();
+        assert_eq!(keys.len(), a1.len());
+        
+        // Exact - items are not constructed.
+        let calls = Cell::new(0);
+        let lazy = map_indexed(&or, |_, d: &Data| { calls.set(calls.get() + 1); d.clone() });
+        assert_eq!(lazy.keys().count(), or.count_keys());
+        assert_eq!(calls.get(), 0);
+    }
+    
+    #[test]
+    fn iter_fold_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H){
+            let mut expected = Vec::new();
+            for (i, d) in h.iter() {
+                expected.push((i, d.borrow().clone()));
+            }
+            
+            let folded = h.iter().fold(Vec::new(), |mut acc, (i, d)| {
+                acc.push((i, d.borrow().clone()));
+                acc
+            });
+            assert_eq!(folded, expected);
+            
+            // Partially consumed.
+            for n in [0, 1, expected.len() / 2] {
+                let mut iter = h.iter();
+                if n != 0 {
+                    iter.nth(n - 1);
+                }
+                let mut rest = Vec::new();
+                iter.for_each(|(i, d)| rest.push((i, d.borrow().clone())));
+                assert_eq!(rest, expected[n..]);
+            }
+        }
+        check(&a1);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)));
+        check(&union(&a1, &a2, |l, r| Data(l.0 + r.0)));
+    }
+    
+    #[test]
+    fn for_each_block_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H){
+            let mut items = Vec::new();
+            h.for_each_block(|base_index, mask, block| {
+                assert_eq!(base_index % H::LevelMaskType::SIZE, 0);
+                let _: ControlFlow<()> = mask.traverse_bits(|lane| {
+                    let data = block.get(lane);
+                    if !data.borrow().is_empty() {
+                        items.push((base_index + lane, data.borrow().clone()));
+                    }
+                    ControlFlow::Continue(())
+                });
+            });
+            assert!(items.into_iter().eq(h.iter().map(|(i, d)| (i, d.borrow().clone()))));
+        }
+        check(&a1);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)));
+        check(&union(&a1, &a2, |l, r| Data(l.0 + r.0)));
+    }
+    
+    #[test]
+    fn traverse_test(){
+        use std::cell::Cell;
+        use crate::map_indexed;
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H){
+            let all: Vec<_> = h.iter().to_pairs();
+            
+            let mut visited = Vec::new();
+            let res: Option<()> = h.traverse(|i, d| {
+                visited.push((i, d.borrow().clone()));
+                ControlFlow::Continue(())
+            });
+            assert!(res.is_none());
+            assert_eq!(visited, all);
+            
+            // Early exit
+            for n in [0, 1, all.len() / 2, all.len() - 1] {
+                let mut count = 0;
+                let res = h.traverse(|i, _| {
+                    count += 1;
+                    if count == n + 1 { ControlFlow::Break(i) } else { ControlFlow::Continue(()) }
+                });
+                assert_eq!(res, Some(all[n].0));
+                assert_eq!(count, n + 1);
+            }
+        }
+        check(&a1);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)));
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        check(&or);
+        
+        // Nothing past Break is constructed.
+        let calls = Cell::new(0);
+        let lazy = map_indexed(&or, |_, d: &Data| { calls.set(calls.get() + 1); d.clone() });
+        let res = lazy.traverse(|i, _| 
+            if calls.get() == 10 { ControlFlow::Break(i) } else { ControlFlow::Continue(()) }
+        );
+        assert_eq!(res, or.iter().nth(9).map(|(i, _)| i));
+        assert_eq!(calls.get(), 10);
+    }
+    
+    #[test]
+    fn iter_counted_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H){
+            let expected: Vec<_> = h.iter()
+                .filter(|(_, d)| !d.borrow().is_empty())
+                .map(|(i, d)| (i, d.borrow().clone()))
+                .collect();
+            let len = expected.len();
+            assert_eq!(h.count_keys(), len);
+            let mut iter = h.iter_counted();
+            assert_eq!(iter.len(), len);
+            for (n, (i, d)) in expected.into_iter().enumerate() {
+                let (i2, d2) = iter.next().unwrap();
+                assert_eq!((i, &d), (i2, d2.borrow()));
+                assert_eq!(iter.size_hint(), (iter.len(), Some(iter.len())));
+                assert_eq!(n + 1 + iter.len(), len);
+            }
+            assert!(iter.next().is_none());
+        }
+        check(&a1);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)));
+        check(&union(&a1, &a2, |l, r| Data(l.0 + r.0)));
+    }
+    
+    #[test]
+    fn find_first_test(){
+        use std::cell::Cell;
+        
+        let mut a1 = Array::default();
+        let mut a2 = Array::default();
+        let last = Array::max_range();
+        for i in [0, 10, 5000, last] {
+            a1.insert(i, Data(i+1));
+            a2.insert(i, Data(i+1));
+        }
+        a1.insert(100, Data(1));
+        a2.insert(200, Data(1));
+        let and = intersection(&a1, &a2, |l, r| 
+            if l.is_empty() || r.is_empty() { Data(0) } else { Data(l.0 + r.0) }
+        );
+        
+        let calls = Cell::new(0);
+        let count = |_: usize, _: &Data| { calls.set(calls.get() + 1); true };
+        
+        // First block
+        assert_eq!(and.find_first(count), Some((0, Data(2))));
+        assert_eq!(calls.get(), 1);
+        
+        // Last block
+        let found = and.find_first(|i, _| i > 5000);
+        assert_eq!(found, Some((last, Data(2*(last+1)))));
+        assert_eq!(and.find_first(|i, _| i > last), None);
+        
+        // Empty items are never passed to `pred`.
+        assert!(and.all(|_, d| !d.is_empty()));
+        assert!(!and.any(|i, _| i == 100 || i == 200));
+        assert!(and.any(|i, _| i == last));
+        
+        calls.set(0);
+        assert!(!and.all(|i, _| { calls.set(calls.get() + 1); i < 10 }));
+        assert_eq!(calls.get(), 2);
+        
+        assert!(Array::default().all(|_, _| false));
+        assert!(!Array::default().any(|_, _| true));
+    }
+    
+    #[test]
+    fn fold_data_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xf01d_da7a);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        let sum = |h: &Array| h.iter().map(|(_, d)| d.0).sum::<usize>();
+        assert_eq!(a1.fold_data(0, |acc, _, d| acc + d.0), sum(&a1));
+        assert_eq!(a1.fold_data_unordered(0, |acc, _, d| acc + d.0), sum(&a1));
+        
+        // Keys are passed in order.
+        let keys = a1.fold_data(Vec::new(), |mut acc, i, _| { acc.push(i); acc });
+        assert!(keys.iter().copied().eq(a1.iter().map(|(i, _)| i)));
+        
+        // non-exact
+        let and = intersection(&a1, &a2, |l, r| 
+            if l.is_empty() || r.is_empty() { Data(0) } else { Data(l.0 + r.0) }
+        );
+        assert_eq!(
+            and.fold_data(0, |acc, _, d| { assert!(!d.is_empty()); acc + 1 }),
+            and.count_keys()
+        );
+        assert_eq!(
+            and.fold_data(0, |acc, _, d| acc + d.0),
+            and.iter().map(|(_, d)| d.0).sum::<usize>()
+        );
+    }
+    
+    #[test]
+    fn estimate_max_len_test(){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe57_1a7e);
+        let (a1, a2) = random_pair(&mut rng, 10_000);
+        assert_eq!(a1.estimate_max_len(), a1.count_keys());
+        
+        // non-exact
+        let and = intersection(&a1, &a2, |l, r| 
+            if l.is_empty() || r.is_empty() { Data(0) } else { Data(l.0 + r.0) }
+        );
+        let len = and.count_keys();
+        let max_len = and.estimate_max_len();
+        assert!(max_len >= len);
+        assert_eq!(max_len, and.iter().count());
+        
+        // exact
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        assert_eq!(or.estimate_max_len(), or.count_keys());
+        
+        assert_eq!(and.bounded_len(max_len), Some(max_len));
+        assert_eq!(and.bounded_len(max_len - 1), None);
+        assert_eq!(and.bounded_len(0), None);
+        assert_eq!(Array::default().bounded_len(0), Some(0));
+    }
+    
+    #[test]
+    fn iter_resume_test(){
+        use crate::IterPos;
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H, rng: &mut impl Rng){
+            let all: Vec<_> = h.iter().to_pairs();
+            
+            // In chunks
+            let mut resumed = Vec::new();
+            let mut pos = IterPos::from_index(0);
+            loop {
+                let mut iter = Iter::resume_at(h, pos);
+                let n = rng.gen_range(1..300);
+                let len = resumed.len();
+                resumed.extend(
+                    Iterator::take(&mut iter, n).map(|(i, d)| (i, d.borrow().clone()))
+                );
+                pos = iter.position();
+                if resumed.len() - len < n {
+                    break;
+                }
+            }
+            assert_eq!(resumed, all);
+            
+            // From arbitrary keys
+            for _ in 0..100 {
+                let index = rng.gen_range(0..=H::max_range());
+                let expected = all.iter().filter(|(i, _)| *i >= index);
+                let iter = Iter::resume_at(h, IterPos::from_index(index));
+                assert!(iter.map(|(i, d)| (i, d.borrow().clone())).eq(expected.cloned()));
+            }
+            
+            let past_end = Iter::resume_at(h, IterPos::from_index(H::max_range() + 1));
+            assert_eq!(past_end.count(), 0);
+            
+            // Overshooting nth consumes the rest.
+            for skip in [all.len() - 3, all.len(), all.len() + 100] {
+                let mut iter = h.iter();
+                Iterator::take(&mut iter, 3).for_each(drop);
+                assert!(iter.nth(skip).is_none());
+                assert_eq!(Iter::resume_at(h, iter.position()).count(), 0);
+            }
+        }
+        check(&a1, &mut rng);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+        check(&union(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+        
+        fn check_mutation<Levels: crate::SparseArrayLevels>(){
+            type A<Levels> = SparseArray<Levels, Data>;
+            let max = A::<Levels>::max_range();
+            let mut a = A::<Levels>::default();
+            let keys: Vec<_> = (0..=max).step_by(3).collect();
+            for &i in &keys {
+                a.insert(i, Data(i+1));
+            }
+            
+            let mut iter = a.iter();
+            let chunk: Vec<_> = Iterator::take(&mut iter, 10).map(|(i, _)| i).collect();
+            assert_eq!(chunk, keys[..10]);
+            let pos = iter.position();
+            assert_eq!(pos.index(), keys[9] + 1);
+            
+            // Resume point removed, consumed and following items changed.
+            a.remove(keys[10]);
+            a.remove(keys[0]);
+            a.insert(keys[5] + 1, Data(1));
+            a.insert(keys[11] - 1, Data(1));
+            
+            let iter = Iter::resume_at(&a, pos);
+            let rest: Vec<_> = a.iter().map(|(i, _)| i).filter(|&i| i >= pos.index()).collect();
+            assert_eq!(iter.len(), rest.len());
+            assert!(iter.map(|(i, _)| i).eq(rest.iter().copied()));
+            assert_eq!(rest[0], keys[11] - 1);
+            
+            // Whole block removed.
+            let block = Levels::Mask::SIZE;
+            if max < 2*block {
+                return;
+            }
+            for i in block..2*block {
+                a.remove(i);
+            }
+            let iter = Iter::resume_at(&a, IterPos::from_index(block + 1));
+            assert_eq!(iter.len(), a.iter().filter(|(i, _)| *i > block).count());
+            assert_eq!(iter.map(|(i, _)| i).next(), a.iter().map(|(i, _)| i).find(|&i| i > block));
+        }
+        check_mutation::<config::width_64::depth_1>();
+        check_mutation::<config::width_64::depth_2>();
+        check_mutation::<config::width_64::depth_3>();
+    }
+    
+    #[test]
+    fn iter_peek_test(){
+        use std::cell::Cell;
+        use crate::map_indexed;
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let (a1, a2) = random_pair(&mut rng, Array::max_range());
+        
+        fn check<H: SparseHierarchy<DataType = Data>>(h: &H, rng: &mut impl Rng){
+            let all: Vec<_> = h.iter().to_pairs();
+            
+            let mut iter = h.iter();
+            let mut n = 0;
+            loop {
+                let peeks = rng.gen_range(0..3);
+                for _ in 0..peeks {
+                    assert_eq!(iter.peek_index(), all.get(n).map(|(i, _)| *i));
+                    let peeked = iter.peek().map(|(i, d)| (i, d.borrow().clone()));
+                    assert_eq!(peeked.as_ref(), all.get(n));
+                }
+                let pos = iter.position();
+                let item = iter.next().map(|(i, d)| (i, d.borrow().clone()));
+                assert_eq!(item.as_ref(), all.get(n));
+                let Some((i, _)) = item else { break };
+                assert!(pos.index() <= i);
+                n += 1;
+            }
+            assert_eq!(n, all.len());
+            assert_eq!(iter.peek_index(), None);
+            assert_eq!(iter.peek_index(), None);
+        }
+        check(&a1, &mut rng);
+        check(&intersection(&a1, &a2, |l, r| Data(l.0 + r.0)), &mut rng);
+        let or = union(&a1, &a2, |l, r| Data(l.0 + r.0));
+        check(&or, &mut rng);
+        
+        // Exact hierarchy: peek_index does not construct data.
+        let calls = Cell::new(0);
+        let lazy = map_indexed(&or, |_, d: &Data| { calls.set(calls.get() + 1); d.clone() });
+        let mut iter = lazy.iter();
+        for _ in 0..100 {
+            iter.peek_index();
+            iter.next();
+        }
+        assert_eq!(calls.get(), 100);
+        
+        // Peek at block boundary, then next.
+        let mut a = Array::default();
+        for i in 60..70 {
+            a.insert(i, Data(i+1));
+        }
+        let mut iter = a.iter();
+        assert_eq!(iter.nth(3).map(|(i, _)| i), Some(63));
+        assert_eq!(iter.peek_index(), Some(64));
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next().map(|(i, _)| i), Some(64));
+    }
+    
+    #[test]
+    fn iter_clone_test(){
+        use crate::{intersection_fold, map_indexed, range_view};
+        
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let arrays: Vec<_> = (0..3).map(|_| {
+            let mut a = Array::default();
+            for _ in 0..4000 {
+                let v = rng.gen_range(0..50_000);
+                a.insert(v, Data(v+1));
+            }
+            a
+        }).collect();
+        
+        fn check<H>(h: &H)
+        where
+            H: SparseHierarchy<DataType = Data, State: Clone>
+        {
+            let all: Vec<_> = h.iter().to_pairs();
+            let collect = |iter: Iter<H>| -> Vec<_> {
+                iter.map(|(i, d)| (i, d.borrow().clone())).collect()
+            };
+            for n in [0, 1, all.len() / 2, all.len()] {
+                let mut iter = h.iter();
+                if n != 0 {
+                    iter.nth(n - 1);
+                }
+                let fork = iter.clone();
+                // Advancing the fork does not affect the original.
+                let mut lookahead = fork.clone();
+                lookahead.nth(10);
+                assert_eq!(collect(fork), all[n..]);
+                assert_eq!(collect(iter), all[n..]);
+            }
+            
+            let keys = h.keys();
+            assert!(keys.clone().eq(keys));
+            
+            let mut cursor = h.point_cursor();
+            let first = all.first().map(|(i, _)| *i).unwrap_or(0);
+            cursor.get(first);
+            let mut fork = cursor.clone();
+            for (i, d) in &all {
+                assert_eq!(fork.get(*i).map(|d| d.borrow().clone()).as_ref(), Some(d));
+            }
+            assert_eq!(
+                cursor.get(first).map(|d| d.borrow().clone()),
+                all.first().map(|(_, d)| d.clone())
+            );
+        }
+        check(&arrays[0]);
+        let or = union(&arrays[0], &arrays[1], |l, r| Data(l.0 + r.0));
+        check(&or);
+        check(&intersection(&arrays[0], &arrays[1], |l, r| Data(l.0 + r.0)));
+        check(&intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d|
+            if acc.is_empty() || d.is_empty() { Data(0) } else { Data(acc.0 + d.0) }
+        ));
+        check(&map_indexed(range_view(&or, 1000..40_000), |_, d: &Data| d.clone()));
+    }
+    
+    #[test]
+    fn iter_fused_test(){
+        use std::iter::FusedIterator;
+        use crate::{keys_view, map_indexed};
+        
+        fn check<H: SparseHierarchy>(h: &H, len: usize){
+            fn exhaust<I: FusedIterator>(mut iter: I, len: usize){
+                for _ in 0..len {
+                    assert!(iter.next().is_some());
+                }
+                for _ in 0..5 {
+                    assert!(iter.next().is_none());
+                }
+            }
+            exhaust(h.iter(), len);
+            exhaust(h.keys(), len);
+            exhaust(h.iter_indexed(), len);
+            exhaust(h.iter_counted(), len);
+            
+            let mut iter = h.iter();
+            assert!(iter.nth(len).is_none());
+            assert!(iter.next().is_none());
+            assert!(iter.peek_index().is_none());
+            assert!(iter.next().is_none());
+        }
+        
+        let empty = Array::default();
+        check(&empty, 0);
+        let mut single = Array::default();
+        single.insert(Array::max_range(), Data(1));
+        check(&single, 1);
+        let mut a = Array::default();
+        for i in (0..10_000).step_by(7) {
+            a.insert(i, Data(i+1));
+        }
+        check(&a, a.len());
+        
+        let or = union(&a, &single, |l, r| Data(l.0 + r.0));
+        check(&or, a.len() + 1);
+        check(&intersection(&a, &single, |l, r| Data(l.0 + r.0)), 0);
+        check(&map_indexed(&or, |_, d: &Data| d.clone()), a.len() + 1);
+        check(&keys_view(&a), a.len());
+        
+        type Small = SparseArray<config::width_64::depth_1, Data>;
+        check(&Small::default(), 0);
+        let mut small = Small::default();
+        small.insert(63, Data(1));
+        check(&small, 1);
+    }
+}
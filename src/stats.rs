@@ -0,0 +1,73 @@
+use std::fmt;
+
+/// [SparseArray] memory and shape statistics.
+/// 
+/// Returned by [SparseArray::stats()]. Intended for choosing 
+/// configuration empirically, and for spotting memory regressions.
+/// [Display]ed as a compact table.
+/// 
+/// [SparseArray]: crate::SparseArray
+/// [SparseArray::stats()]: crate::SparseArray::stats
+/// [Display]: fmt::Display
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    /// Element count.
+    pub len: usize,
+    /// Per level, from root.
+    pub levels: Vec<LevelStats>,
+    /// Data, keys and their bookkeeping storage capacity, in bytes.
+    /// 
+    /// Heap memory owned by data items themselves is not counted.
+    pub data_bytes: usize,
+}
+
+impl Stats {
+    /// Levels and data storage, in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.levels.iter().map(|level| level.bytes).sum::<usize>()
+            + self.data_bytes
+    }
+    
+    /// [total_bytes()] per element. `0.0` for empty container.
+    /// 
+    /// [total_bytes()]: Self::total_bytes
+    pub fn bytes_per_element(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        self.total_bytes() as f64 / self.len as f64
+    }
+}
+
+/// Single level statistics of [Stats].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelStats {
+    /// Blocks in use.
+    pub blocks: usize,
+    /// Blocks in storage - including free ones, and index-0 empty block.
+    pub allocated_blocks: usize,
+    /// Average children per block in use.
+    pub avg_children: f64,
+    /// Max children per block in use.
+    pub max_children: usize,
+    /// Blocks storage, in bytes. 
+    /// 
+    /// Counts all allocated blocks, by their inline size.
+    pub bytes: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "len {}, {} bytes, {:.1} bytes/element", 
+            self.len, self.total_bytes(), self.bytes_per_element())?;
+        writeln!(f, "{:>5} {:>10} {:>10} {:>8} {:>6} {:>12}", 
+            "level", "blocks", "allocated", "avg", "max", "bytes")?;
+        for (level_n, level) in self.levels.iter().enumerate() {
+            writeln!(f, "{:>5} {:>10} {:>10} {:>8.2} {:>6} {:>12}", 
+                level_n, level.blocks, level.allocated_blocks, 
+                level.avg_children, level.max_children, level.bytes)?;
+        }
+        writeln!(f, "{:>5} {:>10} {:>10} {:>8} {:>6} {:>12}", 
+            "data", self.len, "", "", "", self.data_bytes)
+    }
+}
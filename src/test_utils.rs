@@ -0,0 +1,26 @@
+//! Fixtures shared by unit tests.
+
+use rand::Rng;
+use crate::{config, Empty, SparseArray};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Data(pub usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Up to 1000 items, with indices in `0..10_000`.
+pub fn random_array(rng: &mut impl Rng) -> SparseArray<config::width_64::depth_3, Data> {
+    let mut a = SparseArray::default();
+    for _ in 0..1000 {
+        let v = rng.gen_range(0..10_000);
+        a.insert(v, Data(v + 1));
+    }
+    a
+}
@@ -0,0 +1,164 @@
+//! [proptest] strategies and reference models, for property testing.
+//!
+//! Available with `testing` feature.
+//!
+//! Array depth and width are set by `Levels` type parameter, as usual.
+//! Strategies generate [SparseArray]s by inserting generated items one by one,
+//! so array shrinks together with its items.
+//!
+//! # Example
+//!
+//! ```
+//! # use hi_sparse_array::{config, union};
+//! # use hi_sparse_array::testing::{sparse_array_pair, to_btreemap};
+//! # use proptest::prelude::*;
+//! proptest!(|((a, b) in sparse_array_pair::<config::width_64::depth_3, _, _>(100, 0.5, any::<u32>().prop_map(Some)))| {
+//!     let mut expected = to_btreemap(&b);
+//!     expected.extend(to_btreemap(&a));
+//!     let u = union(&a, &b, |l, r| l.or(*r));
+//!     prop_assert_eq!(to_btreemap(&u), expected);
+//! });
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use proptest::bool::weighted;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use crate::{Empty, SparseArray, SparseArrayLevels, SparseHierarchy};
+
+/// Reference model of [SparseHierarchy] - its non-empty items, by index.
+pub fn to_btreemap<H>(h: &H) -> BTreeMap<usize, H::DataType>
+where
+    H: SparseHierarchy<DataType: Clone>
+{
+    h.iter()
+        .map(|(i, d)| (i, d.borrow().clone()))
+        .filter(|(_, d)| !d.is_empty())
+        .collect()
+}
+
+#[inline]
+fn from_items<Levels, Data>(items: impl IntoIterator<Item = (usize, Data)>) -> SparseArray<Levels, Data>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+{
+    let mut array = SparseArray::default();
+    for (index, value) in items {
+        array.insert(index, value);
+    }
+    array
+}
+
+#[inline]
+fn max_index<Levels, Data>() -> usize
+where
+    Levels: SparseArrayLevels,
+    Data: Empty,
+{
+    SparseArray::<Levels, Data>::max_range()
+}
+
+fn array_in<Levels, Data, S>(keys: RangeInclusive<usize>, max_len: usize, value: S)
+    -> impl Strategy<Value = SparseArray<Levels, Data>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data>,
+{
+    vec((keys, value), 0..=max_len).prop_map(from_items)
+}
+
+fn array_pair_in<Levels, Data, S>(keys: RangeInclusive<usize>, max_len: usize, overlap: f64, value: S)
+    -> impl Strategy<Value = (SparseArray<Levels, Data>, SparseArray<Levels, Data>)>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data> + Clone,
+{
+    let items1 = vec((keys.clone(), value.clone()), 0..=max_len);
+    let items2 = vec((weighted(overlap), any::<Index>(), keys, value), 0..=max_len);
+    (items1, items2).prop_map(|(items1, items2)| {
+        let items2: Vec<_> = items2.into_iter()
+            .map(|(shared, i, key, value)|
+                if shared && !items1.is_empty() {
+                    (items1[i.index(items1.len())].0, value)
+                } else {
+                    (key, value)
+                }
+            )
+            .collect();
+        (from_items(items1), from_items(items2))
+    })
+}
+
+/// [SparseArray] with up to `max_len` items, with indices in `0..max_len`.
+///
+/// Items are packed close - most blocks are shared.
+pub fn dense_array<Levels, Data, S>(max_len: usize, value: S)
+    -> impl Strategy<Value = SparseArray<Levels, Data>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data>,
+{
+    let max_index = max_len.saturating_sub(1).min(max_index::<Levels, Data>());
+    array_in(0..=max_index, max_len, value)
+}
+
+/// [SparseArray] with up to `max_len` items, with indices in the whole
+/// [max_range()].
+///
+/// Items are spread apart - most have their own blocks.
+///
+/// [max_range()]: SparseHierarchy::max_range
+pub fn sparse_array<Levels, Data, S>(max_len: usize, value: S)
+    -> impl Strategy<Value = SparseArray<Levels, Data>>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data>,
+{
+    array_in(0..=max_index::<Levels, Data>(), max_len, value)
+}
+
+/// Pair of [dense_array()]s.
+///
+/// `overlap` (`0.0..=1.0`) - probability of the second array index
+/// being taken from the first array.
+///
+/// # Panics
+///
+/// Panics if `overlap` is outside `0.0..=1.0`.
+pub fn dense_array_pair<Levels, Data, S>(max_len: usize, overlap: f64, value: S)
+    -> impl Strategy<Value = (SparseArray<Levels, Data>, SparseArray<Levels, Data>)>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data> + Clone,
+{
+    let max_index = max_len.saturating_sub(1).min(max_index::<Levels, Data>());
+    array_pair_in(0..=max_index, max_len, overlap, value)
+}
+
+/// Pair of [sparse_array()]s.
+///
+/// `overlap` (`0.0..=1.0`) - probability of the second array index
+/// being taken from the first array.
+///
+/// # Panics
+///
+/// Panics if `overlap` is outside `0.0..=1.0`.
+pub fn sparse_array_pair<Levels, Data, S>(max_len: usize, overlap: f64, value: S)
+    -> impl Strategy<Value = (SparseArray<Levels, Data>, SparseArray<Levels, Data>)>
+where
+    Levels: SparseArrayLevels,
+    Data: Empty + Debug,
+    S: Strategy<Value = Data> + Clone,
+{
+    array_pair_in(0..=max_index::<Levels, Data>(), max_len, overlap, value)
+}
@@ -0,0 +1,367 @@
+//! [LendingIterator] and its adapters.
+
+use crate::{Iter, SparseHierarchy};
+
+mod sealed {
+    pub trait Sealed: Sized {}
+    pub struct Bounds<T>(T);
+    impl<T> Sealed for Bounds<T> {}
+}
+use sealed::{Bounds, Sealed};
+
+/// Item type of [LendingIterator], borrowing it for `'a`.
+///
+/// `ImplicitBounds` is never specified - it brings `Self: 'a` into
+/// `for<'a>` bounds.
+pub trait LendingIteratorItem<'a, ImplicitBounds: Sealed = Bounds<&'a Self>> {
+    type Item;
+}
+
+/// [LendingIterator] item, borrowing `I` for `'a`.
+pub type LendingItem<'a, I> = <I as LendingIteratorItem<'a>>::Item;
+
+/// Iterator, whose items may borrow iterator itself.
+///
+/// Items live until the next [next()] call, so they can not be collected -
+/// but adapters can still be composed. Closures receive items with
+/// their short lifetime.
+///
+/// Item type is defined by [LendingIteratorItem] - GAT with `where Self: 'a`
+/// can not be used in closure bounds without requiring `Self: 'static`.
+///
+/// [Iter] implements both [Iterator] and [LendingIterator], so the first
+/// adapter applied to it needs fully qualified syntax.
+///
+/// # Example
+///
+/// ```
+/// # use hi_sparse_array::{config, union_fold, SparseArray, SparseHierarchy};
+/// # use hi_sparse_array::utils::LendingIterator;
+/// type Array = SparseArray<config::width_64::depth_2, Option<usize>>;
+/// let arrays: Vec<Array> = (1..4).map(|k| {
+///     let mut a = Array::default();
+///     for i in (0..100).step_by(k) {
+///         a.insert(i, Some(1));
+///     }
+///     a
+/// }).collect();
+/// let counts = union_fold(&arrays[0], arrays[1..].iter(), |acc, d| acc.zip(*d).map(|(a, d)| a + d).or(*d));
+///
+/// let mut iter = LendingIterator::filter(counts.iter(), |(_, n)| *n == Some(3))
+///     .map(|(i, _)| i)
+///     .take(3);
+/// while let Some(i) = iter.next() {
+///     assert_eq!(i % 6, 0);
+/// }
+/// ```
+///
+/// [next()]: Self::next
+pub trait LendingIterator: for<'a> LendingIteratorItem<'a> {
+    fn next(&mut self) -> Option<LendingItem<'_, Self>>;
+
+    /// Maps items with `f`. Output can not borrow item.
+    #[inline]
+    fn map<F, B>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(LendingItem<'_, Self>) -> B,
+    {
+        Map{iter: self, f}
+    }
+
+    /// Items for which `f` returns true.
+    #[inline]
+    fn filter<F>(self, f: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&LendingItem<'_, Self>) -> bool,
+    {
+        Filter{iter: self, f}
+    }
+
+    /// First `n` items.
+    #[inline]
+    fn take(self, n: usize) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take{iter: self, n}
+    }
+
+    /// Items while `f` returns true.
+    #[inline]
+    fn take_while<F>(self, f: F) -> TakeWhile<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&LendingItem<'_, Self>) -> bool,
+    {
+        TakeWhile{iter: self, f, done: false}
+    }
+
+    /// First item, and then each `step`th.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is 0.
+    #[inline]
+    fn step_by(self, step: usize) -> StepBy<Self>
+    where
+        Self: Sized,
+    {
+        assert!(step != 0);
+        StepBy{iter: self, skip: step - 1, first_take: true}
+    }
+}
+
+impl<'a, I: LendingIterator + ?Sized> LendingIteratorItem<'a> for &mut I {
+    type Item = LendingItem<'a, I>;
+}
+impl<I: LendingIterator + ?Sized> LendingIterator for &mut I {
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        (**self).next()
+    }
+}
+
+/// Items of [Iter] borrow container, not iterator.
+impl<'i, 'a, T> LendingIteratorItem<'i> for Iter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    type Item = (usize, T::Data<'a>);
+}
+impl<'a, T> LendingIterator for Iter<'a, T>
+where
+    T: SparseHierarchy,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        Iterator::next(self)
+    }
+}
+
+/// Constructed by [LendingIterator::map()].
+#[derive(Clone)]
+pub struct Map<I, F>{
+    iter: I,
+    f: F,
+}
+
+impl<'a, I, F, B> LendingIteratorItem<'a> for Map<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(LendingItem<'_, I>) -> B,
+{
+    type Item = B;
+}
+impl<I, F, B> LendingIterator for Map<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(LendingItem<'_, I>) -> B,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        self.iter.next().map(&mut self.f)
+    }
+}
+
+/// Constructed by [LendingIterator::filter()].
+#[derive(Clone)]
+pub struct Filter<I, F>{
+    iter: I,
+    f: F,
+}
+
+impl<'a, I, F> LendingIteratorItem<'a> for Filter<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(&LendingItem<'_, I>) -> bool,
+{
+    type Item = LendingItem<'a, I>;
+}
+impl<I, F> LendingIterator for Filter<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(&LendingItem<'_, I>) -> bool,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        loop {
+            // Current borrow checker rejects returning item, borrowed
+            // in the loop (NLL problem case #3). Each iteration's item
+            // is dropped before the next one is requested.
+            let iter: *mut I = &mut self.iter;
+            let item = unsafe{ (*iter).next() }?;
+            if (self.f)(&item) {
+                return Some(item);
+            }
+        }
+    }
+}
+
+/// Constructed by [LendingIterator::take()].
+#[derive(Clone)]
+pub struct Take<I>{
+    iter: I,
+    n: usize,
+}
+
+impl<'a, I> LendingIteratorItem<'a> for Take<I>
+where
+    I: LendingIterator,
+{
+    type Item = LendingItem<'a, I>;
+}
+impl<I> LendingIterator for Take<I>
+where
+    I: LendingIterator,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        if self.n == 0 {
+            return None;
+        }
+        self.n -= 1;
+        self.iter.next()
+    }
+}
+
+/// Constructed by [LendingIterator::take_while()].
+#[derive(Clone)]
+pub struct TakeWhile<I, F>{
+    iter: I,
+    f: F,
+    done: bool,
+}
+
+impl<'a, I, F> LendingIteratorItem<'a> for TakeWhile<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(&LendingItem<'_, I>) -> bool,
+{
+    type Item = LendingItem<'a, I>;
+}
+impl<I, F> LendingIterator for TakeWhile<I, F>
+where
+    I: LendingIterator,
+    F: FnMut(&LendingItem<'_, I>) -> bool,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        if self.done {
+            return None;
+        }
+        let item = self.iter.next()?;
+        if (self.f)(&item) {
+            Some(item)
+        } else {
+            self.done = true;
+            None
+        }
+    }
+}
+
+/// Constructed by [LendingIterator::step_by()].
+#[derive(Clone)]
+pub struct StepBy<I>{
+    iter: I,
+    skip: usize,
+    first_take: bool,
+}
+
+impl<'a, I> LendingIteratorItem<'a> for StepBy<I>
+where
+    I: LendingIterator,
+{
+    type Item = LendingItem<'a, I>;
+}
+impl<I> LendingIterator for StepBy<I>
+where
+    I: LendingIterator,
+{
+    #[inline]
+    fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+        if self.first_take {
+            self.first_take = false;
+        } else {
+            for _ in 0..self.skip {
+                self.iter.next()?;
+            }
+        }
+        self.iter.next()
+    }
+}
+
+#[cfg(test)]
+mod test{
+    use crate::{config, union_fold, SparseArray};
+    use crate::test_utils::Data;
+    use super::*;
+
+    /// Items borrow iterator - each is a window into its buffer.
+    struct Windows{
+        buf: Vec<usize>,
+        next: usize,
+    }
+    impl<'a> LendingIteratorItem<'a> for Windows {
+        type Item = &'a mut [usize];
+    }
+    impl LendingIterator for Windows {
+        fn next(&mut self) -> Option<LendingItem<'_, Self>> {
+            let window = self.buf.get_mut(self.next..self.next + 2)?;
+            self.next += 1;
+            Some(window)
+        }
+    }
+
+    fn collect<I, T>(mut iter: I) -> Vec<T>
+    where
+        I: LendingIterator,
+        for<'a> LendingItem<'a, I>: Into<T>,
+    {
+        let mut out = Vec::new();
+        while let Some(item) = iter.next() {
+            out.push(item.into());
+        }
+        out
+    }
+
+    #[test]
+    fn test_lending_adapters(){
+        type Array = SparseArray<config::width_64::depth_3, Data>;
+        let arrays: Vec<_> = (1..4).map(|k| {
+            let mut a = Array::default();
+            for i in (0..1000).step_by(k) {
+                a.insert(i, Data(1));
+            }
+            a
+        }).collect();
+        let or = union_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+        let expected = |i: usize| 1 + (i % 2 == 0) as usize + (i % 3 == 0) as usize;
+
+        let iter = LendingIterator::filter(or.iter(), |(_, d)| d.0 > 1)
+            .map(|(i, d)| (i, d.0))
+            .step_by(2)
+            .take(10);
+        assert_eq!(
+            collect::<_, (usize, usize)>(iter),
+            Iterator::take(
+                (0..1000).map(|i| (i, expected(i))).filter(|(_, n)| *n > 1).step_by(2),
+                10
+            ).collect::<Vec<_>>()
+        );
+
+        let iter = LendingIterator::take_while(or.iter(), |(i, _)| *i < 10)
+            .map(|(_, d)| d);
+        assert_eq!(collect::<_, Data>(iter), (0..10).map(|i| Data(expected(i))).collect::<Vec<_>>());
+
+        // Lending items pass through adapters.
+        let mut iter = Windows{buf: (0..10).collect(), next: 0}
+            .filter(|w| w[1] % 3 == 0)
+            .take(2);
+        while let Some(w) = iter.next() {
+            w[0] += 100;
+        }
+        assert_eq!(iter.iter.iter.buf, [0, 1, 102, 3, 4, 105, 6, 7, 8, 9]);
+    }
+}
@@ -2,9 +2,29 @@ use std::borrow::Borrow;
 
 pub(crate) mod primitive;
 pub(crate) mod array;
+pub mod lending_iterator;
 
 pub use primitive::*;
 pub use array::*;
+pub use lending_iterator::{LendingIterator, LendingIteratorItem, LendingItem};
+
+/// Hint CPU to load `ptr` into cache. No-op on unsupported architectures.
+#[cfg(feature = "prefetch")]
+#[inline(always)]
+pub(crate) fn prefetch_read<T>(ptr: *const T) {
+    #[cfg(target_arch = "x86_64")]
+    unsafe{
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr.cast());
+    }
+    #[cfg(target_arch = "x86")]
+    unsafe{
+        use std::arch::x86::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(ptr.cast());
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    let _ = ptr;
+}
 
 /// `Borrowable` means it can be either T or &T.
 /// 
@@ -30,9 +30,13 @@ pub trait Primitive:
     const ONE : Self;
     
     fn from_usize(i: usize) -> Self;
+    /// `None`, if `i` does not fit.
+    fn try_from_usize(i: usize) -> Option<Self>;
     fn as_usize(self) -> usize;
     
     fn trailing_zeros(self) -> u32;
+    fn leading_zeros(self) -> u32;
+    fn count_ones(self) -> u32;
     fn wrapping_neg(self) -> Self;
     
     fn is_zero(self) -> bool;
@@ -52,6 +56,11 @@ macro_rules! impl_primitive {
                 i as Self
             }
             
+            #[inline]
+            fn try_from_usize(i: usize) -> Option<Self> {
+                Self::try_from(i).ok()
+            }
+            
             #[inline]
             fn as_usize(self) -> usize {
                 self as usize
@@ -62,6 +71,16 @@ macro_rules! impl_primitive {
                 self.trailing_zeros()
             }
 
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                self.leading_zeros()
+            }
+
+            #[inline]
+            fn count_ones(self) -> u32 {
+                self.count_ones()
+            }
+
             #[inline]
             fn wrapping_neg(self) -> Self {
                 self.wrapping_neg()
@@ -79,4 +98,5 @@ impl_primitive!(u8);
 impl_primitive!(u16);
 impl_primitive!(u32);
 impl_primitive!(u64);
+impl_primitive!(u128);
 impl_primitive!(usize);
\ No newline at end of file
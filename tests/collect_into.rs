@@ -0,0 +1,75 @@
+//! collect_into allocation reuse.
+//! 
+//! Separate test binary, because of the counting global allocator.
+
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{collect_into, Empty, intersection_fold, SparseHierarchy};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Data(usize);
+impl Empty for Data {
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = common::Array<Data>;
+
+#[test]
+fn collect_into_no_alloc_test(){
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let arrays: Vec<_> = (0..3).map(|_|{
+        let mut a = Array::default();
+        for _ in 0..4000 {
+            let v = rng.gen_range(0..common::RANGE);
+            a.insert(v, Data(v+1));
+        }
+        a
+    }).collect();
+    let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| Data(acc.0 + d.0));
+    
+    let mut dst = Array::default();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    collect_into(&and, &mut dst);
+    assert!(ALLOCATIONS.load(Ordering::Relaxed) > before);
+    let expected: Vec<_> = dst.iter().map(|(i, d)| (i, d.clone())).collect();
+    
+    for _ in 0..3 {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        collect_into(&and, &mut dst);
+        let after = ALLOCATIONS.load(Ordering::Relaxed);
+        assert_eq!(after - before, 0);
+    }
+    itertools::assert_equal(dst.iter().map(|(i, d)| (i, d.clone())), expected);
+}
@@ -1,6 +1,12 @@
+// Not every test crate uses every helper.
+#![allow(dead_code)]
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use rand::Rng;
 use hi_sparse_array::level::{IntrusiveListLevel, SingleBlockLevel};
 use hi_sparse_array::level_block::{Block, SmallBlock};
-use hi_sparse_array::SparseArray;
+use hi_sparse_array::{Empty, SparseArray, SparseHierarchy};
 
 type Lvl0Block = Block<u64, [u8; 64]>;
 type Lvl1Block = Block<u64, [u16; 64]>;
@@ -17,4 +23,33 @@ type CompactLvls = (
 
 // TODO: switch to Compact on flag for CI. 
 pub type Array<Data> = SparseArray</*Compact*/Lvls, Data>;
-pub const RANGE: usize = 260_000;
\ No newline at end of file
+pub const RANGE: usize = 260_000;
+
+/// Reference model of hierarchy - its non-empty items, by index.
+pub fn to_btreemap<H>(h: &H) -> BTreeMap<usize, H::DataType>
+where
+    H: SparseHierarchy<DataType: Clone>
+{
+    h.iter()
+        .map(|(i, d)| (i, d.borrow().clone()))
+        .filter(|(_, d)| !d.is_empty())
+        .collect()
+}
+
+/// Two random key sets of `len` keys max, in `0..range`.
+/// 
+/// `overlap` (`0.0..=1.0`) - probability of the second set key 
+/// being taken from the first set.
+pub fn gen_key_pair(rng: &mut impl Rng, range: usize, len: usize, overlap: f64) 
+    -> (BTreeSet<usize>, BTreeSet<usize>)
+{
+    let keys1: Vec<usize> = (0..len).map(|_| rng.gen_range(0..range)).collect();
+    let keys2 = (0..len).map(|_| 
+        if rng.gen_bool(overlap) {
+            keys1[rng.gen_range(0..len)]
+        } else {
+            rng.gen_range(0..range)
+        }
+    ).collect();
+    (keys1.into_iter().collect(), keys2)
+}
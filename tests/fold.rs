@@ -0,0 +1,88 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use itertools::assert_equal;
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{BinaryOp, BitBlock, config, Empty, fold, SparseArray, SparseHierarchy};
+use hi_sparse_array::const_utils::ConstTrue;
+use hi_sparse_array::utils::Take;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Merge layers, keeping max item.
+struct MaxOp<M>(PhantomData<M>);
+impl<M: BitBlock> BinaryOp for MaxOp<M>{
+    const EXACT_HIERARCHY: bool = true;
+    type SKIP_EMPTY_HIERARCHIES = ConstTrue;
+    type LevelMask = M;
+
+    #[inline]
+    fn lvl_op(
+        &self,
+        left : impl Take<M>,
+        right: impl Take<M>
+    ) -> M {
+        left.take_or_clone() | right.take_or_clone()
+    }
+
+    type Left  = Data;
+    type Right = Data;
+    type Out   = Data;
+
+    #[inline]
+    fn data_op(
+        &self,
+        left : impl Borrow<Data>,
+        right: impl Borrow<Data>
+    ) -> Data {
+        Data(left.borrow().0.max(right.borrow().0))
+    }
+}
+
+#[test]
+fn fold_skip_empty_test(){
+    type Array = SparseArray<config::width_64::depth_3, Data>;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xf01d_5c1b);
+
+    // Each layer populates its own small region.
+    let layers: Vec<Array> = (0..48).map(|l| {
+        let mut a = Array::default();
+        for _ in 0..20 {
+            let i = l * 5000 + rng.gen_range(0..300);
+            a.insert(i, Data(rng.gen_range(1..100)));
+        }
+        a
+    }).collect();
+    let init = Array::default();
+
+    let max = fold(MaxOp(PhantomData), &init, layers.iter());
+
+    let mut expected: Vec<(usize, Data)> = Vec::new();
+    for layer in &layers {
+        expected.extend(layer.iter().map(|(i, d)| (i, *d)));
+    }
+    expected.sort_by_key(|(i, _)| *i);
+    expected.dedup_by(|(i1, d1), (i0, d0)| {
+        if i0 == i1 {
+            d0.0 = d0.0.max(d1.0);
+            true
+        } else {
+            false
+        }
+    });
+
+    assert_equal(max.iter(), expected.iter().copied());
+    for &(i, d) in &expected {
+        assert_eq!(max.get(i), d);
+    }
+    assert_eq!(max.count_keys(), expected.len());
+}
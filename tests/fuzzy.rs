@@ -34,6 +34,8 @@ fn fuzzy_test(){
     let mut h = Map::default();
     
     fn check(rng: &mut impl Rng, a: &Array, h: &Map) {
+        a.validate().unwrap();
+        
         // iter + unordered_iter
         {
             let a_items: Vec<_> = a.iter().map(|(_,d)|d).collect();
@@ -47,7 +49,19 @@ fn fuzzy_test(){
             assert_equal(&a_items, &a_unordered_items);
             assert_equal(&a_unordered_items, &h_items);
         }
-        
+
+        // keys + len
+        {
+            let a_keys: Vec<_> = a.iter().map(|(k,_)|k).collect();
+            assert_equal(a.keys(), a_keys.iter().copied());
+
+            let mut h_keys: Vec<_> = h.keys().copied().collect();
+            h_keys.sort();
+            assert_equal(&a_keys, &h_keys);
+
+            assert_eq!(a.len(), h.len());
+        }
+
         // get
         for (k, v) in h {
             let d = a.get(*k).0;
@@ -90,4 +104,53 @@ fn fuzzy_test(){
     }
     h.clear();
     check(&mut rng, &a, &h);
-}
\ No newline at end of file
+}
+/// Stateful test: random op script, applied to both array and 
+/// `BTreeMap` model, with structure validation after each op.
+#[test]
+fn op_script_test(){
+    use std::collections::BTreeMap;
+    
+    #[derive(Debug)]
+    enum Op {
+        Insert(usize, usize),
+        Remove(usize),
+        Get(usize),
+        Clear,
+    }
+    
+    fn gen_op(rng: &mut impl Rng, range: usize) -> Op {
+        match rng.gen_range(0..100) {
+            0 => Op::Clear,
+            1..=50 => Op::Insert(rng.gen_range(0..range), rng.gen()),
+            51..=80 => Op::Remove(rng.gen_range(0..range)),
+            _ => Op::Get(rng.gen_range(0..range)),
+        }
+    }
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x5eed);
+    // Narrow range - to hit the same blocks over and over.
+    for range in [100, 5000, common::RANGE] {
+        let mut a = Array::default();
+        let mut m = BTreeMap::new();
+        for _ in 0..3000 {
+            let op = gen_op(&mut rng, range);
+            match op {
+                Op::Insert(i, v) => {
+                    a.insert(i, Data(v));
+                    m.insert(i, Data(v));
+                }
+                Op::Remove(i) => assert_eq!(a.remove(i), m.remove(&i), "{op:?}"),
+                Op::Get(i) => assert_eq!(a.try_get(i), m.get(&i), "{op:?}"),
+                Op::Clear => {
+                    a.clear();
+                    m.clear();
+                }
+            }
+            if let Err(e) = a.validate() {
+                panic!("{e} after {op:?}");
+            }
+        }
+        assert_equal(a.iter().map(|(i, d)| (i, d.0)), m.iter().map(|(i, d)| (*i, d.0)));
+    }
+}
@@ -0,0 +1,48 @@
+//! Allocation count of Iter collect. In separate binary, because of global allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use hi_sparse_array::{config, SparseArray, SparseHierarchy};
+
+struct CountingAllocator;
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn collect_allocations_test(){
+    type Array = SparseArray<config::width_64::depth_3, Option<usize>>;
+    let mut a = Array::default();
+    for i in (0..10_000).step_by(3) {
+        a.insert(i, Some(i));
+    }
+    // Stored empty item is still an item.
+    a.insert(1, None);
+
+    let iter = a.iter();
+    assert_eq!(iter.len(), a.len());
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let items: Vec<_> = iter.collect();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    assert_eq!(items.len(), a.len());
+    assert_eq!(allocations, 1);
+}
@@ -2,7 +2,7 @@ use std::ops::Range;
 use itertools::assert_equal;
 use hi_sparse_array::level_block::Block;
 use hi_sparse_array::level::{ILevel, IntrusiveListLevel, SingleBlockLevel};
-use hi_sparse_array::{Empty, SparseArray, SparseArrayLevels};
+use hi_sparse_array::{BitArray, Empty, SparseArray, SparseArrayLevels};
 use hi_sparse_array::Iter;
 use hi_sparse_array::SparseHierarchy;
 
@@ -38,9 +38,6 @@ fn level_depth_test(){
             assert!(array.may_contain(i));
         }
         
-        /*for (index, data) in CachingBlockIter::new(&array){
-            println!("{index}: {:}", data.0);
-        }*/
         assert_equal(Iter::new(&array).map(|(_, d)|d.0 as usize), range.clone());
         assert_equal(Iter::new(&array).map(|(i, _)|i), range.clone());
     }
@@ -61,4 +58,41 @@ fn level_depth_test(){
         type Array = SparseArray<(SingleBlockLevel<Lvl0Block>, IntrusiveListLevel<Lvl1Block>, IntrusiveListLevel<Lvl2Block>), DataBlock>;
         do_test(Array::default(), 0..64*64*64);
     }
+    {
+        use hi_sparse_array::config::{width_16, width_32};
+        do_test(SparseArray::<width_16::depth_1, DataBlock>::default(), 0..16);
+        do_test(SparseArray::<width_16::depth_2, DataBlock>::default(), 0..16*16);
+        do_test(SparseArray::<width_16::depth_3, DataBlock>::default(), 0..16*16*16);
+        do_test(SparseArray::<width_16::depth_4, DataBlock>::default(), 0..16*16*16*16);
+        do_test(SparseArray::<width_16::depth_3, DataBlock>::default(), 15*16*16 + 3..15*16*16 + 40);
+        do_test(SparseArray::<width_32::depth_1, DataBlock>::default(), 0..32);
+        do_test(SparseArray::<width_32::depth_2, DataBlock>::default(), 0..32*32);
+        do_test(SparseArray::<width_32::depth_3, DataBlock>::default(), 0..32*32*32);
+        do_test(SparseArray::<width_32::depth_3, DataBlock>::default(), 31*32*32 + 3..31*32*32 + 70);
+    }
+    {
+        type Array = SparseArray<hi_sparse_array::config::width_128_u128::depth_2, DataBlock>;
+        do_test(Array::default(), 0..128*128);
+        do_test(Array::default(), 60..70);
+        do_test(Array::default(), 127*128 + 60..127*128 + 70);
+    }
+    {
+        type Array = SparseArray<(
+            SingleBlockLevel<Block<BitArray<2>, [u8;128]>>,
+            IntrusiveListLevel<Block<BitArray<2>, [u16;128]>>
+        ), DataBlock>;
+        do_test(Array::default(), 0..128*128);
+        do_test(Array::default(), 60..70);
+    }
+    {
+        type Array = SparseArray<hi_sparse_array::config::width_512::depth_2, DataBlock>;
+        do_test(Array::default(), 0..512*512);
+        do_test(Array::default(), 500*512 + 60..500*512 + 200);
+    }
+    #[cfg(feature = "simd")]
+    {
+        type Array = SparseArray<hi_sparse_array::config::width_512_u64x8::depth_2, DataBlock>;
+        do_test(Array::default(), 0..512*512);
+        do_test(Array::default(), 500*512 + 60..500*512 + 200);
+    }
 }
\ No newline at end of file
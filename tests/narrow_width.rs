@@ -0,0 +1,70 @@
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use itertools::assert_equal;
+use rand::{Rng, SeedableRng};
+use hi_sparse_array::{complement, config, difference, Empty, intersection, is_subset, SparseArray, SparseArrayLevels, SparseHierarchy, symmetric_difference, union};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Data(usize);
+impl Empty for Data{
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+fn keys(h: &impl SparseHierarchy<DataType = Data>) -> Vec<usize> {
+    h.iter()
+        .filter(|(_, d)| !d.borrow().is_empty())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Ops at masks narrower than u64.
+fn do_test<Levels: SparseArrayLevels>(){
+    type Array<Levels> = SparseArray<Levels, Data>;
+    let max = Array::<Levels>::max_range();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x1632_5eed);
+    for _ in 0..20 {
+        let mut a1 = Array::<Levels>::default();
+        let mut a2 = Array::<Levels>::default();
+        let mut s1 = BTreeSet::new();
+        let mut s2 = BTreeSet::new();
+        for _ in 0..rng.gen_range(0..max/2) {
+            let v = rng.gen_range(0..=max);
+            a1.insert(v, Data(v+1));
+            s1.insert(v);
+            let v = rng.gen_range(0..=max);
+            a2.insert(v, Data(v+1));
+            s2.insert(v);
+        }
+
+        assert_equal(keys(&a1), s1.iter().copied());
+        assert_equal(keys(&union(&a1, &a2, |l, r| Data(l.0 + r.0))), s1.union(&s2).copied());
+        assert_equal(keys(&intersection(&a1, &a2, |l, _| l.clone())), s1.intersection(&s2).copied());
+        assert_equal(keys(&difference(&a1, &a2)), s1.difference(&s2).copied());
+        assert_equal(
+            keys(&symmetric_difference(&a1, &a2, |l, r| Data(l.0 + r.0))),
+            s1.symmetric_difference(&s2).copied()
+        );
+        assert_equal(
+            complement(&a1, ..).iter().map(|(i, _)| i),
+            (0..=max).filter(|i| !s1.contains(i))
+        );
+        assert_eq!(is_subset(&a1, &a2), s1.is_subset(&s2));
+        assert!(is_subset(&intersection(&a1, &a2, |l, _| l.clone()), &a1));
+    }
+}
+
+#[test]
+fn narrow_width_ops_test(){
+    do_test::<config::width_16::depth_1>();
+    do_test::<config::width_16::depth_2>();
+    do_test::<config::width_16::depth_3>();
+    do_test::<config::width_32::depth_1>();
+    do_test::<config::width_32::depth_2>();
+    do_test::<config::width_32::depth_3>();
+}
@@ -0,0 +1,85 @@
+//! Binary ops against `BTreeMap` reference model.
+
+mod common;
+
+use std::collections::BTreeMap;
+use rand::SeedableRng;
+use hi_sparse_array::{difference, intersection, symmetric_difference, union, Empty};
+use common::{gen_key_pair, to_btreemap};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Val(usize);
+impl Empty for Val {
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Array = common::Array<Val>;
+type Model = BTreeMap<usize, Val>;
+
+/// Calls `f` with array pairs and their models, for various 
+/// ranges and key overlaps.
+fn for_each_pair(mut f: impl FnMut(&Array, &Array, &Model, &Model)) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x0b5e55ed);
+    for range in [100, 10_000, common::RANGE] {
+        for overlap in [0.0, 0.3, 0.9, 1.0] {
+            let (keys1, keys2) = gen_key_pair(&mut rng, range, 1000, overlap);
+            let a = Array::from_keys(keys1, |i| Val(i + 1));
+            let b = Array::from_keys(keys2, |i| Val(2 * (i + 1)));
+            let (ma, mb) = (to_btreemap(&a), to_btreemap(&b));
+            f(&a, &b, &ma, &mb);
+        }
+    }
+}
+
+#[test]
+fn union_model_test(){
+    for_each_pair(|a, b, ma, mb| {
+        let mut expected = ma.clone();
+        for (i, v) in mb {
+            expected.entry(*i).or_insert(Val(0)).0 += v.0;
+        }
+        let u = union(a, b, |l, r| Val(l.0 + r.0));
+        assert_eq!(to_btreemap(&u), expected);
+    });
+}
+
+#[test]
+fn intersection_model_test(){
+    for_each_pair(|a, b, ma, mb| {
+        let expected: Model = ma.iter()
+            .filter_map(|(i, l)| mb.get(i).map(|r| (*i, Val(l.0 + r.0))))
+            .collect();
+        let and = intersection(a, b, |l, r| Val(l.0 + r.0));
+        assert_eq!(to_btreemap(&and), expected);
+    });
+}
+
+#[test]
+fn difference_model_test(){
+    for_each_pair(|a, b, ma, mb| {
+        let expected: Model = ma.iter()
+            .filter(|(i, _)| !mb.contains_key(i))
+            .map(|(i, v)| (*i, *v))
+            .collect();
+        assert_eq!(to_btreemap(&difference(a, b)), expected);
+    });
+}
+
+#[test]
+fn symmetric_difference_model_test(){
+    for_each_pair(|a, b, ma, mb| {
+        let mut expected: Model = ma.iter()
+            .filter(|(i, _)| !mb.contains_key(i))
+            .map(|(i, v)| (*i, *v))
+            .collect();
+        expected.extend(mb.iter().filter(|(i, _)| !ma.contains_key(i)).map(|(i, v)| (*i, *v)));
+        let xor = symmetric_difference(a, b, |l, r| Val(l.0 + r.0));
+        assert_eq!(to_btreemap(&xor), expected);
+    });
+}
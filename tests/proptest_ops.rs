@@ -0,0 +1,75 @@
+//! Binary ops against `BTreeMap` reference model, with `testing` strategies.
+#![cfg(feature = "testing")]
+
+use std::collections::BTreeMap;
+use proptest::prelude::*;
+use hi_sparse_array::{config, difference, intersection, union, Empty, SparseArray};
+use hi_sparse_array::testing::{dense_array, dense_array_pair, sparse_array_pair, to_btreemap};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Val(usize);
+impl Empty for Val {
+    fn empty() -> Self {
+        Self(0)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+type Levels = config::width_64::depth_3;
+type Array = SparseArray<Levels, Val>;
+type Model = BTreeMap<usize, Val>;
+
+fn val() -> impl Strategy<Value = Val> + Clone {
+    (1..1000usize).prop_map(Val)
+}
+
+fn pair() -> impl Strategy<Value = (Array, Array)> {
+    prop_oneof![
+        dense_array_pair(300, 0.5, val()),
+        sparse_array_pair(300, 0.5, val()),
+        (0.0..=1.0).prop_flat_map(|overlap| dense_array_pair(300, overlap, val())),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn model_test(a in dense_array::<Levels, _, _>(300, val())){
+        let model = to_btreemap(&a);
+        prop_assert_eq!(a.len(), model.len());
+        for (i, v) in &model {
+            prop_assert_eq!(a.try_get(*i), Some(v));
+        }
+    }
+
+    #[test]
+    fn union_test((a, b) in pair()){
+        let mut expected = to_btreemap(&a);
+        for (i, v) in to_btreemap(&b) {
+            expected.entry(i).or_insert(Val(0)).0 += v.0;
+        }
+        let u = union(&a, &b, |l, r| Val(l.0 + r.0));
+        prop_assert_eq!(to_btreemap(&u), expected);
+    }
+
+    #[test]
+    fn intersection_test((a, b) in pair()){
+        let mb = to_btreemap(&b);
+        let expected: Model = to_btreemap(&a).into_iter()
+            .filter_map(|(i, l)| mb.get(&i).map(|r| (i, Val(l.0 + r.0))))
+            .collect();
+        let and = intersection(&a, &b, |l, r| Val(l.0 + r.0));
+        prop_assert_eq!(to_btreemap(&and), expected);
+    }
+
+    #[test]
+    fn difference_test((a, b) in pair()){
+        let mb = to_btreemap(&b);
+        let expected: Model = to_btreemap(&a).into_iter()
+            .filter(|(i, _)| !mb.contains_key(i))
+            .collect();
+        prop_assert_eq!(to_btreemap(&difference(&a, &b)), expected);
+    }
+}
@@ -34,8 +34,9 @@ impl Empty for DataBlock{
         Self(0)
     }
 
+    // AndOp is not exact - iteration checks fold results for emptiness.
     fn is_empty(&self) -> bool {
-        todo!()
+        self.0 == 0
     }
 }
 
@@ -33,6 +33,7 @@ fn insert_test(){
         let v = rng.gen_range(0..RANGE);
         *small_block_array.get_mut(v) = Data(v);
     }
+    small_block_array.validate().unwrap();
 }
 
 #[test]
@@ -43,6 +44,1082 @@ fn remove_test(){
     *a.get_mut(400) = Data(400);
     
     a.remove(1);
+    a.validate().unwrap();
     a.remove(2);
+    a.validate().unwrap();
     a.remove(400);
-}
\ No newline at end of file
+    a.validate().unwrap();
+}
+#[test]
+fn try_insert_range_test(){
+    use hi_sparse_array::{config, IndexOutOfRange, SparseArrayLevels};
+    
+    fn do_test<Levels: SparseArrayLevels>(){
+        let mut a: SparseArray<Levels, Data> = Default::default();
+        let max_range = SparseArray::<Levels, Data>::max_range();
+        
+        assert_eq!(a.try_insert(max_range, Data(1)), Ok(()));
+        assert_eq!(a.get(max_range), &Data(1));
+        assert_eq!(
+            a.try_insert(max_range + 1, Data(2)), 
+            Err(IndexOutOfRange{ index: max_range + 1, max_range }.into())
+        );
+        assert_eq!(a.unordered_iter().count(), 1);
+    }
+    
+    do_test::<config::width_64::depth_1>();
+    do_test::<config::width_64::depth_2>();
+    do_test::<config::width_64::depth_3>();
+    assert_eq!(SparseArray::<config::width_64::depth_2, Data>::max_range(), 64*64 - 1);
+}
+
+#[test]
+#[should_panic(expected = "index 4096 is out of range 0..=4095")]
+fn insert_out_of_range_test(){
+    let mut a: SparseArray<hi_sparse_array::config::width_64::depth_2, Data> = Default::default();
+    a.insert(4096, Data(0));
+}
+
+#[test]
+fn clear_test(){
+    let mut a = Array::default();
+    for _ in 0..3 {
+        for i in (0..3000).step_by(7) {
+            a.insert(i, Data(i));
+        }
+        assert_eq!(a.len(), 429);
+        a.validate().unwrap();
+        
+        a.clear();
+        a.validate().unwrap();
+        assert_eq!(a.len(), 0);
+        assert_eq!(a.iter().count(), 0);
+        assert_eq!(a.get(7).0, 0);
+    }
+    
+    a.insert(14, Data(14));
+    a.validate().unwrap();
+    assert_eq!(a.get(14).0, 14);
+    assert_eq!(a.keys().collect::<Vec<_>>(), [14]);
+}
+
+#[test]
+fn data_blocks_test(){
+    type Array = SparseArray<hi_sparse_array::config::width_64::depth_3, Data>;
+    let mut a = Array::default();
+    for i in [0, 1, 63, 64, 200, 4095, 4096, 100_000] {
+        a.insert(i, Data(i));
+    }
+    
+    let mut items = Vec::new();
+    for (base_index, mask, data_block) in a.data_blocks() {
+        assert_eq!(base_index % 64, 0);
+        for lane in 0..64 {
+            if mask & (1 << lane) != 0 {
+                items.push((base_index + lane, data_block.get(lane).clone()));
+            } else {
+                assert_eq!(data_block.get(lane).0, 0);
+            }
+        }
+    }
+    assert_eq!(items, a.iter().map(|(i, d)| (i, d.clone())).collect::<Vec<_>>());
+    
+    for (_, mask, mut data_block) in a.data_blocks_mut() {
+        for lane in 0..64 {
+            let data = data_block.get_mut(lane);
+            assert_eq!(data.is_some(), mask & (1 << lane) != 0);
+            if let Some(data) = data {
+                data.0 += 1;
+            }
+        }
+    }
+    for (i, d) in a.iter() {
+        assert_eq!(d.0, i + 1);
+    }
+}
+
+#[test]
+fn conversion_test(){
+    use hi_sparse_array::{config, collect_into, SparseArrayLevels};
+    
+    fn do_test<Levels0, Levels1>(range: usize)
+    where
+        Levels0: SparseArrayLevels,
+        Levels1: SparseArrayLevels<Mask = Levels0::Mask, LevelCount = Levels0::LevelCount>,
+    {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let mut a0 = SparseArray::<Levels0, Data>::default();
+        for _ in 0..1000 {
+            let v = rng.gen_range(0..range);
+            a0.insert(v, Data(v));
+        }
+        
+        let a1 = SparseArray::<Levels1, Data>::from(&a0);
+        assert_eq!(a1.len(), a0.len());
+        itertools::assert_equal(a1.iter(), a0.iter());
+        
+        let mut back = SparseArray::<Levels0, Data>::default();
+        back.insert(0, Data(42));
+        collect_into(&a1, &mut back);
+        itertools::assert_equal(back.iter(), a0.iter());
+    }
+    
+    do_test::<config::width_64::depth_2, config::sbo::width_64::depth_2>(64*64);
+    do_test::<config::width_64::depth_3, config::sbo::width_64::depth_3>(common::RANGE);
+    do_test::<config::sbo::width_64::depth_3, config::width_64::depth_3>(common::RANGE);
+}
+
+#[test]
+fn materialize_test(){
+    use hi_sparse_array::{config, intersection_fold, materialize, SparseArrayLevels};
+    
+    // Intersection is not exact - its iteration checks items for emptiness.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    
+    fn do_test<Levels: SparseArrayLevels>(range: usize){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let arrays: Vec<_> = (0..3).map(|_|{
+            let mut a = SparseArray::<Levels, Val>::default();
+            for _ in 0..4000 {
+                let v = rng.gen_range(0..range);
+                a.insert(v, Val(v+1));
+            }
+            a
+        }).collect();
+        
+        let and = intersection_fold(&arrays[0], arrays[1..].iter(), |acc, d| Val(acc.0 + d.0));
+        let m: SparseArray<Levels, Val> = materialize(&and);
+        
+        let mut expected = SparseArray::<Levels, Val>::default();
+        for (i, d) in and.iter() {
+            expected.insert(i, d);
+        }
+        assert_eq!(m.len(), expected.len());
+        itertools::assert_equal(m.iter(), expected.iter());
+    }
+    
+    do_test::<config::width_64::depth_2>(64*64);
+    do_test::<config::width_64::depth_3>(common::RANGE);
+    do_test::<config::sbo::width_64::depth_4>(common::RANGE);
+}
+
+#[test]
+fn union_with_test(){
+    use hi_sparse_array::{materialize, union};
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    fn do_test(a_range: std::ops::Range<usize>, b_range: std::ops::Range<usize>){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let mut a = Array::default();
+        let mut b = Array::default();
+        for _ in 0..2000 {
+            let v = rng.gen_range(a_range.clone());
+            a.insert(v, Val(v+1));
+            let v = rng.gen_range(b_range.clone());
+            b.insert(v, Val(v+1));
+        }
+        let expected: Array = materialize(&union(&a, &b, |l, r| Val(l.0 + r.0)));
+        
+        let mut by_ref = Array::from(&a);
+        by_ref.union_with(&b, |l, r| l.0 += r.0);
+        itertools::assert_equal(by_ref.iter(), expected.iter());
+        
+        let mut by_val = Array::from(&a);
+        by_val.union_with_into(b, |l, r| l.0 += r.0);
+        itertools::assert_equal(by_val.iter(), expected.iter());
+    }
+    
+    // overlapping
+    do_test(0..common::RANGE, 0..common::RANGE);
+    do_test(0..10_000, 0..10_000);
+    // disjoint
+    do_test(0..10_000, 10_000..common::RANGE);
+}
+
+#[test]
+fn merge_into_test(){
+    use hi_sparse_array::{merge_into, union};
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut a = Array::default();
+    let mut b = Array::default();
+    let mut c = Array::default();
+    for _ in 0..2000 {
+        let v = rng.gen_range(0..10_000);
+        a.insert(v, Val(v+1));
+        let v = rng.gen_range(0..10_000);
+        b.insert(v, Val(v+1));
+        let v = rng.gen_range(0..10_000);
+        c.insert(v, Val(v+1));
+    }
+    
+    // Update, insert and remove.
+    let mut dst = Array::from(&c);
+    let src = union(&a, &b, |l, r| Val(l.0 + r.0));
+    merge_into(&src, &mut dst, |slot, v| 
+        if v.0 % 3 == 0 {
+            *slot = Val::empty();
+        } else {
+            slot.0 += v.0;
+        }
+    );
+    
+    let expected: Vec<_> = (0..10_000).filter_map(|i| {
+        let v = src.get(i);
+        let d = c.get(i);
+        if v.is_empty() {
+            (!d.is_empty()).then(|| (i, d.clone()))
+        } else if v.0 % 3 == 0 {
+            None
+        } else {
+            Some((i, Val(d.0 + v.0)))
+        }
+    }).collect();
+    itertools::assert_equal(dst.iter().map(|(i, d)| (i, d.clone())), expected.iter().cloned());
+    assert_eq!(dst.len(), expected.len());
+    for (i, d) in &expected {
+        assert_eq!(dst.get(*i), d);
+    }
+}
+
+#[test]
+fn operators_test(){
+    use hi_sparse_array::{difference, intersection, symmetric_difference, union};
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let arrays: Vec<_> = (0..3).map(|_| {
+        let mut a = Array::default();
+        for _ in 0..2000 {
+            let v = rng.gen_range(0..10_000);
+            a.insert(v, Val(v+1));
+        }
+        a
+    }).collect();
+    let (a, b, c) = (&arrays[0], &arrays[1], &arrays[2]);
+    let left = |l: &Val, r: &Val| if l.is_empty() { r.clone() } else { l.clone() };
+    let both = |l: &Val, r: &Val| if r.is_empty() { Val(0) } else { l.clone() };
+    fn non_empty(h: &impl SparseHierarchy<DataType = Val>) -> Vec<(usize, Val)> {
+        use std::borrow::Borrow;
+        h.iter()
+            .map(|(i, d)| (i, d.borrow().clone()))
+            .filter(|(_, d)| !d.is_empty())
+            .collect()
+    }
+    
+    assert_eq!(non_empty(&(a & b)), non_empty(&intersection(a, b, both)));
+    assert_eq!(non_empty(&(a | b)), non_empty(&union(a, b, left)));
+    assert_eq!(non_empty(&(a - b)), non_empty(&difference(a, b)));
+    assert_eq!(non_empty(&(a ^ b)), non_empty(&symmetric_difference(a, b, left)));
+    
+    // Chained
+    assert_eq!(
+        non_empty(&((a | b) - c)),
+        non_empty(&difference(union(a, b, left), c))
+    );
+    assert_eq!(
+        non_empty(&(a & b ^ c)),
+        non_empty(&symmetric_difference(intersection(a, b, both), c, left))
+    );
+}
+
+#[test]
+fn from_keys_test(){
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut keys: Vec<usize> = (0..5000).map(|_| rng.gen_range(0..common::RANGE)).collect();
+    
+    // Unsorted, with repeats.
+    let a = Array::from_keys(keys.iter().copied(), |i| Data(i+1));
+    keys.sort();
+    keys.dedup();
+    itertools::assert_equal(a.iter().map(|(i, d)| (i, d.0)), keys.iter().map(|&i| (i, i+1)));
+    assert_eq!(a.len(), keys.len());
+    
+    // Sorted
+    let b = Array::from_keys(keys.iter().copied(), |i| Data(i+1));
+    itertools::assert_equal(a.iter(), b.iter());
+    for &i in &keys {
+        assert_eq!(b.get(i), &Data(i+1));
+    }
+}
+
+#[test]
+fn intersect_with_test(){
+    use hi_sparse_array::{intersection, ExactHierarchy};
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    fn do_test(a_range: std::ops::Range<usize>, b_range: std::ops::Range<usize>){
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+        let mut a = Array::default();
+        let mut b = Array::default();
+        for _ in 0..2000 {
+            let v = rng.gen_range(a_range.clone());
+            a.insert(v, Val(v+1));
+            let v = rng.gen_range(b_range.clone());
+            b.insert(v, Val(v+1));
+        }
+        let and = intersection(&a, &b, |l, r| Val(l.0 + r.0));
+        // non-exact intersection have empty items.
+        let mut expected = Array::default();
+        for (i, v) in and.iter().filter(|(_, v)| !v.is_empty()) {
+            expected.insert(i, v);
+        }
+        
+        let mut a1 = Array::from(&a);
+        a1.intersect_with(&b, |l, r| l.0 += r.0);
+        a1.validate().unwrap();
+        assert_eq!(a1.len(), expected.len());
+        itertools::assert_equal(a1.iter(), expected.iter());
+        
+        let mut a2 = Array::from(&a);
+        a2.intersect_with(unsafe{ &ExactHierarchy::new_unchecked(&b) }, |l, r| l.0 += r.0);
+        itertools::assert_equal(a2.iter(), expected.iter());
+    }
+    
+    // overlapping
+    do_test(0..common::RANGE, 0..common::RANGE);
+    do_test(0..10_000, 0..10_000);
+    // disjoint
+    do_test(0..10_000, 10_000..common::RANGE);
+}
+
+#[test]
+fn subtract_test(){
+    use std::collections::BTreeSet;
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    for range in [100, 10_000, common::RANGE] {
+        let mut a = Array::default();
+        let mut b = Array::default();
+        let mut sa = BTreeSet::new();
+        let mut sb = BTreeSet::new();
+        for _ in 0..rng.gen_range(0..2000) {
+            let v = rng.gen_range(0..range);
+            a.insert(v, Val(v+1));
+            sa.insert(v);
+        }
+        for _ in 0..rng.gen_range(0..2000) {
+            let v = rng.gen_range(0..range);
+            b.insert(v, Val(v+1));
+            sb.insert(v);
+        }
+        
+        let removed = a.subtract(&b);
+        a.validate().unwrap();
+        let len = sa.len();
+        sa.retain(|v| !sb.contains(v));
+        assert_eq!(removed, len - sa.len());
+        assert_eq!(a.len(), sa.len());
+        itertools::assert_equal(a.iter().map(|(i, _)| i), sa.iter().copied());
+        for (i, v) in a.iter() {
+            assert_eq!(v, &Val(i+1));
+        }
+        
+        assert_eq!(a.subtract(&b), 0);
+    }
+}
+
+#[test]
+fn partition_test(){
+    use std::cell::Cell;
+    use std::rc::Rc;
+    
+    #[derive(Debug)]
+    struct Tracked{
+        key: usize,
+        drops: Rc<Cell<usize>>,
+    }
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+    type Array = common::Array<Option<Tracked>>;
+    
+    let drops = Rc::new(Cell::new(0));
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut keys: Vec<usize> = (0..3000).map(|_| rng.gen_range(0..common::RANGE)).collect();
+    keys.sort();
+    keys.dedup();
+    keys.shuffle(&mut rng);
+    
+    let mut a = Array::default();
+    for &key in &keys {
+        a.insert(key, Some(Tracked{key, drops: drops.clone()}));
+    }
+    
+    let (even, odd) = a.partition(|i, d| {
+        assert_eq!(d.as_ref().unwrap().key, i);
+        i % 2 == 0
+    });
+    assert_eq!(drops.get(), 0);
+    even.validate().unwrap();
+    odd.validate().unwrap();
+    assert_eq!(even.len() + odd.len(), keys.len());
+    
+    keys.sort();
+    itertools::assert_equal(even.keys(), keys.iter().copied().filter(|i| i % 2 == 0));
+    itertools::assert_equal(odd.keys(), keys.iter().copied().filter(|i| i % 2 == 1));
+    for (i, d) in even.iter().chain(odd.iter()) {
+        assert_eq!(d.as_ref().unwrap().key, i);
+    }
+    
+    drop(even);
+    drop(odd);
+    assert_eq!(drops.get(), keys.len());
+}
+
+#[test]
+fn symmetric_difference_with_test(){
+    use std::collections::BTreeSet;
+    
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    for range in [100, 10_000, common::RANGE] {
+        let mut a = Array::default();
+        let mut b = Array::default();
+        let mut sa = BTreeSet::new();
+        let mut sb = BTreeSet::new();
+        for _ in 0..rng.gen_range(0..2000) {
+            let v = rng.gen_range(0..range);
+            a.insert(v, Val(v+1));
+            sa.insert(v);
+        }
+        for _ in 0..rng.gen_range(0..2000) {
+            let v = rng.gen_range(0..range);
+            b.insert(v, Val(v+1));
+            sb.insert(v);
+        }
+        
+        a.symmetric_difference_with(&b);
+        a.validate().unwrap();
+        let expected: BTreeSet<_> = sa.symmetric_difference(&sb).copied().collect();
+        assert_eq!(a.len(), expected.len());
+        itertools::assert_equal(a.iter().map(|(i, _)| i), expected.iter().copied());
+        for (i, v) in a.iter() {
+            assert_eq!(v, &Val(i+1));
+        }
+        
+        // Applying twice restores keys of the original.
+        a.symmetric_difference_with(&b);
+        itertools::assert_equal(a.keys(), sa.iter().copied());
+    }
+}
+
+#[test]
+fn iter_indexed_test(){
+    use hi_sparse_array::{Index, IndexOutOfRange, intersection};
+    
+    // Intersection is not exact - its iteration checks items for emptiness.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut a = Array::default();
+    let mut b = Array::default();
+    for _ in 0..2000 {
+        let v = rng.gen_range(0..common::RANGE);
+        a.insert(v, Val(v+1));
+        let v = rng.gen_range(0..common::RANGE);
+        b.insert(v, Val(v+1));
+    }
+    
+    let iter = a.iter_indexed();
+    assert_eq!(iter.len(), a.len());
+    let mut copy = Array::default();
+    for (index, data) in iter {
+        assert_eq!(a.get_at(index), data);
+        copy.insert_at(index, data.clone());
+    }
+    itertools::assert_equal(copy.iter(), a.iter());
+    
+    let and = intersection(&a, &b, |l, r| Val(l.0 + r.0));
+    for (index, data) in and.iter_indexed() {
+        assert_eq!(and.get_at(index), data);
+        assert_eq!(a.get_at(index).0 + b.get_at(index).0, data.0);
+    }
+    
+    type I = Index<u64, <Array as SparseHierarchy>::LevelCount>;
+    let max_range = Array::max_range();
+    assert_eq!(I::new(max_range).map(usize::from), Ok(max_range));
+    assert_eq!(I::try_from(max_range + 1), Err(IndexOutOfRange{ index: max_range + 1, max_range }));
+}
+
+#[test]
+fn iter_cloned_test(){
+    use itertools::Itertools;
+    use hi_sparse_array::{config, intersection};
+    
+    // Intersection is not exact - its iteration checks items for emptiness.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Val(usize);
+    impl Empty for Val {
+        fn empty() -> Self {
+            Self(0)
+        }
+    
+        fn is_empty(&self) -> bool {
+            self.0 == 0
+        }
+    }
+    type Array = common::Array<Val>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0xe15bb9db3dee3a0f);
+    let mut a = Array::default();
+    let mut b = Array::default();
+    for _ in 0..2000 {
+        let v = rng.gen_range(0..common::RANGE);
+        a.insert(v, Val(v+1));
+        let v = rng.gen_range(0..common::RANGE);
+        b.insert(v, Val(v+1));
+    }
+    
+    let cloned = a.iter().cloned();
+    assert_eq!(cloned.len(), a.len());
+    let pairs: Vec<(usize, Val)> = cloned.collect();
+    assert!(pairs.iter().map(|(i, d)| (*i, d)).eq(a.iter()));
+    // Works with itertools adapters, that need owned items.
+    let chunks: Vec<Vec<_>> = a.iter().cloned()
+        .chunk_by(|(i, _)| i / 1000).into_iter()
+        .map(|(_, chunk)| chunk.collect())
+        .collect();
+    assert_eq!(chunks.concat(), pairs);
+    
+    let and = intersection(&a, &b, |l, r| Val(l.0 + r.0));
+    let expected: Vec<_> = a.iter()
+        .filter_map(|(i, d)| Some((i, Val(d.0 + b.try_get(i)?.0))))
+        .collect();
+    assert_eq!(and.iter().cloned().collect::<Vec<_>>(), expected);
+    assert_eq!(and.iter().cloned().nth(3), expected.get(3).cloned());
+    
+    type Copyable = SparseArray<config::width_64::depth_2, Option<u32>>;
+    let mut c = Copyable::default();
+    c.insert(5, Some(1));
+    c.insert(300, Some(2));
+    assert_eq!(c.iter().copied().collect::<Vec<_>>(), [(5, Some(1)), (300, Some(2))]);
+}
+
+#[test]
+fn typed_key_test(){
+    use std::collections::BTreeMap;
+    use hi_sparse_array::{config, IndexOutOfRange};
+    
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+    struct EntityId(u32);
+    impl From<EntityId> for usize {
+        fn from(id: EntityId) -> Self {
+            id.0 as usize
+        }
+    }
+    impl TryFrom<usize> for EntityId {
+        type Error = std::num::TryFromIntError;
+        fn try_from(index: usize) -> Result<Self, Self::Error> {
+            u32::try_from(index).map(EntityId)
+        }
+    }
+    
+    type Entities = SparseArray<config::width_64::depth_3, Data, EntityId>;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0x1d5);
+    let mut array = Entities::default();
+    let mut expected = BTreeMap::new();
+    for _ in 0..2000 {
+        let id = EntityId(rng.gen_range(0..common::RANGE as u32));
+        if rng.gen_bool(0.2) {
+            assert_eq!(array.remove(id), expected.remove(&id));
+        } else {
+            array.insert(id, Data(id.0 as usize));
+            expected.insert(id, Data(id.0 as usize));
+        }
+    }
+    
+    let items: Vec<_> = array.iter_keyed().map(|(k, d)| (k, d.clone())).collect();
+    assert!(items.into_iter().eq(expected.clone()));
+    assert_eq!(array.iter_keyed().len(), expected.len());
+    
+    let mut unordered: Vec<_> = array.unordered_iter().map(|(k, d)| (k, d.clone())).collect();
+    unordered.sort();
+    assert!(unordered.into_iter().eq(expected.clone()));
+    
+    for (&id, data) in &expected {
+        assert_eq!(array.try_get(id), Some(data));
+        array.get_mut(id).0 += 1;
+        assert_eq!(array.try_get_mut(id).map(|d| d.0), Some(data.0 + 1));
+    }
+    assert_eq!(array.try_get(EntityId(common::RANGE as u32 - 1)), expected.get(&EntityId(common::RANGE as u32 - 1)));
+    
+    // Range check composes with Index.
+    let max_range = Entities::max_range();
+    assert_eq!(
+        array.try_insert(EntityId(max_range as u32 + 1), Data(0)),
+        Err(IndexOutOfRange{ index: max_range + 1, max_range }.into())
+    );
+    
+    let keys: Vec<EntityId> = expected.keys().copied().collect();
+    let from_keys = Entities::from_keys(keys.iter().copied(), |id| Data(id.0 as usize));
+    assert!(from_keys.iter_keyed().map(|(k, _)| k).eq(keys.iter().copied()));
+    
+    let (even, odd) = from_keys.partition(|id, _| id.0 % 2 == 0);
+    assert!(even.iter_keyed().all(|(id, _)| id.0 % 2 == 0));
+    assert!(odd.iter_keyed().all(|(id, _)| id.0 % 2 == 1));
+    assert_eq!(even.len() + odd.len(), keys.len());
+}
+
+#[test]
+fn u64_key_test(){
+    use hi_sparse_array::{config, IndexOutOfRange, SparseArrayLevels};
+    
+    type Ids = SparseArray<config::width_64::depth_6, Data, u64>;
+    let max_range = config::width_64::depth_6::MAX_INDEX;
+    
+    let mut ids = Ids::default();
+    let keys: Vec<u64> = if cfg!(target_pointer_width = "64") {
+        // Above 32-bit range.
+        vec![0, 5, u32::MAX as u64, 1 << 33, (1 << 36) - 1]
+    } else {
+        vec![0, 5, 1 << 20, u32::MAX as u64]
+    };
+    for &k in &keys {
+        ids.insert(k, Data(k as usize));
+    }
+    assert!(ids.keys().eq(keys.iter().map(|&k| k as usize)));
+    assert!(ids.iter_keyed().map(|(k, _)| k).eq(keys.iter().copied()));
+    assert_eq!(ids.try_get(keys[3]), Some(&Data(keys[3] as usize)));
+    assert_eq!(ids.remove(keys[3]), Some(Data(keys[3] as usize)));
+    assert_eq!(ids.try_get(keys[3]), None);
+    
+    // Not truncated to usize, even where u64 is wider.
+    type Wide = SparseArray<config::width_64::depth_6, Data, u128>;
+    let mut wide = Wide::default();
+    let key = usize::MAX as u128 + 1;
+    let err = IndexOutOfRange{ index: usize::MAX, max_range };
+    assert_eq!(wide.try_insert(key, Data(1)), Err(err.into()));
+    assert_eq!(wide.try_insert(key - 1, Data(1)).is_ok(), max_range == usize::MAX);
+    assert!(std::panic::catch_unwind(move || wide.insert(key, Data(1))).is_err());
+}
+
+#[test]
+fn max_index_test(){
+    use hi_sparse_array::{config, Index, IndexOutOfRange};
+    use hi_sparse_array::config::SparseArrayLevels;
+    
+    fn check<Levels: SparseArrayLevels>(expected: usize){
+        type A<Levels> = SparseArray<Levels, Data>;
+        type I<Levels> = Index<<Levels as SparseArrayLevels>::Mask, <Levels as SparseArrayLevels>::LevelCount>;
+        
+        assert_eq!(Levels::MAX_INDEX, expected);
+        assert_eq!(A::<Levels>::MAX_INDEX, expected);
+        assert_eq!(A::<Levels>::max_range(), expected);
+        assert_eq!(I::<Levels>::MAX, expected);
+        
+        let max = expected;
+        let mut a = A::<Levels>::default();
+        assert_eq!(a.try_insert(max, Data(1)), Ok(()));
+        assert_eq!(a.try_get(max), Some(&Data(1)));
+        assert_eq!(a.iter().map(|(i, _)| i).collect::<Vec<_>>(), [max]);
+        let err = IndexOutOfRange{ index: max + 1, max_range: max };
+        assert_eq!(a.try_insert(max + 1, Data(2)), Err(err.into()));
+        assert_eq!(I::<Levels>::new(max).map(usize::from), Ok(max));
+        assert_eq!(I::<Levels>::new(max + 1), Err(err));
+        
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            a.insert(max + 1, Data(2));
+        }));
+        assert!(panicked.is_err());
+    }
+    check::<config::width_16::depth_1>(15);
+    check::<config::width_16::depth_3>(4095);
+    check::<config::width_32::depth_2>(1023);
+    check::<config::width_64::depth_2>(4095);
+    check::<config::width_64::depth_3>(262_143);
+    check::<config::width_64::depth_4>(16_777_215);
+    check::<config::width_512::depth_2>(262_143);
+    
+    // Capacity does not fit usize - whole usize range is valid.
+    type Deep = SparseArray<config::width_512::depth_8, Data>;
+    assert_eq!(Deep::MAX_INDEX, usize::MAX);
+    let mut deep = Deep::default();
+    for i in [0, u32::MAX as usize + 1, usize::MAX / 3, usize::MAX] {
+        assert_eq!(deep.try_insert(i, Data(i)), Ok(()));
+    }
+    assert!(deep.iter().map(|(i, d)| (i, d.0)).eq(
+        [0, u32::MAX as usize + 1, usize::MAX / 3, usize::MAX].map(|i| (i, i))
+    ));
+}
+
+#[test]
+fn index_from_primitive_test(){
+    use hi_sparse_array::{config, Index, IndexOutOfRange};
+    use hi_sparse_array::const_utils::ConstUsize;
+    
+    fn from<T, I: From<T>>(){}
+    fn try_from<T, I: TryFrom<T, Error = IndexOutOfRange>>(){}
+    
+    from    ::<u16, Index<u64, ConstUsize<3>>>();
+    try_from::<u16, Index<u64, ConstUsize<2>>>();
+    from    ::<u32, Index<u64, ConstUsize<6>>>();
+    try_from::<u32, Index<u64, ConstUsize<5>>>();
+    from    ::<u16, Index<u128, ConstUsize<3>>>();
+    from    ::<u32, Index<u128, ConstUsize<5>>>();
+    try_from::<u32, Index<u128, ConstUsize<4>>>();
+    
+    type A = SparseArray<config::width_64::depth_3, Data>;
+    let mut a = A::default();
+    a.insert_at(u16::MAX.into(), Data(1));
+    a.insert_at(12_345u16.into(), Data(2));
+    assert_eq!(a.get_at(u16::MAX.into()), &Data(1));
+    assert_eq!(a.get_at(12_345u16.into()), &Data(2));
+    assert_eq!(a.get_at(0u16.into()), &Data::empty());
+    
+    type I2 = Index<u64, ConstUsize<2>>;
+    assert_eq!(I2::try_from(4095u16).map(usize::from), Ok(4095));
+    assert_eq!(I2::try_from(4096u16), Err(IndexOutOfRange{ index: 4096, max_range: 4095 }));
+    
+    type I6 = Index<u64, ConstUsize<6>>;
+    assert_eq!(usize::from(I6::from(u32::MAX)), u32::MAX as usize);
+}
+
+#[test]
+fn dump_test(){
+    use hi_sparse_array::config;
+    
+    let mut a: SparseArray<config::width_64::depth_2, Data> = Default::default();
+    a.insert(3, Data(30));
+    a.insert(200, Data(2000));
+    a.insert(130, Data(1300));
+    
+    let mut s = String::new();
+    a.dump(&mut s).unwrap();
+    assert_eq!(s, "\
+level 0:
+  block 0: mask 0x000000000000000d, 3 children
+    0 -> block 1
+    2 -> block 3
+    3 -> block 2
+level 1:
+  block 1: mask 0x0000000000000008, 1 children
+    key 3 -> data 1
+  block 3: mask 0x0000000000000004, 1 children
+    key 130 -> data 3
+  block 2: mask 0x0000000000000100, 1 children
+    key 200 -> data 2
+");
+    
+    let dot = a.to_dot();
+    assert!(dot.starts_with("digraph SparseArray {"));
+    assert!(dot.contains("L0_0 -> L1_2 [label=\"3\"];"));
+    assert!(dot.contains("L1_2 -> D2 [label=\"200\"];"));
+    
+    let empty: SparseArray<config::width_64::depth_2, Data> = Default::default();
+    let mut s = String::new();
+    empty.dump(&mut s).unwrap();
+    assert_eq!(s, "level 0:\n  block 0: mask 0x0000000000000000, 0 children\nlevel 1:\n");
+}
+
+#[test]
+fn stats_test(){
+    use hi_sparse_array::config;
+    
+    let mut a: SparseArray<config::width_64::depth_2, Data> = Default::default();
+    for i in [0, 1, 64, 4000] {
+        a.insert(i, Data(i));
+    }
+    let stats = a.stats();
+    assert_eq!(stats.len, 4);
+    assert_eq!(stats.levels.len(), 2);
+    assert_eq!(stats.levels[0].blocks, 1);
+    assert_eq!(stats.levels[0].max_children, 3);
+    assert_eq!(stats.levels[1].blocks, 3);
+    assert_eq!(stats.levels[1].allocated_blocks, 4);
+    assert_eq!(stats.levels[1].max_children, 2);
+    assert!((stats.levels[1].avg_children - 4.0 / 3.0).abs() < 1e-9);
+    assert!(stats.total_bytes() > stats.data_bytes);
+    assert_eq!(stats.bytes_per_element(), stats.total_bytes() as f64 / 4.0);
+    assert!(stats.to_string().starts_with("len 4, "));
+    
+    // Removed blocks stay allocated.
+    for i in [0, 1, 64, 4000] {
+        a.remove(i);
+    }
+    let stats = a.stats();
+    assert_eq!(stats.len, 0);
+    assert_eq!(stats.levels[1].blocks, 0);
+    assert_eq!(stats.levels[1].allocated_blocks, 4);
+    assert_eq!(stats.levels[1].avg_children, 0.0);
+    assert_eq!(stats.bytes_per_element(), 0.0);
+}
+
+#[test]
+fn send_sync_test(){
+    use hi_sparse_array::{
+        config, difference, intersection, intersection_fold, union, union_fold, 
+        DataBlock, DataBlockMut, PointCursor, Iter, KeysIter
+    };
+    
+    fn assert_send_sync<T: Send + Sync>(_: &T){}
+    fn assert_send_sync_type<T: Send + Sync>(){}
+    
+    type A = SparseArray<config::width_64::depth_3, Data>;
+    type SboA = SparseArray<config::sbo::width_64::depth_3, Data>;
+    assert_send_sync_type::<A>();
+    assert_send_sync_type::<SboA>();
+    assert_send_sync_type::<<A as SparseHierarchy>::State>();
+    assert_send_sync_type::<Iter<'static, A>>();
+    assert_send_sync_type::<KeysIter<'static, SboA>>();
+    assert_send_sync_type::<PointCursor<'static, A>>();
+    assert_send_sync_type::<DataBlock<'static, config::width_64::depth_3, Data>>();
+    assert_send_sync_type::<DataBlockMut<'static, config::width_64::depth_3, Data>>();
+    
+    let a = A::default();
+    let f = |l: &Data, r: &Data| Data(l.0 + r.0);
+    assert_send_sync(&union(&a, &a, f).iter());
+    assert_send_sync(&intersection(&a, &a, f).iter());
+    assert_send_sync(&difference(&a, &a).iter());
+    assert_send_sync(&union_fold(&a, [&a].into_iter(), |l: Data, r: &Data| Data(l.0 + r.0)).iter());
+    assert_send_sync(&intersection_fold(&a, [&a].into_iter(), |l: Data, r: &Data| Data(l.0 + r.0)).iter());
+    
+    // Iteration state is usable from other threads.
+    let mut a = A::default();
+    for i in [1, 100, 10_000] {
+        a.insert(i, Data(i));
+    }
+    let mut iter = a.iter();
+    iter.next();
+    let rest: Vec<usize> = std::thread::scope(|s| 
+        s.spawn(move || iter.map(|(i, _)| i).collect()).join().unwrap()
+    );
+    assert_eq!(rest, [100, 10_000]);
+}
+
+#[test]
+fn max_index_remove_test(){
+    use hi_sparse_array::config;
+    use hi_sparse_array::config::SparseArrayLevels;
+    
+    fn check<Levels: SparseArrayLevels>(){
+        let max = Levels::MAX_INDEX;
+        let mid = max / 2;
+        let mut a = SparseArray::<Levels, Data>::default();
+        assert_eq!(a.try_get(max), None);
+        assert_eq!(a.remove(max), None);
+        
+        // Max index is stored last - removing mid swaps it into mid's slot.
+        a.insert(0, Data(0));
+        a.insert(mid, Data(mid));
+        a.insert(max, Data(max));
+        assert_eq!(a.try_get(max), Some(&Data(max)));
+        assert_eq!(a.remove(mid), Some(Data(mid)));
+        assert_eq!(a.try_get(max), Some(&Data(max)));
+        assert!(a.iter().map(|(i, _)| i).eq([0, max]));
+        
+        assert_eq!(a.remove(max), Some(Data(max)));
+        assert_eq!(a.try_get(max), None);
+        assert_eq!(a.remove(max), None);
+        assert_eq!(a.try_get(0), Some(&Data(0)));
+        a.validate().unwrap();
+        
+        // Max index is stored first - removing it swaps the last one in.
+        a.clear();
+        a.insert(max, Data(max));
+        a.insert(0, Data(0));
+        a.insert(mid, Data(mid));
+        assert_eq!(a.remove(max), Some(Data(max)));
+        assert!(a.iter().map(|(i, d)| (i, d.0)).eq([(0, 0), (mid, mid)]));
+        assert!(a.unordered_iter().all(|(i, _)| i != max));
+        
+        a.insert(max, Data(1));
+        *a.get_mut(max) = Data(2);
+        assert_eq!(a.unordered_iter().find(|(i, _)| *i == max), Some((max, &Data(2))));
+        assert_eq!(a.len(), 3);
+        a.validate().unwrap();
+    }
+    check::<config::width_64::depth_1>();
+    check::<config::width_64::depth_2>();
+    check::<config::width_64::depth_3>();
+    check::<config::width_64::depth_4>();
+    check::<config::width_16::depth_3>();
+    check::<config::width_512::depth_8>();
+}
+
+#[test]
+fn capacity_exceeded_test(){
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use hi_sparse_array::{CapacityExceeded, Error, IndexOutOfRange};
+    use hi_sparse_array::level::{IntrusiveListLevel, SingleBlockLevel};
+    use hi_sparse_array::level_block::Block;
+    
+    fn panic_message(f: impl FnOnce()) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        payload.downcast::<String>().map(|s| *s).unwrap()
+    }
+    
+    // Last level addresses up to 255 items with u8.
+    type Narrow = SparseArray<(
+        SingleBlockLevel<Block<u64, [u8; 64]>>,
+        IntrusiveListLevel<Block<u64, [u8; 64]>>,
+    ), Data>;
+    let mut a = Narrow::default();
+    for i in 0..255 {
+        assert_eq!(a.try_insert(i*3, Data(i)), Ok(()));
+    }
+    let err = Error::CapacityExceeded(CapacityExceeded{ max_block_index: 255 });
+    assert_eq!(a.try_insert(1, Data(0)), Err(err));
+    assert_eq!(a.len(), 255);
+    // Existing item replacement does not need a new slot.
+    assert_eq!(a.try_insert(3, Data(100)), Ok(()));
+    assert_eq!(a.try_get(3), Some(&Data(100)));
+    // Index is checked first.
+    let out_of_range = Error::from(IndexOutOfRange{ index: 4096, max_range: 4095 });
+    assert_eq!(a.try_insert(4096, Data(0)), Err(out_of_range));
+    
+    // Panicking versions report the same errors.
+    assert_eq!(panic_message(|| a.insert(1, Data(0))), err.to_string());
+    assert_eq!(panic_message(|| { a.get_mut(4096); }), out_of_range.to_string());
+    
+    a.remove(0);
+    assert_eq!(a.try_insert(1, Data(1)), Ok(()));
+    assert_eq!(a.try_get(1), Some(&Data(1)));
+    
+    // Level 1 addresses up to 255 level 2 blocks with u8.
+    type NarrowMiddle = SparseArray<(
+        SingleBlockLevel<Block<u64, [u8; 64]>>,
+        IntrusiveListLevel<Block<u64, [u8; 64]>>,
+        IntrusiveListLevel<Block<u64, [u32; 64]>>,
+    ), Data>;
+    let mut a = NarrowMiddle::default();
+    for i in 0..255 {
+        a.insert(i*64, Data(i));
+    }
+    // Checked before anything is inserted.
+    let stats = a.stats();
+    assert_eq!(a.try_insert(255*64, Data(0)), Err(err));
+    assert_eq!(a.len(), 255);
+    assert_eq!(a.stats(), stats);
+    a.validate().unwrap();
+    // Existing level 2 block has room.
+    assert_eq!(a.try_insert(254*64 + 1, Data(1)), Ok(()));
+    assert_eq!(panic_message(|| a.insert(255*64, Data(0))), err.to_string());
+    
+    // New intermediate block, but no room for item.
+    let mut a = Narrow::default();
+    for i in 0..255 {
+        a.insert(i, Data(i));
+    }
+    let stats = a.stats();
+    assert_eq!(a.try_insert(4000, Data(0)), Err(err));
+    assert_eq!(a.stats(), stats);
+    a.validate().unwrap();
+}
+
+/// Block indices of all levels fit all blocks/items of full container.
+#[cfg(feature = "simd")]
+#[test]
+fn width_256_full_test(){
+    use hi_sparse_array::config;
+    
+    fn check<Levels: hi_sparse_array::config::SparseArrayLevels>(){
+        let mut a = SparseArray::<Levels, Data>::default();
+        for i in 0..=Levels::MAX_INDEX {
+            assert_eq!(a.try_insert(i, Data(i)), Ok(()));
+        }
+        assert_eq!(a.len(), Levels::MAX_INDEX + 1);
+        assert!(a.iter().map(|(i, d)| (i, d.0)).eq((0..=Levels::MAX_INDEX).map(|i| (i, i))));
+    }
+    check::<config::width_256::depth_1>();
+    check::<config::width_256::depth_2>();
+    check::<config::sbo::width_256::depth_2>();
+}